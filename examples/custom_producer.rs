@@ -0,0 +1,63 @@
+//! Shows how to write your own metrics plugin with
+//! [`MetricProducerAppExt`].
+
+use bevy::prelude::*;
+use bevy_egui::EguiPlugin;
+use bevy_metrics_dashboard::{
+    DashboardPlugin, DashboardWindow, MetricProducerAppExt, RegistryPlugin,
+};
+use metrics::{counter, describe_counter, describe_gauge, gauge, Unit};
+use rand::Rng;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(EguiPlugin)
+        .add_plugins(RegistryPlugin::default())
+        .add_plugins(DashboardPlugin::default())
+        .add_plugins(EnemyMetricsPlugin)
+        .add_systems(Startup, create_dashboard)
+        .add_systems(Update, spawn_and_despawn_enemies)
+        .run();
+}
+
+#[derive(Component)]
+struct Enemy;
+
+/// Produces metrics about [`Enemy`] entities.
+struct EnemyMetricsPlugin;
+
+impl Plugin for EnemyMetricsPlugin {
+    fn build(&self, app: &mut App) {
+        // Descriptions are registered at startup, and the producer is
+        // scheduled before the dashboard consumes the metrics.
+        app.describe_metrics(describe_enemy_metrics)
+            .add_metric_producer(update_enemy_metrics);
+    }
+}
+
+fn describe_enemy_metrics() {
+    describe_gauge!("enemies::alive", Unit::Count, "Enemies currently alive");
+    describe_counter!("enemies::spawned", Unit::Count, "Total enemies spawned");
+}
+
+fn update_enemy_metrics(enemies: Query<(), With<Enemy>>, added: Query<(), Added<Enemy>>) {
+    gauge!("enemies::alive").set(enemies.iter().len() as f64);
+    counter!("enemies::spawned").increment(added.iter().count() as u64);
+}
+
+fn create_dashboard(mut commands: Commands) {
+    commands.spawn(DashboardWindow::new("Metrics Dashboard"));
+}
+
+fn spawn_and_despawn_enemies(mut commands: Commands, enemies: Query<Entity, With<Enemy>>) {
+    let mut rng = rand::thread_rng();
+    for _ in 0..rng.gen_range(0..3) {
+        commands.spawn(Enemy);
+    }
+    for entity in &enemies {
+        if rng.gen_bool(0.02) {
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -11,7 +11,7 @@ fn main() {
         .add_plugins(DefaultPlugins)
         .add_plugins(EguiPlugin)
         .add_plugins(RegistryPlugin::default())
-        .add_plugins(DashboardPlugin)
+        .add_plugins(DashboardPlugin::default())
         .add_systems(Startup, (describe_metrics, create_dashboard))
         .add_systems(Update, update_metrics)
         .run();
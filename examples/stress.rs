@@ -12,9 +12,9 @@ fn main() {
         .add_plugins(DefaultPlugins)
         .add_plugins(EguiPlugin)
         .add_plugins(RegistryPlugin::default())
-        .add_plugins(CoreMetricsPlugin)
+        .add_plugins(CoreMetricsPlugin::default())
         .add_plugins(RenderMetricsPlugin)
-        .add_plugins(DashboardPlugin)
+        .add_plugins(DashboardPlugin::default())
         .add_systems(Startup, (create_dashboard, setup))
         .add_systems(Update, move_camera)
         .run();
@@ -47,7 +47,7 @@ fn setup(mut commands: Commands, assets: Res<AssetServer>) {
             let scale = Vec3::splat(rng.gen::<f32>() * 2.0);
 
             sprites.push((
-                Sprite{
+                Sprite {
                     image: sprite_handle.clone(),
                     custom_size: Some(tile_size),
                     color: Color::WHITE,
@@ -57,7 +57,7 @@ fn setup(mut commands: Commands, assets: Res<AssetServer>) {
                     translation,
                     rotation,
                     scale,
-                }
+                },
             ));
         }
     }
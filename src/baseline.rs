@@ -0,0 +1,385 @@
+//! Summary statistics of a run, saved as a baseline for regression tracking.
+//!
+//! Record a run with the [`BaselinePlugin`], then compare its [`Baseline`]
+//! against one saved from a previous run, e.g. in CI:
+//!
+//! ```no_run
+//! # use bevy_metrics_dashboard::baseline::{Baseline, Tolerances};
+//! # let current = Baseline::default();
+//! let previous = Baseline::load("baseline.json").unwrap();
+//! let report = current.compare(&previous, &Tolerances::new(0.1));
+//! if !report.passed() {
+//!     panic!("{report}");
+//! }
+//! ```
+
+use crate::{
+    metric_kind_str,
+    registry::{BucketCursor, MetricKey, MetricsRegistry},
+    HistogramConsumerAppExt,
+};
+use bevy::{prelude::*, utils::HashMap};
+use metrics::Key;
+use metrics_util::{storage::Summary, MetricKind};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    sync::atomic::Ordering,
+};
+
+/// Summary statistics of one metric over a run.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct MetricStats {
+    pub count: u64,
+    pub mean: f64,
+    pub p95: f64,
+    pub p99: f64,
+}
+
+impl MetricStats {
+    const NAMES: [&'static str; 3] = ["mean", "p95", "p99"];
+
+    fn values(&self) -> [f64; 3] {
+        [self.mean, self.p95, self.p99]
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct BaselineEntry {
+    pub name: String,
+    /// One of "counter", "gauge", or "histogram".
+    pub kind: String,
+    /// Labels in the order they were recorded.
+    #[serde(default)]
+    pub labels: Vec<(String, String)>,
+    pub stats: MetricStats,
+}
+
+impl BaselineEntry {
+    fn same_metric(&self, other: &Self) -> bool {
+        self.name == other.name && self.kind == other.kind && self.labels == other.labels
+    }
+
+    fn title(&self) -> String {
+        let mut title = format!("{} ({})", self.name, self.kind);
+        for (k, v) in &self.labels {
+            title.push_str(&format!(" {k}={v}"));
+        }
+        title
+    }
+}
+
+/// The [`MetricStats`] of every metric recorded during a run.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Baseline {
+    pub metrics: Vec<BaselineEntry>,
+}
+
+impl Baseline {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_json())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let json = std::fs::read_to_string(path)?;
+        Self::from_json(&json).map_err(std::io::Error::other)
+    }
+
+    /// Compare this run against `previous`, reporting every statistic that
+    /// changed by more than its tolerance.
+    pub fn compare(&self, previous: &Self, tolerances: &Tolerances) -> BaselineReport {
+        let mut report = BaselineReport::default();
+        for entry in &self.metrics {
+            let Some(old) = previous.metrics.iter().find(|e| e.same_metric(entry)) else {
+                report.added.push(entry.title());
+                continue;
+            };
+            let tolerance = tolerances.for_metric(&entry.name);
+            let values = MetricStats::NAMES
+                .into_iter()
+                .zip(old.stats.values())
+                .zip(entry.stats.values());
+            for ((statistic, baseline), current) in values {
+                let change = relative_change(baseline, current);
+                if change.abs() > tolerance {
+                    report.deviations.push(Deviation {
+                        metric: entry.title(),
+                        statistic,
+                        baseline,
+                        current,
+                        change,
+                    });
+                }
+            }
+        }
+        for entry in &previous.metrics {
+            if !self.metrics.iter().any(|e| e.same_metric(entry)) {
+                report.missing.push(entry.title());
+            }
+        }
+        report
+    }
+}
+
+/// The change from `baseline` to `current` as a fraction of `baseline`.
+///
+/// A zero baseline has no scale, so the absolute change is used instead.
+fn relative_change(baseline: f64, current: f64) -> f64 {
+    if baseline == 0.0 {
+        current
+    } else {
+        (current - baseline) / baseline.abs()
+    }
+}
+
+/// The largest relative change of any statistic that's allowed when comparing
+/// baselines, e.g. `0.1` for 10%.
+#[derive(Clone, Debug)]
+pub struct Tolerances {
+    default: f64,
+    by_name: HashMap<String, f64>,
+}
+
+impl Default for Tolerances {
+    fn default() -> Self {
+        Self::new(0.1)
+    }
+}
+
+impl Tolerances {
+    pub fn new(default: f64) -> Self {
+        Self {
+            default,
+            by_name: HashMap::default(),
+        }
+    }
+
+    /// Use `tolerance` for all metrics named `name`.
+    pub fn with_metric(mut self, name: impl Into<String>, tolerance: f64) -> Self {
+        self.by_name.insert(name.into(), tolerance);
+        self
+    }
+
+    pub fn for_metric(&self, name: &str) -> f64 {
+        self.by_name.get(name).copied().unwrap_or(self.default)
+    }
+}
+
+/// A statistic that changed by more than its tolerance.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Deviation {
+    pub metric: String,
+    /// One of "mean", "p95", or "p99".
+    pub statistic: &'static str,
+    pub baseline: f64,
+    pub current: f64,
+    /// The relative change from `baseline` to `current`.
+    pub change: f64,
+}
+
+/// The result of [`Baseline::compare`].
+#[derive(Clone, Debug, Default)]
+pub struct BaselineReport {
+    pub deviations: Vec<Deviation>,
+    /// Metrics in the previous baseline that weren't recorded this run.
+    pub missing: Vec<String>,
+    /// Metrics recorded this run that aren't in the previous baseline.
+    pub added: Vec<String>,
+}
+
+impl BaselineReport {
+    /// `true` if no statistic changed by more than its tolerance.
+    pub fn passed(&self) -> bool {
+        self.deviations.is_empty()
+    }
+}
+
+impl fmt::Display for BaselineReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for d in &self.deviations {
+            writeln!(
+                f,
+                "{} {}: {:.4} -> {:.4} ({:+.1}%)",
+                d.metric,
+                d.statistic,
+                d.baseline,
+                d.current,
+                100.0 * d.change
+            )?;
+        }
+        for metric in &self.missing {
+            writeln!(f, "{metric}: missing")?;
+        }
+        for metric in &self.added {
+            writeln!(f, "{metric}: new")?;
+        }
+        Ok(())
+    }
+}
+
+struct Accumulator {
+    sum: f64,
+    summary: Summary,
+}
+
+impl Default for Accumulator {
+    fn default() -> Self {
+        Self {
+            sum: 0.0,
+            summary: Summary::with_defaults(),
+        }
+    }
+}
+
+impl Accumulator {
+    fn add(&mut self, value: f64) {
+        self.sum += value;
+        self.summary.add(value);
+    }
+
+    fn stats(&self) -> Option<MetricStats> {
+        let count = self.summary.count();
+        (count > 0).then(|| MetricStats {
+            count: count as u64,
+            mean: self.sum / count as f64,
+            p95: self.summary.quantile(0.95).unwrap_or_default(),
+            p99: self.summary.quantile(0.99).unwrap_or_default(),
+        })
+    }
+}
+
+fn accumulator<'a>(
+    accumulators: &'a mut HashMap<MetricKey, Accumulator>,
+    key: &Key,
+    kind: MetricKind,
+) -> &'a mut Accumulator {
+    accumulators
+        .entry(MetricKey::new(key.clone(), kind))
+        .or_default()
+}
+
+/// Accumulates [`MetricStats`] for every metric in a registry.
+///
+/// Counters are summarized by their increase per sample, gauges by their
+/// value at every sample, and histograms by every recorded value.
+/// Percentiles are estimated with a quantile sketch, so memory stays bounded
+/// over long runs.
+#[derive(Default, Resource)]
+pub struct BaselineRecorder {
+    accumulators: HashMap<MetricKey, Accumulator>,
+    last_counters: HashMap<Key, u64>,
+    cursors: HashMap<Key, BucketCursor>,
+}
+
+impl BaselineRecorder {
+    /// Sample the current values of all metrics in `registry`.
+    pub fn sample(&mut self, registry: &MetricsRegistry) {
+        let Self {
+            accumulators,
+            last_counters,
+            cursors,
+        } = self;
+        registry.visit_counters(|key, counter| {
+            let value = counter.load(Ordering::Relaxed);
+            if let Some(last) = last_counters.insert(key.clone(), value) {
+                accumulator(accumulators, key, MetricKind::Counter)
+                    .add(value.saturating_sub(last) as f64);
+            }
+        });
+        registry.visit_gauges(|key, gauge| {
+            let value = f64::from_bits(gauge.load(Ordering::Relaxed));
+            accumulator(accumulators, key, MetricKind::Gauge).add(value);
+        });
+        registry.visit_histograms(|key, bucket| {
+            let cursor = cursors
+                .entry(key.clone())
+                .or_insert_with(|| BucketCursor::new(registry));
+            let accumulator = accumulator(accumulators, key, MetricKind::Histogram);
+            cursor.read_new(bucket, |value| accumulator.add(value));
+        });
+    }
+
+    /// The statistics of everything sampled so far, sorted by metric.
+    pub fn baseline(&self) -> Baseline {
+        let mut metrics: Vec<_> = self
+            .accumulators
+            .iter()
+            .filter_map(|(key, acc)| {
+                Some(BaselineEntry {
+                    name: key.key.name().to_owned(),
+                    kind: metric_kind_str(key.kind).to_owned(),
+                    labels: key
+                        .key
+                        .labels()
+                        .map(|l| (l.key().to_owned(), l.value().to_owned()))
+                        .collect(),
+                    stats: acc.stats()?,
+                })
+            })
+            .collect();
+        metrics.sort_by(|a, b| (&a.name, &a.kind, &a.labels).cmp(&(&b.name, &b.kind, &b.labels)));
+        Baseline { metrics }
+    }
+
+    /// Forget everything sampled so far, e.g. after a warm-up period.
+    pub fn reset(&mut self) {
+        *self = default();
+    }
+
+    fn sample_system(mut recorder: ResMut<Self>, registry: Res<MetricsRegistry>) {
+        recorder.sample(&registry);
+    }
+}
+
+/// Samples every metric into the [`BaselineRecorder`] resource once per
+/// frame, optionally saving the [`Baseline`] to a file when the app exits.
+///
+/// This doesn't need the [`DashboardPlugin`](crate::DashboardPlugin), so it
+/// works in headless runs.
+#[derive(Default)]
+pub struct BaselinePlugin {
+    output_path: Option<PathBuf>,
+}
+
+impl BaselinePlugin {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Save the baseline to `path` on [`AppExit`].
+    pub fn with_output_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.output_path = Some(path.into());
+        self
+    }
+}
+
+impl Plugin for BaselinePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BaselineRecorder>()
+            .add_histogram_consumer(BaselineRecorder::sample_system);
+
+        if let Some(path) = self.output_path.clone() {
+            app.add_systems(
+                Last,
+                (move |mut exits: EventReader<AppExit>, recorder: Res<BaselineRecorder>| {
+                    if exits.read().next().is_none() {
+                        return;
+                    }
+                    if let Err(e) = recorder.baseline().save(&path) {
+                        error!("Failed to save baseline to {}: {e}", path.display());
+                    }
+                })
+                .after(BaselineRecorder::sample_system),
+            );
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use crate::{
+    metric_kind_str,
+    registry::{DescriptionKey, MetricsRegistry},
+};
+use bevy::prelude::*;
+use bevy_egui::egui::{Color32, RichText, Ui};
+use std::time::{Duration, Instant};
+
+/// Warns when a metric name has too many series, i.e. label combinations.
+///
+/// Labels with per-entity values (like entity IDs) can silently create a huge
+/// number of series, each of which stays in the registry forever. This plugin
+/// periodically counts series per name, sends a [`MetricCardinalityWarning`]
+/// when a name first exceeds the limit, and shows a warning banner in every
+/// [`DashboardWindow`](crate::DashboardWindow).
+pub struct CardinalityWarningPlugin {
+    max_series_per_name: usize,
+    check_period: Duration,
+}
+
+impl Default for CardinalityWarningPlugin {
+    fn default() -> Self {
+        Self {
+            max_series_per_name: 100,
+            check_period: Duration::from_secs(1),
+        }
+    }
+}
+
+impl CardinalityWarningPlugin {
+    pub fn with_max_series_per_name(mut self, max: usize) -> Self {
+        self.max_series_per_name = max;
+        self
+    }
+
+    /// Set how often series are counted.
+    pub fn with_check_period(mut self, period: Duration) -> Self {
+        self.check_period = period;
+        self
+    }
+}
+
+impl Plugin for CardinalityWarningPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<MetricCardinalityWarning>()
+            .insert_resource(CardinalityWarnings {
+                max_series_per_name: self.max_series_per_name,
+                check_period: self.check_period,
+                last_check_time: None,
+                exceeded: Vec::new(),
+                show_banner: true,
+            })
+            .add_systems(Update, CardinalityWarnings::check_system);
+    }
+}
+
+/// Sent when the number of series of a metric name first exceeds the limit of
+/// the [`CardinalityWarningPlugin`].
+#[derive(Clone, Debug, Event)]
+pub struct MetricCardinalityWarning {
+    pub key: DescriptionKey,
+    pub n_series: usize,
+}
+
+/// The metric names that currently exceed the series limit.
+///
+/// Modify this resource to change the limit at runtime.
+#[derive(Resource)]
+pub struct CardinalityWarnings {
+    max_series_per_name: usize,
+    check_period: Duration,
+    last_check_time: Option<Instant>,
+    exceeded: Vec<(DescriptionKey, usize)>,
+    show_banner: bool,
+}
+
+impl CardinalityWarnings {
+    pub fn max_series_per_name(&self) -> usize {
+        self.max_series_per_name
+    }
+
+    pub fn set_max_series_per_name(&mut self, max: usize) {
+        self.max_series_per_name = max;
+        // Recheck on the next update.
+        self.last_check_time = None;
+    }
+
+    /// Show or hide the warning banner in dashboard windows. Events are sent
+    /// regardless.
+    pub fn set_show_banner(&mut self, show: bool) {
+        self.show_banner = show;
+    }
+
+    /// The names exceeding the limit with their number of series, sorted by
+    /// descending number of series.
+    pub fn exceeded(&self) -> &[(DescriptionKey, usize)] {
+        &self.exceeded
+    }
+
+    fn check_system(
+        mut warnings: ResMut<Self>,
+        registry: Res<MetricsRegistry>,
+        mut events: EventWriter<MetricCardinalityWarning>,
+    ) {
+        let check = warnings
+            .last_check_time
+            .is_none_or(|t| t.elapsed() > warnings.check_period);
+        if !check {
+            return;
+        }
+        warnings.last_check_time = Some(Instant::now());
+
+        let max = warnings.max_series_per_name;
+        let mut exceeded: Vec<_> = registry
+            .series_counts_by_name()
+            .into_iter()
+            .filter(|&(_, n)| n > max)
+            .collect();
+        exceeded.sort_by(|(k1, n1), (k2, n2)| n2.cmp(n1).then_with(|| k1.name.cmp(&k2.name)));
+
+        for (key, n_series) in &exceeded {
+            if !warnings.exceeded.iter().any(|(k, _)| k == key) {
+                warn!(
+                    "Metric {} ({}) has {n_series} series, exceeding the limit of {max}",
+                    key.name.as_str(),
+                    metric_kind_str(key.kind)
+                );
+                events.send(MetricCardinalityWarning {
+                    key: key.clone(),
+                    n_series: *n_series,
+                });
+            }
+        }
+        warnings.exceeded = exceeded;
+    }
+
+    pub(crate) fn draw_banner(&self, ui: &mut Ui) {
+        if !self.show_banner || self.exceeded.is_empty() {
+            return;
+        }
+        let text = format!(
+            "⚠ {} metric(s) exceed {} series",
+            self.exceeded.len(),
+            self.max_series_per_name
+        );
+        ui.collapsing(RichText::new(text).color(Color32::YELLOW), |ui| {
+            for (key, n_series) in &self.exceeded {
+                ui.label(format!(
+                    "{} ({}): {n_series} series",
+                    key.name.as_str(),
+                    metric_kind_str(key.kind)
+                ));
+            }
+        });
+    }
+}
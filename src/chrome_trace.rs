@@ -0,0 +1,221 @@
+//! Export metrics recorded over a session as a Chrome trace, to view them
+//! alongside CPU traces in [Perfetto](https://ui.perfetto.dev) or
+//! `chrome://tracing`.
+//!
+//! ```no_run
+//! # use bevy::prelude::*;
+//! # use bevy_metrics_dashboard::{chrome_trace::ChromeTracePlugin, RegistryPlugin};
+//! App::new().add_plugins((
+//!     DefaultPlugins,
+//!     RegistryPlugin::default(),
+//!     ChromeTracePlugin::new().with_output_path("metrics_trace.json"),
+//! ));
+//! ```
+
+use crate::{
+    registry::{key_text, BucketCursor, MetricKey, MetricsRegistry},
+    HistogramConsumerAppExt,
+};
+use bevy::{prelude::*, utils::HashMap};
+use metrics::Key;
+use metrics_util::MetricKind;
+use serde_json::{json, Map, Value};
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::Ordering,
+    time::Duration,
+};
+
+/// One point of a counter track.
+struct CounterEvent {
+    track: usize,
+    micros: f64,
+    args: Vec<(&'static str, f64)>,
+}
+
+/// Records every metric in a registry as counter tracks of a Chrome trace.
+///
+/// Counters and gauges add a point to their track whenever their value
+/// changes. Histograms add a point with the count, mean, and max of the
+/// samples recorded since the previous sample, which Perfetto shows as one
+/// track each.
+///
+/// Every point is kept until [`Self::reset`], so memory grows with the
+/// length of the session.
+#[derive(Default, Resource)]
+pub struct ChromeTraceRecorder {
+    tracks: HashMap<MetricKey, usize>,
+    track_names: Vec<String>,
+    events: Vec<CounterEvent>,
+    last_values: HashMap<MetricKey, u64>,
+    cursors: HashMap<Key, BucketCursor>,
+}
+
+impl ChromeTraceRecorder {
+    /// Sample all metrics in `registry`, at `timestamp` since the start of
+    /// the session.
+    pub fn sample(&mut self, registry: &MetricsRegistry, timestamp: Duration) {
+        let micros = 1e6 * timestamp.as_secs_f64();
+        registry.visit_counters(|key, counter| {
+            let value = counter.load(Ordering::Relaxed);
+            let key = MetricKey::new(key.clone(), MetricKind::Counter);
+            if self.value_changed(&key, value) {
+                self.push(&key, micros, vec![("value", value as f64)]);
+            }
+        });
+        registry.visit_gauges(|key, gauge| {
+            let bits = gauge.load(Ordering::Relaxed);
+            let key = MetricKey::new(key.clone(), MetricKind::Gauge);
+            if self.value_changed(&key, bits) {
+                self.push(&key, micros, vec![("value", f64::from_bits(bits))]);
+            }
+        });
+        registry.visit_histograms(|key, bucket| {
+            let cursor = self
+                .cursors
+                .entry(key.clone())
+                .or_insert_with(|| BucketCursor::new(registry));
+            let (mut count, mut sum, mut max) = (0, 0.0, f64::NEG_INFINITY);
+            cursor.read_new(bucket, |value| {
+                count += 1;
+                sum += value;
+                max = max.max(value);
+            });
+            if count > 0 {
+                let key = MetricKey::new(key.clone(), MetricKind::Histogram);
+                let args = vec![
+                    ("count", count as f64),
+                    ("mean", sum / count as f64),
+                    ("max", max),
+                ];
+                self.push(&key, micros, args);
+            }
+        });
+    }
+
+    /// The number of points recorded on all tracks.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Everything recorded so far, in the Chrome trace event format.
+    pub fn to_json(&self) -> String {
+        let mut trace_events = vec![json!({
+            "name": "process_name",
+            "ph": "M",
+            "pid": 1,
+            "args": { "name": "metrics" },
+        })];
+        trace_events.extend(self.events.iter().map(|event| {
+            let args: Map<String, Value> = event
+                .args
+                .iter()
+                .map(|&(name, value)| (name.to_owned(), json!(value)))
+                .collect();
+            json!({
+                "name": self.track_names[event.track],
+                "ph": "C",
+                "ts": event.micros,
+                "pid": 1,
+                "tid": 1,
+                "args": args,
+            })
+        }));
+        let trace = json!({
+            "displayTimeUnit": "ms",
+            "traceEvents": trace_events,
+        });
+        trace.to_string()
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_json())
+    }
+
+    /// Forget everything recorded so far, e.g. after a warm-up period.
+    pub fn reset(&mut self) {
+        *self = default();
+    }
+
+    /// Returns `true` if `value` differs from the last one seen for `key`.
+    fn value_changed(&mut self, key: &MetricKey, value: u64) -> bool {
+        match self.last_values.get_mut(key) {
+            Some(last) if *last == value => false,
+            Some(last) => {
+                *last = value;
+                true
+            }
+            None => {
+                self.last_values.insert(key.clone(), value);
+                true
+            }
+        }
+    }
+
+    fn push(&mut self, key: &MetricKey, micros: f64, args: Vec<(&'static str, f64)>) {
+        let track = *self.tracks.entry(key.clone()).or_insert_with(|| {
+            self.track_names.push(key_text(&key.key));
+            self.track_names.len() - 1
+        });
+        self.events.push(CounterEvent {
+            track,
+            micros,
+            args,
+        });
+    }
+
+    fn sample_system(
+        mut recorder: ResMut<Self>,
+        registry: Res<MetricsRegistry>,
+        time: Res<Time<Real>>,
+    ) {
+        recorder.sample(&registry, time.elapsed());
+    }
+}
+
+/// Samples every metric into the [`ChromeTraceRecorder`] resource once per
+/// frame, optionally saving the trace to a file when the app exits.
+///
+/// Timestamps are the [`Time<Real>`] elapsed since startup.
+#[derive(Default)]
+pub struct ChromeTracePlugin {
+    output_path: Option<PathBuf>,
+}
+
+impl ChromeTracePlugin {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Save the trace to `path` on [`AppExit`].
+    pub fn with_output_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.output_path = Some(path.into());
+        self
+    }
+}
+
+impl Plugin for ChromeTracePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ChromeTraceRecorder>()
+            .add_histogram_consumer(ChromeTraceRecorder::sample_system);
+
+        if let Some(path) = self.output_path.clone() {
+            app.add_systems(
+                Last,
+                (move |mut exits: EventReader<AppExit>, recorder: Res<ChromeTraceRecorder>| {
+                    if exits.read().next().is_none() {
+                        return;
+                    }
+                    if let Err(e) = recorder.save(&path) {
+                        error!("Failed to save trace to {}: {e}", path.display());
+                    }
+                })
+                .after(ChromeTraceRecorder::sample_system),
+            );
+        }
+    }
+}
@@ -1,16 +1,80 @@
+use crate::MetricProducerAppExt;
 use bevy::{ecs::entity::Entities, prelude::*};
-use metrics::{describe_gauge, describe_histogram, gauge, histogram, Unit};
+use metrics::{
+    counter, describe_counter, describe_gauge, describe_histogram, gauge, histogram, Unit,
+};
 
 /// Provides core metrics like frame time, entity count, etc.
-pub struct CoreMetricsPlugin;
+///
+/// For each slow frame threshold `t` (by default 16.7 and 33.4 ms), a
+/// counter "frames_over_{t}ms" counts the frames that took longer than `t`,
+/// named after the whole milliseconds of `t`, e.g. "frames_over_16ms".
+/// Thresholds with the same name as an earlier one are ignored.
+pub struct CoreMetricsPlugin {
+    slow_frame_thresholds_ms: Vec<f64>,
+}
+
+impl Default for CoreMetricsPlugin {
+    fn default() -> Self {
+        Self {
+            slow_frame_thresholds_ms: vec![16.7, 33.4],
+        }
+    }
+}
+
+impl CoreMetricsPlugin {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the frame time thresholds, in milliseconds, that have slow
+    /// frame counters.
+    pub fn with_slow_frame_thresholds(
+        mut self,
+        thresholds_ms: impl IntoIterator<Item = f64>,
+    ) -> Self {
+        self.slow_frame_thresholds_ms = thresholds_ms.into_iter().collect();
+        self
+    }
+}
 
 impl Plugin for CoreMetricsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, describe_core_metrics)
-            .add_systems(Update, update_core_metrics);
+        let mut slow_frame_counters: Vec<(f64, String)> = Vec::new();
+        for &ms in &self.slow_frame_thresholds_ms {
+            let name = format!("frames_over_{}ms", ms.trunc());
+            if slow_frame_counters.iter().any(|(_, other)| *other == name) {
+                warn!("Ignoring slow frame threshold {ms} ms, {name} already counts");
+                continue;
+            }
+            slow_frame_counters.push((ms, name));
+        }
+        let names: Vec<_> = slow_frame_counters
+            .iter()
+            .map(|(_, name)| name.clone())
+            .collect();
+
+        app.describe_metrics(describe_core_metrics)
+            .describe_metrics(move || {
+                for name in &names {
+                    describe_counter!(
+                        name.clone(),
+                        Unit::Count,
+                        "Frames with a longer frame time delta"
+                    );
+                }
+            })
+            .add_metric_producer(update_core_metrics)
+            .add_metric_producer(move |time: Res<Time>| {
+                let ms = 1000.0 * time.delta_secs_f64();
+                for (threshold, name) in &slow_frame_counters {
+                    if ms > *threshold {
+                        counter!(name.clone()).increment(1);
+                    }
+                }
+            });
     }
 }
-
 fn describe_core_metrics() {
     describe_gauge!("frame_time", Unit::Milliseconds, "Frame time delta");
     describe_histogram!("frame_time", Unit::Milliseconds, "Frame time delta");
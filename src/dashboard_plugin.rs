@@ -1,24 +1,366 @@
 use crate::{
-    dashboard_window::{CachedPlotConfigs, RequestPlot},
+    dashboard_window::{
+        send_pending_entity_selections, send_pending_plot_requests, CachedPlotConfigs,
+        CachedPlotData, RequestPlot, RequestPlots, SelectEntity,
+    },
+    frame_snapshot::FrameSnapshot,
+    leaderboard::LeaderboardWindow,
+    locale::DashboardLocale,
+    metrics_table::MetricsTableWindow,
     namespace_tree::NamespaceTreeWindow,
-    ClearBucketsSystem, DashboardWindow,
+    palette::Palette,
+    registry::MetricsRegistry,
+    session_info::SessionInfoWindow,
+    spike_log::SpikeLogWindow,
+    startup_config::StartupConfig,
+    timeline::{Annotation, SessionTimeline},
+    DashboardWindow, HistogramConsumerAppExt, HistogramRetention, HistogramSketches, MetricExpired,
+    PlotTemplates, ValueFormatters,
 };
-use bevy::prelude::*;
+use bevy::{
+    ecs::{intern::Interned, schedule::ScheduleLabel},
+    prelude::*,
+};
+use bevy_egui::EguiContexts;
+use std::sync::Arc;
+
+/// Adds systems to an app, deferred until the plugin is built.
+type AddSystems = Arc<dyn Fn(&mut App) + Send + Sync>;
+
+/// Updates and renders all [`DashboardWindow`], [`NamespaceTreeWindow`],
+/// [`MetricsTableWindow`], [`LeaderboardWindow`], [`SpikeLogWindow`], and
+/// [`SessionInfoWindow`] entities.
+pub struct DashboardPlugin {
+    read_only: bool,
+    pause_key: Option<KeyCode>,
+    update_schedule: Interned<dyn ScheduleLabel>,
+    update_set: Option<Interned<dyn SystemSet>>,
+    background_mode: bool,
+    /// Adds the system updating [`DashboardsVisible`] from a state.
+    visibility: Option<AddSystems>,
+    startup_config: StartupConfig,
+}
+
+impl Default for DashboardPlugin {
+    fn default() -> Self {
+        Self {
+            read_only: false,
+            pause_key: None,
+            update_schedule: Last.intern(),
+            update_set: None,
+            background_mode: false,
+            visibility: None,
+            startup_config: default(),
+        }
+    }
+}
+
+impl DashboardPlugin {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Make every [`DashboardWindow`] read-only, regardless of its
+    /// [`DashboardConfig::read_only`](crate::DashboardConfig::read_only).
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
 
-/// Updates and renders all [`DashboardWindow`] and [`NamespaceTreeWindow`]
-/// entities.
-pub struct DashboardPlugin;
+    /// Send [`PauseDashboards::Toggle`] whenever `key` is pressed, unless
+    /// `egui` is taking keyboard input.
+    pub fn with_pause_key(mut self, key: KeyCode) -> Self {
+        self.pause_key = Some(key);
+        self
+    }
+
+    /// Apply settings chosen when the app starts, e.g. from
+    /// [`StartupConfig::from_env`].
+    ///
+    /// The config's pause key and read-only setting replace those of this
+    /// plugin if they're set, and its windows are spawned in [`Startup`].
+    pub fn with_startup_config(mut self, config: StartupConfig) -> Self {
+        if let Some(key) = config.pause_key {
+            self.pause_key = Some(key);
+        }
+        if let Some(read_only) = config.read_only {
+            self.read_only = read_only;
+        }
+        self.startup_config = config;
+        self
+    }
+
+    /// Update plot data in `schedule` instead of [`Last`].
+    ///
+    /// In [`Last`], updates run after the
+    /// [`ProduceMetricsSystem`](crate::ProduceMetricsSystem) and before the
+    /// [`ClearBucketsSystem`](crate::ClearBucketsSystem). In any other
+    /// schedule, that ordering is up to the app, e.g. by also calling
+    /// [`with_update_set`](Self::with_update_set), or by using
+    /// [`ClearBucketsPolicy::WhenConsumed`](crate::ClearBucketsPolicy::WhenConsumed)
+    /// so buckets are only cleared after the plots have read them.
+    pub fn with_update_schedule(mut self, schedule: impl ScheduleLabel) -> Self {
+        self.update_schedule = schedule.intern();
+        self
+    }
+
+    /// Add the systems that update plot data to `set`, so they can be ordered
+    /// relative to the app's own systems.
+    pub fn with_update_set(mut self, set: impl SystemSet) -> Self {
+        self.update_set = Some(set.intern());
+        self
+    }
+
+    /// Stop sampling metrics while there are no windows to show them.
+    ///
+    /// See [`DashboardActivity::Background`].
+    pub fn with_background_mode(mut self) -> Self {
+        self.background_mode = true;
+        self
+    }
+
+    /// Only draw windows while the app is in one of `states`, e.g. to never
+    /// show dashboards during shipping gameplay states.
+    ///
+    /// Hidden windows keep sampling metrics, so their plots are up to date
+    /// when they reappear. See [`DashboardsVisible`].
+    #[cfg(feature = "states")]
+    pub fn visible_in_states<S: States>(mut self, states: impl IntoIterator<Item = S>) -> Self {
+        let states: Vec<S> = states.into_iter().collect();
+        self.visibility = Some(Arc::new(move |app: &mut App| {
+            let states = states.clone();
+            app.add_systems(
+                Update,
+                (move |state: Option<Res<State<S>>>, mut visible: ResMut<DashboardsVisible>| {
+                    let is_visible = state.is_some_and(|s| states.contains(s.get()));
+                    if visible.0 != is_visible {
+                        visible.0 = is_visible;
+                    }
+                })
+                .before(DashboardWindow::draw_all),
+            );
+        }));
+        self
+    }
+}
+
+/// Whether windows are drawn.
+///
+/// Inserted by the [`DashboardPlugin`]. With the `states` feature, this is
+/// `false` outside the states given to `DashboardPlugin::visible_in_states`.
+/// Otherwise it's only changed by the app.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Resource)]
+pub struct DashboardsVisible(pub bool);
+
+impl Default for DashboardsVisible {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+/// Whether any window is sampling metrics.
+///
+/// Inserted by the [`DashboardPlugin`], and only ever
+/// [`Background`](Self::Background) when enabled with
+/// [`DashboardPlugin::with_background_mode`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Resource)]
+pub enum DashboardActivity {
+    #[default]
+    Foreground,
+    /// No [`DashboardWindow`], [`MetricsTableWindow`], [`LeaderboardWindow`],
+    /// or [`SpikeLogWindow`] exists.
+    ///
+    /// Plots aren't updated, and histogram samples are summarized in
+    /// [`HistogramSketches`] instead of being kept in their buckets (see
+    /// [`HistogramRetention::Sketch`]). The sketches are reset every time the
+    /// dashboards go to the background.
+    Background,
+}
+
+/// Any window that samples metrics.
+type SamplingWindow = Or<(
+    With<DashboardWindow>,
+    With<MetricsTableWindow>,
+    With<LeaderboardWindow>,
+    With<SpikeLogWindow>,
+)>;
+
+impl DashboardActivity {
+    fn update_system(
+        mut activity: ResMut<Self>,
+        retention: Option<ResMut<HistogramRetention>>,
+        sketches: Option<ResMut<HistogramSketches>>,
+        windows: Query<(), SamplingWindow>,
+    ) {
+        let new_activity = if windows.is_empty() {
+            Self::Background
+        } else {
+            Self::Foreground
+        };
+        if *activity == new_activity {
+            return;
+        }
+        *activity = new_activity;
+        if let Some(mut retention) = retention {
+            *retention = match new_activity {
+                Self::Foreground => HistogramRetention::Buckets,
+                Self::Background => HistogramRetention::Sketch,
+            };
+        }
+        if let Some(mut sketches) = sketches.filter(|_| new_activity == Self::Background) {
+            sketches.clear();
+        }
+    }
+}
+
+/// Forces every [`DashboardWindow`] to be read-only when `true`.
+///
+/// Inserted by the [`DashboardPlugin`].
+#[derive(Clone, Copy, Debug, Default, Resource)]
+pub struct ReadOnlyDashboards(pub bool);
+
+/// Pauses or resumes every [`DashboardWindow`] and [`MetricsTableWindow`] at
+/// once, e.g. to freeze everything right after a spike.
+#[derive(Clone, Copy, Debug, Eq, Event, PartialEq)]
+pub enum PauseDashboards {
+    /// Pause all windows if any is running, otherwise resume all of them.
+    Toggle,
+    Pause,
+    Resume,
+}
+
+impl PauseDashboards {
+    fn apply_system(
+        mut events: EventReader<Self>,
+        mut dashboards: Query<&mut DashboardWindow>,
+        mut tables: Query<&mut MetricsTableWindow>,
+    ) {
+        for event in events.read() {
+            let paused = match event {
+                Self::Toggle => {
+                    dashboards.iter().any(|w| !w.dashboard().config().paused)
+                        || tables.iter().any(|t| !t.is_paused())
+                }
+                Self::Pause => true,
+                Self::Resume => false,
+            };
+            for mut window in &mut dashboards {
+                window.dashboard_mut().config_mut().paused = paused;
+            }
+            for mut table in &mut tables {
+                table.set_paused(paused);
+            }
+        }
+    }
+}
 
 impl Plugin for DashboardPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
+        if let Some(key) = self.pause_key {
+            app.add_systems(
+                Update,
+                (move |keys: Option<Res<ButtonInput<KeyCode>>>,
+                       mut ctxts: EguiContexts,
+                       mut events: EventWriter<PauseDashboards>| {
+                    if !keys.is_some_and(|k| k.just_pressed(key)) {
+                        return;
+                    }
+                    if ctxts
+                        .try_ctx_mut()
+                        .is_some_and(|c| c.wants_keyboard_input())
+                    {
+                        return;
+                    }
+                    events.send(PauseDashboards::Toggle);
+                })
+                .before(PauseDashboards::apply_system),
+            );
+        }
+
         app.add_event::<RequestPlot>()
+            .add_event::<RequestPlots>()
+            .add_event::<SelectEntity>()
+            .add_event::<PauseDashboards>()
+            .add_event::<Annotation>()
+            .add_event::<MetricExpired>()
             .init_resource::<CachedPlotConfigs>()
+            .init_resource::<CachedPlotData>()
+            .init_resource::<ValueFormatters>()
+            .init_resource::<PlotTemplates>()
+            .init_resource::<Palette>()
+            .init_resource::<DashboardLocale>()
+            .init_resource::<FrameSnapshot>()
+            .init_resource::<SessionTimeline>()
+            .insert_resource(ReadOnlyDashboards(self.read_only))
+            .init_resource::<DashboardActivity>()
+            .init_resource::<DashboardsVisible>()
+            .add_systems(PreUpdate, DashboardLocale::install_system)
             .add_systems(
                 Update,
-                (DashboardWindow::draw_all, NamespaceTreeWindow::draw_all),
+                (
+                    send_pending_plot_requests.before(RequestPlot::resolve_deferred_system),
+                    RequestPlot::resolve_deferred_system
+                        .before(DashboardWindow::apply_requests_all),
+                    // Requests aren't lost while windows are hidden.
+                    DashboardWindow::apply_requests_all.before(DashboardWindow::draw_all),
+                    send_pending_entity_selections,
+                    PauseDashboards::apply_system.before(DashboardWindow::draw_all),
+                    SessionTimeline::record_system.before(DashboardWindow::draw_all),
+                    (
+                        DashboardWindow::draw_all,
+                        NamespaceTreeWindow::draw_all,
+                        MetricsTableWindow::draw_all,
+                        LeaderboardWindow::draw_all,
+                        SpikeLogWindow::draw_all,
+                        SessionInfoWindow::draw_all,
+                    )
+                        .run_if(resource_equals(DashboardsVisible(true))),
+                    CachedPlotData::remove_expired_system,
+                ),
+            );
+        if let Some(add_visibility_system) = &self.visibility {
+            add_visibility_system(app);
+        }
+        let startup_config = self.startup_config.clone();
+        if startup_config.autospawn || startup_config.layout.is_some() {
+            app.add_systems(
+                Startup,
+                move |mut commands: Commands,
+                      registry: Res<MetricsRegistry>,
+                      templates: Res<PlotTemplates>| {
+                    startup_config.spawn_windows(&mut commands, &registry, &templates);
+                },
+            );
+        }
+
+        // Enforce strict ordering:
+        // metrics producers --> snapshot --> metrics consumers --> bucket clearing
+        let background_mode = self.background_mode;
+        let update_systems = (
+            DashboardActivity::update_system.run_if(move || background_mode),
+            // Expiry isn't missed in the background.
+            (
+                FrameSnapshot::remove_expired_system,
+                DashboardWindow::expire_all,
+            ),
+            (
+                FrameSnapshot::capture_system,
+                (
+                    DashboardWindow::update_all,
+                    MetricsTableWindow::update_all,
+                    LeaderboardWindow::update_all,
+                    SpikeLogWindow::update_all,
+                ),
             )
-            // Enforce strict ordering:
-            // metrics producers (before Last) --> metrics consumers --> bucket clearing
-            .add_systems(Last, DashboardWindow::update_all.before(ClearBucketsSystem));
+                .chain()
+                .run_if(resource_equals(DashboardActivity::Foreground)),
+        )
+            .chain();
+        match self.update_set {
+            Some(set) => {
+                app.add_histogram_consumer_to(self.update_schedule, update_systems.in_set(set))
+            }
+            None => app.add_histogram_consumer_to(self.update_schedule, update_systems),
+        };
     }
 }
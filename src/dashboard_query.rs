@@ -0,0 +1,66 @@
+use crate::{registry::MetricKey, DashboardWindow};
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+/// A metric plotted in a [`DashboardWindow`], as listed by
+/// [`DashboardQuery::plotted_metrics`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PlottedMetric<'a> {
+    pub window: Entity,
+    pub title: &'a str,
+    pub key: &'a MetricKey,
+}
+
+/// Lists the open [`DashboardWindow`]s and the metrics they plot, e.g. for a
+/// "what am I watching" HUD or for test assertions.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_metrics_dashboard::DashboardQuery;
+/// fn log_watched_metrics(dashboards: DashboardQuery) {
+///     for plotted in dashboards.plotted_metrics() {
+///         info!("{} plots {}", plotted.title, plotted.key.title(None, 0));
+///     }
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct DashboardQuery<'w, 's> {
+    windows: Query<'w, 's, (Entity, &'static DashboardWindow)>,
+}
+
+impl DashboardQuery<'_, '_> {
+    pub fn windows(&self) -> impl Iterator<Item = (Entity, &DashboardWindow)> {
+        self.windows.iter()
+    }
+
+    /// Every metric plotted in every window, once per plot, including the
+    /// series of multi-series plots.
+    pub fn plotted_metrics(&self) -> impl Iterator<Item = PlottedMetric<'_>> {
+        self.windows.iter().flat_map(|(window, dashboard)| {
+            let title = dashboard.title();
+            dashboard
+                .dashboard()
+                .plotted_metrics()
+                .map(move |key| PlottedMetric { window, title, key })
+        })
+    }
+
+    /// The metrics plotted in `window`, or `None` if it isn't a
+    /// [`DashboardWindow`].
+    pub fn metrics_in(&self, window: Entity) -> Option<Vec<&MetricKey>> {
+        let (_, dashboard) = self.windows.get(window).ok()?;
+        Some(dashboard.dashboard().plotted_metrics().collect())
+    }
+
+    /// The windows that plot `key`.
+    pub fn windows_plotting(&self, key: &MetricKey) -> Vec<Entity> {
+        self.windows
+            .iter()
+            .filter(|(_, dashboard)| dashboard.dashboard().plotted_metrics().any(|k| k == key))
+            .map(|(window, _)| window)
+            .collect()
+    }
+
+    pub fn is_plotted(&self, key: &MetricKey) -> bool {
+        self.plotted_metrics().any(|plotted| plotted.key == key)
+    }
+}
@@ -0,0 +1,58 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+/// Chooses where a dashboard window entity is drawn.
+///
+/// Add this component next to a [`DashboardWindow`](crate::DashboardWindow),
+/// [`NamespaceTreeWindow`](crate::namespace_tree::NamespaceTreeWindow), or
+/// [`MetricsTableWindow`](crate::metrics_table::MetricsTableWindow). Without
+/// it, windows are drawn on the primary window's `egui` context.
+///
+/// - For multi-window setups, `context` is the `Window` entity.
+/// - For render layers or in-world screens, `context` is an entity with
+///   [`EguiRenderToImage`](bevy_egui::EguiRenderToImage), whose image can be
+///   displayed by any camera.
+/// - For split-screen, set `area` to the viewport of the intended camera, in
+///   `egui` points, to keep the window inside that viewport.
+#[derive(Clone, Copy, Component, Debug)]
+pub struct DashboardTarget {
+    pub context: Entity,
+    pub area: Option<egui::Rect>,
+}
+
+impl DashboardTarget {
+    pub fn new(context: Entity) -> Self {
+        Self {
+            context,
+            area: None,
+        }
+    }
+
+    pub fn with_area(mut self, area: egui::Rect) -> Self {
+        self.area = Some(area);
+        self
+    }
+
+    /// Get the context for `target`, or the primary context if `target` is
+    /// `None`.
+    pub(crate) fn ctx<'a>(
+        target: Option<&Self>,
+        ctxts: &'a mut EguiContexts,
+    ) -> Option<&'a mut egui::Context> {
+        match target {
+            Some(target) => ctxts.try_ctx_for_entity_mut(target.context),
+            None => ctxts.try_ctx_mut(),
+        }
+    }
+
+    /// Keep `window` inside the target area, if any.
+    pub(crate) fn constrain<'a>(
+        target: Option<&Self>,
+        window: egui::Window<'a>,
+    ) -> egui::Window<'a> {
+        match target.and_then(|t| t.area) {
+            Some(area) => window.default_pos(area.min).constrain_to(area),
+            None => window,
+        }
+    }
+}
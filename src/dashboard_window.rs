@@ -1,39 +1,503 @@
 use crate::{
+    cardinality::CardinalityWarnings,
+    dashboard_plugin::ReadOnlyDashboards,
+    dashboard_target::DashboardTarget,
+    frame_snapshot::FrameSnapshot,
+    locale::tr,
     namespace_tree::NamespaceTreeWindow,
-    plots::{window_size_slider, MetricPlot, MetricPlotConfig},
-    registry::{MetricKey, MetricsRegistry},
+    palette::Palette,
+    plot_templates::PlotTemplates,
+    plots::{
+        chip, draw_metric_chips, window_size_slider, CustomSeries, MetricPlot, MetricPlotConfig,
+        MultiSeriesPlot,
+    },
+    registry::{label_text, DescriptionKey, MetricKey, MetricsRegistry},
+    registry_plugin::MetricExpired,
     search_bar::SearchBar,
+    timeline::SessionTimeline,
+    value_formatters::ValueFormatters,
+    write_counting::{WriteDiagnostics, WriteStats},
 };
-use bevy::{prelude::*, utils::HashMap};
+use bevy::{ecs::system::SystemParam, prelude::*, utils::HashMap};
 use bevy_egui::{
-    egui::{self, Ui},
-    EguiContexts,
+    egui::{self, collapsing_header::CollapsingState, Color32, DragValue, Ui},
+    EguiContext, EguiContexts,
 };
-use metrics::Unit;
+use metrics::{Key, Label, Unit};
+use metrics_util::MetricKind;
+use std::time::{Duration, Instant};
 
+/// Plots a metric in every [`DashboardWindow`].
 #[derive(Clone, Event)]
 pub struct RequestPlot {
     pub key: MetricKey,
     pub unit: Option<Unit>,
+    /// Instead of creating the metric if it doesn't exist, wait until metrics
+    /// named like `key` exist, and then plot every series of them regardless
+    /// of labels. See [`RequestPlot::by_name`].
+    pub wait_for_metric: bool,
+}
+
+impl RequestPlot {
+    /// Plot the metric `key` right away, creating it if it doesn't exist.
+    pub fn new(key: MetricKey, unit: Option<Unit>) -> Self {
+        Self {
+            key,
+            unit,
+            wait_for_metric: false,
+        }
+    }
+
+    /// Plot every series of the metric `name` once it's recorded, with the
+    /// unit from its description.
+    ///
+    /// Until then, the request is kept pending, so requesting a plot at
+    /// startup doesn't create an unlabeled metric that's never written.
+    pub fn by_name(name: impl Into<String>, kind: MetricKind) -> Self {
+        Self {
+            key: MetricKey::new(Key::from_name(name.into()), kind),
+            unit: None,
+            wait_for_metric: true,
+        }
+    }
+
+    /// Resends requests made with [`Self::by_name`] as immediate requests
+    /// once their metrics exist.
+    pub(crate) fn resolve_deferred_system(
+        registry: Res<MetricsRegistry>,
+        mut pending: Local<Vec<RequestPlot>>,
+        mut requests: ParamSet<(EventReader<RequestPlot>, EventWriter<RequestPlot>)>,
+    ) {
+        pending.extend(
+            requests
+                .p0()
+                .read()
+                .filter(|request| request.wait_for_metric)
+                .cloned(),
+        );
+        if pending.is_empty() {
+            return;
+        }
+        let mut resolved = Vec::new();
+        pending.retain(|request| {
+            let series = registry.find_by_name(request.key.key.name(), request.key.kind);
+            if series.is_empty() {
+                return true;
+            }
+            for result in series {
+                let unit = request
+                    .unit
+                    .or_else(|| result.description.and_then(|d| d.unit));
+                resolved.push(RequestPlot::new(result.key, unit));
+            }
+            false
+        });
+        requests.p1().send_batch(resolved);
+    }
+}
+
+/// Plots a batch of metrics in every [`DashboardWindow`] at once.
+///
+/// Requests for the same metric in one frame, from this event, [`RequestPlot`],
+/// or the search bar, only add one plot.
+#[derive(Clone, Debug, Default, Event)]
+pub struct RequestPlots(pub Vec<(MetricKey, Option<Unit>)>);
+
+/// Draw a small button that requests a plot of `key` when clicked, so any
+/// `egui` UI can link into the dashboard.
+///
+/// The request is sent as a [`RequestPlot`] event by the
+/// [`DashboardPlugin`](crate::DashboardPlugin) on the next update.
+pub fn plot_button(ui: &mut Ui, key: MetricKey, unit: Option<Unit>) -> egui::Response {
+    let response = ui
+        .small_button("📈")
+        .on_hover_text(format!("Plot {}", key.title(None, 0)));
+    if response.clicked() {
+        ui.ctx().data_mut(|d| {
+            d.get_temp_mut_or_default::<Vec<RequestPlot>>(pending_requests_id())
+                .push(RequestPlot::new(key, unit))
+        });
+    }
+    response
+}
+
+fn pending_requests_id() -> egui::Id {
+    egui::Id::new("bevy_metrics_dashboard::pending_plot_requests")
+}
+
+/// Sends the [`RequestPlot`] events queued by [`plot_button`] in any `egui`
+/// context.
+pub(crate) fn send_pending_plot_requests(
+    mut contexts: Query<&mut EguiContext>,
+    mut requests: EventWriter<RequestPlot>,
+) {
+    for mut ctxt in &mut contexts {
+        let pending = ctxt
+            .get_mut()
+            .data_mut(|d| d.remove_temp::<Vec<RequestPlot>>(pending_requests_id()));
+        requests.send_batch(pending.into_iter().flatten());
+    }
+}
+
+/// Requests that editor or inspector UIs focus an entity.
+///
+/// Sent when a metric label whose value is formatted like an [`Entity`], e.g.
+/// the `entity` labels written by the `RenderMetricsPlugin`, is clicked.
+#[derive(Clone, Copy, Debug, Event)]
+pub struct SelectEntity(pub Entity);
+
+/// Draw a metric label as `key=value`.
+///
+/// If the value is formatted like an [`Entity`], it's drawn as a link that
+/// sends a [`SelectEntity`] event when clicked.
+pub fn metric_label(ui: &mut Ui, label: &Label) -> egui::Response {
+    let Some(entity) = parse_entity(label.value()) else {
+        return ui.label(label_text(label));
+    };
+    ui.horizontal(|ui| {
+        ui.spacing_mut().item_spacing.x = 0.0;
+        ui.label(format!("{}=", label.key()));
+        let response = ui
+            .link(label.value())
+            .on_hover_text(format!("Select entity {entity}"));
+        if response.clicked() {
+            ui.ctx().data_mut(|d| {
+                d.get_temp_mut_or_default::<Vec<SelectEntity>>(pending_selections_id())
+                    .push(SelectEntity(entity))
+            });
+        }
+    })
+    .response
+}
+
+/// Parse the `{index}v{generation}` format of [`Entity`]'s `Display` impl.
+pub(crate) fn parse_entity(text: &str) -> Option<Entity> {
+    let (index, generation) = text.split_once('v')?;
+    let index: u32 = index.parse().ok()?;
+    let generation: u32 = generation.parse().ok()?;
+    Entity::try_from_bits((u64::from(generation) << 32) | u64::from(index)).ok()
+}
+
+fn pending_selections_id() -> egui::Id {
+    egui::Id::new("bevy_metrics_dashboard::pending_entity_selections")
+}
+
+/// Sends the [`SelectEntity`] events queued by [`metric_label`] in any `egui`
+/// context.
+pub(crate) fn send_pending_entity_selections(
+    mut contexts: Query<&mut EguiContext>,
+    mut selections: EventWriter<SelectEntity>,
+) {
+    for mut ctxt in &mut contexts {
+        let pending = ctxt
+            .get_mut()
+            .data_mut(|d| d.remove_temp::<Vec<SelectEntity>>(pending_selections_id()));
+        selections.send_batch(pending.into_iter().flatten());
+    }
 }
 
 /// Cache of configs for plots that have been opened and removed.
 #[derive(Default, Deref, DerefMut, Resource)]
 pub struct CachedPlotConfigs(HashMap<MetricKey, MetricPlotConfig>);
 
+/// Cache of plots that have been removed, including their data.
+///
+/// Disabled by default. When a retention period is set, removed plots keep
+/// their data for that long, and re-adding the same metric restores it. The
+/// cached plots are not updated while they are removed.
+#[derive(Default, Resource)]
+pub struct CachedPlotData {
+    retention: Option<Duration>,
+    plots: HashMap<MetricKey, (Instant, MetricPlot)>,
+}
+
+impl CachedPlotData {
+    pub fn with_retention(retention: Duration) -> Self {
+        Self {
+            retention: Some(retention),
+            plots: default(),
+        }
+    }
+
+    pub fn retention(&self) -> Option<Duration> {
+        self.retention
+    }
+
+    /// Set how long removed plots keep their data, or `None` to disable
+    /// caching.
+    pub fn set_retention(&mut self, retention: Option<Duration>) {
+        self.retention = retention;
+        self.remove_expired();
+    }
+
+    fn insert(&mut self, plot: MetricPlot) {
+        if self.retention.is_some() {
+            self.plots
+                .insert(plot.key().clone(), (Instant::now(), plot));
+        }
+    }
+
+    fn take(&mut self, key: &MetricKey) -> Option<MetricPlot> {
+        self.remove_expired();
+        self.plots.remove(key).map(|(_, plot)| plot)
+    }
+
+    /// Drop all cached plots that have been removed for longer than the
+    /// retention period.
+    pub fn remove_expired(&mut self) {
+        let Some(retention) = self.retention else {
+            self.plots.clear();
+            return;
+        };
+        self.plots
+            .retain(|_, (removed_time, _)| removed_time.elapsed() <= retention);
+    }
+
+    pub(crate) fn remove_expired_system(mut cached: ResMut<Self>) {
+        if !cached.plots.is_empty() {
+            cached.remove_expired();
+        }
+    }
+}
+
 /// An `egui` window that can search for metrics and plot them.
+///
+/// The window draws a [`Dashboard`] with [`draw_dashboard`].
 #[derive(Component)]
 pub struct DashboardWindow {
     title: String,
     search_bar: SearchBar,
-    plots: Vec<MetricPlot>,
-    config: DashboardConfig,
+    dashboard: Dashboard,
+    default_pos: Option<egui::Pos2>,
+    default_size: Option<egui::Vec2>,
+    rect: Option<egui::Rect>,
+    content_size: Option<egui::Vec2>,
+    bring_to_front: bool,
 }
 
-#[derive(Default)]
 pub struct DashboardConfig {
     pub global_window_size: Option<usize>,
     pub paused: bool,
+    /// Hide the controls for adding, removing, and configuring plots, so the
+    /// layout is fixed but still visible.
+    pub read_only: bool,
+    /// Repaint plots at most this often, e.g. 100 ms for 10 Hz, to save the
+    /// cost of laying out and tessellating them every frame.
+    ///
+    /// Plots still sample every frame; only drawing is throttled. `None`
+    /// redraws every frame.
+    pub redraw_interval: Option<Duration>,
+    /// The number of columns the plots are laid out in. At least 1.
+    pub column_count: usize,
+    /// Plot samples at the frame they were recorded on, instead of their
+    /// sample number, so plots with different windows or start times line up.
+    pub align_by_frame: bool,
+    /// The order that metric plots are drawn in.
+    pub sort_mode: PlotSortMode,
+    /// Draw the [`SessionTimeline`] ribbon at the top of the window.
+    pub show_timeline: bool,
+    /// Draw a strip under each line plot showing its whole window, with the
+    /// visible region highlighted. This helps to keep context while zoomed
+    /// into large windows.
+    pub show_minimaps: bool,
+    /// Mark plots of metrics that haven't been updated for this many frames
+    /// as stalled. `None` never does.
+    ///
+    /// This needs the [`RegistryPlugin`](crate::RegistryPlugin) to be built
+    /// [`with_recency`](crate::RegistryPlugin::with_recency).
+    pub stalled_after: Option<u64>,
+    /// Show how often each plotted metric is written, flagging hot and dead
+    /// metrics. `None` doesn't.
+    ///
+    /// This needs the [`RegistryPlugin`](crate::RegistryPlugin) to be built
+    /// [`with_write_counting`](crate::RegistryPlugin::with_write_counting).
+    pub write_diagnostics: Option<WriteDiagnostics>,
+}
+
+/// The order of the metric plots in a [`Dashboard`].
+///
+/// Custom series and multi-series plots are always drawn first, newest
+/// first.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum PlotSortMode {
+    /// The most recently added plot first.
+    #[default]
+    Newest,
+    /// The first added plot first.
+    Oldest,
+    /// Alphabetically by plot name.
+    Name,
+    /// Counters, then gauges, then histograms, alphabetically within each.
+    Kind,
+    /// By latest value, largest first. Plots without a value come last.
+    Value,
+}
+
+impl PlotSortMode {
+    const ALL: [Self; 5] = [
+        Self::Newest,
+        Self::Oldest,
+        Self::Name,
+        Self::Kind,
+        Self::Value,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Newest => "Newest",
+            Self::Oldest => "Oldest",
+            Self::Name => "Name",
+            Self::Kind => "Kind",
+            Self::Value => "Value",
+        }
+    }
+
+    /// The indices of `plots` in this order.
+    fn order(self, plots: &[MetricPlot]) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..plots.len()).collect();
+        let kind_rank = |kind| match kind {
+            MetricKind::Counter => 0,
+            MetricKind::Gauge => 1,
+            MetricKind::Histogram => 2,
+        };
+        match self {
+            Self::Newest => order.reverse(),
+            Self::Oldest => {}
+            Self::Name => order.sort_by(|&a, &b| plots[a].name().cmp(plots[b].name())),
+            Self::Kind => order.sort_by(|&a, &b| {
+                let (a, b) = (&plots[a], &plots[b]);
+                kind_rank(a.key().kind)
+                    .cmp(&kind_rank(b.key().kind))
+                    .then_with(|| a.name().cmp(b.name()))
+            }),
+            Self::Value => order.sort_by(|&a, &b| {
+                let value = |i: usize| plots[i].latest_value().unwrap_or(f64::NEG_INFINITY);
+                value(b).total_cmp(&value(a))
+            }),
+        }
+        order
+    }
+}
+
+impl Default for DashboardConfig {
+    fn default() -> Self {
+        Self {
+            global_window_size: None,
+            paused: false,
+            read_only: false,
+            redraw_interval: None,
+            column_count: 1,
+            align_by_frame: false,
+            sort_mode: default(),
+            show_timeline: true,
+            show_minimaps: false,
+            stalled_after: Some(300),
+            write_diagnostics: None,
+        }
+    }
+}
+
+/// Configures a [`DashboardWindow`] before it's spawned.
+///
+/// ```
+/// # use bevy_metrics_dashboard::DashboardWindow;
+/// let window = DashboardWindow::builder("Frame Times")
+///     .paused(true)
+///     .global_window_size(1000)
+///     .column_count(2)
+///     .build();
+///
+/// assert!(window.dashboard().config().paused);
+/// assert_eq!(window.dashboard().config().column_count, 2);
+/// ```
+pub struct DashboardWindowBuilder {
+    title: String,
+    config: DashboardConfig,
+    default_pos: Option<egui::Pos2>,
+    default_size: Option<egui::Vec2>,
+}
+
+impl DashboardWindowBuilder {
+    pub fn paused(mut self, paused: bool) -> Self {
+        self.config.paused = paused;
+        self
+    }
+
+    /// Link the X axes of all plots to show `window_size` samples.
+    pub fn global_window_size(mut self, window_size: usize) -> Self {
+        self.config.global_window_size = Some(window_size);
+        self
+    }
+
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.config.read_only = read_only;
+        self
+    }
+
+    /// See [`DashboardConfig::redraw_interval`].
+    pub fn redraw_interval(mut self, interval: Duration) -> Self {
+        self.config.redraw_interval = Some(interval);
+        self
+    }
+
+    /// See [`DashboardConfig::align_by_frame`].
+    pub fn align_by_frame(mut self, align_by_frame: bool) -> Self {
+        self.config.align_by_frame = align_by_frame;
+        self
+    }
+
+    pub fn column_count(mut self, column_count: usize) -> Self {
+        self.config.column_count = column_count.max(1);
+        self
+    }
+
+    pub fn sort_mode(mut self, sort_mode: PlotSortMode) -> Self {
+        self.config.sort_mode = sort_mode;
+        self
+    }
+
+    pub fn show_timeline(mut self, show_timeline: bool) -> Self {
+        self.config.show_timeline = show_timeline;
+        self
+    }
+
+    /// See [`DashboardConfig::show_minimaps`].
+    pub fn show_minimaps(mut self, show_minimaps: bool) -> Self {
+        self.config.show_minimaps = show_minimaps;
+        self
+    }
+
+    /// See [`DashboardConfig::stalled_after`].
+    pub fn stalled_after(mut self, frames: Option<u64>) -> Self {
+        self.config.stalled_after = frames;
+        self
+    }
+
+    /// See [`DashboardConfig::write_diagnostics`].
+    pub fn write_diagnostics(mut self, diagnostics: Option<WriteDiagnostics>) -> Self {
+        self.config.write_diagnostics = diagnostics;
+        self
+    }
+
+    /// See [`DashboardWindow::set_default_pos`].
+    pub fn default_pos(mut self, pos: egui::Pos2) -> Self {
+        self.default_pos = Some(pos);
+        self
+    }
+
+    /// See [`DashboardWindow::set_default_size`].
+    pub fn default_size(mut self, size: egui::Vec2) -> Self {
+        self.default_size = Some(size);
+        self
+    }
+
+    pub fn build(self) -> DashboardWindow {
+        let mut window = DashboardWindow::new(self.title);
+        window.dashboard.config = self.config;
+        window.default_pos = self.default_pos;
+        window.default_size = self.default_size;
+        window
+    }
 }
 
 impl DashboardWindow {
@@ -41,136 +505,1145 @@ impl DashboardWindow {
         Self {
             title: title.into(),
             search_bar: default(),
-            plots: default(),
+            dashboard: default(),
+            default_pos: None,
+            default_size: None,
+            rect: None,
+            content_size: None,
+            bring_to_front: false,
+        }
+    }
+
+    pub fn builder(title: impl Into<String>) -> DashboardWindowBuilder {
+        DashboardWindowBuilder {
+            title: title.into(),
             config: default(),
+            default_pos: None,
+            default_size: None,
         }
     }
 
-    pub(crate) fn update_all(mut windows: Query<&mut Self>) {
-        for mut window in &mut windows {
-            if !window.config.paused {
-                window.update();
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn dashboard(&self) -> &Dashboard {
+        &self.dashboard
+    }
+
+    pub fn dashboard_mut(&mut self) -> &mut Dashboard {
+        &mut self.dashboard
+    }
+
+    /// Where the window is first placed, in `egui` points.
+    ///
+    /// Once the window has been drawn, `egui` remembers where the user moved
+    /// it, and this is ignored.
+    pub fn set_default_pos(&mut self, pos: egui::Pos2) {
+        self.default_pos = Some(pos);
+    }
+
+    /// The size of the window's contents when it's first drawn, in `egui`
+    /// points.
+    pub fn set_default_size(&mut self, size: egui::Vec2) {
+        self.default_size = Some(size);
+    }
+
+    pub fn default_pos(&self) -> Option<egui::Pos2> {
+        self.default_pos
+    }
+
+    pub fn default_size(&self) -> Option<egui::Vec2> {
+        self.default_size
+    }
+
+    /// The area covered by the window when it was last drawn, including its
+    /// title bar.
+    pub fn rect(&self) -> Option<egui::Rect> {
+        self.rect
+    }
+
+    /// The size of the window's contents when it was last drawn, comparable
+    /// to [`default_size`](Self::default_size).
+    pub fn content_size(&self) -> Option<egui::Vec2> {
+        self.content_size
+    }
+
+    /// Draw the window above all other windows on the next frame.
+    pub fn bring_to_front(&mut self) {
+        self.bring_to_front = true;
+    }
+
+    pub(crate) fn expire_all(
+        mut expired: EventReader<MetricExpired>,
+        mut cached_data: ResMut<CachedPlotData>,
+        mut windows: Query<&mut Self>,
+    ) {
+        for MetricExpired { key } in expired.read() {
+            for mut window in &mut windows {
+                window.dashboard.metric_expired(key);
+            }
+            if let Some((_, plot)) = cached_data.plots.get_mut(key) {
+                plot.metric_expired(key);
             }
         }
     }
 
-    pub(crate) fn update(&mut self) {
-        for plot in &mut self.plots {
-            plot.update();
+    pub(crate) fn update_all(snapshot: Res<FrameSnapshot>, mut windows: Query<&mut Self>) {
+        for mut window in &mut windows {
+            window.dashboard.update_from_snapshot(&snapshot);
         }
     }
 
-    pub(crate) fn draw_all(
-        mut commands: Commands,
+    /// Add the plots of [`RequestPlot`] and [`RequestPlots`] events to every
+    /// window, even while windows are hidden.
+    pub(crate) fn apply_requests_all(
         registry: Res<MetricsRegistry>,
-        mut cached_configs: ResMut<CachedPlotConfigs>,
-        mut ctxts: EguiContexts,
+        cached_configs: Res<CachedPlotConfigs>,
+        mut cached_data: ResMut<CachedPlotData>,
         mut requests: EventReader<RequestPlot>,
-        mut windows: Query<(Entity, &mut Self)>,
+        mut batches: EventReader<RequestPlots>,
+        mut windows: Query<&mut Self>,
     ) {
-        let requests: Vec<_> = requests.read().cloned().collect();
+        let requests: Vec<_> = requests
+            .read()
+            .filter(|request| !request.wait_for_metric)
+            .map(|request| (request.key.clone(), request.unit))
+            .chain(batches.read().flat_map(|batch| batch.0.iter().cloned()))
+            .collect();
 
-        let ctxt = ctxts.ctx_mut();
-        for (entity, mut window) in &mut windows {
-            for RequestPlot { key, unit } in requests.iter().cloned() {
-                window.add_plot(&registry, &cached_configs, key, unit);
-            }
+        for mut window in &mut windows {
+            window.dashboard.apply_plot_requests(
+                &registry,
+                &cached_configs,
+                &mut cached_data,
+                requests.iter().cloned(),
+            );
+        }
+    }
 
+    pub(crate) fn draw_all(
+        mut commands: Commands,
+        mut resources: DashboardSystemResources,
+        read_only: Res<ReadOnlyDashboards>,
+        cardinality: Option<Res<CardinalityWarnings>>,
+        timeline: Option<Res<SessionTimeline>>,
+        mut ctxts: EguiContexts,
+        mut windows: Query<(Entity, &mut Self, Option<&DashboardTarget>)>,
+    ) {
+        for (entity, mut window, target) in &mut windows {
+            let Some(ctxt) = DashboardTarget::ctx(target, &mut ctxts) else {
+                continue;
+            };
             let mut open = true;
-            egui::Window::new(&window.title)
-                .open(&mut open)
-                .show(ctxt, |ui| {
-                    ui.horizontal(|ui| {
-                        window.add_search_results(&registry, &cached_configs, ui);
-                        if ui.button("Browse").clicked() {
-                            commands.spawn(NamespaceTreeWindow::new("Namespace Viewer"));
-                        }
-                    });
-                    ui.collapsing("Global Settings", |ui| {
-                        window.configure_ui(ui);
-                    });
-                    ui.separator();
-                    window.draw_plots(&mut cached_configs, ui);
-                });
+            let window = &mut *window;
+            let mut egui_window =
+                DashboardTarget::constrain(target, egui::Window::new(&window.title));
+            if let Some(pos) = window.default_pos {
+                egui_window = egui_window.default_pos(pos);
+            }
+            if let Some(size) = window.default_size {
+                egui_window = egui_window.default_size(size);
+            }
+            let response = egui_window.open(&mut open).show(ctxt, |ui| {
+                window.content_size = Some(ui.max_rect().size());
+                if let Some(cardinality) = &cardinality {
+                    cardinality.draw_banner(ui);
+                }
+                if let Some(timeline) = timeline
+                    .as_ref()
+                    .filter(|_| window.dashboard.config.show_timeline)
+                {
+                    if let Some(time) = timeline.draw(ui) {
+                        window.dashboard.jump_to(time);
+                    }
+                }
+                let resources = resources.dashboard_resources();
+                // Only read-only while drawn, so the window's own config is
+                // kept, e.g. when exported.
+                let config_read_only = window.dashboard.config.read_only;
+                window.dashboard.config.read_only |= read_only.0;
+                let response =
+                    draw_dashboard(&mut window.dashboard, &mut window.search_bar, resources, ui);
+                window.dashboard.config.read_only = config_read_only;
+                if response.browse_clicked {
+                    let tree = NamespaceTreeWindow::new(tr(ui, "Namespace Viewer"));
+                    let mut tree_entity = commands.spawn(tree);
+                    if let Some(&target) = target {
+                        tree_entity.insert(target);
+                    }
+                }
+            });
+            if let Some(response) = response {
+                window.rect = Some(response.response.rect);
+                if std::mem::take(&mut window.bring_to_front) {
+                    ctxt.move_to_top(response.response.layer_id);
+                }
+            }
             if !open {
                 commands.entity(entity).despawn();
             }
         }
     }
+}
+
+/// The plots of a dashboard and their shared settings, without any UI.
+///
+/// This can be driven programmatically, e.g. from tests and tools, and drawn
+/// with [`draw_dashboard`].
+#[derive(Default)]
+pub struct Dashboard {
+    plots: Vec<MetricPlot>,
+    custom_series: Vec<CustomSeries>,
+    multi_series_plots: Vec<MultiSeriesPlot>,
+    config: DashboardConfig,
+    /// Metrics added since requests were last applied, and at most since the
+    /// `egui` pass `recently_added_pass`, so a request sent by another widget
+    /// in the same frame doesn't add them again.
+    recently_added: Vec<MetricKey>,
+    recently_added_pass: u64,
+    focused_plot: Option<MetricKey>,
+}
+
+/// An action on a single metric plot, triggered by pressing its key while the
+/// plot is hovered, or else [focused](Dashboard::focused_plot).
+///
+/// Shortcuts are ignored while the pointer isn't over the dashboard or a text
+/// field has keyboard focus, and only
+/// [`Pause`](Self::Pause) works in a read-only dashboard.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PlotShortcut {
+    /// P: pause or resume the plot.
+    Pause,
+    /// R: remove the plot.
+    Remove,
+    /// D: toggle plotting the derivative.
+    Derivative,
+    /// S: open the plot and its settings.
+    Settings,
+}
+
+impl PlotShortcut {
+    pub const ALL: [Self; 4] = [Self::Pause, Self::Remove, Self::Derivative, Self::Settings];
+
+    pub fn key(self) -> egui::Key {
+        match self {
+            Self::Pause => egui::Key::P,
+            Self::Remove => egui::Key::R,
+            Self::Derivative => egui::Key::D,
+            Self::Settings => egui::Key::S,
+        }
+    }
+
+    /// The shortcut pressed this frame without modifiers, if any.
+    fn pressed(ui: &Ui) -> Option<Self> {
+        if ui.ctx().wants_keyboard_input() {
+            return None;
+        }
+        ui.input(|i| {
+            Self::ALL
+                .into_iter()
+                .find(|s| i.modifiers.is_none() && i.key_pressed(s.key()))
+        })
+    }
+}
+
+impl Dashboard {
+    pub fn new() -> Self {
+        default()
+    }
+
+    pub fn plots(&self) -> &[MetricPlot] {
+        &self.plots
+    }
+
+    pub fn plots_mut(&mut self) -> &mut [MetricPlot] {
+        &mut self.plots
+    }
+
+    /// The metric of every plot, followed by the metrics of every
+    /// multi-series plot. A metric is listed once per plot of it.
+    pub fn plotted_metrics(&self) -> impl Iterator<Item = &MetricKey> {
+        let multi_series = self
+            .multi_series_plots
+            .iter()
+            .flat_map(|plot| plot.series().iter().map(|(series, _)| series.key()));
+        self.plots.iter().map(MetricPlot::key).chain(multi_series)
+    }
 
-    pub(crate) fn add_search_results(
+    pub fn config(&self) -> &DashboardConfig {
+        &self.config
+    }
+
+    pub fn config_mut(&mut self) -> &mut DashboardConfig {
+        &mut self.config
+    }
+
+    /// Add a new plot of the metric identified by `key`.
+    ///
+    /// If the dashboard already has a plot of this metric, the new plot gets a
+    /// unique name.
+    pub fn add_plot(
         &mut self,
         registry: &MetricsRegistry,
-        cached_configs: &CachedPlotConfigs,
-        ui: &mut Ui,
-    ) {
-        let Some(selected) = self.search_bar.draw(registry, ui) else {
+        key: MetricKey,
+        unit: Option<Unit>,
+        config: MetricPlotConfig,
+    ) -> &mut MetricPlot {
+        let plot = MetricPlot::new(registry, "", key, unit, config);
+        self.insert_plot(plot)
+    }
+
+    /// Add an existing plot, renaming it after its metric.
+    pub fn insert_plot(&mut self, mut plot: MetricPlot) -> &mut MetricPlot {
+        // If we already have this metric, give it a unique name.
+        let n_duplicates = self.plots.iter().filter(|p| p.key() == plot.key()).count();
+        plot.set_name(plot.key().title(None, n_duplicates));
+        self.plots.push(plot);
+        self.plots.last_mut().unwrap()
+    }
+
+    /// Remove and return the plot at `index`, finishing its recording.
+    ///
+    /// # Panics
+    ///
+    /// If `index` is out of bounds.
+    pub fn remove_plot(&mut self, index: usize) -> MetricPlot {
+        let mut plot = self.plots.remove(index);
+        finish_recording(&mut plot);
+        plot
+    }
+
+    pub fn custom_series(&self) -> &[CustomSeries] {
+        &self.custom_series
+    }
+
+    pub fn custom_series_mut(&mut self) -> &mut [CustomSeries] {
+        &mut self.custom_series
+    }
+
+    /// Add a plot of values that don't come from the registry.
+    pub fn add_custom_series(&mut self, series: CustomSeries) -> &mut CustomSeries {
+        self.custom_series.push(series);
+        self.custom_series.last_mut().unwrap()
+    }
+
+    /// Remove and return the custom series at `index`.
+    ///
+    /// # Panics
+    ///
+    /// If `index` is out of bounds.
+    pub fn remove_custom_series(&mut self, index: usize) -> CustomSeries {
+        self.custom_series.remove(index)
+    }
+
+    pub fn multi_series_plots(&self) -> &[MultiSeriesPlot] {
+        &self.multi_series_plots
+    }
+
+    pub fn multi_series_plots_mut(&mut self) -> &mut [MultiSeriesPlot] {
+        &mut self.multi_series_plots
+    }
+
+    /// Add a chart of several metrics, e.g. with different units.
+    pub fn add_multi_series_plot(&mut self, plot: MultiSeriesPlot) -> &mut MultiSeriesPlot {
+        self.multi_series_plots.push(plot);
+        self.multi_series_plots.last_mut().unwrap()
+    }
+
+    /// Remove and return the multi-series plot at `index`.
+    ///
+    /// # Panics
+    ///
+    /// If `index` is out of bounds.
+    pub fn remove_multi_series_plot(&mut self, index: usize) -> MultiSeriesPlot {
+        self.multi_series_plots.remove(index)
+    }
+
+    pub fn clear(&mut self) {
+        for mut plot in self.plots.drain(..) {
+            finish_recording(&mut plot);
+        }
+        self.custom_series.clear();
+        self.multi_series_plots.clear();
+    }
+
+    /// Pull data into all plots, unless the dashboard is paused, in which case
+    /// only [recordings](MetricPlot::start_recording) are written.
+    ///
+    /// This should run in the [`Last`] schedule **before**
+    /// [`ClearBucketsSystem`](crate::ClearBucketsSystem) to ensure no data
+    /// is missed.
+    pub fn update(&mut self) {
+        self.update_with(None);
+    }
+
+    /// Like [`Self::update`], but counters and gauges are read from
+    /// `snapshot`.
+    pub fn update_from_snapshot(&mut self, snapshot: &FrameSnapshot) {
+        self.update_with(Some(snapshot));
+    }
+
+    fn update_with(&mut self, snapshot: Option<&FrameSnapshot>) {
+        if self.config.paused {
+            for plot in &mut self.plots {
+                plot.record(snapshot);
+            }
             return;
-        };
+        }
+        for plot in &mut self.plots {
+            plot.update_with(snapshot);
+        }
+        for series in &mut self.custom_series {
+            series.update();
+        }
+        for plot in &mut self.multi_series_plots {
+            plot.update_with(snapshot);
+        }
+    }
 
-        self.add_plot(
-            registry,
-            cached_configs,
-            selected.key,
-            selected.description.and_then(|d| d.unit),
-        );
+    /// Follow the deletion of the metric identified by `key` from the
+    /// registry, see [`MetricPlot::metric_expired`].
+    pub fn metric_expired(&mut self, key: &MetricKey) {
+        for plot in &mut self.plots {
+            plot.metric_expired(key);
+        }
+        for plot in &mut self.multi_series_plots {
+            plot.metric_expired(key);
+        }
+    }
+
+    /// Add a plot for each requested metric, like the [`RequestPlot`] and
+    /// [`RequestPlots`] events.
+    ///
+    /// Metrics requested more than once, or already added from the search bar
+    /// since the last call, are only added once.
+    pub fn apply_plot_requests(
+        &mut self,
+        registry: &MetricsRegistry,
+        cached_configs: &CachedPlotConfigs,
+        cached_data: &mut CachedPlotData,
+        requests: impl IntoIterator<Item = (MetricKey, Option<Unit>)>,
+    ) {
+        for (key, unit) in requests {
+            if !self.recently_added.contains(&key) {
+                self.add_plot_or_restore(registry, cached_configs, cached_data, key, unit);
+            }
+        }
+        self.recently_added.clear();
     }
 
-    fn add_plot(
+    /// Add a plot, preferring removed plots from `cached_data` and configs
+    /// from `cached_configs`.
+    fn add_plot_or_restore(
         &mut self,
         registry: &MetricsRegistry,
         cached_configs: &CachedPlotConfigs,
+        cached_data: &mut CachedPlotData,
         key: MetricKey,
         unit: Option<Unit>,
     ) {
-        // If we already have this metric, give it a unique name.
-        let n_duplicates = self.plots.iter().filter(|p| p.key() == &key).count();
+        self.recently_added.push(key.clone());
+        if let Some(plot) = cached_data.take(&key) {
+            self.insert_plot(plot);
+            return;
+        }
 
         let plot_config = cached_configs
             .get(&key)
             .cloned()
             .unwrap_or_else(|| MetricPlotConfig::default_for_kind(key.kind));
-        self.plots.push(MetricPlot::new(
-            registry,
-            key.title(None, n_duplicates),
-            key,
-            unit,
-            plot_config,
-        ));
+        self.add_plot(registry, key, unit, plot_config);
     }
 
-    pub(crate) fn configure_ui(&mut self, ui: &mut Ui) {
-        ui.checkbox(&mut self.config.paused, "Pause");
+    fn configure_ui(&mut self, ui: &mut Ui) {
+        ui.checkbox(&mut self.config.paused, tr(ui, "Pause"));
 
         let mut lock_window_size = self.config.global_window_size.is_some();
-        ui.checkbox(&mut lock_window_size, "Link X Axes");
+        ui.checkbox(&mut lock_window_size, tr(ui, "Link X Axes"));
         if lock_window_size {
             let window_size = self.config.global_window_size.get_or_insert(500);
-            ui.add(window_size_slider(window_size));
+            ui.add(window_size_slider(window_size, ui));
         } else {
             self.config.global_window_size = None;
         }
+        ui.checkbox(&mut self.config.align_by_frame, tr(ui, "Align by Frame"))
+            .on_hover_text(tr(
+                ui,
+                "Plot samples at the frame they were recorded on, so all plots line up",
+            ));
+
+        ui.checkbox(&mut self.config.show_timeline, tr(ui, "Show Timeline"));
+        ui.checkbox(&mut self.config.show_minimaps, tr(ui, "Mini-maps"))
+            .on_hover_text(tr(
+                ui,
+                "Show the whole window under each line plot, with the visible region highlighted",
+            ));
+
+        let mut write_diagnostics = self.config.write_diagnostics.is_some();
+        ui.checkbox(&mut write_diagnostics, tr(ui, "Write Counts"))
+            .on_hover_text(tr(
+                ui,
+                "Show how often plotted metrics are written, flagging hot and dead ones",
+            ));
+        if write_diagnostics {
+            self.config.write_diagnostics.get_or_insert_with(default);
+        } else {
+            self.config.write_diagnostics = None;
+        }
+
+        ui.add(
+            DragValue::new(&mut self.config.column_count)
+                .range(1..=8)
+                .prefix(tr(ui, "Columns: ")),
+        );
+
+        let sort_mode = &mut self.config.sort_mode;
+        egui::ComboBox::from_label(tr(ui, "Sort By"))
+            .selected_text(tr(ui, sort_mode.label()))
+            .show_ui(ui, |ui| {
+                for mode in PlotSortMode::ALL {
+                    ui.selectable_value(sort_mode, mode, tr(ui, mode.label()));
+                }
+            });
+    }
+
+    /// Pause the dashboard and center every plot on the samples recorded at
+    /// `time`, e.g. a moment clicked on the [`SessionTimeline`].
+    ///
+    /// Plots only keep the samples in their window, so they can only jump as
+    /// far back as their window size allows.
+    pub fn jump_to(&mut self, time: Instant) {
+        self.config.paused = true;
+        for plot in &mut self.plots {
+            plot.focus_on(time);
+        }
+        for series in &mut self.custom_series {
+            series.focus_on(time);
+        }
+        for plot in &mut self.multi_series_plots {
+            plot.focus_on(time);
+        }
+    }
+
+    /// Apply `edit` to the config of every plot, and of every config in
+    /// `cached_configs`, so re-added plots keep the change too.
+    ///
+    /// `edit` returns `true` if it changed the config, which resets that
+    /// plot's data. Match on the config to only change plots of one kind, e.g.
+    /// to set the smoothing of all gauges.
+    pub fn edit_all_configs(
+        &mut self,
+        cached_configs: &mut CachedPlotConfigs,
+        mut edit: impl FnMut(&mut MetricPlotConfig) -> bool,
+    ) {
+        for plot in &mut self.plots {
+            let mut config = plot.clone_config();
+            if edit(&mut config) {
+                plot.set_config(config);
+            }
+        }
+        for config in cached_configs.values_mut() {
+            edit(config);
+        }
+    }
+
+    /// Set the smoothing weight of every gauge plotted as a line.
+    pub fn set_all_gauge_smoothing(&mut self, cached_configs: &mut CachedPlotConfigs, weight: f64) {
+        self.edit_all_configs(cached_configs, |config| {
+            if let MetricPlotConfig::Gauge(config) = config {
+                config.smoothing_weight = weight;
+                return true;
+            }
+            false
+        });
+    }
+
+    /// Set the window size of every histogram plot, including gauges plotted
+    /// as histograms. `None` keeps all samples.
+    pub fn set_all_histogram_windows(
+        &mut self,
+        cached_configs: &mut CachedPlotConfigs,
+        window_size: Option<usize>,
+    ) {
+        self.edit_all_configs(cached_configs, |config| {
+            if let MetricPlotConfig::Histogram(config) = config {
+                config.window_size = window_size;
+                return true;
+            }
+            false
+        });
     }
 
-    pub(crate) fn draw_plots(&mut self, cached_configs: &mut CachedPlotConfigs, ui: &mut Ui) {
+    /// The metric plot that [`PlotShortcut`]s apply to when no plot is
+    /// hovered. Clicking a plot focuses it, and clicking anywhere else or
+    /// moving the pointer off the dashboard clears the focus, so only one
+    /// dashboard has a focused plot.
+    pub fn focused_plot(&self) -> Option<&MetricKey> {
+        self.focused_plot.as_ref()
+    }
+
+    pub fn set_focused_plot(&mut self, key: Option<MetricKey>) {
+        self.focused_plot = key;
+    }
+
+    /// Apply `shortcut` to the metric plot at `index`. For
+    /// [`PlotShortcut::Remove`], the plot is removed and cached like with its
+    /// close button, so re-adding the metric restores it.
+    ///
+    /// Returns `false` if the shortcut doesn't apply to this plot, or isn't
+    /// allowed because the dashboard is read-only.
+    pub fn apply_shortcut(
+        &mut self,
+        index: usize,
+        shortcut: PlotShortcut,
+        cached_configs: &mut CachedPlotConfigs,
+        cached_data: &mut CachedPlotData,
+    ) -> bool {
+        if self.config.read_only && shortcut != PlotShortcut::Pause {
+            return false;
+        }
+        let plot = &mut self.plots[index];
+        match shortcut {
+            PlotShortcut::Pause => plot.set_paused(!plot.is_paused()),
+            PlotShortcut::Remove => self.remove_to_cache(index, cached_configs, cached_data),
+            PlotShortcut::Derivative => return plot.toggle_derivative(),
+            PlotShortcut::Settings => plot.open_settings(),
+        }
+        true
+    }
+
+    fn remove_to_cache(
+        &mut self,
+        index: usize,
+        cached_configs: &mut CachedPlotConfigs,
+        cached_data: &mut CachedPlotData,
+    ) {
+        let plot = self.remove_plot(index);
+        cached_configs.insert(plot.key().clone(), plot.clone_config());
+        cached_data.insert(plot);
+    }
+
+    /// The indices of the metric plots in the order they're drawn.
+    pub fn plot_order(&self) -> Vec<usize> {
+        self.config.sort_mode.order(&self.plots)
+    }
+
+    fn draw_plots(&mut self, resources: DashboardResources, ui: &mut Ui) {
+        let DashboardResources {
+            registry,
+            cached_configs,
+            cached_data,
+            formatters,
+            templates,
+            palette,
+        } = resources;
         let mut remove_plots = Vec::new();
+        let mut remove_series = Vec::new();
+        let mut remove_multi_series = Vec::new();
+        let mut hovered_plot = None;
+        let mut shortcut = None;
+        let pointer_over_dashboard = ui.rect_contains_pointer(ui.max_rect());
 
+        let n_columns = self.config.column_count.max(1);
+        let plot_order = self.plot_order();
         egui::ScrollArea::vertical().show(ui, |ui| {
-            for (i, plot) in self.plots.iter_mut().enumerate().rev() {
-                // TODO: avoid string copy here?
-                ui.collapsing(plot.name().to_owned(), |ui| {
-                    if ui.button("Remove").clicked() {
-                        remove_plots.push(i);
+            ui.columns(n_columns, |columns| {
+                let mut column = (0..n_columns).cycle();
+                for (i, series) in self.custom_series.iter_mut().enumerate().rev() {
+                    let ui = &mut columns[column.next().unwrap()];
+                    let id = ui.make_persistent_id(("custom_series", series.name()));
+                    CollapsingState::load_with_default_open(ui.ctx(), id, false)
+                        .show_header(ui, |ui| {
+                            ui.label(series.name());
+                            chip("custom", Color32::LIGHT_RED, ui);
+                        })
+                        .body(|ui| {
+                            if !self.config.read_only && ui.button(tr(ui, "Remove")).clicked() {
+                                remove_series.push(i);
+                            }
+                            series.draw(&self.config, formatters, palette, ui);
+                        });
+                }
+                for (i, plot) in self.multi_series_plots.iter_mut().enumerate().rev() {
+                    let ui = &mut columns[column.next().unwrap()];
+                    let id = ui.make_persistent_id(("multi_series", plot.name()));
+                    CollapsingState::load_with_default_open(ui.ctx(), id, false)
+                        .show_header(ui, |ui| {
+                            ui.label(plot.name());
+                            for (series, _) in plot.series() {
+                                draw_metric_chips(series.key().kind, series.unit(), ui);
+                            }
+                        })
+                        .body(|ui| {
+                            if !self.config.read_only && ui.button(tr(ui, "Remove")).clicked() {
+                                remove_multi_series.push(i);
+                            }
+                            plot.draw(&self.config, formatters, palette, ui);
+                        });
+                }
+                for i in plot_order {
+                    let plot = &mut self.plots[i];
+                    let ui = &mut columns[column.next().unwrap()];
+                    let id = ui.make_persistent_id(plot.name());
+                    let mut state = CollapsingState::load_with_default_open(ui.ctx(), id, false);
+                    if plot.settings_requested() {
+                        state.set_open(true);
+                    }
+                    let (_, header, body) = state
+                        .show_header(ui, |ui| {
+                            let header = ui.label(plot.name());
+                            if !plot.note().is_empty() {
+                                header.on_hover_text(plot.note());
+                            }
+                            draw_metric_chips(plot.key().kind, plot.unit(), ui);
+                            if plot.is_paused() {
+                                chip(&tr(ui, "paused"), Color32::LIGHT_BLUE, ui);
+                            }
+                            let idle = registry.generations_since_update(plot.key());
+                            if let (Some(idle), Some(after)) = (idle, self.config.stalled_after) {
+                                if idle >= after {
+                                    chip(&tr(ui, "stalled"), Color32::GOLD, ui);
+                                }
+                            }
+                            if let Some(diagnostics) = &self.config.write_diagnostics {
+                                if let Some(stats) = registry.write_stats(plot.key()) {
+                                    write_stats_chip(diagnostics, &stats, ui);
+                                }
+                            }
+                        })
+                        .body(|ui| {
+                            if !plot.note().is_empty() {
+                                ui.label(egui::RichText::new(plot.note()).weak().italics());
+                            }
+                            if !self.config.read_only {
+                                ui.horizontal(|ui| {
+                                    if ui.button(tr(ui, "Remove")).clicked() {
+                                        remove_plots.push(i);
+                                    }
+                                    if !plot.is_histogram() {
+                                        event_counter_menu(plot, registry, ui);
+                                    }
+                                });
+                            }
+                            if plot.key().key.labels().len() > 0 {
+                                ui.horizontal_wrapped(|ui| {
+                                    for label in plot.key().key.labels() {
+                                        metric_label(ui, label);
+                                    }
+                                });
+                            }
+
+                            plot.draw(&self.config, formatters, templates, palette, ui);
+                        });
+                    let mut rect = header.response.rect;
+                    if let Some(body) = body {
+                        rect = rect.union(body.response.rect);
                     }
+                    if ui.rect_contains_pointer(rect) {
+                        hovered_plot = Some(i);
+                    }
+                }
+                shortcut = PlotShortcut::pressed(&columns[0]);
+            });
+        });
+
+        if !pointer_over_dashboard {
+            // Keys pressed elsewhere, e.g. during gameplay, aren't shortcuts.
+            self.focused_plot = None;
+            shortcut = None;
+        } else if ui.input(|i| i.pointer.primary_clicked()) {
+            self.focused_plot = hovered_plot.map(|i| self.plots[i].key().clone());
+        }
+        let shortcut_plot = hovered_plot.or_else(|| {
+            let focused = self.focused_plot.as_ref()?;
+            self.plots.iter().position(|p| p.key() == focused)
+        });
+        if let (Some(shortcut), Some(i)) = (shortcut, shortcut_plot) {
+            if shortcut == PlotShortcut::Remove {
+                if !self.config.read_only {
+                    remove_plots.push(i);
+                }
+            } else {
+                self.apply_shortcut(i, shortcut, cached_configs, cached_data);
+            }
+        }
+
+        for i in remove_series {
+            self.custom_series.remove(i);
+        }
+        for i in remove_multi_series {
+            self.multi_series_plots.remove(i);
+        }
+        // Remove from the back, so the remaining indices stay valid.
+        remove_plots.sort_unstable_by(|a, b| b.cmp(a));
+        for i in remove_plots {
+            self.remove_to_cache(i, cached_configs, cached_data);
+        }
+    }
+}
+
+fn finish_recording(plot: &mut MetricPlot) {
+    if let Err(e) = plot.stop_recording() {
+        error!("Failed to finish recording {}: {e}", plot.name());
+    }
+}
+
+/// Shows how often a plotted metric is written, flagged if hot or dead.
+fn write_stats_chip(diagnostics: &WriteDiagnostics, stats: &WriteStats, ui: &mut Ui) {
+    let (text, fill) = if diagnostics.is_dead(stats) {
+        (tr(ui, "dead"), Color32::GRAY)
+    } else {
+        let text = format!("{} {}", stats.writes_per_frame, tr(ui, "writes/frame"));
+        if diagnostics.is_hot(stats) {
+            (text, Color32::LIGHT_RED)
+        } else {
+            (text, Color32::LIGHT_GRAY)
+        }
+    };
+    ui.scope(|ui| chip(&text, fill, ui))
+        .response
+        .on_hover_text(format!(
+            "{}: {}",
+            tr(ui, "Max writes/frame"),
+            stats.max_writes_per_frame
+        ));
+}
 
-                    plot.draw(&self.config, ui);
+/// Lists the hot and dead metrics of the whole registry.
+fn write_diagnostics_ui(diagnostics: &WriteDiagnostics, registry: &MetricsRegistry, ui: &mut Ui) {
+    let mut flagged: Vec<_> = registry
+        .all_write_stats()
+        .into_iter()
+        .filter(|(_, stats)| diagnostics.is_hot(stats) || diagnostics.is_dead(stats))
+        .collect();
+    if flagged.is_empty() {
+        ui.label(tr(ui, "No hot or dead metrics"));
+        return;
+    }
+    flagged.sort_by(|(a, sa), (b, sb)| {
+        sb.writes_per_frame
+            .cmp(&sa.writes_per_frame)
+            .then_with(|| a.key.name().cmp(b.key.name()))
+    });
+    egui::ScrollArea::vertical()
+        .id_salt("write_diagnostics")
+        .max_height(200.0)
+        .show(ui, |ui| {
+            for (key, stats) in flagged {
+                ui.horizontal(|ui| {
+                    let unit = registry
+                        .get_description(&DescriptionKey::from(&key))
+                        .and_then(|d| d.unit);
+                    ui.label(key.title(None, 0));
+                    write_stats_chip(diagnostics, &stats, ui);
+                    plot_button(ui, key, unit);
                 });
             }
         });
+}
 
-        for i in remove_plots {
-            let plot = self.plots.remove(i);
-            cached_configs.insert(plot.key().clone(), plot.clone_config());
+/// A menu choosing the counter whose increases are marked under `plot`.
+fn event_counter_menu(plot: &mut MetricPlot, registry: &MetricsRegistry, ui: &mut Ui) {
+    let label = match plot.event_counter() {
+        Some(key) => format!("{}: {}", tr(ui, "Events"), key.key.name()),
+        None => tr(ui, "Events"),
+    };
+    ui.menu_button(label, |ui| {
+        if plot.event_counter().is_some() && ui.button(tr(ui, "None")).clicked() {
+            plot.set_event_counter(registry, None);
+            ui.close_menu();
         }
+        let mut counters: Vec<_> = registry
+            .all_metrics()
+            .into_iter()
+            .filter(|r| r.key.kind == MetricKind::Counter)
+            .collect();
+        counters.sort_by(|a, b| a.key.key.cmp(&b.key.key));
+        egui::ScrollArea::vertical()
+            .max_height(200.0)
+            .show(ui, |ui| {
+                for result in counters {
+                    let selected = plot.event_counter() == Some(&result.key);
+                    if ui
+                        .selectable_label(selected, result.key.title(None, 0))
+                        .clicked()
+                    {
+                        plot.set_event_counter(registry, Some(result.key));
+                        ui.close_menu();
+                    }
+                }
+            });
+    });
+}
+
+/// Adding more than this many search results at once asks for confirmation.
+const CONFIRM_ADD_ALL_ABOVE: usize = 20;
+
+/// An "Add All Results" button that plots every current search result not
+/// already in the dashboard.
+fn add_all_results_ui(
+    dashboard: &mut Dashboard,
+    search_bar: &SearchBar,
+    registry: &MetricsRegistry,
+    cached_configs: &CachedPlotConfigs,
+    cached_data: &mut CachedPlotData,
+    ui: &mut Ui,
+) {
+    let new_results: Vec<_> = search_bar
+        .results()
+        .iter()
+        .filter(|r| dashboard.plots.iter().all(|p| p.key() != &r.key))
+        .collect();
+    if new_results.is_empty() {
+        return;
+    }
+
+    let confirm_id = ui.make_persistent_id("confirm_add_all_results");
+    let mut confirming = ui.data(|d| d.get_temp(confirm_id)).unwrap_or(false);
+    let mut add = false;
+    ui.horizontal(|ui| {
+        if confirming {
+            ui.label(format!(
+                "{} ({})?",
+                tr(ui, "Add All Results"),
+                new_results.len()
+            ));
+            add = ui.button(tr(ui, "Add")).clicked();
+            if add || ui.button(tr(ui, "Cancel")).clicked() {
+                confirming = false;
+            }
+        } else if ui
+            .button(format!(
+                "{} ({})",
+                tr(ui, "Add All Results"),
+                new_results.len()
+            ))
+            .clicked()
+        {
+            if new_results.len() > CONFIRM_ADD_ALL_ABOVE {
+                confirming = true;
+            } else {
+                add = true;
+            }
+        }
+    });
+    ui.data_mut(|d| d.insert_temp(confirm_id, confirming));
+
+    if add {
+        for result in new_results {
+            dashboard.add_plot_or_restore(
+                registry,
+                cached_configs,
+                cached_data,
+                result.key.clone(),
+                result.description.as_ref().and_then(|d| d.unit),
+            );
+        }
+    }
+}
+
+/// Buttons that apply one setting to every plot of a kind, see
+/// [`Dashboard::edit_all_configs`].
+fn bulk_config_ui(dashboard: &mut Dashboard, cached_configs: &mut CachedPlotConfigs, ui: &mut Ui) {
+    let values_id = ui.make_persistent_id("bulk_config_values");
+    let (mut smoothing, mut histogram_window): (f64, usize) =
+        ui.data_mut(|d| *d.get_temp_mut_or(values_id, (0.8, 1000)));
+
+    ui.horizontal(|ui| {
+        ui.add(
+            DragValue::new(&mut smoothing)
+                .range(0.0..=1.0)
+                .speed(0.01)
+                .prefix(tr(ui, "Smoothing Weight: ")),
+        );
+        if ui.button(tr(ui, "Apply to All Gauges")).clicked() {
+            dashboard.set_all_gauge_smoothing(cached_configs, smoothing);
+        }
+    });
+    ui.horizontal(|ui| {
+        ui.add(
+            DragValue::new(&mut histogram_window)
+                .range(1..=usize::MAX)
+                .prefix(tr(ui, "Window: ")),
+        );
+        if ui.button(tr(ui, "Apply to All Histograms")).clicked() {
+            dashboard.set_all_histogram_windows(cached_configs, Some(histogram_window));
+        }
+    });
+    ui.data_mut(|d| d.insert_temp(values_id, (smoothing, histogram_window)));
+}
+
+/// Pick two counter or gauge plots with the same unit, and add a
+/// [`CustomSeries::difference`] of them.
+fn difference_ui(dashboard: &mut Dashboard, registry: &MetricsRegistry, ui: &mut Ui) {
+    let selection_id = ui.make_persistent_id("difference_selection");
+    let (mut a, mut b): (Option<MetricKey>, Option<MetricKey>) =
+        ui.data_mut(|d| d.get_temp(selection_id).unwrap_or_default());
+    let mut scalars: Vec<(&MetricKey, &str, Option<Unit>)> = Vec::new();
+    for plot in &dashboard.plots {
+        let key = plot.key();
+        if key.kind != MetricKind::Histogram && !scalars.iter().any(|(k, ..)| *k == key) {
+            scalars.push((key, plot.name(), plot.unit()));
+        }
+    }
+    // Plots may have been removed since the metrics were selected.
+    let find = |key: &Option<MetricKey>| {
+        let key = key.as_ref()?;
+        scalars.iter().find(|(k, ..)| *k == key)
+    };
+    if find(&a).is_none() {
+        a = None;
+    }
+    if find(&b).is_none() {
+        b = None;
+    }
+    if scalars.len() < 2 {
+        ui.data_mut(|d| d.insert_temp(selection_id, (a, b)));
+        return;
+    }
+    let name_of = |key: &Option<MetricKey>| find(key).map_or("", |(_, name, _)| name);
+    let unit_of = |key: &Option<MetricKey>| find(key).map(|(.., unit)| *unit);
+
+    let mut add = false;
+    ui.horizontal(|ui| {
+        egui::ComboBox::from_id_salt(selection_id.with("a"))
+            .selected_text(name_of(&a))
+            .show_ui(ui, |ui| {
+                for &(key, name, _) in &scalars {
+                    ui.selectable_value(&mut a, Some(key.clone()), name);
+                }
+            });
+        ui.label("-");
+        egui::ComboBox::from_id_salt(selection_id.with("b"))
+            .selected_text(name_of(&b))
+            .show_ui(ui, |ui| {
+                let unit_a = unit_of(&a);
+                for &(key, name, unit) in &scalars {
+                    if a.as_ref() != Some(key) && unit_a.is_none_or(|u| u == unit) {
+                        ui.selectable_value(&mut b, Some(key.clone()), name);
+                    }
+                }
+            });
+        let valid = a.is_some() && b.is_some() && a != b && unit_of(&a) == unit_of(&b);
+        add = ui
+            .add_enabled(valid, egui::Button::new(tr(ui, "Add Difference")))
+            .on_hover_text(tr(ui, "Plot the first metric minus the second"))
+            .clicked();
+    });
+
+    if let (true, Some(key_a), Some(key_b)) = (add, &a, &b) {
+        let unit = unit_of(&a).flatten();
+        if let Some(mut series) = CustomSeries::difference(registry, key_a, key_b) {
+            if let Some(unit) = unit {
+                series = series.with_unit(unit);
+            }
+            dashboard.add_custom_series(series);
+        }
+        (a, b) = (None, None);
+    }
+    ui.data_mut(|d| d.insert_temp(selection_id, (a, b)));
+}
+
+/// The resources of [`DashboardResources`], for systems drawing dashboards.
+#[derive(SystemParam)]
+pub(crate) struct DashboardSystemResources<'w> {
+    registry: Res<'w, MetricsRegistry>,
+    cached_configs: ResMut<'w, CachedPlotConfigs>,
+    cached_data: ResMut<'w, CachedPlotData>,
+    formatters: Res<'w, ValueFormatters>,
+    templates: Res<'w, PlotTemplates>,
+    palette: Res<'w, Palette>,
+}
+
+impl DashboardSystemResources<'_> {
+    fn dashboard_resources(&mut self) -> DashboardResources<'_> {
+        DashboardResources {
+            registry: &self.registry,
+            cached_configs: &mut self.cached_configs,
+            cached_data: &mut self.cached_data,
+            formatters: &self.formatters,
+            templates: &self.templates,
+            palette: &self.palette,
+        }
+    }
+}
+
+/// The resources needed by [`draw_dashboard`].
+pub struct DashboardResources<'a> {
+    pub registry: &'a MetricsRegistry,
+    pub cached_configs: &'a mut CachedPlotConfigs,
+    pub cached_data: &'a mut CachedPlotData,
+    pub formatters: &'a ValueFormatters,
+    pub templates: &'a PlotTemplates,
+    pub palette: &'a Palette,
+}
+
+/// User interactions from [`draw_dashboard`] that the caller should handle.
+#[derive(Debug, Default)]
+pub struct DashboardResponse {
+    /// The "Browse" button was clicked, requesting a namespace viewer.
+    pub browse_clicked: bool,
+}
+
+/// Draw `dashboard` with a search bar for adding plots, and accept user input.
+///
+/// If the dashboard is [`read_only`](DashboardConfig::read_only), only the
+/// plots are drawn.
+pub fn draw_dashboard(
+    dashboard: &mut Dashboard,
+    search_bar: &mut SearchBar,
+    resources: DashboardResources,
+    ui: &mut Ui,
+) -> DashboardResponse {
+    // Dashboards drawn without applying requests would otherwise keep every
+    // metric added from the search bar.
+    let pass = ui.ctx().cumulative_pass_nr();
+    if dashboard.recently_added_pass != pass {
+        dashboard.recently_added_pass = pass;
+        dashboard.recently_added.clear();
+    }
+
+    let mut response = DashboardResponse::default();
+    if dashboard.config.read_only {
+        dashboard.draw_plots(resources, ui);
+        return response;
+    }
+
+    let registry = resources.registry;
+    ui.horizontal(|ui| {
+        if let Some(selected) = search_bar.draw(registry, ui) {
+            dashboard.add_plot_or_restore(
+                registry,
+                resources.cached_configs,
+                resources.cached_data,
+                selected.key,
+                selected.description.and_then(|d| d.unit),
+            );
+        }
+        add_all_results_ui(
+            dashboard,
+            search_bar,
+            registry,
+            resources.cached_configs,
+            resources.cached_data,
+            ui,
+        );
+        response.browse_clicked = ui.button(tr(ui, "Browse")).clicked();
+    });
+    ui.collapsing(tr(ui, "Global Settings"), |ui| {
+        dashboard.configure_ui(ui);
+        difference_ui(dashboard, registry, ui);
+        bulk_config_ui(dashboard, resources.cached_configs, ui);
+        #[cfg(feature = "serde")]
+        crate::window_export::export_import_ui(dashboard, registry, resources.templates, ui);
+    });
+    if let Some(diagnostics) = dashboard.config.write_diagnostics {
+        ui.collapsing(tr(ui, "Write Diagnostics"), |ui| {
+            write_diagnostics_ui(&diagnostics, registry, ui);
+        });
     }
+    ui.separator();
+    dashboard.draw_plots(resources, ui);
+    response
 }
@@ -1,11 +1,21 @@
+use crate::locale::tr;
 use bevy_egui::egui::*;
 
 /// Returns the iterator element corresponding to the newly-selected item.
+///
+/// The popup is as wide as `drop_from_widget`, wrapping long item text, and
+/// scrolls once it's taller than `max_height`. While it's open, the arrow keys
+/// move the highlighted item, Enter selects it, and Escape closes the popup.
+///
+/// When `n_more > 0`, a note is shown below the items to indicate that some
+/// items were omitted.
 pub fn dropdown_list<T, I, F, S>(
     drop_from_widget: Response,
     ui: &mut Ui,
     id_source: impl std::hash::Hash,
     items: I,
+    n_more: usize,
+    max_height: f32,
     get_text: F,
 ) -> Option<T>
 where
@@ -14,36 +24,81 @@ where
     S: Into<WidgetText>,
 {
     let popup_id = ui.make_persistent_id(id_source);
+    let highlight_id = popup_id.with("highlight");
 
-    if drop_from_widget.clicked() {
+    let typed = drop_from_widget.changed() && drop_from_widget.has_focus();
+    if drop_from_widget.clicked() || typed {
         ui.memory_mut(|m| m.open_popup(popup_id));
+        ui.data_mut(|d| d.insert_temp(highlight_id, 0usize));
+    }
+    if !ui.memory(|m| m.is_popup_open(popup_id)) {
+        return None;
     }
 
+    let (up, down, enter, escape) = ui.input(|i| {
+        (
+            i.key_pressed(Key::ArrowUp),
+            i.key_pressed(Key::ArrowDown),
+            i.key_pressed(Key::Enter),
+            i.key_pressed(Key::Escape),
+        )
+    });
+    if escape {
+        ui.memory_mut(|m| m.close_popup());
+        return None;
+    }
+    let mut highlight: usize = ui.data(|d| d.get_temp(highlight_id)).unwrap_or(0);
+    if up {
+        highlight = highlight.saturating_sub(1);
+    }
+    if down {
+        highlight += 1;
+    }
+    let frame_margin = Frame::popup(ui.style()).total_margin().sum().x;
+    let max_width = (drop_from_widget.rect.width() - frame_margin).max(0.0);
+
     let mut return_val = None;
+    let mut n_items = 0;
     popup_below_widget(
         ui,
         popup_id,
         &drop_from_widget,
         PopupCloseBehavior::CloseOnClickOutside,
         |ui: &mut Ui| {
-            let select_first = ui.input(|i| i.key_pressed(Key::Enter));
-            ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
-                let mut first = true;
-                for item in items {
-                    let text = get_text(&item);
-
-                    // TODO: implement arrow key browsing, have the one that'd be
-                    // selected by enter highlighted
-                    if ui.selectable_label(false, text.into()).clicked() || (select_first && first)
-                    {
-                        return_val = Some(item);
-                        ui.memory_mut(|m| m.close_popup());
+            ui.set_max_width(max_width);
+            ui.style_mut().wrap_mode = Some(TextWrapMode::Wrap);
+            ScrollArea::vertical()
+                .max_height(max_height)
+                .show(ui, |ui| {
+                    let stripe = ui.visuals().faint_bg_color;
+                    for (i, item) in items.enumerate() {
+                        n_items = i + 1;
+                        let fill = if i % 2 == 1 {
+                            stripe
+                        } else {
+                            Color32::TRANSPARENT
+                        };
+                        let response = Frame::none()
+                            .fill(fill)
+                            .show(ui, |ui| {
+                                ui.selectable_label(i == highlight, get_text(&item).into())
+                            })
+                            .inner;
+                        if i == highlight && (up || down) {
+                            response.scroll_to_me(None);
+                        }
+                        if response.clicked() || (enter && i == highlight) {
+                            return_val = Some(item);
+                            ui.memory_mut(|m| m.close_popup());
+                        }
+                    }
+                    if n_more > 0 {
+                        ui.weak(format!("{n_more} {}…", tr(ui, "more")));
                     }
-                    first = false;
-                }
-            });
+                });
         },
     );
 
+    ui.data_mut(|d| d.insert_temp(highlight_id, highlight.min(n_items.saturating_sub(1))));
     return_val
 }
@@ -0,0 +1,66 @@
+use crate::MetricProducerAppExt;
+use bevy::{prelude::*, utils::HashSet};
+use metrics::{describe_gauge, gauge, Unit};
+
+/// Provides metrics about the memory layout of the ECS [`World`], to watch
+/// archetype fragmentation and storage growth caused by entity churn.
+///
+/// - "ecs_archetypes": the number of archetypes
+/// - "ecs_tables": the number of tables
+/// - "ecs_component_bytes": the approximate bytes allocated for component
+///   values in tables and sparse sets, not counting change ticks or other
+///   bookkeeping, nor heap memory owned by components
+pub struct EcsMemoryMetricsPlugin;
+
+impl Plugin for EcsMemoryMetricsPlugin {
+    fn build(&self, app: &mut App) {
+        app.describe_metrics(describe_ecs_memory_metrics)
+            .add_metric_producer(update_ecs_memory_metrics);
+    }
+}
+
+fn describe_ecs_memory_metrics() {
+    describe_gauge!(
+        "ecs_archetypes",
+        Unit::Count,
+        "The number of archetypes in the world"
+    );
+    describe_gauge!(
+        "ecs_tables",
+        Unit::Count,
+        "The number of tables in the world"
+    );
+    describe_gauge!(
+        "ecs_component_bytes",
+        Unit::Bytes,
+        "Approximate bytes allocated for component storage"
+    );
+}
+
+fn update_ecs_memory_metrics(world: &World) {
+    let archetypes = world.archetypes();
+    let tables = &world.storages().tables;
+    let components = world.components();
+    let component_size = |id| components.get_info(id).map_or(0, |i| i.layout().size());
+
+    // Several archetypes can share a table, so only count each table once.
+    let mut seen_tables = HashSet::new();
+    let mut bytes = 0;
+    for archetype in archetypes.iter() {
+        if !seen_tables.insert(archetype.table_id().as_usize()) {
+            continue;
+        }
+        let Some(table) = tables.get(archetype.table_id()) else {
+            continue;
+        };
+        let row_size: usize = archetype.table_components().map(component_size).sum();
+        bytes += row_size * table.entity_capacity();
+    }
+    for (id, sparse_set) in world.storages().sparse_sets.iter() {
+        bytes += component_size(id) * sparse_set.len();
+    }
+
+    gauge!("ecs_archetypes").set(archetypes.len() as f64);
+    gauge!("ecs_tables").set(tables.len() as f64);
+    gauge!("ecs_component_bytes").set(bytes as f64);
+}
@@ -0,0 +1,76 @@
+use crate::MetricProducerAppExt;
+use bevy::prelude::*;
+use metrics::{counter, describe_counter, describe_gauge, gauge, Unit};
+use std::any::type_name;
+
+/// Describes the metrics written for each event type tracked with
+/// [`EventMetricsAppExt::track_event_metrics`].
+///
+/// Every tracked event type `E` writes, labeled with `event` set to the type
+/// name of `E`:
+///
+/// - counter "events_sent": the total number of events sent
+/// - gauge "events_queued": the number of events still buffered in
+///   [`Events<E>`], which grows without bound if events are never updated or
+///   read
+///
+/// This plugin is added automatically by
+/// [`EventMetricsAppExt::track_event_metrics`].
+pub struct EventMetricsPlugin;
+
+impl Plugin for EventMetricsPlugin {
+    fn build(&self, app: &mut App) {
+        app.describe_metrics(describe_event_metrics);
+    }
+}
+
+fn describe_event_metrics() {
+    describe_counter!("events_sent", Unit::Count, "Events sent, by event type");
+    describe_gauge!(
+        "events_queued",
+        Unit::Count,
+        "Events buffered in their Events resource, by event type"
+    );
+}
+
+/// [`App`] extension for publishing metrics about [`Events`].
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_metrics_dashboard::EventMetricsAppExt;
+/// #[derive(Event)]
+/// struct Explosion;
+///
+/// App::new()
+///     .add_event::<Explosion>()
+///     .track_event_metrics::<Explosion>();
+/// ```
+pub trait EventMetricsAppExt {
+    /// Publish the metrics described in [`EventMetricsPlugin`] for `E`.
+    ///
+    /// Nothing is written while the [`Events<E>`] resource doesn't exist.
+    fn track_event_metrics<E: Event>(&mut self) -> &mut Self;
+}
+
+impl EventMetricsAppExt for App {
+    fn track_event_metrics<E: Event>(&mut self) -> &mut Self {
+        if !self.is_plugin_added::<EventMetricsPlugin>() {
+            self.add_plugins(EventMetricsPlugin);
+        }
+        self.add_metric_producer(update_event_metrics::<E>)
+    }
+}
+
+fn update_event_metrics<E: Event>(events: Option<Res<Events<E>>>, mut last_total: Local<usize>) {
+    let Some(events) = events else {
+        return;
+    };
+    let labels = [("event", type_name::<E>())];
+
+    // Clearing events doesn't reset this total.
+    let total = events.oldest_event_count() + events.len();
+    counter!("events_sent", &labels).increment(total.saturating_sub(*last_total) as u64);
+    *last_total = total;
+
+    gauge!("events_queued", &labels).set(events.len() as f64);
+}
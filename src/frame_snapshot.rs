@@ -0,0 +1,102 @@
+use crate::{
+    registry::{MetricKey, MetricsRegistry},
+    registry_plugin::MetricExpired,
+};
+use bevy::{core::FrameCount, prelude::*, utils::HashMap};
+use metrics::Key;
+use metrics_util::MetricKind;
+use std::sync::atomic::Ordering;
+
+/// The values of all counters and gauges, read once per frame.
+///
+/// Dashboard widgets read from this snapshot instead of loading atomics
+/// individually, so plots of related metrics always show values from the same
+/// instant. The snapshot is captured in the [`Last`] schedule, after the
+/// [`ProduceMetricsSystem`](crate::ProduceMetricsSystem) and before dashboards
+/// are updated.
+///
+/// Histograms are not included, since their samples are already read in
+/// batches.
+#[derive(Default, Resource)]
+pub struct FrameSnapshot {
+    frame: Option<u32>,
+    counters: HashMap<Key, u64>,
+    gauges: HashMap<Key, f64>,
+}
+
+impl FrameSnapshot {
+    /// Read the current values of all counters and gauges in `registry`.
+    pub fn capture(&mut self, registry: &MetricsRegistry) {
+        registry.visit_counters(|key, counter| {
+            let value = counter.load(Ordering::Relaxed);
+            // Avoid cloning keys we've already seen.
+            if let Some(v) = self.counters.get_mut(key) {
+                *v = value;
+            } else {
+                self.counters.insert(key.clone(), value);
+            }
+        });
+        registry.visit_gauges(|key, gauge| {
+            let value = f64::from_bits(gauge.load(Ordering::Relaxed));
+            if let Some(v) = self.gauges.get_mut(key) {
+                *v = value;
+            } else {
+                self.gauges.insert(key.clone(), value);
+            }
+        });
+    }
+
+    /// Forget the value of a metric deleted from the registry, so it isn't
+    /// read instead of a recreated metric with the same key.
+    pub fn remove(&mut self, key: &MetricKey) {
+        match key.kind {
+            MetricKind::Counter => self.counters.remove(&key.key),
+            MetricKind::Gauge => self.gauges.remove(&key.key).map(f64::to_bits),
+            MetricKind::Histogram => None,
+        };
+    }
+
+    /// The [`FrameCount`] on which the snapshot was captured, if known.
+    pub fn frame(&self) -> Option<u32> {
+        self.frame
+    }
+
+    pub fn set_frame(&mut self, frame: Option<u32>) {
+        self.frame = frame;
+    }
+
+    pub fn counter(&self, key: &Key) -> Option<u64> {
+        self.counters.get(key).copied()
+    }
+
+    pub fn gauge(&self, key: &Key) -> Option<f64> {
+        self.gauges.get(key).copied()
+    }
+
+    /// The value of any counter or gauge, converted to `f64`.
+    pub fn get(&self, key: &MetricKey) -> Option<f64> {
+        match key.kind {
+            MetricKind::Counter => self.counter(&key.key).map(|v| v as f64),
+            MetricKind::Gauge => self.gauge(&key.key),
+            MetricKind::Histogram => None,
+        }
+    }
+
+    pub(crate) fn remove_expired_system(
+        mut snapshot: ResMut<Self>,
+        mut expired: EventReader<MetricExpired>,
+    ) {
+        for MetricExpired { key } in expired.read() {
+            snapshot.remove(key);
+        }
+    }
+
+    pub(crate) fn capture_system(
+        mut snapshot: ResMut<Self>,
+        registry: Res<MetricsRegistry>,
+        frame: Option<Res<FrameCount>>,
+    ) {
+        snapshot.capture(&registry);
+        snapshot.frame = frame.map(|f| f.0);
+    }
+}
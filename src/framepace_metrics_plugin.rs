@@ -0,0 +1,88 @@
+use crate::MetricProducerAppExt;
+use bevy::{diagnostic::DiagnosticsStore, prelude::*};
+use bevy_framepace::{debug::DiagnosticsPlugin, FramepaceSettings, FrametimeLimit};
+use metrics::{describe_gauge, gauge, Unit};
+
+/// Provides the state of the [`bevy_framepace`] frame limiter, so the target
+/// frame time can be plotted next to the actual one.
+///
+/// - gauge "framepace_frame_limit": the frame time that the limiter paces to,
+///   or 0 while limiting is off
+/// - gauge "framepace_sleep_time": how long the limiter slept at the end of
+///   the last frame
+///
+/// The sleep time is derived from the diagnostics of
+/// [`bevy_framepace::debug::DiagnosticsPlugin`], so it's only written when
+/// that plugin is added. Add this plugin after the `FramepacePlugin`.
+pub struct FramepaceMetricsPlugin;
+
+impl Plugin for FramepaceMetricsPlugin {
+    fn build(&self, app: &mut App) {
+        app.describe_metrics(describe_framepace_metrics)
+            .add_metric_producer(update_framepace_metrics);
+    }
+}
+
+fn describe_framepace_metrics() {
+    describe_gauge!(
+        "framepace_frame_limit",
+        Unit::Milliseconds,
+        "The frame time targeted by the frame limiter, or 0 if it's off"
+    );
+    describe_gauge!(
+        "framepace_sleep_time",
+        Unit::Milliseconds,
+        "Time the frame limiter slept at the end of the last frame"
+    );
+}
+
+fn update_framepace_metrics(
+    settings: Option<Res<FramepaceSettings>>,
+    limit: Option<Res<FrametimeLimit>>,
+    diagnostics: Option<Res<DiagnosticsStore>>,
+) {
+    let (Some(settings), Some(limit)) = (settings, limit) else {
+        return;
+    };
+    let limit_ms = if settings.limiter.is_enabled() {
+        let Ok(limit) = limit.0.try_lock() else {
+            return;
+        };
+        1000.0 * limit.as_secs_f64()
+    } else {
+        0.0
+    };
+    gauge!("framepace_frame_limit").set(limit_ms);
+
+    let Some(diagnostics) = diagnostics else {
+        return;
+    };
+    let latest = |path| {
+        diagnostics
+            .get(path)
+            .and_then(|d| d.measurement())
+            .map(|m| m.value)
+    };
+    let previous_oversleep_us = diagnostics
+        .get(&DiagnosticsPlugin::FRAMEPACE_OVERSLEEP)
+        .and_then(|d| {
+            let n_values = d.values().count();
+            d.values().nth(n_values.checked_sub(2)?).copied()
+        })
+        .unwrap_or(0.0);
+    // The limiter sleeps for the rest of the limit, less the previous
+    // oversleep, and the frame ends after the limit by the oversleep. If the
+    // frame left no time to sleep, its oversleep is only how late it was.
+    if let (Some(frame_time_ms), Some(oversleep_us)) = (
+        latest(&DiagnosticsPlugin::FRAMEPACE_FRAMETIME),
+        latest(&DiagnosticsPlugin::FRAMEPACE_OVERSLEEP),
+    ) {
+        let slept = limit_ms - frame_time_ms - 1e-3 * previous_oversleep_us > 0.0;
+        let sleep_ms = if slept {
+            limit_ms - frame_time_ms + 1e-3 * oversleep_us
+        } else {
+            0.0
+        };
+        gauge!("framepace_sleep_time").set(sleep_ms);
+    }
+}
@@ -0,0 +1,133 @@
+use bevy::{prelude::*, utils::HashMap};
+use metrics::Key;
+use std::collections::BTreeMap;
+
+/// The ratio between the bounds of consecutive bins, giving quantiles within
+/// about 1% of the true value.
+const GAMMA: f64 = 1.02;
+
+/// Bins beyond this many are merged, starting from the smallest values.
+const MAX_BINS: usize = 512;
+
+/// A fixed-size summary of histogram samples, supporting approximate
+/// quantiles.
+///
+/// Positive samples are counted in logarithmic bins, so quantiles have a
+/// bounded relative error. Samples that aren't positive are only counted
+/// towards the [`Self::min`] end of the distribution.
+#[derive(Clone, Debug, Default)]
+pub struct HistogramSketch {
+    count: u64,
+    sum: f64,
+    min: f64,
+    max: f64,
+    non_positive: u64,
+    bins: BTreeMap<i32, u64>,
+}
+
+impl HistogramSketch {
+    pub fn insert(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        if self.count == 0 {
+            self.min = value;
+            self.max = value;
+        } else {
+            self.min = self.min.min(value);
+            self.max = self.max.max(value);
+        }
+        self.count += 1;
+        self.sum += value;
+
+        if value <= 0.0 {
+            self.non_positive += 1;
+            return;
+        }
+        let index = (value.ln() / GAMMA.ln()).ceil() as i32;
+        *self.bins.entry(index).or_default() += 1;
+        if self.bins.len() > MAX_BINS {
+            let (_, lowest) = self.bins.pop_first().unwrap();
+            *self.bins.first_entry().unwrap().get_mut() += lowest;
+        }
+    }
+
+    fn extend(&mut self, values: &[f64]) {
+        for &value in values {
+            self.insert(value);
+        }
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn sum(&self) -> f64 {
+        self.sum
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.sum / self.count as f64)
+    }
+
+    pub fn min(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.min)
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.max)
+    }
+
+    /// The approximate value below which a fraction `q` of samples fall, or
+    /// `None` if the sketch is empty.
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        if self.count == 0 {
+            return None;
+        }
+        let rank = (q.clamp(0.0, 1.0) * (self.count - 1) as f64).round() as u64;
+        if rank < self.non_positive {
+            return Some(self.min);
+        }
+        let mut seen = self.non_positive;
+        for (&index, &n) in &self.bins {
+            seen += n;
+            if seen > rank {
+                let value = 2.0 * GAMMA.powi(index) / (GAMMA + 1.0);
+                return Some(value.clamp(self.min, self.max));
+            }
+        }
+        Some(self.max)
+    }
+}
+
+/// The [`HistogramSketch`] of every histogram, filled by the
+/// [`ClearBucketsSystem`](crate::ClearBucketsSystem) while the
+/// [`HistogramRetention`](crate::HistogramRetention) is
+/// [`Sketch`](crate::HistogramRetention::Sketch).
+#[derive(Default, Resource)]
+pub struct HistogramSketches {
+    sketches: HashMap<Key, HistogramSketch>,
+}
+
+impl HistogramSketches {
+    pub fn get(&self, key: &Key) -> Option<&HistogramSketch> {
+        self.sketches.get(key)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Key, &HistogramSketch)> {
+        self.sketches.iter()
+    }
+
+    pub fn insert(&mut self, key: &Key, values: &[f64]) {
+        // Avoid cloning keys we've already seen.
+        if let Some(sketch) = self.sketches.get_mut(key) {
+            sketch.extend(values);
+        } else {
+            self.sketches.entry(key.clone()).or_default().extend(values);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.sketches.clear();
+    }
+}
@@ -0,0 +1,236 @@
+//! A ranking of the metrics matching a name pattern.
+
+use crate::{
+    dashboard_target::DashboardTarget, dashboard_window::plot_button,
+    frame_snapshot::FrameSnapshot, locale::tr, metrics_table::SampledMetric,
+    registry::MetricsRegistry, value_formatters::ValueFormatters,
+};
+use bevy::prelude::*;
+use bevy_egui::{
+    egui::{self, TextEdit, Ui},
+    EguiContexts,
+};
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+/// How series are ranked in a [`LeaderboardWindow`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum RankBy {
+    /// The most recent sample.
+    #[default]
+    Latest,
+    /// The mean of the samples in the window.
+    WindowedMean,
+}
+
+/// An `egui` window showing the top N metrics matching a name pattern, e.g.
+/// the most expensive systems out of all `system_time::*` metrics.
+///
+/// The pattern may contain `*` wildcards, which match any sequence of
+/// characters. The registry is rescanned periodically for new matches.
+#[derive(Component)]
+pub struct LeaderboardWindow {
+    title: String,
+    id: egui::Id,
+    pattern: String,
+    top_n: usize,
+    rank_by: RankBy,
+    window_size: usize,
+    rescan_period: Duration,
+    last_scan_time: Option<Instant>,
+    series: Vec<SampledMetric>,
+}
+
+impl LeaderboardWindow {
+    pub fn new(title: impl Into<String>, pattern: impl Into<String>) -> Self {
+        static WINDOW_ID: AtomicU64 = AtomicU64::new(0);
+        let id = WINDOW_ID.fetch_add(1, Ordering::Relaxed);
+        let title = title.into();
+        let id = format!("{title} {id}").into();
+        Self {
+            title,
+            id,
+            pattern: pattern.into(),
+            top_n: 10,
+            rank_by: default(),
+            window_size: 100,
+            rescan_period: Duration::from_secs(1),
+            last_scan_time: None,
+            series: default(),
+        }
+    }
+
+    pub fn with_top_n(mut self, top_n: usize) -> Self {
+        self.top_n = top_n;
+        self
+    }
+
+    pub fn with_rank_by(mut self, rank_by: RankBy) -> Self {
+        self.rank_by = rank_by;
+        self
+    }
+
+    /// Set the number of samples used for [`RankBy::WindowedMean`].
+    pub fn with_window_size(mut self, window_size: usize) -> Self {
+        self.window_size = window_size.max(1);
+        self
+    }
+
+    pub fn set_rescan_period(&mut self, period: Duration) {
+        self.rescan_period = period;
+    }
+
+    fn rescan(&mut self, registry: &MetricsRegistry) {
+        self.last_scan_time = Some(Instant::now());
+        self.series
+            .retain(|s| glob_match(&self.pattern, s.key.key.name()));
+        for result in registry.all_metrics() {
+            if !glob_match(&self.pattern, result.key.key.name())
+                || self.series.iter().any(|s| s.key == result.key)
+            {
+                continue;
+            }
+            let unit = result.description.and_then(|d| d.unit);
+            self.series.push(SampledMetric::new(
+                registry,
+                result.key,
+                unit,
+                self.window_size,
+            ));
+        }
+    }
+
+    pub(crate) fn update_all(
+        registry: Res<MetricsRegistry>,
+        snapshot: Res<FrameSnapshot>,
+        mut windows: Query<&mut Self>,
+    ) {
+        for mut window in &mut windows {
+            let rescan = window
+                .last_scan_time
+                .is_none_or(|t| t.elapsed() > window.rescan_period);
+            if rescan {
+                window.rescan(&registry);
+            }
+            for series in &mut window.series {
+                series.update(&snapshot);
+            }
+        }
+    }
+
+    pub(crate) fn draw_all(
+        mut commands: Commands,
+        formatters: Res<ValueFormatters>,
+        mut ctxts: EguiContexts,
+        mut windows: Query<(Entity, &mut Self, Option<&DashboardTarget>)>,
+    ) {
+        for (entity, mut window, target) in &mut windows {
+            let Some(ctxt) = DashboardTarget::ctx(target, &mut ctxts) else {
+                continue;
+            };
+            let mut open = true;
+            DashboardTarget::constrain(target, egui::Window::new(&window.title))
+                .id(window.id)
+                .open(&mut open)
+                .show(ctxt, |ui| {
+                    window.draw(&formatters, ui);
+                });
+            if !open {
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+
+    /// Draw the widget and accept user input.
+    pub fn draw(&mut self, formatters: &ValueFormatters, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label(tr(ui, "Pattern:"));
+            let response = TextEdit::singleline(&mut self.pattern)
+                .hint_text("system_time::*")
+                .show(ui)
+                .response;
+            if response.changed() {
+                // Rescan on the next update.
+                self.last_scan_time = None;
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::DragValue::new(&mut self.top_n)
+                    .range(1..=100)
+                    .prefix(tr(ui, "Top: ")),
+            );
+            ui.radio_value(&mut self.rank_by, RankBy::Latest, tr(ui, "Latest"));
+            ui.radio_value(
+                &mut self.rank_by,
+                RankBy::WindowedMean,
+                tr(ui, "Windowed Mean"),
+            );
+        });
+        ui.separator();
+
+        let mut ranked: Vec<_> = self
+            .series
+            .iter()
+            .filter_map(|s| {
+                let score = match self.rank_by {
+                    RankBy::Latest => *s.ring.latest()?,
+                    RankBy::WindowedMean => {
+                        let (sum, count) = s
+                            .ring
+                            .iter_chronological()
+                            .fold((0.0, 0), |(sum, count), &v| (sum + v, count + 1));
+                        if count == 0 {
+                            return None;
+                        }
+                        sum / count as f64
+                    }
+                };
+                Some((score, s))
+            })
+            .collect();
+        ranked.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+
+        egui::Grid::new(self.id.with("grid"))
+            .striped(true)
+            .show(ui, |ui| {
+                for (rank, (score, series)) in ranked.into_iter().take(self.top_n).enumerate() {
+                    ui.label(format!("{}", rank + 1));
+                    ui.label(series.key.title(None, 0));
+                    if let Some(formatter) = formatters.get(&series.key, series.unit) {
+                        ui.label(formatter(score));
+                    } else {
+                        ui.label(format!("{score:.3}"));
+                    }
+                    plot_button(ui, series.key.clone(), series.unit);
+                    ui.end_row();
+                }
+            });
+    }
+}
+
+/// Match `name` against `pattern`, where `*` matches any sequence of
+/// characters.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let Some(first) = parts.next() else {
+        return true;
+    };
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts: Vec<_> = parts.collect();
+    let Some(last) = parts.pop() else {
+        // No wildcards.
+        return rest.is_empty();
+    };
+    for part in parts {
+        let Some(i) = rest.find(part) else {
+            return false;
+        };
+        rest = &rest[i + part.len()..];
+    }
+    rest.ends_with(last)
+}
@@ -1,26 +1,118 @@
 #![doc = include_str!("../README.md")]
 
+#[cfg(feature = "serde")]
+pub mod baseline;
+mod cardinality;
+#[cfg(feature = "serde")]
+pub mod chrome_trace;
 mod core_metrics_plugin;
 mod dashboard_plugin;
+mod dashboard_query;
+mod dashboard_target;
 mod dashboard_window;
 mod dropdown_list;
+mod ecs_memory_metrics_plugin;
+mod event_metrics_plugin;
+mod frame_snapshot;
+mod histogram_sketch;
+pub mod leaderboard;
+pub mod locale;
+pub mod merged_registry;
+pub mod metric_picker;
+mod metric_producer;
+mod metric_storage;
+pub mod metric_triggers;
+pub mod metrics_table;
+pub mod multi_recorder;
 pub mod namespace_tree;
+mod ordering_diagnostics;
+pub mod palette;
+pub mod plot_kind;
+mod plot_recording;
+pub mod plot_templates;
 pub mod plots;
+mod pre_registry_buffer;
 pub mod registry;
 mod registry_plugin;
-mod ring;
+pub mod ring;
 pub mod search_bar;
+pub mod session_info;
+mod sharded_counters;
+pub mod spike_log;
+pub mod startup_config;
+mod startup_metrics_plugin;
+pub mod timeline;
+pub mod value_formatters;
+pub mod wide_csv;
+#[cfg(feature = "serde")]
+pub mod window_export;
+mod write_coalescing;
+mod write_counting;
 
+#[cfg(feature = "framepace")]
+mod framepace_metrics_plugin;
+#[cfg(feature = "native_ui")]
+pub mod native_ui;
+#[cfg(feature = "remote")]
+pub mod remote_plugin;
 #[cfg(feature = "render_metrics")]
 mod render_metrics_plugin;
+#[cfg(feature = "terminal")]
+pub mod terminal;
+#[cfg(feature = "gizmos")]
+pub mod world_overlay;
 
+pub use cardinality::{CardinalityWarningPlugin, CardinalityWarnings, MetricCardinalityWarning};
 pub use core_metrics_plugin::CoreMetricsPlugin;
-pub use dashboard_plugin::DashboardPlugin;
-pub use dashboard_window::{CachedPlotConfigs, DashboardWindow, RequestPlot};
-pub use registry_plugin::{ClearBucketsSystem, RegistryPlugin};
+pub use dashboard_plugin::{
+    DashboardActivity, DashboardPlugin, DashboardsVisible, PauseDashboards, ReadOnlyDashboards,
+};
+pub use dashboard_query::{DashboardQuery, PlottedMetric};
+pub use dashboard_target::DashboardTarget;
+pub use dashboard_window::{
+    draw_dashboard, metric_label, plot_button, CachedPlotConfigs, CachedPlotData, Dashboard,
+    DashboardConfig, DashboardResources, DashboardResponse, DashboardWindow,
+    DashboardWindowBuilder, PlotShortcut, PlotSortMode, RequestPlot, RequestPlots, SelectEntity,
+};
+pub use ecs_memory_metrics_plugin::EcsMemoryMetricsPlugin;
+pub use event_metrics_plugin::{EventMetricsAppExt, EventMetricsPlugin};
+pub use frame_snapshot::FrameSnapshot;
+pub use histogram_sketch::{HistogramSketch, HistogramSketches};
+pub use locale::DashboardLocale;
+pub use merged_registry::MergedRegistryPlugin;
+pub use metric_producer::{MetricProducer, MetricProducerAppExt, ProduceMetricsSystem};
+pub use metric_triggers::MetricTriggerPlugin;
+pub use ordering_diagnostics::{OrderingDiagnostics, OrderingProblem};
+pub use palette::Palette;
+pub use plot_templates::PlotTemplates;
+pub use pre_registry_buffer::PreRegistryBuffer;
+pub use registry_plugin::{
+    ClearBucketsPolicy, ClearBucketsSystem, FinalFlush, FinalFlushReason, FinalFlushSystem,
+    HistogramConsumerAppExt, HistogramConsumerId, HistogramConsumers, HistogramRetention,
+    MetricExpired, MetricRegistered, RegistryPlugin,
+};
+pub use startup_metrics_plugin::StartupMetricsPlugin;
+pub use value_formatters::{AxisFormat, ValueFormatters};
+pub use wide_csv::WideCsvPlugin;
+pub use write_coalescing::WriteCoalescing;
+pub use write_counting::{WriteDiagnostics, WriteStats};
 
+#[cfg(feature = "serde")]
+pub use baseline::BaselinePlugin;
+#[cfg(feature = "serde")]
+pub use chrome_trace::ChromeTracePlugin;
+#[cfg(feature = "framepace")]
+pub use framepace_metrics_plugin::FramepaceMetricsPlugin;
+#[cfg(feature = "native_ui")]
+pub use native_ui::NativeDashboardPlugin;
+#[cfg(feature = "remote")]
+pub use remote_plugin::DashboardRemotePlugin;
 #[cfg(feature = "render_metrics")]
 pub use render_metrics_plugin::RenderMetricsPlugin;
+#[cfg(feature = "terminal")]
+pub use terminal::TerminalMetricsPlugin;
+#[cfg(feature = "gizmos")]
+pub use world_overlay::WorldOverlayPlugin;
 
 pub use bevy_egui;
 pub use metrics;
@@ -29,7 +121,6 @@ pub use metrics_util;
 // TODO:
 // - dedup some of the code between gauge and counter widgets
 // - extract egui-only crate (with no bevy dep)
-// - don't assume there is a single egui context
 // - automatic significant figures
 // - automatically determine histogram bucket bounds
 // - search by label and description
@@ -48,6 +139,16 @@ fn metric_kind_str(kind: MetricKind) -> &'static str {
     }
 }
 
+#[cfg(feature = "serde")]
+fn parse_metric_kind(kind: &str) -> Option<MetricKind> {
+    match kind {
+        "counter" => Some(MetricKind::Counter),
+        "gauge" => Some(MetricKind::Gauge),
+        "histogram" => Some(MetricKind::Histogram),
+        _ => None,
+    }
+}
+
 fn unit_str(unit: Unit) -> &'static str {
     match unit {
         Unit::Count => "count",
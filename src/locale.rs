@@ -0,0 +1,115 @@
+//! Rename units and built-in UI strings, e.g. to localize the dashboard.
+
+use crate::unit_str;
+use bevy::{prelude::*, utils::HashMap};
+use bevy_egui::{
+    egui::{Context, Id, Ui},
+    EguiContext,
+};
+use metrics::Unit;
+use std::sync::Arc;
+
+/// Overrides for unit names and the built-in UI strings.
+///
+/// Strings are looked up by their English default, so only the strings you
+/// want to change need an entry:
+///
+/// ```
+/// # use bevy_metrics_dashboard::{locale::DashboardLocale, metrics::Unit};
+/// let locale = DashboardLocale::new()
+///     .with_unit(Unit::CountPerSecond, "fps")
+///     .with_text("Browse", "Durchsuchen");
+///
+/// assert_eq!(locale.unit_str(Unit::CountPerSecond), "fps");
+/// assert_eq!(locale.unit_str(Unit::Milliseconds), "ms");
+/// assert_eq!(locale.text("Browse"), "Durchsuchen");
+/// assert_eq!(locale.text("Pause"), "Pause");
+/// ```
+///
+/// The [`DashboardPlugin`](crate::DashboardPlugin) installs this resource into
+/// every `egui` context, where widgets find it. When drawing widgets without
+/// the plugin, call [`DashboardLocale::install`] instead.
+#[derive(Clone, Debug, Default, Resource)]
+pub struct DashboardLocale {
+    units: HashMap<Unit, String>,
+    texts: HashMap<String, String>,
+}
+
+impl DashboardLocale {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Display `unit` as `name` in axis labels, chips, and search results.
+    pub fn with_unit(mut self, unit: Unit, name: impl Into<String>) -> Self {
+        self.set_unit(unit, name);
+        self
+    }
+
+    /// Replace the built-in string `default` with `text`.
+    pub fn with_text(mut self, default: impl Into<String>, text: impl Into<String>) -> Self {
+        self.set_text(default, text);
+        self
+    }
+
+    pub fn set_unit(&mut self, unit: Unit, name: impl Into<String>) {
+        self.units.insert(unit, name.into());
+    }
+
+    pub fn set_text(&mut self, default: impl Into<String>, text: impl Into<String>) {
+        self.texts.insert(default.into(), text.into());
+    }
+
+    /// The display name of `unit`.
+    pub fn unit_str(&self, unit: Unit) -> &str {
+        self.units
+            .get(&unit)
+            .map_or_else(|| unit_str(unit), String::as_str)
+    }
+
+    /// The replacement for the built-in string `default`, or `default` itself.
+    pub fn text<'a>(&'a self, default: &'a str) -> &'a str {
+        self.texts.get(default).map_or(default, String::as_str)
+    }
+
+    /// Make widgets drawn with `ctx` use this locale.
+    pub fn install(&self, ctx: &Context) {
+        ctx.data_mut(|d| d.insert_temp(locale_id(), Arc::new(self.clone())));
+    }
+
+    /// The locale installed in `ctx`, or the default if there is none.
+    pub fn get(ctx: &Context) -> Arc<Self> {
+        ctx.data(|d| d.get_temp(locale_id())).unwrap_or_default()
+    }
+
+    pub(crate) fn install_system(
+        locale: Res<Self>,
+        mut installed: Local<Option<Arc<Self>>>,
+        mut contexts: Query<&mut EguiContext>,
+    ) {
+        if locale.is_changed() || installed.is_none() {
+            *installed = Some(Arc::new(locale.clone()));
+        }
+        let Some(installed) = &*installed else {
+            return;
+        };
+        for mut ctx in &mut contexts {
+            ctx.get_mut()
+                .data_mut(|d| d.insert_temp(locale_id(), installed.clone()));
+        }
+    }
+}
+
+fn locale_id() -> Id {
+    Id::new("bevy_metrics_dashboard_locale")
+}
+
+/// The localized version of the built-in string `default`.
+pub(crate) fn tr(ui: &Ui, default: &str) -> String {
+    DashboardLocale::get(ui.ctx()).text(default).to_owned()
+}
+
+/// The localized name of `unit`.
+pub(crate) fn tr_unit(ui: &Ui, unit: Unit) -> String {
+    DashboardLocale::get(ui.ctx()).unit_str(unit).to_owned()
+}
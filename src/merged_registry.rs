@@ -0,0 +1,156 @@
+//! Combine metrics from multiple registries into one.
+
+use crate::{
+    frame_snapshot::FrameSnapshot,
+    registry::{DescriptionKey, MetricKey, MetricsRegistry},
+    ClearBucketsSystem, DashboardWindow,
+};
+use bevy::prelude::*;
+use metrics::{Key, Label, SharedString};
+use metrics_util::MetricKind;
+use std::sync::atomic::Ordering;
+
+/// A set of source registries whose metrics are copied into the dashboard's
+/// [`MetricsRegistry`] every frame.
+///
+/// Each copied metric gets an extra label (`source` by default) identifying
+/// which registry it came from, so e.g. client and server metrics of the same
+/// name can be plotted side by side in one window.
+///
+/// Source registries are typically not installed as the global recorder.
+/// Instead, some transport (e.g. a network client) writes snapshots of a
+/// remote process into them. Histogram samples are drained from the sources
+/// when they are merged.
+#[derive(Resource)]
+pub struct MergedRegistryView {
+    label_key: SharedString,
+    sources: Vec<MergeSource>,
+}
+
+/// A registry that is merged into a [`MergedRegistryView`].
+#[derive(Clone)]
+pub struct MergeSource {
+    /// The value of the source label for all metrics from this registry.
+    pub name: SharedString,
+    pub registry: MetricsRegistry,
+}
+
+impl Default for MergedRegistryView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MergedRegistryView {
+    pub fn new() -> Self {
+        Self {
+            label_key: SharedString::const_str("source"),
+            sources: Vec::new(),
+        }
+    }
+
+    /// Use `label_key` instead of "source" for the label added to merged
+    /// metrics.
+    pub fn with_label_key(mut self, label_key: impl Into<SharedString>) -> Self {
+        self.label_key = label_key.into();
+        self
+    }
+
+    /// Add a registry to be merged, with all of its metrics labeled by
+    /// `name`.
+    pub fn add_source(&mut self, name: impl Into<SharedString>, registry: MetricsRegistry) {
+        self.sources.push(MergeSource {
+            name: name.into(),
+            registry,
+        });
+    }
+
+    /// Stop merging the source called `name`.
+    ///
+    /// Metrics that have already been merged remain in the target registry.
+    pub fn remove_source(&mut self, name: &str) {
+        self.sources.retain(|s| s.name.as_ref() != name);
+    }
+
+    pub fn sources(&self) -> &[MergeSource] {
+        &self.sources
+    }
+
+    /// Copy the current values of all source metrics into `target`.
+    ///
+    /// Counters and gauges are overwritten with the source values, while
+    /// histogram samples are moved out of the source buckets. Sources that are
+    /// the same registry as `target` are skipped.
+    pub fn merge_into(&self, target: &MetricsRegistry) {
+        for source in &self.sources {
+            if source.registry.ptr_eq(target) {
+                continue;
+            }
+
+            // Descriptions are copied after visiting, so we don't hold the
+            // source registry's locks any longer than necessary.
+            let mut merged_keys = Vec::new();
+            source.registry.visit_counters(|key, counter| {
+                let merged_key = self.labeled_key(key, &source.name);
+                target
+                    .get_or_create_counter(&merged_key)
+                    .store(counter.load(Ordering::Relaxed), Ordering::Relaxed);
+                merged_keys.push(MetricKey::new(key.clone(), MetricKind::Counter));
+            });
+            source.registry.visit_gauges(|key, gauge| {
+                let merged_key = self.labeled_key(key, &source.name);
+                target
+                    .get_or_create_gauge(&merged_key)
+                    .store(gauge.load(Ordering::Relaxed), Ordering::Relaxed);
+                merged_keys.push(MetricKey::new(key.clone(), MetricKind::Gauge));
+            });
+            source.registry.visit_histograms(|key, histogram| {
+                let merged_key = self.labeled_key(key, &source.name);
+                let target_histogram = target.get_or_create_histogram(&merged_key);
+                histogram.clear_with(|block| {
+                    for &value in block {
+                        target_histogram.push(value);
+                    }
+                });
+                merged_keys.push(MetricKey::new(key.clone(), MetricKind::Histogram));
+            });
+
+            for key in merged_keys {
+                let desc_key = DescriptionKey::from(&key);
+                if let Some(description) = source.registry.get_description(&desc_key) {
+                    target.add_description_if_missing(desc_key, description);
+                }
+            }
+        }
+    }
+
+    fn labeled_key(&self, key: &Key, source_name: &SharedString) -> Key {
+        key.with_extra_labels(vec![Label::new(
+            self.label_key.clone(),
+            source_name.clone(),
+        )])
+    }
+
+    pub(crate) fn merge_system(view: Res<Self>, registry: Res<MetricsRegistry>) {
+        view.merge_into(&registry);
+    }
+}
+
+/// Merges the sources of a [`MergedRegistryView`] resource into the
+/// [`MetricsRegistry`] resource every frame.
+///
+/// Merging happens in the [`Last`] schedule, before dashboards are updated
+/// and before [`ClearBucketsSystem`].
+pub struct MergedRegistryPlugin;
+
+impl Plugin for MergedRegistryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MergedRegistryView>().add_systems(
+            Last,
+            MergedRegistryView::merge_system
+                .before(FrameSnapshot::capture_system)
+                .before(DashboardWindow::update_all)
+                .before(ClearBucketsSystem),
+        );
+    }
+}
@@ -0,0 +1,86 @@
+//! A standard metric chooser for embedding in other UIs, e.g. editors.
+
+use crate::{
+    locale::tr,
+    namespace_tree::NamespaceTree,
+    registry::{MetricsRegistry, SearchResult},
+    search_bar::SearchBar,
+};
+use bevy_egui::egui::Ui;
+
+/// How a [`MetricPicker`] lists metrics.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum PickerMode {
+    /// Fuzzy search with a [`SearchBar`].
+    #[default]
+    Search,
+    /// Browse by namespace with a [`NamespaceTree`].
+    Tree,
+}
+
+/// A widget with tabs to either search for a metric or browse the namespace
+/// tree.
+///
+/// ```no_run
+/// # use bevy_metrics_dashboard::{
+/// #     bevy_egui::egui::Ui, metric_picker::MetricPicker, registry::MetricsRegistry,
+/// # };
+/// # fn ui(picker: &mut MetricPicker, registry: &MetricsRegistry, ui: &mut Ui) {
+/// if let Some(picked) = picker.draw(registry, ui) {
+///     println!("Picked {}", picked.key.key.name());
+/// }
+/// # }
+/// ```
+#[derive(Default)]
+pub struct MetricPicker {
+    mode: PickerMode,
+    search_bar: SearchBar,
+    tree: NamespaceTree,
+}
+
+impl MetricPicker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start on the `mode` tab.
+    pub fn with_mode(mut self, mode: PickerMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn with_search_bar(mut self, search_bar: SearchBar) -> Self {
+        self.search_bar = search_bar;
+        self
+    }
+
+    pub fn mode(&self) -> PickerMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: PickerMode) {
+        self.mode = mode;
+    }
+
+    pub fn search_bar_mut(&mut self) -> &mut SearchBar {
+        &mut self.search_bar
+    }
+
+    pub fn tree_mut(&mut self) -> &mut NamespaceTree {
+        &mut self.tree
+    }
+
+    /// Draw the widget and accept user input.
+    ///
+    /// If the user picks a metric in either mode, it will be returned.
+    pub fn draw(&mut self, registry: &MetricsRegistry, ui: &mut Ui) -> Option<SearchResult> {
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.mode, PickerMode::Search, tr(ui, "Search"));
+            ui.selectable_value(&mut self.mode, PickerMode::Tree, tr(ui, "Browse"));
+        });
+        match self.mode {
+            PickerMode::Search => self.search_bar.draw(registry, ui),
+            PickerMode::Tree => self.tree.draw(registry, ui),
+        }
+    }
+}
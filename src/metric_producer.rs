@@ -0,0 +1,70 @@
+use crate::ClearBucketsSystem;
+use bevy::{ecs::schedule::SystemConfigs, prelude::*};
+
+/// The [`SystemSet`] in the [`Last`] schedule where metrics are written by
+/// systems added with [`MetricProducerAppExt::add_metric_producer`].
+///
+/// This set runs before all dashboard consumers and before
+/// [`ClearBucketsSystem`].
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, SystemSet)]
+pub struct ProduceMetricsSystem;
+
+/// Describes the metrics written by some producer.
+///
+/// This is implemented for any `Fn()`, so a function that calls the
+/// `describe_*!` macros can be used directly.
+pub trait MetricProducer: Send + Sync + 'static {
+    /// Describe all produced metrics, e.g. with
+    /// [`describe_gauge!`](metrics::describe_gauge).
+    fn describe_metrics(&self);
+}
+
+impl<F: Fn() + Send + Sync + 'static> MetricProducer for F {
+    fn describe_metrics(&self) {
+        self()
+    }
+}
+
+/// [`App`] extension methods for plugins that produce metrics.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_metrics_dashboard::MetricProducerAppExt;
+/// # use bevy_metrics_dashboard::metrics::{describe_gauge, gauge, Unit};
+/// fn describe() {
+///     describe_gauge!("enemies", Unit::Count, "Living enemies");
+/// }
+///
+/// fn produce(enemies: Query<&Name>) {
+///     gauge!("enemies").set(enemies.iter().len() as f64);
+/// }
+///
+/// App::new()
+///     .describe_metrics(describe)
+///     .add_metric_producer(produce);
+/// ```
+pub trait MetricProducerAppExt {
+    /// Add systems that write metrics.
+    ///
+    /// The systems run in the [`ProduceMetricsSystem`] set, so their
+    /// samples are always seen by dashboards before buckets are cleared.
+    fn add_metric_producer<M>(&mut self, systems: impl IntoSystemConfigs<M>) -> &mut Self;
+
+    /// Describe the metrics of `producer` during [`Startup`].
+    ///
+    /// Descriptions are deferred to [`Startup`] so they are never dropped
+    /// because the global recorder is not yet installed.
+    fn describe_metrics(&mut self, producer: impl MetricProducer) -> &mut Self;
+}
+
+impl MetricProducerAppExt for App {
+    fn add_metric_producer<M>(&mut self, systems: impl IntoSystemConfigs<M>) -> &mut Self {
+        let systems: SystemConfigs = systems.into_configs();
+        self.configure_sets(Last, ProduceMetricsSystem.before(ClearBucketsSystem))
+            .add_systems(Last, systems.in_set(ProduceMetricsSystem))
+    }
+
+    fn describe_metrics(&mut self, producer: impl MetricProducer) -> &mut Self {
+        self.add_systems(Startup, move || producer.describe_metrics())
+    }
+}
@@ -0,0 +1,155 @@
+use crate::sharded_counters::Stripes;
+use metrics::{Counter, CounterFn, Gauge, GaugeFn, Histogram, HistogramFn, Key};
+use metrics_util::{
+    registry::{Generation, Generational, GenerationalAtomicStorage, Storage},
+    storage::AtomicBucket,
+};
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    Arc, OnceLock,
+};
+
+/// A metric in a [`MetricsRegistry`](crate::registry::MetricsRegistry), and
+/// what the registry has done with it since it was created. `H` is the kind
+/// of handle a [`Recorder`](metrics::Recorder) registers for it.
+pub(crate) struct Stored<T, H> {
+    handle: Generational<Arc<T>>,
+    state: Arc<StoredState<H>>,
+}
+
+struct StoredState<H> {
+    added: AtomicBool,
+    registered: AtomicBool,
+    counted: OnceLock<Option<H>>,
+    stripes: OnceLock<Arc<Stripes>>,
+}
+
+impl<T, H> Stored<T, H> {
+    fn new(handle: Generational<Arc<T>>) -> Self {
+        Self {
+            handle,
+            state: Arc::new(StoredState {
+                added: AtomicBool::new(false),
+                registered: AtomicBool::new(false),
+                counted: OnceLock::new(),
+                stripes: OnceLock::new(),
+            }),
+        }
+    }
+
+    /// The metric's atomic. Writing to it directly doesn't count as an
+    /// update, see [`Self::generation`].
+    pub fn atomic(&self) -> &Arc<T> {
+        self.handle.get_inner()
+    }
+
+    /// A handle to the metric's atomic that counts its writes.
+    pub fn handle(&self) -> Generational<Arc<T>> {
+        self.handle.clone()
+    }
+
+    /// Changes on every write through a [`Self::handle`], even when the
+    /// value stays the same.
+    pub fn generation(&self) -> Generation {
+        self.handle.get_generation()
+    }
+
+    /// Returns `true` on the first access to this metric only, even across
+    /// threads.
+    pub fn claim_added(&self) -> bool {
+        claim(&self.state.added)
+    }
+
+    /// Returns `true` on the first registration of this metric by a
+    /// [`Recorder`](metrics::Recorder) only, even across threads.
+    pub fn claim_registered(&self) -> bool {
+        claim(&self.state.registered)
+    }
+
+    /// The handle counting this metric's writes, built by `init` on the first
+    /// call only. `init` returns `None` if the metric doesn't count them.
+    pub fn counted_handle(&self, init: impl FnOnce() -> Option<H>) -> Option<&H> {
+        self.state.counted.get_or_init(init).as_ref()
+    }
+}
+
+impl Stored<AtomicU64, Counter> {
+    /// The stripes of this counter while it's sharded, created by `init` on
+    /// the first call only.
+    pub fn stripes(&self, init: impl FnOnce() -> Arc<Stripes>) -> &Arc<Stripes> {
+        self.state.stripes.get_or_init(init)
+    }
+}
+
+impl<T, H> Clone for Stored<T, H> {
+    fn clone(&self) -> Self {
+        Self {
+            handle: self.handle.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl CounterFn for Stored<AtomicU64, Counter> {
+    fn increment(&self, value: u64) {
+        CounterFn::increment(&self.handle, value);
+    }
+
+    fn absolute(&self, value: u64) {
+        self.handle.absolute(value);
+    }
+}
+
+impl GaugeFn for Stored<AtomicU64, Gauge> {
+    fn increment(&self, value: f64) {
+        GaugeFn::increment(&self.handle, value);
+    }
+
+    fn decrement(&self, value: f64) {
+        self.handle.decrement(value);
+    }
+
+    fn set(&self, value: f64) {
+        self.handle.set(value);
+    }
+}
+
+impl HistogramFn for Stored<AtomicBucket<f64>, Histogram> {
+    fn record(&self, value: f64) {
+        self.handle.record(value);
+    }
+}
+
+/// Set `flag`, returning `true` if it wasn't set. Loading it first keeps its
+/// cache line shared by all threads once it's set.
+fn claim(flag: &AtomicBool) -> bool {
+    !flag.load(Ordering::Acquire) && !flag.swap(true, Ordering::AcqRel)
+}
+
+/// Like [`GenerationalAtomicStorage`], with the state of each metric next to
+/// its atomic.
+pub(crate) struct MetricStorage(GenerationalAtomicStorage);
+
+impl MetricStorage {
+    pub fn new() -> Self {
+        Self(GenerationalAtomicStorage::atomic())
+    }
+}
+
+impl Storage<Key> for MetricStorage {
+    type Counter = Stored<AtomicU64, Counter>;
+    type Gauge = Stored<AtomicU64, Gauge>;
+    type Histogram = Stored<AtomicBucket<f64>, Histogram>;
+
+    fn counter(&self, key: &Key) -> Self::Counter {
+        Stored::new(self.0.counter(key))
+    }
+
+    fn gauge(&self, key: &Key) -> Self::Gauge {
+        Stored::new(self.0.gauge(key))
+    }
+
+    fn histogram(&self, key: &Key) -> Self::Histogram {
+        Stored::new(self.0.histogram(key))
+    }
+}
@@ -0,0 +1,360 @@
+//! Send events when metrics meet a condition, e.g. to capture a screenshot or
+//! dump game state when frame time spikes.
+//!
+//! ```no_run
+//! # use bevy::prelude::*;
+//! # use bevy_metrics_dashboard::{
+//! #     metric_triggers::{MetricTriggerEvent, MetricTriggerPlugin, TriggerRule},
+//! #     metrics::Key,
+//! #     metrics_util::MetricKind,
+//! #     registry::MetricKey,
+//! # };
+//! let frame_time = MetricKey::new(Key::from_static_name("frame_time"), MetricKind::Gauge);
+//! App::new()
+//!     .add_plugins(
+//!         MetricTriggerPlugin::new().with_rule(TriggerRule::above("slow frame", frame_time, 33.0)),
+//!     )
+//!     .add_systems(Update, |mut triggers: EventReader<MetricTriggerEvent>| {
+//!         for trigger in triggers.read() {
+//!             info!("{} fired with {}", trigger.rule, trigger.value);
+//!         }
+//!     });
+//! ```
+
+use crate::{
+    registry::{BucketCursor, MetricKey, MetricsRegistry},
+    HistogramConsumerAppExt,
+};
+use bevy::{core::FrameCount, prelude::*};
+use metrics_util::{storage::AtomicBucket, MetricKind};
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+pub type TriggerPredicate = Arc<dyn Fn(f64) -> bool + Send + Sync>;
+
+/// Fires a [`MetricTriggerEvent`] whenever the value of a metric starts to
+/// satisfy a predicate.
+///
+/// A rule fires once when its predicate becomes `true`, and again only after
+/// the predicate has been `false` for at least one check. Counters are tested
+/// by their total, gauges by their value, and histograms by every sample
+/// recorded since the last check.
+///
+/// To ignore values that flap around a threshold, give the rule an
+/// [exit condition](Self::with_exit_when) that must be met before it can fire
+/// again. To ignore short spikes, require the predicate to hold for a
+/// [number of frames](Self::with_min_frames) or a
+/// [duration](Self::with_min_duration) before firing.
+#[derive(Clone)]
+pub struct TriggerRule {
+    name: String,
+    key: MetricKey,
+    predicate: TriggerPredicate,
+    exit: Option<TriggerPredicate>,
+    cooldown: Duration,
+    min_frames: u32,
+    min_duration: Duration,
+}
+
+impl TriggerRule {
+    pub fn new(
+        name: impl Into<String>,
+        key: MetricKey,
+        predicate: impl Fn(f64) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            key,
+            predicate: Arc::new(predicate),
+            exit: None,
+            cooldown: Duration::ZERO,
+            min_frames: 1,
+            min_duration: Duration::ZERO,
+        }
+    }
+
+    /// Fire when the value exceeds `threshold`.
+    pub fn above(name: impl Into<String>, key: MetricKey, threshold: f64) -> Self {
+        Self::new(name, key, move |value| value > threshold)
+    }
+
+    /// Fire when the value drops below `threshold`.
+    pub fn below(name: impl Into<String>, key: MetricKey, threshold: f64) -> Self {
+        Self::new(name, key, move |value| value < threshold)
+    }
+
+    /// Never fire more than once per `cooldown`, e.g. to limit screenshots
+    /// while a metric flaps around its threshold.
+    pub fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Once the predicate is met, consider it met until `exit` is `true`,
+    /// instead of until the predicate is `false`.
+    ///
+    /// Histograms exit when any new sample satisfies `exit`.
+    pub fn with_exit_when(mut self, exit: impl Fn(f64) -> bool + Send + Sync + 'static) -> Self {
+        self.exit = Some(Arc::new(exit));
+        self
+    }
+
+    /// Only fire again once the value drops below `threshold`, e.g. enter
+    /// above 33 ms and exit below 20 ms.
+    pub fn with_exit_below(self, threshold: f64) -> Self {
+        self.with_exit_when(move |value| value < threshold)
+    }
+
+    /// Only fire again once the value exceeds `threshold`.
+    pub fn with_exit_above(self, threshold: f64) -> Self {
+        self.with_exit_when(move |value| value > threshold)
+    }
+
+    /// Only fire once the predicate has been met on `frames` consecutive
+    /// checks. `0` is treated like `1`, which is the default.
+    pub fn with_min_frames(mut self, frames: u32) -> Self {
+        self.min_frames = frames.max(1);
+        self
+    }
+
+    /// Only fire once the predicate has been met for at least `duration`.
+    pub fn with_min_duration(mut self, duration: Duration) -> Self {
+        self.min_duration = duration;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn key(&self) -> &MetricKey {
+        &self.key
+    }
+
+    pub fn cooldown(&self) -> Duration {
+        self.cooldown
+    }
+
+    pub fn min_frames(&self) -> u32 {
+        self.min_frames
+    }
+
+    pub fn min_duration(&self) -> Duration {
+        self.min_duration
+    }
+}
+
+/// Sent when a [`TriggerRule`] fires.
+#[derive(Clone, Debug, Event)]
+pub struct MetricTriggerEvent {
+    /// The [`TriggerRule::name`].
+    pub rule: String,
+    pub key: MetricKey,
+    /// The value that satisfied the predicate on the check that fired, or
+    /// else the latest value, e.g. if the rule fired after a
+    /// [minimum duration](TriggerRule::with_min_duration).
+    pub value: f64,
+    /// The [`FrameCount`] on which the rule fired, if known.
+    pub frame: Option<u32>,
+}
+
+enum TriggerSource {
+    Counter(Arc<AtomicU64>),
+    Gauge(Arc<AtomicU64>),
+    Histogram(Arc<AtomicBucket<f64>>, BucketCursor),
+}
+
+impl TriggerSource {
+    fn new(registry: &MetricsRegistry, key: &MetricKey) -> Self {
+        match key.kind {
+            MetricKind::Counter => Self::Counter(registry.get_or_create_counter(&key.key)),
+            MetricKind::Gauge => Self::Gauge(registry.get_or_create_gauge(&key.key)),
+            MetricKind::Histogram => Self::Histogram(
+                registry.get_or_create_histogram(&key.key),
+                BucketCursor::new(registry),
+            ),
+        }
+    }
+
+    /// Test the new values against the `enter` and `exit` predicates.
+    fn check(&mut self, enter: &TriggerPredicate, exit: Option<&TriggerPredicate>) -> Check {
+        let mut check = Check::default();
+        let mut test = |value: f64| {
+            check.latest = Some(value);
+            if check.entered.is_none() && enter(value) {
+                check.entered = Some(value);
+            }
+            check.exited |= exit.is_some_and(|exit| exit(value));
+        };
+        match self {
+            Self::Counter(counter) => test(counter.load(Ordering::Relaxed) as f64),
+            Self::Gauge(gauge) => test(f64::from_bits(gauge.load(Ordering::Relaxed))),
+            Self::Histogram(bucket, cursor) => cursor.read_new(bucket, test),
+        }
+        check
+    }
+}
+
+/// The new values of a [`TriggerSource`] tested by a [`TriggerRule`].
+#[derive(Default)]
+struct Check {
+    latest: Option<f64>,
+    /// The first value that satisfied the predicate.
+    entered: Option<f64>,
+    /// Whether any value satisfied the exit condition.
+    exited: bool,
+}
+
+struct ActiveRule {
+    rule: TriggerRule,
+    /// Created on the first check, so rules can be added without a registry.
+    source: Option<TriggerSource>,
+    was_met: bool,
+    /// The number of consecutive checks and the time that the predicate has
+    /// been met.
+    met_checks: u32,
+    met_since: Option<Instant>,
+    /// Whether this episode of the predicate being met has been handled, by
+    /// firing or being ignored during the cooldown.
+    handled: bool,
+    last_fired: Option<Instant>,
+}
+
+/// The [`TriggerRule`]s checked every frame by the [`MetricTriggerPlugin`].
+///
+/// Modify this resource to add or remove rules at runtime.
+#[derive(Default, Resource)]
+pub struct MetricTriggers {
+    rules: Vec<ActiveRule>,
+}
+
+impl MetricTriggers {
+    /// Add `rule`, replacing any rule with the same name.
+    pub fn add(&mut self, rule: TriggerRule) {
+        self.remove(&rule.name);
+        self.rules.push(ActiveRule {
+            rule,
+            source: None,
+            was_met: false,
+            met_checks: 0,
+            met_since: None,
+            handled: false,
+            last_fired: None,
+        });
+    }
+
+    /// Returns `true` if a rule named `name` was removed.
+    pub fn remove(&mut self, name: &str) -> bool {
+        let n_rules = self.rules.len();
+        self.rules.retain(|r| r.rule.name != name);
+        self.rules.len() != n_rules
+    }
+
+    pub fn rules(&self) -> impl Iterator<Item = &TriggerRule> {
+        self.rules.iter().map(|r| &r.rule)
+    }
+
+    /// Test every rule against the current values in `registry`, returning
+    /// the events of the rules that fired.
+    pub fn check(
+        &mut self,
+        registry: &MetricsRegistry,
+        frame: Option<u32>,
+    ) -> Vec<MetricTriggerEvent> {
+        let now = Instant::now();
+        let mut fired = Vec::new();
+        for active in &mut self.rules {
+            let ActiveRule {
+                rule,
+                source,
+                was_met,
+                met_checks,
+                met_since,
+                handled,
+                last_fired,
+            } = active;
+            let source = source.get_or_insert_with(|| TriggerSource::new(registry, &rule.key));
+            let check = source.check(&rule.predicate, rule.exit.as_ref());
+            let is_met = if *was_met && rule.exit.is_some() {
+                !check.exited
+            } else {
+                check.entered.is_some()
+            };
+            *was_met = is_met;
+            if !is_met {
+                *met_checks = 0;
+                *met_since = None;
+                *handled = false;
+                continue;
+            }
+            *met_checks += 1;
+            let since = *met_since.get_or_insert(now);
+            let debounced =
+                *met_checks >= rule.min_frames && now.duration_since(since) >= rule.min_duration;
+            if *handled || !debounced {
+                continue;
+            }
+            *handled = true;
+
+            let cooled_down = last_fired.is_none_or(|t| now.duration_since(t) >= rule.cooldown);
+            if let Some(value) = check.entered.or(check.latest).filter(|_| cooled_down) {
+                *last_fired = Some(now);
+                fired.push(MetricTriggerEvent {
+                    rule: rule.name.clone(),
+                    key: rule.key.clone(),
+                    value,
+                    frame,
+                });
+            }
+        }
+        fired
+    }
+
+    fn check_system(
+        mut triggers: ResMut<Self>,
+        registry: Res<MetricsRegistry>,
+        frame: Option<Res<FrameCount>>,
+        mut events: EventWriter<MetricTriggerEvent>,
+    ) {
+        events.send_batch(triggers.check(&registry, frame.map(|f| f.0)));
+    }
+}
+
+/// Checks the [`MetricTriggers`] every frame and sends a
+/// [`MetricTriggerEvent`] for each rule that fires.
+///
+/// Rules are checked in [`Last`], so events are read on the next frame. This
+/// doesn't need the [`DashboardPlugin`](crate::DashboardPlugin), so it works
+/// in headless runs.
+#[derive(Default)]
+pub struct MetricTriggerPlugin {
+    rules: Vec<TriggerRule>,
+}
+
+impl MetricTriggerPlugin {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_rule(mut self, rule: TriggerRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+}
+
+impl Plugin for MetricTriggerPlugin {
+    fn build(&self, app: &mut App) {
+        let mut triggers = MetricTriggers::default();
+        for rule in &self.rules {
+            triggers.add(rule.clone());
+        }
+        app.add_event::<MetricTriggerEvent>()
+            .insert_resource(triggers)
+            .add_histogram_consumer(MetricTriggers::check_system);
+    }
+}
@@ -0,0 +1,279 @@
+//! A compact table of metric values.
+
+use crate::{
+    dashboard_target::DashboardTarget,
+    frame_snapshot::FrameSnapshot,
+    locale::tr,
+    palette::Palette,
+    registry::{BucketCursor, MetricKey, MetricsRegistry},
+    ring::Ring,
+    search_bar::SearchBar,
+    value_formatters::ValueFormatters,
+};
+use bevy::prelude::*;
+use bevy_egui::{
+    egui::{self, pos2, Color32, Sense, Shape, Stroke, Ui},
+    EguiContexts,
+};
+use metrics::{atomics::AtomicU64, Unit};
+use metrics_util::{storage::AtomicBucket, MetricKind};
+use std::sync::{
+    atomic::{self, Ordering},
+    Arc,
+};
+
+/// An `egui` window showing one row per metric with its latest value and an
+/// optional sparkline of recent samples.
+///
+/// Histogram rows show the mean of the samples recorded each frame.
+#[derive(Component)]
+pub struct MetricsTableWindow {
+    title: String,
+    id: egui::Id,
+    search_bar: SearchBar,
+    rows: Vec<SampledMetric>,
+    show_sparklines: bool,
+    sparkline_len: usize,
+    paused: bool,
+}
+
+/// A metric whose value is sampled into a ring buffer every update.
+///
+/// Histograms are sampled as the mean of the samples recorded since the last
+/// update.
+pub(crate) struct SampledMetric {
+    pub(crate) key: MetricKey,
+    pub(crate) unit: Option<Unit>,
+    source: RowSource,
+    pub(crate) ring: Ring<f64>,
+}
+
+enum RowSource {
+    Counter(Arc<AtomicU64>),
+    Gauge(Arc<AtomicU64>),
+    Histogram(Arc<AtomicBucket<f64>>, BucketCursor),
+}
+
+impl SampledMetric {
+    pub(crate) fn new(
+        registry: &MetricsRegistry,
+        key: MetricKey,
+        unit: Option<Unit>,
+        len: usize,
+    ) -> Self {
+        let source = match key.kind {
+            MetricKind::Counter => RowSource::Counter(registry.get_or_create_counter(&key.key)),
+            MetricKind::Gauge => RowSource::Gauge(registry.get_or_create_gauge(&key.key)),
+            MetricKind::Histogram => {
+                let source = registry.get_or_create_histogram(&key.key);
+                RowSource::Histogram(source, BucketCursor::new(registry))
+            }
+        };
+        Self {
+            key,
+            unit,
+            source,
+            ring: Ring::new(len),
+        }
+    }
+
+    /// Sample the metric, preferring the value in `snapshot`.
+    pub(crate) fn update(&mut self, snapshot: &FrameSnapshot) {
+        let value = match &mut self.source {
+            RowSource::Counter(c) => snapshot
+                .counter(&self.key.key)
+                .unwrap_or_else(|| c.load(Ordering::Relaxed))
+                as f64,
+            RowSource::Gauge(g) => snapshot
+                .gauge(&self.key.key)
+                .unwrap_or_else(|| f64::from_bits(g.load(Ordering::Relaxed))),
+            RowSource::Histogram(h, cursor) => {
+                let mut sum = 0.0;
+                let mut count = 0;
+                cursor.read_new(h, |value| {
+                    sum += value;
+                    count += 1;
+                });
+                if count == 0 {
+                    return;
+                }
+                sum / count as f64
+            }
+        };
+        self.ring.push(value);
+    }
+}
+
+impl MetricsTableWindow {
+    pub fn new(title: impl Into<String>) -> Self {
+        static WINDOW_ID: atomic::AtomicU64 = atomic::AtomicU64::new(0);
+        let id = WINDOW_ID.fetch_add(1, Ordering::Relaxed);
+        let title = title.into();
+        let id = format!("{title} {id}").into();
+        Self {
+            title,
+            id,
+            search_bar: default(),
+            rows: default(),
+            show_sparklines: true,
+            sparkline_len: 100,
+            paused: false,
+        }
+    }
+
+    /// Show or hide the sparkline column.
+    pub fn with_sparklines(mut self, show: bool) -> Self {
+        self.show_sparklines = show;
+        self
+    }
+
+    /// Set the number of samples shown in each sparkline.
+    pub fn with_sparkline_len(mut self, len: usize) -> Self {
+        self.set_sparkline_len(len);
+        self
+    }
+
+    pub fn set_sparkline_len(&mut self, len: usize) {
+        self.sparkline_len = len.max(1);
+        for row in &mut self.rows {
+            row.ring.set_max_len(self.sparkline_len);
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Stop or resume sampling the rows.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    /// Add a row for the metric identified by `key`.
+    pub fn add_row(&mut self, registry: &MetricsRegistry, key: MetricKey, unit: Option<Unit>) {
+        if self.rows.iter().any(|r| r.key == key) {
+            return;
+        }
+        self.rows
+            .push(SampledMetric::new(registry, key, unit, self.sparkline_len));
+    }
+
+    pub(crate) fn update_all(snapshot: Res<FrameSnapshot>, mut windows: Query<&mut Self>) {
+        for mut window in &mut windows {
+            if !window.paused {
+                for row in &mut window.rows {
+                    row.update(&snapshot);
+                }
+            }
+        }
+    }
+
+    pub(crate) fn draw_all(
+        mut commands: Commands,
+        registry: Res<MetricsRegistry>,
+        formatters: Res<ValueFormatters>,
+        palette: Res<Palette>,
+        mut ctxts: EguiContexts,
+        mut windows: Query<(Entity, &mut Self, Option<&DashboardTarget>)>,
+    ) {
+        for (entity, mut window, target) in &mut windows {
+            let Some(ctxt) = DashboardTarget::ctx(target, &mut ctxts) else {
+                continue;
+            };
+            let mut open = true;
+            DashboardTarget::constrain(target, egui::Window::new(&window.title))
+                .id(window.id)
+                .open(&mut open)
+                .show(ctxt, |ui| {
+                    window.draw(&registry, &formatters, &palette, ui);
+                });
+            if !open {
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+
+    /// Draw the widget and accept user input.
+    pub fn draw(
+        &mut self,
+        registry: &MetricsRegistry,
+        formatters: &ValueFormatters,
+        palette: &Palette,
+        ui: &mut Ui,
+    ) {
+        if let Some(selected) = self.search_bar.draw(registry, ui) {
+            self.add_row(
+                registry,
+                selected.key,
+                selected.description.and_then(|d| d.unit),
+            );
+        }
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.paused, tr(ui, "Pause"));
+            ui.checkbox(&mut self.show_sparklines, tr(ui, "Sparklines"));
+            let mut len = self.sparkline_len;
+            if ui
+                .add(egui::Slider::new(&mut len, 10..=1000).text(tr(ui, "Samples")))
+                .changed()
+            {
+                self.set_sparkline_len(len);
+            }
+        });
+        ui.separator();
+
+        let mut remove_row = None;
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new(self.id.with("grid"))
+                .striped(true)
+                .show(ui, |ui| {
+                    for (i, row) in self.rows.iter().enumerate() {
+                        ui.label(row.key.title(None, 0));
+                        let latest = row.ring.latest().map(|&v| {
+                            if let Some(formatter) = formatters.get(&row.key, row.unit) {
+                                formatter(v)
+                            } else {
+                                format!("{v:.3}")
+                            }
+                        });
+                        ui.label(latest.unwrap_or_default());
+                        if self.show_sparklines {
+                            draw_sparkline(&row.ring, palette.color_for(&row.key), ui);
+                        }
+                        if ui.small_button(tr(ui, "Remove")).clicked() {
+                            remove_row = Some(i);
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+        if let Some(i) = remove_row {
+            self.rows.remove(i);
+        }
+    }
+}
+
+fn draw_sparkline(ring: &Ring<f64>, color: Color32, ui: &mut Ui) {
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(120.0, 16.0), Sense::hover());
+    let (min, max) = ring
+        .iter_chronological()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &v| {
+            (min.min(v), max.max(v))
+        });
+    let range = (max - min).max(f64::EPSILON);
+    let dx = rect.width() / ring.max_len().max(2).saturating_sub(1) as f32;
+    let points: Vec<_> = ring
+        .iter_chronological()
+        .enumerate()
+        .map(|(i, &v)| {
+            let t = ((v - min) / range) as f32;
+            pos2(
+                rect.left() + i as f32 * dx,
+                rect.bottom() - t * rect.height(),
+            )
+        })
+        .collect();
+    if points.len() > 1 {
+        ui.painter()
+            .add(Shape::line(points, Stroke::new(1.0, color)));
+    }
+}
@@ -0,0 +1,168 @@
+//! Fan out metrics to several recorders.
+
+use metrics::{
+    Counter, CounterFn, Gauge, GaugeFn, Histogram, HistogramFn, Key, KeyName, Metadata, Recorder,
+    SharedString, Unit,
+};
+use std::sync::Arc;
+
+/// A [`Recorder`] that forwards every description, registration, and sample
+/// to all of its recorders.
+///
+/// Only one global recorder can ever be installed, so to use the dashboard
+/// alongside another recorder (e.g. a Prometheus exporter), install a
+/// `MultiRecorder` of both instead. The
+/// [`RegistryPlugin`](crate::RegistryPlugin) does this for you with
+/// [`RegistryPlugin::with_chained_recorder`](crate::RegistryPlugin::with_chained_recorder).
+#[derive(Clone, Default)]
+pub struct MultiRecorder {
+    recorders: Vec<Arc<dyn Recorder + Send + Sync>>,
+}
+
+impl MultiRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, recorder: impl Recorder + Send + Sync + 'static) -> Self {
+        self.push(recorder);
+        self
+    }
+
+    pub fn push(&mut self, recorder: impl Recorder + Send + Sync + 'static) {
+        self.recorders.push(Arc::new(recorder));
+    }
+
+    pub fn len(&self) -> usize {
+        self.recorders.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.recorders.is_empty()
+    }
+}
+
+impl Recorder for MultiRecorder {
+    fn describe_counter(&self, key: KeyName, unit: Option<Unit>, description: SharedString) {
+        for recorder in &self.recorders {
+            recorder.describe_counter(key.clone(), unit, description.clone());
+        }
+    }
+
+    fn describe_gauge(&self, key: KeyName, unit: Option<Unit>, description: SharedString) {
+        for recorder in &self.recorders {
+            recorder.describe_gauge(key.clone(), unit, description.clone());
+        }
+    }
+
+    fn describe_histogram(&self, key: KeyName, unit: Option<Unit>, description: SharedString) {
+        for recorder in &self.recorders {
+            recorder.describe_histogram(key.clone(), unit, description.clone());
+        }
+    }
+
+    fn register_counter(&self, key: &Key, metadata: &Metadata<'_>) -> Counter {
+        fan_out_counter(
+            self.recorders
+                .iter()
+                .map(|r| r.register_counter(key, metadata))
+                .collect(),
+        )
+    }
+
+    fn register_gauge(&self, key: &Key, metadata: &Metadata<'_>) -> Gauge {
+        fan_out_gauge(
+            self.recorders
+                .iter()
+                .map(|r| r.register_gauge(key, metadata))
+                .collect(),
+        )
+    }
+
+    fn register_histogram(&self, key: &Key, metadata: &Metadata<'_>) -> Histogram {
+        fan_out_histogram(
+            self.recorders
+                .iter()
+                .map(|r| r.register_histogram(key, metadata))
+                .collect(),
+        )
+    }
+}
+
+/// A [`Counter`] that writes to all of `counters`.
+pub(crate) fn fan_out_counter(mut counters: Vec<Counter>) -> Counter {
+    if counters.len() == 1 {
+        return counters.pop().unwrap();
+    }
+    Counter::from_arc(Arc::new(FanOutCounter(counters)))
+}
+
+/// A [`Gauge`] that writes to all of `gauges`.
+pub(crate) fn fan_out_gauge(mut gauges: Vec<Gauge>) -> Gauge {
+    if gauges.len() == 1 {
+        return gauges.pop().unwrap();
+    }
+    Gauge::from_arc(Arc::new(FanOutGauge(gauges)))
+}
+
+/// A [`Histogram`] that writes to all of `histograms`.
+pub(crate) fn fan_out_histogram(mut histograms: Vec<Histogram>) -> Histogram {
+    if histograms.len() == 1 {
+        return histograms.pop().unwrap();
+    }
+    Histogram::from_arc(Arc::new(FanOutHistogram(histograms)))
+}
+
+struct FanOutCounter(Vec<Counter>);
+
+impl CounterFn for FanOutCounter {
+    fn increment(&self, value: u64) {
+        for counter in &self.0 {
+            counter.increment(value);
+        }
+    }
+
+    fn absolute(&self, value: u64) {
+        for counter in &self.0 {
+            counter.absolute(value);
+        }
+    }
+}
+
+struct FanOutGauge(Vec<Gauge>);
+
+impl GaugeFn for FanOutGauge {
+    fn increment(&self, value: f64) {
+        for gauge in &self.0 {
+            gauge.increment(value);
+        }
+    }
+
+    fn decrement(&self, value: f64) {
+        for gauge in &self.0 {
+            gauge.decrement(value);
+        }
+    }
+
+    fn set(&self, value: f64) {
+        for gauge in &self.0 {
+            gauge.set(value);
+        }
+    }
+}
+
+struct FanOutHistogram(Vec<Histogram>);
+
+impl HistogramFn for FanOutHistogram {
+    fn record(&self, value: f64) {
+        for histogram in &self.0 {
+            histogram.record(value);
+        }
+    }
+
+    fn record_many(&self, value: f64, count: usize) {
+        for histogram in &self.0 {
+            histogram.record_many(value, count);
+        }
+    }
+}
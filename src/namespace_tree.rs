@@ -1,13 +1,15 @@
 use crate::{
+    dashboard_target::DashboardTarget,
     dashboard_window::RequestPlot,
-    registry::{MetricsRegistry, SearchResult},
+    locale::{tr, DashboardLocale},
+    registry::{DescriptionKey, FuzzyNameMatcher, MetricsRegistry, SearchResult},
 };
 use bevy::{
     prelude::*,
     tasks::{block_on, AsyncComputeTaskPool, Task},
 };
 use bevy_egui::{
-    egui::{self, Ui},
+    egui::{self, TextEdit, Ui},
     EguiContexts,
 };
 use std::{
@@ -15,16 +17,27 @@ use std::{
     time::{Duration, Instant},
 };
 
-/// A widget that shows all metrics metadata in a tree, grouped by namespace.
+/// An `egui` window that shows a [`NamespaceTree`].
 ///
-/// For example, a metric with name "foo::bar::baz" would be found by expanding
-/// "foo", then "bar", then "baz".
+/// Metrics selected in the tree are plotted in every [`DashboardWindow`](crate::DashboardWindow).
 #[derive(Component)]
 pub struct NamespaceTreeWindow {
     title: String,
     id: egui::Id,
+    tree: NamespaceTree,
+}
+
+/// A widget that shows all metrics metadata in a tree, grouped by namespace.
+///
+/// For example, a metric with name "foo::bar::baz" would be found by expanding
+/// "foo", then "bar", then "baz". Metrics can also be grouped by the module
+/// that registered them (see [`TreeGrouping`]).
+pub struct NamespaceTree {
+    filter_input: String,
+    scope: Option<String>,
+    grouping: TreeGrouping,
     force_refresh: bool,
-    refresh_period: Duration,
+    refresh_period: Option<Duration>,
     last_refresh_time: Instant,
     refresh_task: Option<Task<Vec<NamespaceNode>>>,
     roots: Vec<NamespaceNode>,
@@ -39,20 +52,46 @@ impl NamespaceTreeWindow {
         Self {
             title,
             id,
-            force_refresh: true,
-            refresh_period: Duration::from_secs(5),
-            last_refresh_time: Instant::now(),
-            refresh_task: Default::default(),
-            roots: Default::default(),
+            tree: default(),
         }
     }
 
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn tree(&self) -> &NamespaceTree {
+        &self.tree
+    }
+
+    pub fn tree_mut(&mut self) -> &mut NamespaceTree {
+        &mut self.tree
+    }
+
     pub fn force_refresh(&mut self) {
-        self.force_refresh = true;
+        self.tree.force_refresh();
     }
 
     pub fn set_refresh_period(&mut self, period: Duration) {
-        self.refresh_period = period;
+        self.tree.set_refresh_period(period);
+    }
+
+    pub fn set_manual_refresh(&mut self) {
+        self.tree.set_manual_refresh();
+    }
+
+    /// Only show metrics whose names fuzzy-match `filter`.
+    pub fn set_filter(&mut self, filter: impl Into<String>) {
+        self.tree.set_filter(filter);
+    }
+
+    pub fn set_grouping(&mut self, grouping: TreeGrouping) {
+        self.tree.set_grouping(grouping);
+    }
+
+    /// Only show metrics in `namespace`. See [`NamespaceTree::set_scope`].
+    pub fn set_scope(&mut self, namespace: Option<String>) {
+        self.tree.set_scope(namespace);
     }
 
     pub(crate) fn draw_all(
@@ -60,20 +99,20 @@ impl NamespaceTreeWindow {
         registry: Res<MetricsRegistry>,
         mut ctxts: EguiContexts,
         mut requests: EventWriter<RequestPlot>,
-        mut windows: Query<(Entity, &mut Self)>,
+        mut windows: Query<(Entity, &mut Self, Option<&DashboardTarget>)>,
     ) {
-        let ctxt = ctxts.ctx_mut();
-        for (entity, mut window) in &mut windows {
+        for (entity, mut window, target) in &mut windows {
+            let Some(ctxt) = DashboardTarget::ctx(target, &mut ctxts) else {
+                continue;
+            };
             let mut open = true;
-            egui::Window::new(&window.title)
+            DashboardTarget::constrain(target, egui::Window::new(&window.title))
                 .id(window.id)
                 .open(&mut open)
                 .show(ctxt, |ui| {
                     if let Some(result) = window.draw(&registry, ui) {
-                        requests.send(RequestPlot {
-                            key: result.key,
-                            unit: result.description.and_then(|d| d.unit),
-                        });
+                        let unit = result.description.and_then(|d| d.unit);
+                        requests.send(RequestPlot::new(result.key, unit));
                     }
                 });
             if !open {
@@ -86,12 +125,156 @@ impl NamespaceTreeWindow {
     ///
     /// If the user selects a metric, it will be returned.
     pub fn draw(&mut self, registry: &MetricsRegistry, ui: &mut Ui) -> Option<SearchResult> {
-        if self.force_refresh || self.last_refresh_time.elapsed() > self.refresh_period {
+        self.tree.draw(registry, ui)
+    }
+}
+
+impl Default for NamespaceTree {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NamespaceTree {
+    pub fn new() -> Self {
+        Self {
+            filter_input: Default::default(),
+            scope: None,
+            grouping: Default::default(),
+            force_refresh: true,
+            refresh_period: Some(Duration::from_secs(5)),
+            last_refresh_time: Instant::now(),
+            refresh_task: Default::default(),
+            roots: Default::default(),
+        }
+    }
+
+    pub fn force_refresh(&mut self) {
+        self.force_refresh = true;
+    }
+
+    /// How often the tree is rebuilt from the registry, or `None` if it's only
+    /// rebuilt by [`Self::force_refresh`] or the "Refresh" button.
+    pub fn refresh_period(&self) -> Option<Duration> {
+        self.refresh_period
+    }
+
+    /// Rebuild the tree from the registry this often. Defaults to 5 seconds.
+    pub fn set_refresh_period(&mut self, period: Duration) {
+        self.refresh_period = Some(period);
+    }
+
+    /// Only rebuild the tree when requested.
+    pub fn set_manual_refresh(&mut self) {
+        self.refresh_period = None;
+    }
+
+    /// `true` while the tree is being rebuilt in the background.
+    pub fn is_refreshing(&self) -> bool {
+        self.refresh_task.is_some()
+    }
+
+    pub fn filter(&self) -> &str {
+        &self.filter_input
+    }
+
+    /// Only show metrics whose names fuzzy-match `filter`.
+    pub fn set_filter(&mut self, filter: impl Into<String>) {
+        self.filter_input = filter.into();
+        self.force_refresh = true;
+    }
+
+    /// The namespace that the tree is limited to, if any.
+    pub fn scope(&self) -> Option<&str> {
+        self.scope.as_deref()
+    }
+
+    /// Only show metrics in `namespace`, matching the filter against the
+    /// rest of their names.
+    ///
+    /// When grouped by [`TreeGrouping::Name`], right-clicking a namespace in
+    /// the tree also sets it as the scope.
+    pub fn set_scope(&mut self, namespace: Option<String>) {
+        self.scope = namespace;
+        self.force_refresh = true;
+    }
+
+    pub fn grouping(&self) -> TreeGrouping {
+        self.grouping
+    }
+
+    pub fn set_grouping(&mut self, grouping: TreeGrouping) {
+        self.grouping = grouping;
+        self.force_refresh = true;
+    }
+
+    /// Draw the widget and accept user input.
+    ///
+    /// If the user selects a metric, it will be returned.
+    pub fn draw(&mut self, registry: &MetricsRegistry, ui: &mut Ui) -> Option<SearchResult> {
+        ui.horizontal(|ui| {
+            ui.label(tr(ui, "Filter:"));
+            let response = TextEdit::singleline(&mut self.filter_input)
+                .hint_text(tr(ui, "metric name"))
+                .show(ui)
+                .response
+                .on_hover_text(tr(
+                    ui,
+                    "Names must match every word. Prefix a word with - to exclude names \
+                    containing it.",
+                ));
+            if response.changed() {
+                self.force_refresh = true;
+            }
+        });
+        if let Some(scope) = self.scope.clone() {
+            ui.horizontal(|ui| {
+                ui.label(format!("{} {scope}", tr(ui, "In namespace:")));
+                if ui.button(tr(ui, "Clear")).clicked() {
+                    self.set_scope(None);
+                }
+            });
+        }
+        ui.horizontal(|ui| {
+            ui.label(tr(ui, "Group by:"));
+            let before = self.grouping;
+            ui.selectable_value(&mut self.grouping, TreeGrouping::Name, tr(ui, "Name"));
+            ui.selectable_value(&mut self.grouping, TreeGrouping::Target, tr(ui, "Module"));
+            if self.grouping != before {
+                self.force_refresh = true;
+            }
+        });
+        ui.horizontal(|ui| {
+            if ui.button(tr(ui, "Refresh")).clicked() {
+                self.force_refresh = true;
+            }
+            self.refresh_period_menu(ui);
+            if self.is_refreshing() {
+                ui.spinner();
+            }
+        });
+
+        let period_elapsed = self
+            .refresh_period
+            .is_some_and(|period| self.last_refresh_time.elapsed() > period);
+        if self.force_refresh || period_elapsed {
             self.force_refresh = false;
             let task_registry = registry.clone();
+            let filter = self.filter_input.clone();
+            let scope = self.scope.clone();
+            let grouping = self.grouping;
             self.refresh_task = Some(AsyncComputeTaskPool::get().spawn(async move {
-                let mut results = task_registry.all_metrics();
-                NamespaceNode::tree_from_results(&mut results)
+                // Only the branches leading to matching metrics are kept.
+                let results = match (&scope, filter.is_empty()) {
+                    (None, true) => task_registry.all_metrics(),
+                    (None, false) => task_registry.fuzzy_search_by_name(&filter),
+                    (Some(scope), _) => task_registry.search_in_namespace(
+                        scope,
+                        &filter,
+                        &FuzzyNameMatcher::default(),
+                    ),
+                };
+                NamespaceNode::tree_from_results(results, grouping, &task_registry)
             }));
             self.last_refresh_time = Instant::now();
         }
@@ -106,32 +289,75 @@ impl NamespaceTreeWindow {
         }
 
         let mut selected = None;
+        let mut new_scope = None;
+        let scopes = (self.grouping == TreeGrouping::Name).then_some(&mut new_scope);
         egui::ScrollArea::new([false, true]).show(ui, |ui| {
-            Self::draw_recursive(&self.roots, &mut selected, ui);
+            Self::draw_recursive(&self.roots, &mut selected, scopes, ui);
         });
+        if new_scope.is_some() {
+            self.set_scope(new_scope);
+        }
         selected
     }
 
-    fn draw_recursive(nodes: &[NamespaceNode], selected: &mut Option<SearchResult>, ui: &mut Ui) {
+    fn refresh_period_menu(&mut self, ui: &mut Ui) {
+        const PERIODS: [u64; 4] = [1, 5, 30, 60];
+        let label = |period: Option<Duration>, ui: &Ui| match period {
+            Some(period) => format!("{} s", period.as_secs_f32()),
+            None => tr(ui, "Manual"),
+        };
+        egui::ComboBox::from_id_salt("refresh_period")
+            .selected_text(label(self.refresh_period, ui))
+            .show_ui(ui, |ui| {
+                for period in PERIODS.map(|secs| Some(Duration::from_secs(secs))) {
+                    let text = label(period, ui);
+                    ui.selectable_value(&mut self.refresh_period, period, text);
+                }
+                let text = label(None, ui);
+                ui.selectable_value(&mut self.refresh_period, None, text);
+            })
+            .response
+            .on_hover_text(tr(ui, "How often the tree is rebuilt from the registry"));
+    }
+
+    /// Namespaces can be chosen as the new scope if `new_scope` is `Some`.
+    fn draw_recursive(
+        nodes: &[NamespaceNode],
+        selected: &mut Option<SearchResult>,
+        mut new_scope: Option<&mut Option<String>>,
+        ui: &mut Ui,
+    ) {
         for node in nodes {
             match node {
                 NamespaceNode::Namespace {
                     display_path: path_component,
+                    path,
                     children,
                 } => {
-                    ui.collapsing(path_component, |ui| {
-                        Self::draw_recursive(children, selected, ui);
+                    let response = ui.collapsing(path_component, |ui| {
+                        Self::draw_recursive(children, selected, new_scope.as_deref_mut(), ui);
                     });
+                    if let Some(new_scope) = new_scope.as_deref_mut() {
+                        response.header_response.context_menu(|ui| {
+                            if ui.button(tr(ui, "Search in namespace")).clicked() {
+                                *new_scope = Some(path.clone());
+                                ui.close_menu();
+                            }
+                        });
+                    }
                 }
                 NamespaceNode::Metric {
                     display_path,
                     result,
                 } => {
                     ui.horizontal(|ui| {
-                        if ui.button("Plot").clicked() {
+                        if ui.button(tr(ui, "Plot")).clicked() {
                             *selected = Some(result.clone());
                         }
-                        ui.label(result.detailed_text(Some(display_path)));
+                        ui.label(result.localized_detailed_text(
+                            Some(display_path),
+                            &DashboardLocale::get(ui.ctx()),
+                        ));
                     });
                 }
             }
@@ -142,6 +368,8 @@ impl NamespaceTreeWindow {
 enum NamespaceNode {
     Namespace {
         display_path: String,
+        /// The full path from the root, joined with `::`.
+        path: String,
         children: Vec<NamespaceNode>,
     },
     Metric {
@@ -150,84 +378,72 @@ enum NamespaceNode {
     },
 }
 
-impl NamespaceNode {
-    fn tree_from_results(results: &mut [SearchResult]) -> Vec<Self> {
-        results.sort_unstable_by(|r1, r2| r1.key.key.name().cmp(r2.key.key.name()));
-        Self::tree_from_sorted_results_recursive(results, 0)
-    }
+/// A result with the path to its leaf in the tree.
+type PathedResult = (Vec<String>, SearchResult);
 
-    fn tree_from_sorted_results_recursive(
-        mut results: &[SearchResult],
-        path_start: usize,
+impl NamespaceNode {
+    fn tree_from_results(
+        results: Vec<SearchResult>,
+        grouping: TreeGrouping,
+        registry: &MetricsRegistry,
     ) -> Vec<Self> {
-        const DELIM: &str = "::";
+        let mut pathed: Vec<PathedResult> = results
+            .into_iter()
+            .filter_map(|r| Some((grouping.path(&r, registry)?, r)))
+            .collect();
+        pathed.sort_unstable_by(|(p1, _), (p2, _)| p1.cmp(p2));
+        Self::tree_from_sorted_results_recursive(&pathed, 0)
+    }
 
+    fn tree_from_sorted_results_recursive(mut results: &[PathedResult], depth: usize) -> Vec<Self> {
         // Having sorted results allows us to create a subtree from a range
         // of results.
         //
         // The results themselves are cheap to clone, so we take the easier
         // tactic of cloning results into the tree instead of moving them.
         let mut nodes = Vec::new();
-        while let Some(first_result) = results.first() {
-            let first_path = &first_result.key.key.name()[path_start..];
-            if first_path.starts_with(':') {
-                // Skip invalid path.
+        while let Some((first_path, first_result)) = results.first() {
+            let group_name = &first_path[depth];
+            if first_path.len() == depth + 1 {
+                // This result is a leaf.
+                nodes.push(Self::Metric {
+                    display_path: group_name.clone(),
+                    result: first_result.clone(),
+                });
                 results = &results[1..];
                 continue;
             }
 
-            if let Some((group_name, _)) =
-                first_result.key.key.name()[path_start..].split_once(DELIM)
-            {
-                // Split a group off the front of the results.
-                let group_end = results
-                    .iter()
-                    .position(|r| !r.key.key.name()[path_start..].starts_with(group_name))
-                    .unwrap_or(results.len());
-                let (group, rem) = results.split_at(group_end);
-
-                // Recurse and create node from children.
-                let new_path_start = path_start + group_name.len() + DELIM.len();
-                let children = Self::tree_from_sorted_results_recursive(group, new_path_start);
-                if let Some(node) = Self::create_parent_node(group_name, children) {
-                    nodes.push(node);
-                }
-                results = rem;
-            } else {
-                // No delimiter. This result is a leaf.
-                let (leaf_result, rem) = results.split_first().unwrap();
-                let leaf_name = leaf_result.key.key.name();
-                let is_invalid_path = leaf_name.is_empty() || leaf_name.ends_with(':');
-                if !is_invalid_path {
-                    // Only display last component of path.
-                    let display_path = leaf_result
-                        .key
-                        .key
-                        .name()
-                        .rsplit_once(':')
-                        .map(|(_, end)| end)
-                        .unwrap_or(leaf_result.key.key.name());
-                    nodes.push(Self::Metric {
-                        display_path: display_path.into(),
-                        result: leaf_result.clone(),
-                    });
-                }
-                results = rem;
+            // Split a group off the front of the results.
+            let group_end = results
+                .iter()
+                .position(|(p, _)| p.len() == depth + 1 || p[depth] != *group_name)
+                .unwrap_or(results.len());
+            let (group, rem) = results.split_at(group_end);
+
+            // Recurse and create node from children.
+            let children = Self::tree_from_sorted_results_recursive(group, depth + 1);
+            let group_path = first_path[..=depth].join("::");
+            if let Some(node) = Self::create_parent_node(group_name, group_path, children) {
+                nodes.push(node);
             }
+            results = rem;
         }
         nodes
     }
 
-    fn create_parent_node(group_name: &str, children: Vec<Self>) -> Option<Self> {
+    fn create_parent_node(group_name: &str, path: String, children: Vec<Self>) -> Option<Self> {
         match children.len() {
             0 => None,
             1 => {
                 let collapsed = match children.into_iter().next().unwrap() {
                     Self::Namespace {
                         display_path: path_component,
+                        path,
                         children,
                     } => Self::Namespace {
                         display_path: format!("{group_name}::{path_component}"),
+                        path,
                         children,
                     },
                     Self::Metric {
@@ -242,8 +458,52 @@ impl NamespaceNode {
             }
             _ => Some(Self::Namespace {
                 display_path: group_name.into(),
+                path,
                 children,
             }),
         }
     }
 }
+
+/// How a [`NamespaceTree`] groups metrics.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TreeGrouping {
+    /// By the `::`-separated namespaces of metric names.
+    #[default]
+    Name,
+    /// By the crate and module that registered each metric, from
+    /// [`MetricsRegistry::get_target`].
+    ///
+    /// Metrics with unknown targets are shown at the root.
+    Target,
+}
+
+impl TreeGrouping {
+    /// The path to `result` in the tree, or `None` if its name can't be
+    /// displayed.
+    fn path(self, result: &SearchResult, registry: &MetricsRegistry) -> Option<Vec<String>> {
+        const DELIM: &str = "::";
+
+        let name = result.key.key.name();
+        let is_invalid_component = |c: &str| c.is_empty() || c.starts_with(':') || c.ends_with(':');
+        match self {
+            Self::Name => {
+                let path: Vec<String> = name.split(DELIM).map(Into::into).collect();
+                (!path.iter().any(|c| is_invalid_component(c))).then_some(path)
+            }
+            Self::Target => {
+                if name.is_empty() {
+                    return None;
+                }
+                let mut path: Vec<String> = registry
+                    .get_target(&DescriptionKey::from(&result.key))
+                    .map(|target| target.split(DELIM).map(Into::into).collect())
+                    .unwrap_or_default();
+                path.retain(|c| !is_invalid_component(c));
+                path.push(name.into());
+                Some(path)
+            }
+        }
+    }
+}
@@ -0,0 +1,250 @@
+//! A simplified dashboard drawn with `bevy_ui` instead of `egui`.
+//!
+//! This is experimental. It only shows the latest value and a sparkline of
+//! each metric, and can't be configured from the UI.
+//!
+//! ```no_run
+//! # use bevy::prelude::*;
+//! # use bevy_metrics_dashboard::{
+//! #     metrics::Key,
+//! #     metrics_util::MetricKind,
+//! #     native_ui::{NativeDashboard, NativeDashboardPlugin},
+//! #     registry::MetricKey,
+//! #     RegistryPlugin,
+//! # };
+//! let frame_time = MetricKey::new(Key::from_static_name("frame_time"), MetricKind::Gauge);
+//! App::new()
+//!     .add_plugins((
+//!         DefaultPlugins,
+//!         RegistryPlugin::default(),
+//!         NativeDashboardPlugin,
+//!     ))
+//!     .add_systems(Startup, move |mut commands: Commands| {
+//!         commands.spawn(Camera2d);
+//!         commands.spawn(NativeDashboard::new().with_metric(frame_time.clone(), None));
+//!     });
+//! ```
+
+use crate::{
+    metrics_table::SampledMetric,
+    registry::{MetricKey, MetricsRegistry},
+    unit_str, FrameSnapshot, HistogramConsumerAppExt, Palette, ValueFormatters,
+};
+use bevy::prelude::*;
+use metrics::Unit;
+
+/// A `bevy_ui` node showing one row per metric, with its latest value and a
+/// sparkline of recent samples.
+///
+/// Histogram rows show the mean of the samples recorded each frame.
+///
+/// Sparklines are drawn as one bar node per sample, so they don't need any
+/// gizmos, meshes, or shaders. The dashboard lays out its own rows, so spawn
+/// it as a child of another node to position it.
+#[derive(Component)]
+#[require(Node)]
+pub struct NativeDashboard {
+    /// Metrics to add once the registry is available.
+    pending: Vec<(MetricKey, Option<Unit>)>,
+    rows: Vec<NativeRow>,
+    sparkline_len: usize,
+    sparkline_size: Vec2,
+}
+
+struct NativeRow {
+    metric: SampledMetric,
+    readout: Entity,
+    bars: Vec<Entity>,
+}
+
+impl Default for NativeDashboard {
+    fn default() -> Self {
+        Self {
+            pending: Vec::new(),
+            rows: Vec::new(),
+            sparkline_len: 60,
+            sparkline_size: Vec2::new(120.0, 16.0),
+        }
+    }
+}
+
+impl NativeDashboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a row for the metric identified by `key`.
+    pub fn with_metric(mut self, key: MetricKey, unit: Option<Unit>) -> Self {
+        self.add_metric(key, unit);
+        self
+    }
+
+    /// Set the number of samples shown in each sparkline, 60 by default.
+    ///
+    /// This only applies to rows added afterwards.
+    pub fn with_sparkline_len(mut self, len: usize) -> Self {
+        self.sparkline_len = len.max(1);
+        self
+    }
+
+    /// Set the size of each sparkline in logical pixels, 120 x 16 by default.
+    pub fn with_sparkline_size(mut self, size: Vec2) -> Self {
+        self.sparkline_size = size;
+        self
+    }
+
+    /// Add a row for the metric identified by `key` on the next update.
+    pub fn add_metric(&mut self, key: MetricKey, unit: Option<Unit>) {
+        let exists = self.rows.iter().any(|r| r.metric.key == key)
+            || self.pending.iter().any(|(k, _)| *k == key);
+        if !exists {
+            self.pending.push((key, unit));
+        }
+    }
+
+    pub fn metrics(&self) -> impl Iterator<Item = &MetricKey> {
+        self.rows
+            .iter()
+            .map(|r| &r.metric.key)
+            .chain(self.pending.iter().map(|(k, _)| k))
+    }
+
+    /// The most recent sample of the metric identified by `key`, if it has a
+    /// row.
+    pub fn latest_value(&self, key: &MetricKey) -> Option<f64> {
+        let row = self.rows.iter().find(|r| r.metric.key == *key)?;
+        row.metric.ring.latest().copied()
+    }
+
+    fn spawn_rows_system(
+        mut commands: Commands,
+        registry: Res<MetricsRegistry>,
+        palette: Option<Res<Palette>>,
+        mut dashboards: Query<(Entity, &mut Self, &mut Node)>,
+    ) {
+        let default_palette = Palette::default();
+        let palette = palette.as_deref().unwrap_or(&default_palette);
+        for (entity, mut dashboard, mut node) in &mut dashboards {
+            if dashboard.pending.is_empty() {
+                continue;
+            }
+            node.flex_direction = FlexDirection::Column;
+            node.row_gap = Val::Px(2.0);
+
+            let dashboard = &mut *dashboard;
+            for (key, unit) in std::mem::take(&mut dashboard.pending) {
+                let color = palette.color_for(&key);
+                let color = Color::srgba_u8(color.r(), color.g(), color.b(), color.a());
+                let len = dashboard.sparkline_len;
+                let size = dashboard.sparkline_size;
+                let mut readout = Entity::PLACEHOLDER;
+                let mut bars = Vec::with_capacity(len);
+                commands.entity(entity).with_children(|parent| {
+                    parent
+                        .spawn(Node {
+                            column_gap: Val::Px(8.0),
+                            align_items: AlignItems::Center,
+                            ..default()
+                        })
+                        .with_children(|row| {
+                            row.spawn(Node {
+                                width: Val::Px(size.x),
+                                height: Val::Px(size.y),
+                                align_items: AlignItems::End,
+                                ..default()
+                            })
+                            .with_children(|sparkline| {
+                                for _ in 0..len {
+                                    let bar = Node {
+                                        width: Val::Percent(100.0 / len as f32),
+                                        height: Val::Percent(0.0),
+                                        ..default()
+                                    };
+                                    bars.push(sparkline.spawn((bar, BackgroundColor(color))).id());
+                                }
+                            });
+                            readout = row.spawn(Text::default()).id();
+                        });
+                });
+                let metric = SampledMetric::new(&registry, key, unit, len);
+                dashboard.rows.push(NativeRow {
+                    metric,
+                    readout,
+                    bars,
+                });
+            }
+        }
+    }
+
+    fn sample_system(mut dashboards: Query<&mut Self>) {
+        // The DashboardPlugin's snapshot may be stale, e.g. in the background.
+        let live = FrameSnapshot::default();
+        for mut dashboard in &mut dashboards {
+            for row in &mut dashboard.rows {
+                row.metric.update(&live);
+            }
+        }
+    }
+
+    fn redraw_system(
+        formatters: Option<Res<ValueFormatters>>,
+        dashboards: Query<&Self>,
+        mut texts: Query<&mut Text>,
+        mut nodes: Query<&mut Node, Without<Self>>,
+    ) {
+        for dashboard in &dashboards {
+            for row in &dashboard.rows {
+                let SampledMetric {
+                    key, unit, ring, ..
+                } = &row.metric;
+                if let Ok(mut text) = texts.get_mut(row.readout) {
+                    let value = ring.latest().map_or_else(String::new, |&v| {
+                        match formatters.as_ref().and_then(|f| f.get(key, *unit)) {
+                            Some(formatter) => formatter(v),
+                            None => {
+                                let unit = unit.map_or("", unit_str);
+                                format!("{v:.3} {unit}")
+                            }
+                        }
+                    });
+                    text.0 = format!("{} = {value}", key.title(None, 0));
+                }
+
+                let (min, max) = ring
+                    .iter_chronological()
+                    .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &v| {
+                        (min.min(v), max.max(v))
+                    });
+                let range = (max - min).max(f64::EPSILON);
+                // Right-align the samples, so the newest is always last.
+                let n_empty = row.bars.len().saturating_sub(ring.len());
+                let values =
+                    std::iter::repeat_n(None, n_empty).chain(ring.iter_chronological().map(Some));
+                for (&bar, value) in row.bars.iter().zip(values) {
+                    if let Ok(mut node) = nodes.get_mut(bar) {
+                        // Keep a sliver of every sample visible.
+                        let t = value.map_or(0.0, |&v| 0.05 + 0.95 * (v - min) / range);
+                        node.height = Val::Percent(100.0 * t as f32);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Samples and redraws every [`NativeDashboard`].
+///
+/// This doesn't need `egui` or the [`DashboardPlugin`](crate::DashboardPlugin),
+/// but it uses the [`Palette`] and [`ValueFormatters`] if they exist.
+pub struct NativeDashboardPlugin;
+
+impl Plugin for NativeDashboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, NativeDashboard::spawn_rows_system)
+            .add_histogram_consumer(NativeDashboard::sample_system)
+            .add_systems(
+                Last,
+                NativeDashboard::redraw_system.after(NativeDashboard::sample_system),
+            );
+    }
+}
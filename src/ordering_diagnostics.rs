@@ -0,0 +1,235 @@
+use crate::{
+    registry::MetricsRegistry, ClearBucketsSystem, HistogramConsumers, ProduceMetricsSystem,
+};
+use bevy::{
+    ecs::{
+        component::Components,
+        schedule::{NodeId, ScheduleGraph},
+    },
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use std::fmt;
+
+/// A system ordering in the [`Last`] schedule that silently drops histogram
+/// samples, found by
+/// [`RegistryPlugin::with_ordering_diagnostics`](crate::RegistryPlugin::with_ordering_diagnostics).
+///
+/// Systems are named as in Bevy's logs, e.g. `my_game::plot_frame_times`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum OrderingProblem {
+    /// `consumer` reads the registry after the [`ClearBucketsSystem`], so it
+    /// never sees the samples of the frame.
+    ConsumerAfterClear { consumer: String },
+    /// `consumer` reads the registry without being ordered relative to the
+    /// [`ClearBucketsSystem`], so it misses samples on frames that it runs
+    /// after.
+    ConsumerNotBeforeClear { consumer: String },
+    /// `producer`, in the [`ProduceMetricsSystem`] set, isn't ordered before
+    /// `consumer`, so its samples can be cleared before `consumer` reads them.
+    ProducerNotBeforeConsumer { producer: String, consumer: String },
+}
+
+impl fmt::Display for OrderingProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ConsumerAfterClear { consumer } => write!(
+                f,
+                "{consumer} reads the MetricsRegistry after ClearBucketsSystem, so it never \
+                sees histogram samples. Order it .before(ClearBucketsSystem)."
+            ),
+            Self::ConsumerNotBeforeClear { consumer } => write!(
+                f,
+                "{consumer} reads the MetricsRegistry but isn't ordered before \
+                ClearBucketsSystem, so it may miss histogram samples. Order it \
+                .before(ClearBucketsSystem)."
+            ),
+            Self::ProducerNotBeforeConsumer { producer, consumer } => write!(
+                f,
+                "{producer} produces metrics but isn't ordered before {consumer}, so its \
+                histogram samples may be cleared before they are read. Order {consumer} \
+                .after(ProduceMetricsSystem)."
+            ),
+        }
+    }
+}
+
+/// The [`OrderingProblem`]s of the [`Last`] schedule, checked once after it
+/// first runs.
+///
+/// Only systems that access the [`MetricsRegistry`] or
+/// [`HistogramConsumers`] resources are known to be consumers, and only
+/// systems in the [`ProduceMetricsSystem`] set are known to be producers.
+/// Consumers holding their own registry handle, and producers writing through
+/// the `metrics` macros from other sets, aren't checked.
+#[derive(Debug, Default, Resource)]
+pub struct OrderingDiagnostics {
+    checked: bool,
+    problems: Vec<OrderingProblem>,
+}
+
+impl OrderingDiagnostics {
+    /// Whether the [`Last`] schedule has been checked yet.
+    pub fn is_checked(&self) -> bool {
+        self.checked
+    }
+
+    pub fn problems(&self) -> &[OrderingProblem] {
+        &self.problems
+    }
+
+    /// Find the problems of an initialized `schedule`, or `None` if it hasn't
+    /// been initialized.
+    pub fn find_problems(
+        schedule: &Schedule,
+        components: &Components,
+    ) -> Option<Vec<OrderingProblem>> {
+        let resources: Vec<_> = [
+            components.resource_id::<MetricsRegistry>(),
+            components.resource_id::<HistogramConsumers>(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        let graph = schedule.graph();
+        let systems: HashMap<NodeId, String> = schedule
+            .systems()
+            .ok()?
+            .map(|(id, system)| (id, system.name().into_owned()))
+            .collect();
+        let accesses_registry: HashSet<NodeId> = schedule
+            .systems()
+            .ok()?
+            .filter(|(_, system)| {
+                system
+                    .component_access()
+                    .resource_reads_and_writes()
+                    .any(|id| resources.contains(&id))
+            })
+            .map(|(id, _)| id)
+            .collect();
+
+        let clears = systems_in_set(graph, &ClearBucketsSystem);
+        if clears.is_empty() {
+            return Some(Vec::new());
+        }
+        let producers = systems_in_set(graph, &ProduceMetricsSystem);
+        let successors = flatten_dependencies(graph);
+        let mut consumers: Vec<_> = accesses_registry
+            .iter()
+            .copied()
+            .filter(|id| !clears.contains(id) && !producers.contains(id))
+            .collect();
+        consumers.sort_by(|a, b| systems[a].cmp(&systems[b]));
+
+        let mut problems = Vec::new();
+        for consumer in consumers {
+            let name = systems[&consumer].clone();
+            let after = reachable(&successors, consumer);
+            let before_clear = clears.iter().all(|clear| after.contains(clear));
+            if !before_clear {
+                let after_clear = clears
+                    .iter()
+                    .any(|&clear| reachable(&successors, clear).contains(&consumer));
+                problems.push(if after_clear {
+                    OrderingProblem::ConsumerAfterClear { consumer: name }
+                } else {
+                    OrderingProblem::ConsumerNotBeforeClear { consumer: name }
+                });
+                continue;
+            }
+            let mut late_producers: Vec<_> = producers
+                .iter()
+                .filter(|&&producer| !reachable(&successors, producer).contains(&consumer))
+                .map(|producer| systems[producer].clone())
+                .collect();
+            late_producers.sort();
+            problems.extend(late_producers.into_iter().map(|producer| {
+                OrderingProblem::ProducerNotBeforeConsumer {
+                    producer,
+                    consumer: name.clone(),
+                }
+            }));
+        }
+        Some(problems)
+    }
+
+    pub(crate) fn check_system(
+        schedules: Res<Schedules>,
+        components: &Components,
+        mut diagnostics: ResMut<Self>,
+    ) {
+        if diagnostics.checked {
+            return;
+        }
+        let Some(schedule) = schedules.get(Last) else {
+            return;
+        };
+        let Some(problems) = Self::find_problems(schedule, components) else {
+            return;
+        };
+        for problem in &problems {
+            warn!("Metrics system ordering: {problem}");
+        }
+        diagnostics.checked = true;
+        diagnostics.problems = problems;
+    }
+}
+
+/// Every system in `set` or its subsets.
+fn systems_in_set(graph: &ScheduleGraph, set: &dyn SystemSet) -> HashSet<NodeId> {
+    let Some((set_id, _, _)) = graph.system_sets().find(|(_, s, _)| *s == set) else {
+        return HashSet::new();
+    };
+    systems_under(graph, set_id)
+}
+
+fn systems_under(graph: &ScheduleGraph, node: NodeId) -> HashSet<NodeId> {
+    let hierarchy = graph.hierarchy().graph();
+    let mut systems = HashSet::new();
+    let mut stack = vec![node];
+    while let Some(node) = stack.pop() {
+        if node.is_system() {
+            systems.insert(node);
+        } else {
+            stack.extend(hierarchy.neighbors(node));
+        }
+    }
+    systems
+}
+
+/// The systems ordered directly after each system, with ordering between
+/// sets applied to all of their systems.
+fn flatten_dependencies(graph: &ScheduleGraph) -> HashMap<NodeId, Vec<NodeId>> {
+    let mut members = HashMap::<NodeId, HashSet<NodeId>>::new();
+    let mut successors = HashMap::<NodeId, Vec<NodeId>>::new();
+    for (before, after, _) in graph.dependency().graph().all_edges() {
+        for id in [before, after] {
+            members
+                .entry(id)
+                .or_insert_with(|| systems_under(graph, id));
+        }
+        for &system in &members[&before] {
+            successors
+                .entry(system)
+                .or_default()
+                .extend(members[&after].iter().copied());
+        }
+    }
+    successors
+}
+
+/// Every system ordered after `system`, directly or not.
+fn reachable(successors: &HashMap<NodeId, Vec<NodeId>>, system: NodeId) -> HashSet<NodeId> {
+    let mut reached = HashSet::new();
+    let mut stack = vec![system];
+    while let Some(node) = stack.pop() {
+        for &next in successors.get(&node).into_iter().flatten() {
+            if reached.insert(next) {
+                stack.push(next);
+            }
+        }
+    }
+    reached
+}
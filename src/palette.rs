@@ -0,0 +1,87 @@
+//! Colors assigned to plotted series.
+
+use crate::registry::MetricKey;
+use bevy::{prelude::Resource, utils::HashMap};
+use bevy_egui::egui::Color32;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+/// The Okabe-Ito palette, which stays distinguishable for the most common
+/// kinds of color blindness. Black is omitted so every color is visible on
+/// the dark theme.
+pub const OKABE_ITO: [Color32; 7] = [
+    Color32::from_rgb(230, 159, 0),
+    Color32::from_rgb(86, 180, 233),
+    Color32::from_rgb(0, 158, 115),
+    Color32::from_rgb(240, 228, 66),
+    Color32::from_rgb(0, 114, 178),
+    Color32::from_rgb(213, 94, 0),
+    Color32::from_rgb(204, 121, 167),
+];
+
+/// Assigns a color to every metric series.
+///
+/// Each [`MetricKey`] is hashed to pick a palette color, so the same metric
+/// gets the same color in every plot, window, and run of the same build.
+/// Specific keys can be pinned to a color with [`Palette::set_color`].
+///
+/// Defaults to [`OKABE_ITO`].
+#[derive(Clone, Resource)]
+pub struct Palette {
+    colors: Vec<Color32>,
+    overrides: HashMap<MetricKey, Color32>,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::new(OKABE_ITO.to_vec())
+    }
+}
+
+impl Palette {
+    /// # Panics
+    ///
+    /// If `colors` is empty.
+    pub fn new(colors: Vec<Color32>) -> Self {
+        assert!(!colors.is_empty(), "Palette must have at least one color");
+        Self {
+            colors,
+            overrides: HashMap::default(),
+        }
+    }
+
+    pub fn colors(&self) -> &[Color32] {
+        &self.colors
+    }
+
+    /// Always use `color` for the series of `key`.
+    pub fn set_color(&mut self, key: MetricKey, color: Color32) {
+        self.overrides.insert(key, color);
+    }
+
+    pub fn remove_color(&mut self, key: &MetricKey) {
+        self.overrides.remove(key);
+    }
+
+    /// The color of the series of `key`.
+    pub fn color_for(&self, key: &MetricKey) -> Color32 {
+        if let Some(&color) = self.overrides.get(key) {
+            return color;
+        }
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        self.color_at(hasher.finish() as usize)
+    }
+
+    /// The color of a series that isn't a metric, like a
+    /// [`CustomSeries`](crate::plots::CustomSeries).
+    pub fn color_for_name(&self, name: &str) -> Color32 {
+        let mut hasher = DefaultHasher::new();
+        name.hash(&mut hasher);
+        self.color_at(hasher.finish() as usize)
+    }
+
+    /// The `i`th palette color, wrapping around.
+    pub fn color_at(&self, i: usize) -> Color32 {
+        self.colors[i % self.colors.len()]
+    }
+}
@@ -0,0 +1,156 @@
+//! Custom ways of plotting a metric, alongside the built-in counter, gauge,
+//! and histogram plots.
+//!
+//! A [`PlotKind`] is registered as a [`PlotTemplate`](crate::plot_templates::PlotTemplate),
+//! so it's offered in the settings of every plot of its [`MetricKind`]. Plots
+//! of custom kinds are otherwise like any other [`MetricPlot`](crate::plots::MetricPlot):
+//! they live in dashboards, are cached when removed, and follow the layout.
+//!
+//! ```no_run
+//! # use bevy::prelude::*;
+//! # use bevy_metrics_dashboard::{
+//! #     bevy_egui::egui::Ui,
+//! #     metrics::atomics::AtomicU64,
+//! #     metrics_util::MetricKind,
+//! #     plot_kind::{PlotContext, PlotKind},
+//! #     registry::MetricKey,
+//! #     FrameSnapshot, PlotTemplates,
+//! # };
+//! # use std::sync::{atomic::Ordering, Arc};
+//! /// Shows a heading in degrees.
+//! struct Compass {
+//!     source: Arc<AtomicU64>,
+//!     degrees: f64,
+//! }
+//!
+//! impl PlotKind for Compass {
+//!     fn update(&mut self, key: &MetricKey, snapshot: Option<&FrameSnapshot>) {
+//!         self.degrees = snapshot
+//!             .and_then(|s| s.gauge(&key.key))
+//!             .unwrap_or_else(|| f64::from_bits(self.source.load(Ordering::Relaxed)));
+//!     }
+//!
+//!     fn draw(&mut self, ctx: &PlotContext, ui: &mut Ui) {
+//!         ui.label(format!("{}: {:.0}°", ctx.name(), self.degrees));
+//!     }
+//! }
+//!
+//! let mut templates = PlotTemplates::default();
+//! templates.register_kind("compass", MetricKind::Gauge, |registry, key, _unit| {
+//!     let source = registry.get_or_create_gauge(&key.key);
+//!     Box::new(Compass { source, degrees: 0.0 })
+//! });
+//! App::new().insert_resource(templates);
+//! ```
+
+use crate::{
+    dashboard_window::DashboardConfig,
+    frame_snapshot::FrameSnapshot,
+    plots::EventTrack,
+    registry::{MetricKey, MetricsRegistry},
+    value_formatters::{AxisFormat, ValueFormatter},
+};
+use bevy_egui::egui::{Color32, Id, Ui};
+use egui_plot::{Plot, PlotUi};
+use metrics::Unit;
+use std::sync::Arc;
+
+/// A way of plotting one metric.
+///
+/// The built-in counter, gauge, and histogram plots implement this too.
+pub trait PlotKind: Send + Sync + 'static {
+    /// Pull new data for the metric identified by `key`, reading counters and
+    /// gauges from `snapshot` if there is one.
+    ///
+    /// This runs once per frame, while the plot isn't paused.
+    fn update(&mut self, key: &MetricKey, snapshot: Option<&FrameSnapshot>);
+
+    /// Draw the plot, under its header.
+    fn draw(&mut self, ctx: &PlotContext, ui: &mut Ui);
+
+    /// Draw settings specific to this kind, at the end of the plot's
+    /// "Settings" section. Read-only dashboards don't show settings.
+    fn configure_ui(&mut self, _ctx: &PlotContext, _ui: &mut Ui) {}
+
+    /// The most recently plotted value, for sorting and summaries.
+    fn latest_value(&self) -> Option<f64> {
+        None
+    }
+}
+
+/// Creates the [`PlotKind`] of a plot of the metric identified by the key.
+pub type PlotKindFactory =
+    Arc<dyn Fn(&MetricsRegistry, &MetricKey, Option<Unit>) -> Box<dyn PlotKind> + Send + Sync>;
+
+/// What a [`PlotKind`] needs to know about the plot being drawn.
+pub struct PlotContext<'a> {
+    pub(crate) dash_config: &'a DashboardConfig,
+    pub(crate) name: &'a str,
+    pub(crate) plot_id: Id,
+    pub(crate) unit: Option<Unit>,
+    pub(crate) formatter: Option<&'a ValueFormatter>,
+    pub(crate) axis_format: AxisFormat,
+    pub(crate) color: Color32,
+    pub(crate) events: Option<(&'a EventTrack, Color32)>,
+}
+
+impl<'a> PlotContext<'a> {
+    pub fn dash_config(&self) -> &DashboardConfig {
+        self.dash_config
+    }
+
+    pub fn name(&self) -> &str {
+        self.name
+    }
+
+    /// The ID of the plot, unique within its dashboard.
+    pub fn plot_id(&self) -> Id {
+        self.plot_id
+    }
+
+    pub fn unit(&self) -> Option<Unit> {
+        self.unit
+    }
+
+    /// The formatter for values of this plot, if any.
+    pub fn formatter(&self) -> Option<&ValueFormatter> {
+        self.formatter
+    }
+
+    pub fn axis_format(&self) -> AxisFormat {
+        self.axis_format
+    }
+
+    /// The plot's [`Palette`](crate::Palette) color.
+    pub fn color(&self) -> Color32 {
+        self.color
+    }
+
+    /// A [`Plot`] with the same ID, size, and tooltip as the built-in plots.
+    pub fn new_plot(&self) -> Plot<'a> {
+        let plot = Plot::new(self.name)
+            .id(self.plot_id)
+            .allow_scroll(false)
+            .view_aspect(2.0)
+            .auto_bounds([true, true].into());
+        if let Some(formatter) = self.formatter.cloned() {
+            plot.label_formatter(move |_, point| {
+                format!("x = {:.0}\ny = {}", point.x, formatter(point.y))
+            })
+        } else {
+            plot
+        }
+    }
+
+    /// Show `plot`, respecting the dashboard's
+    /// [`redraw_interval`](DashboardConfig::redraw_interval).
+    pub fn show_plot<'b>(&self, plot: Plot<'b>, ui: &mut Ui, build: impl FnOnce(&mut PlotUi) + 'b) {
+        crate::plots::show_plot(
+            plot,
+            self.plot_id,
+            self.dash_config.redraw_interval,
+            ui,
+            build,
+        );
+    }
+}
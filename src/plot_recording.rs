@@ -0,0 +1,179 @@
+use crate::{
+    frame_snapshot::FrameSnapshot,
+    locale::tr,
+    registry::{key_text, label_text, BucketCursor, MetricKey, MetricsRegistry},
+};
+use bevy::log::error;
+use bevy_egui::egui::Ui;
+use metrics_util::MetricKind;
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::atomic::Ordering,
+    time::{Instant, SystemTime},
+};
+
+/// Streams every sample of a plot's metric to a CSV file, regardless of the
+/// plot's window, see
+/// [`MetricPlot::start_recording`](crate::plots::MetricPlot::start_recording).
+///
+/// Counters and gauges are recorded once per update, at their raw value
+/// before smoothing. Histograms are recorded once per sample.
+pub(crate) struct PlotRecording {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    start: Instant,
+    cursor: Option<BucketCursor>,
+    n_samples: u64,
+}
+
+impl PlotRecording {
+    pub fn start(path: PathBuf) -> io::Result<Self> {
+        let file = File::create(&path)?;
+        Self::with_file(path, file)
+    }
+
+    /// Start recording `key` to a new file in the working directory, like
+    /// `render_time-gpu_0-1700000000.csv`, numbered like
+    /// `render_time-gpu_0-1700000000-2.csv` if that file already exists.
+    pub fn start_new(key: &MetricKey) -> io::Result<Self> {
+        let stem = file_stem(key);
+        let mut n = 1;
+        loop {
+            let path = PathBuf::from(match n {
+                1 => format!("{stem}.csv"),
+                n => format!("{stem}-{n}.csv"),
+            });
+            match File::options().write(true).create_new(true).open(&path) {
+                Ok(file) => return Self::with_file(path, file),
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => n += 1,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    fn with_file(path: PathBuf, file: File) -> io::Result<Self> {
+        let mut writer = BufWriter::new(file);
+        writeln!(writer, "frame,seconds,value")?;
+        Ok(Self {
+            path,
+            writer,
+            start: Instant::now(),
+            cursor: None,
+            n_samples: 0,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn n_samples(&self) -> u64 {
+        self.n_samples
+    }
+
+    /// Write the samples of `key` since the previous call.
+    pub fn record(
+        &mut self,
+        registry: &MetricsRegistry,
+        key: &MetricKey,
+        snapshot: Option<&FrameSnapshot>,
+    ) -> io::Result<()> {
+        let frame = snapshot
+            .and_then(FrameSnapshot::frame)
+            .map_or_else(String::new, |f| f.to_string());
+        let seconds = self.start.elapsed().as_secs_f64();
+        let value = match key.kind {
+            MetricKind::Counter => snapshot
+                .and_then(|s| s.counter(&key.key))
+                .unwrap_or_else(|| {
+                    registry
+                        .get_or_create_counter(&key.key)
+                        .load(Ordering::Relaxed)
+                }) as f64,
+            MetricKind::Gauge => snapshot.and_then(|s| s.gauge(&key.key)).unwrap_or_else(|| {
+                f64::from_bits(
+                    registry
+                        .get_or_create_gauge(&key.key)
+                        .load(Ordering::Relaxed),
+                )
+            }),
+            MetricKind::Histogram => {
+                let bucket = registry.get_or_create_histogram(&key.key);
+                let cursor = self
+                    .cursor
+                    .get_or_insert_with(|| BucketCursor::new(registry));
+                let mut result = Ok(());
+                cursor.read_new(&bucket, |value| {
+                    if result.is_ok() {
+                        result = writeln!(self.writer, "{frame},{seconds},{value}");
+                        self.n_samples += 1;
+                    }
+                });
+                return result;
+            }
+        };
+        self.n_samples += 1;
+        writeln!(self.writer, "{frame},{seconds},{value}")
+    }
+
+    /// Flush the file and close it.
+    pub fn finish(mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// The file name of a new recording of `key`, without its extension, from
+/// its name, its labels, and the time.
+fn file_stem(key: &MetricKey) -> String {
+    let sanitize = |text: &str| -> String {
+        text.chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect()
+    };
+    let mut stem = sanitize(key.key.name());
+    for label in key.key.labels() {
+        stem.push('-');
+        stem.push_str(&sanitize(&label_text(label)));
+    }
+    let secs = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    format!("{stem}-{secs}")
+}
+
+/// A checkbox to start and stop recording `key`, with the file being written.
+pub(crate) fn recording_ui(key: &MetricKey, recording: &mut Option<PlotRecording>, ui: &mut Ui) {
+    ui.horizontal(|ui| {
+        let mut enabled = recording.is_some();
+        let response = ui
+            .checkbox(&mut enabled, tr(ui, "Record to Disk"))
+            .on_hover_text(tr(
+                ui,
+                "Write every sample to a CSV file while the plot is open, not just the \
+                visible window",
+            ));
+        if response.changed() {
+            if enabled {
+                match PlotRecording::start_new(key) {
+                    Ok(started) => *recording = Some(started),
+                    Err(e) => error!("Failed to start recording {}: {e}", key_text(&key.key)),
+                }
+            } else if let Some(stopped) = recording.take() {
+                let path = stopped.path().to_owned();
+                if let Err(e) = stopped.finish() {
+                    error!("Failed to finish recording to {}: {e}", path.display());
+                }
+            }
+        }
+        if let Some(recording) = recording {
+            ui.weak(format!(
+                "{} ({} {})",
+                recording.path().display(),
+                recording.n_samples(),
+                tr(ui, "samples")
+            ));
+        }
+    });
+}
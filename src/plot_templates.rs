@@ -0,0 +1,179 @@
+//! Reusable plot configurations.
+
+use crate::{
+    plot_kind::PlotKind,
+    plots::{
+        BucketConfig, BucketRange, CounterPlotConfig, CustomPlotConfig, GaugePlotConfig,
+        HistogramPlotConfig, MetricPlotConfig,
+    },
+    registry::{MetricKey, MetricsRegistry},
+    value_formatters::ValueFormatter,
+};
+use bevy::prelude::{default, Resource};
+use metrics::Unit;
+use metrics_util::MetricKind;
+use std::sync::Arc;
+
+/// A named plot configuration that can be applied to any plot of the same
+/// [`MetricKind`] from its settings.
+#[derive(Clone)]
+pub struct PlotTemplate {
+    pub name: String,
+    /// The unit this template is meant for. Templates without a unit, and
+    /// all templates for metrics without a unit, are offered regardless.
+    pub unit: Option<Unit>,
+    pub config: MetricPlotConfig,
+    /// Overrides the [`ValueFormatters`](crate::ValueFormatters) of plots
+    /// using this template.
+    pub formatter: Option<ValueFormatter>,
+}
+
+impl PlotTemplate {
+    pub fn new(name: impl Into<String>, config: MetricPlotConfig) -> Self {
+        Self {
+            name: name.into(),
+            unit: None,
+            config,
+            formatter: None,
+        }
+    }
+
+    pub fn with_unit(mut self, unit: Unit) -> Self {
+        self.unit = Some(unit);
+        self
+    }
+
+    pub fn with_formatter(
+        mut self,
+        formatter: impl Fn(f64) -> String + Send + Sync + 'static,
+    ) -> Self {
+        self.formatter = Some(Arc::new(formatter));
+        self
+    }
+
+    pub fn kind(&self) -> MetricKind {
+        self.config.kind()
+    }
+}
+
+/// The [`PlotTemplate`]s offered in plot settings.
+///
+/// The default set contains built-in templates:
+///
+/// - "latency-ms": a histogram of 0–33 ms with logarithmic buckets and a p99
+///   marker
+/// - "bytes": counters and gauges formatted in MiB
+///
+/// ```
+/// # use bevy_metrics_dashboard::plot_templates::{PlotTemplate, PlotTemplates};
+/// # use bevy_metrics_dashboard::plots::{GaugePlotConfig, MetricPlotConfig};
+/// let mut templates = PlotTemplates::default();
+/// templates.add(PlotTemplate::new(
+///     "smooth",
+///     MetricPlotConfig::Gauge(GaugePlotConfig {
+///         smoothing_weight: 0.99,
+///         ..Default::default()
+///     }),
+/// ));
+/// ```
+#[derive(Clone, Resource)]
+pub struct PlotTemplates {
+    templates: Vec<PlotTemplate>,
+}
+
+impl Default for PlotTemplates {
+    fn default() -> Self {
+        let mut templates = Self::empty();
+        for template in built_in_templates() {
+            templates.add(template);
+        }
+        templates
+    }
+}
+
+impl PlotTemplates {
+    /// A set without the built-in templates.
+    pub fn empty() -> Self {
+        Self {
+            templates: Vec::new(),
+        }
+    }
+
+    /// Add `template`, replacing any existing template with the same name and
+    /// kind.
+    pub fn add(&mut self, template: PlotTemplate) {
+        self.templates
+            .retain(|t| t.name != template.name || t.kind() != template.kind());
+        self.templates.push(template);
+    }
+
+    /// Offer a custom [`PlotKind`] called `name` for metrics of
+    /// `metric_kind`, with plots created by `factory`. See
+    /// [`plot_kind`](crate::plot_kind).
+    pub fn register_kind(
+        &mut self,
+        name: impl Into<String>,
+        metric_kind: MetricKind,
+        factory: impl Fn(&MetricsRegistry, &MetricKey, Option<Unit>) -> Box<dyn PlotKind>
+            + Send
+            + Sync
+            + 'static,
+    ) {
+        let name = name.into();
+        let config = CustomPlotConfig::new(name.clone(), metric_kind, factory);
+        self.add(PlotTemplate::new(name, MetricPlotConfig::Custom(config)));
+    }
+
+    /// Remove all templates called `name`.
+    pub fn remove(&mut self, name: &str) {
+        self.templates.retain(|t| t.name != name);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &PlotTemplate> {
+        self.templates.iter()
+    }
+
+    /// The templates applicable to a metric of `kind` and `unit`.
+    pub fn matching(
+        &self,
+        kind: MetricKind,
+        unit: Option<Unit>,
+    ) -> impl Iterator<Item = &PlotTemplate> {
+        self.templates.iter().filter(move |t| {
+            t.kind() == kind && (t.unit.is_none() || unit.is_none() || t.unit == unit)
+        })
+    }
+}
+
+fn built_in_templates() -> Vec<PlotTemplate> {
+    let latency_range = BucketRange {
+        n_buckets: 16,
+        min: 0.5,
+        max: 33.3,
+        log_scale: true,
+    };
+    let latency = HistogramPlotConfig {
+        buckets: BucketConfig {
+            bounds: latency_range.get_bounds(),
+            range_input: latency_range,
+        },
+        overlay_percentile: Some(99.0),
+        auto_range: false,
+        ..default()
+    };
+    let mib = |bytes: f64| format!("{:.2} MiB", bytes / (1024.0 * 1024.0));
+
+    vec![
+        PlotTemplate::new("latency-ms", MetricPlotConfig::Histogram(Box::new(latency)))
+            .with_unit(Unit::Milliseconds),
+        PlotTemplate::new(
+            "bytes",
+            MetricPlotConfig::Counter(CounterPlotConfig::default()),
+        )
+        .with_unit(Unit::Bytes)
+        .with_formatter(mib),
+        PlotTemplate::new("bytes", MetricPlotConfig::Gauge(GaugePlotConfig::default()))
+            .with_unit(Unit::Bytes)
+            .with_formatter(mib),
+    ]
+}
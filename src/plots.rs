@@ -1,18 +1,45 @@
 //! Widgets for plotting metrics.
 
 use crate::dashboard_window::DashboardConfig;
-use crate::registry::{MetricKey, MetricsRegistry};
+use crate::frame_snapshot::FrameSnapshot;
+use crate::locale::{tr, tr_unit};
+use crate::metric_kind_str;
+use crate::palette::Palette;
+use crate::plot_kind::{PlotContext, PlotKind, PlotKindFactory};
+use crate::plot_recording::{recording_ui, PlotRecording};
+use crate::plot_templates::{PlotTemplate, PlotTemplates};
+use crate::registry::{labels_text, BucketCursor, MetricKey, MetricsRegistry};
 use crate::ring::Ring;
-use crate::unit_str;
-use bevy::prelude::default;
-use bevy_egui::egui::{Color32, DragValue, Slider, Ui};
-use egui_plot::{Bar, BarChart, Line, Plot, PlotPoint, PlotPoints};
+use crate::value_formatters::{AxisFormat, ValueFormatter, ValueFormatters};
+use bevy::{prelude::default, utils::HashMap};
+use bevy_egui::egui::{
+    collapsing_header::CollapsingState,
+    emath,
+    epaint::{ClippedPrimitive, Primitive},
+    pos2, show_tooltip_at_pointer, vec2, Checkbox, Color32, DragValue, Frame, Grid, Id, Margin,
+    Pos2, Rect, RichText, Sense, Shape, Slider, Stroke, TextEdit, Ui,
+};
+use egui_plot::{
+    Axis, AxisHints, Bar, BarChart, GridMark, HPlacement, Legend, Line, LineStyle, Plot,
+    PlotBounds, PlotMemory, PlotPoint, PlotPoints, PlotResponse, PlotUi, Polygon, VLine,
+};
 use float_ord::FloatOrd;
 use metrics::atomics::AtomicU64;
 use metrics::Unit;
-use metrics_util::{storage::AtomicBucket, MetricKind};
+use metrics_util::{
+    storage::{AtomicBucket, Summary},
+    MetricKind,
+};
 use smallvec::{smallvec, SmallVec};
-use std::sync::{atomic::Ordering, Arc};
+use std::{
+    collections::VecDeque,
+    fmt::Write,
+    io,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    sync::{atomic::Ordering, mpsc::Receiver, Arc, Mutex},
+    time::{Duration, Instant},
+};
 
 // BUG: If you have a constant metric, it results in a perfectly horizontal line
 // plot. Such a plot seems invisible because the axes auto-fit with a height
@@ -21,13 +48,35 @@ use std::sync::{atomic::Ordering, Arc};
 // https://github.com/emilk/egui/issues/3970
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MetricPlotConfig {
     Counter(CounterPlotConfig),
     Gauge(GaugePlotConfig),
-    Histogram(HistogramPlotConfig),
+    /// Boxed, since it's much larger than the other configs.
+    Histogram(Box<HistogramPlotConfig>),
+    /// A [`PlotKind`] from outside this crate.
+    Custom(CustomPlotConfig),
 }
 
 impl MetricPlotConfig {
+    pub fn kind(&self) -> MetricKind {
+        match self {
+            MetricPlotConfig::Counter(_) => MetricKind::Counter,
+            MetricPlotConfig::Gauge(_) => MetricKind::Gauge,
+            MetricPlotConfig::Histogram(_) => MetricKind::Histogram,
+            MetricPlotConfig::Custom(config) => config.metric_kind,
+        }
+    }
+
+    /// Whether metrics of `kind` can be plotted with this config.
+    ///
+    /// Besides their own configs, gauges can be plotted as histograms of
+    /// their values.
+    pub fn supports_kind(&self, kind: MetricKind) -> bool {
+        self.kind() == kind
+            || (kind == MetricKind::Gauge && matches!(self, MetricPlotConfig::Histogram(_)))
+    }
+
     pub fn default_for_kind(kind: MetricKind) -> Self {
         match kind {
             MetricKind::Counter => MetricPlotConfig::Counter(default()),
@@ -38,25 +87,50 @@ impl MetricPlotConfig {
 }
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct CounterPlotConfig {
     pub window_size: usize,
+    /// Instead of a fixed `window_size`, keep about this much time of
+    /// samples, e.g. the last 10 seconds, at the measured sample rate.
+    ///
+    /// Linked X axes take precedence.
+    pub auto_window: Option<Duration>,
     pub derivative: bool,
+    /// Draw the increments on each frame as bars instead of a line, which
+    /// better represents bursty event counters. This takes precedence over
+    /// `derivative`.
+    pub increment_bars: bool,
+    pub y_lock: YAxisLock,
 }
 
 impl Default for CounterPlotConfig {
     fn default() -> Self {
         Self {
             window_size: 500,
+            auto_window: None,
             derivative: false,
+            increment_bars: false,
+            y_lock: default(),
         }
     }
 }
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct GaugePlotConfig {
     pub smoothing_weight: f64,
     pub window_size: usize,
+    /// See [`CounterPlotConfig::auto_window`].
+    pub auto_window: Option<Duration>,
     pub derivative: bool,
+    /// Fit a line to the visible samples and show its slope per minute, e.g.
+    /// to tell whether an entity count or memory usage is leaking.
+    pub trend: bool,
+    /// Trends steeper than this per minute are highlighted as a likely leak.
+    pub leak_threshold_per_minute: f64,
+    pub y_lock: YAxisLock,
 }
 
 impl Default for GaugePlotConfig {
@@ -64,30 +138,229 @@ impl Default for GaugePlotConfig {
         Self {
             smoothing_weight: 0.8,
             window_size: 500,
+            auto_window: None,
             derivative: false,
+            trend: false,
+            leak_threshold_per_minute: 0.0,
+            y_lock: default(),
+        }
+    }
+}
+
+/// Fixed ends of a line plot's Y axis, so it doesn't rescale whenever a spike
+/// enters or leaves the window.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct YAxisLock {
+    /// The bottom of the axis, or fitted to the samples if `None`.
+    pub min: Option<f64>,
+    /// The top of the axis, or fitted to the samples if `None`.
+    pub max: Option<f64>,
+}
+
+impl YAxisLock {
+    pub fn is_locked(&self) -> bool {
+        self.min.is_some() || self.max.is_some()
+    }
+
+    /// The range of the Y axis when plotting `points`, or `None` if neither
+    /// end is locked.
+    ///
+    /// If the locked end is past all of the points, the range extends one
+    /// unit from it.
+    pub fn range(&self, points: &[PlotPoint]) -> Option<RangeInclusive<f64>> {
+        if !self.is_locked() {
+            return None;
+        }
+        let (fitted_min, fitted_max) = points
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), p| {
+                (min.min(p.y), max.max(p.y))
+            });
+        let min = self.min.unwrap_or(fitted_min);
+        let max = self.max.unwrap_or(fitted_max);
+        if min < max && min.is_finite() && max.is_finite() {
+            Some(min..=max)
+        } else if let Some(min) = self.min {
+            Some(min..=min + 1.0)
+        } else {
+            self.max.map(|max| max - 1.0..=max)
         }
     }
 }
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct HistogramPlotConfig {
     /// When `Some`, the bar chart is derived from a sliding window of
     /// data. Otherwise, the bar chart retains all data until it is reset or
     /// reconfigured.
     pub window_size: Option<usize>,
+    /// Resize the sliding window to hold about this much time of values at
+    /// their measured rate, so rarely recorded histograms still fill it.
+    pub auto_window: Option<Duration>,
     pub buckets: BucketConfig,
+    pub mode: HistogramPlotMode,
+    /// When `Some`, a vertical line marks this percentile (in the range
+    /// `[0, 100]`) of the bar chart's data.
+    pub overlay_percentile: Option<f64>,
+    /// If the metric's unit is a duration, replace the bucket range with one
+    /// fitted to the first observed values.
+    ///
+    /// This is turned off once the range is fitted or edited, so the fitted
+    /// range is kept by clones of this config.
+    pub auto_range: bool,
+    /// How empty buckets are drawn in the bar chart.
+    pub empty_buckets: EmptyBuckets,
+    /// Draw each bar as a percentage of all samples in the buckets instead of
+    /// a count, so histograms with different windows can be compared.
+    pub normalize: bool,
+    /// Show the count, sum, rate, and quantiles of every sample since the
+    /// plot was created, from a [`HistogramSummary`].
+    ///
+    /// Without a sliding window, the percentile marker also uses the summary,
+    /// so it covers the same samples as the bars.
+    pub summary_readouts: bool,
 }
 
 impl Default for HistogramPlotConfig {
     fn default() -> Self {
         Self {
             window_size: Some(500),
+            auto_window: None,
             buckets: default(),
+            mode: default(),
+            overlay_percentile: None,
+            auto_range: true,
+            empty_buckets: default(),
+            normalize: false,
+            summary_readouts: false,
+        }
+    }
+}
+
+/// Plots a metric with a custom [`PlotKind`], created by its factory.
+///
+/// Only the name and metric kind are serialized. Deserialized configs get
+/// their factory back from the kind registered under the same name with
+/// [`PlotTemplates::register_kind`], see [`Self::resolve_factory`]. Plots of
+/// configs without a factory use the default config for their kind.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CustomPlotConfig {
+    pub name: String,
+    #[cfg_attr(feature = "serde", serde(with = "metric_kind_serde"))]
+    pub metric_kind: MetricKind,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    factory: Option<PlotKindFactory>,
+}
+
+impl CustomPlotConfig {
+    /// Plot metrics of `metric_kind` with the [`PlotKind`]s made by
+    /// `factory`.
+    pub fn new(
+        name: impl Into<String>,
+        metric_kind: MetricKind,
+        factory: impl Fn(&MetricsRegistry, &MetricKey, Option<Unit>) -> Box<dyn PlotKind>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            metric_kind,
+            factory: Some(Arc::new(factory)),
+        }
+    }
+
+    /// Take the factory of the custom kind in `templates` with the same name
+    /// and metric kind, if this config has none, e.g. after deserializing it.
+    ///
+    /// Returns `false` if this config still has no factory.
+    pub fn resolve_factory(&mut self, templates: &PlotTemplates) -> bool {
+        if self.factory.is_none() {
+            self.factory = templates.iter().find_map(|t| match &t.config {
+                MetricPlotConfig::Custom(c)
+                    if c.name == self.name && c.metric_kind == self.metric_kind =>
+                {
+                    c.factory.clone()
+                }
+                _ => None,
+            });
+        }
+        self.factory.is_some()
+    }
+}
+
+#[cfg(feature = "serde")]
+mod metric_kind_serde {
+    use metrics_util::MetricKind;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(kind: &MetricKind, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(crate::metric_kind_str(*kind))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<MetricKind, D::Error> {
+        let kind = String::deserialize(deserializer)?;
+        crate::parse_metric_kind(&kind)
+            .ok_or_else(|| D::Error::custom(format!("unknown metric kind {kind}")))
+    }
+}
+
+/// How a histogram's bar chart draws buckets without any samples.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EmptyBuckets {
+    /// Draw every bucket.
+    #[default]
+    Show,
+    /// Hide the empty buckets before the first and after the last occupied
+    /// bucket.
+    Trim,
+    /// Trim, and also collapse each run of empty buckets between occupied
+    /// ones into a narrow gap. The X axis is no longer linear, but its labels
+    /// still show the bucket values.
+    Merge,
+}
+
+impl EmptyBuckets {
+    const ALL: [Self; 3] = [Self::Show, Self::Trim, Self::Merge];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Show => "Show",
+            Self::Trim => "Trim",
+            Self::Merge => "Merge",
         }
     }
 }
 
+/// How a histogram is plotted.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HistogramPlotMode {
+    /// A bar chart of bucket counts.
+    #[default]
+    Buckets,
+    /// A line of the chosen percentile of the histogram's current window,
+    /// computed every frame, e.g. a rolling p99 of frame times.
+    ///
+    /// Without a sliding window, the percentile is computed from the samples
+    /// recorded since the last update.
+    Percentile {
+        /// In the range `[0, 100]`.
+        percentile: f64,
+        /// The number of frames shown in the line plot.
+        history_size: usize,
+    },
+}
+
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct BucketConfig {
     /// Sorted list of boundaries between contiguous bucket ranges.
     pub bounds: BoundsVec,
@@ -98,13 +371,34 @@ pub type BoundsVec = SmallVec<[f64; 16]>;
 pub type CountsVec = SmallVec<[u32; 16]>;
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct BucketRange {
     pub n_buckets: usize,
     pub min: f64,
     pub max: f64,
+    /// Space bucket bounds geometrically instead of linearly. This requires
+    /// `min > 0`, so smaller values of `min` are clamped.
+    pub log_scale: bool,
 }
 
 impl BucketRange {
+    /// Fit the range to `values`, rounding its ends outward to 1, 2, or 5
+    /// times a power of 10. Non-negative values get a range starting at 0.
+    pub fn fit(&mut self, values: &[f64]) {
+        let (min, max) = values
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &v| {
+                (min.min(v), max.max(v))
+            });
+        if !min.is_finite() || !max.is_finite() {
+            return;
+        }
+        self.min = if min >= 0.0 { 0.0 } else { -round_up(-min) };
+        self.max = if max > 0.0 { round_up(max) } else { 0.0 };
+        self.clamp_max();
+    }
+
     /// Prevent `self.min >= self.max` by clamping `self.min`.
     pub fn clamp_min(&mut self) {
         self.min = self.min.min(self.max - 0.001);
@@ -117,11 +411,30 @@ impl BucketRange {
 
     pub fn get_bounds(&self) -> BoundsVec {
         assert!(self.max > self.min, "{} > {}", self.max, self.min);
+        if self.log_scale {
+            let min = self.min.max(Self::MIN_LOG_SCALE).min(self.max * 0.5);
+            let ratio = (self.max / min).powf(1.0 / self.n_buckets as f64);
+            return (0..=self.n_buckets)
+                .map(|i| min * ratio.powi(i as i32))
+                .collect();
+        }
         let width = (self.max - self.min) / self.n_buckets as f64;
         (0..=self.n_buckets)
             .map(|i| self.min + i as f64 * width)
             .collect()
     }
+
+    const MIN_LOG_SCALE: f64 = 1e-6;
+}
+
+/// The smallest of 1, 2, or 5 times a power of 10 that is at least `x > 0`.
+fn round_up(x: f64) -> f64 {
+    let power = 10f64.powf(x.log10().floor());
+    [1.0, 2.0, 5.0, 10.0]
+        .into_iter()
+        .map(|m| m * power)
+        .find(|&r| r >= x)
+        .unwrap_or(10.0 * power)
 }
 
 impl Default for BucketRange {
@@ -130,11 +443,55 @@ impl Default for BucketRange {
             min: 0.0,
             max: 10.0,
             n_buckets: 10,
+            log_scale: false,
         }
     }
 }
 
 impl BucketConfig {
+    /// Repair a config that would break plotting, e.g. one loaded from a
+    /// hand-edited file, returning `false` if it was already valid.
+    ///
+    /// An inverted range is swapped, an empty one is widened, a log scale
+    /// needs a positive maximum, and bounds are
+    /// rebuilt from the range unless there are at least 2 of them, all
+    /// finite and increasing.
+    pub fn sanitize(&mut self) -> bool {
+        let range = &mut self.range_input;
+        let mut changed = false;
+        if !range.min.is_finite() || !range.max.is_finite() {
+            *range = BucketRange {
+                log_scale: range.log_scale,
+                ..default()
+            };
+            changed = true;
+        }
+        if range.min > range.max {
+            std::mem::swap(&mut range.min, &mut range.max);
+            changed = true;
+        }
+        if range.min == range.max {
+            range.clamp_max();
+            changed = true;
+        }
+        if range.n_buckets == 0 {
+            range.n_buckets = 1;
+            changed = true;
+        }
+        if range.log_scale && range.max <= 0.0 {
+            range.log_scale = false;
+            changed = true;
+        }
+        let valid_bounds = self.bounds.len() >= 2
+            && self.bounds.iter().all(|b| b.is_finite())
+            && self.bounds.windows(2).all(|w| w[0] < w[1]);
+        if !valid_bounds {
+            self.bounds = self.range_input.get_bounds();
+            changed = true;
+        }
+        changed
+    }
+
     pub fn get_bounds(&self) -> Option<BoundsVec> {
         let mut new_bounds = self.range_input.get_bounds();
         if new_bounds.is_empty() {
@@ -158,31 +515,285 @@ impl Default for BucketConfig {
 
 /// A plot for any [`MetricKind`], rendering with [`egui_plot`].
 pub struct MetricPlot {
+    registry: MetricsRegistry,
     name: String,
     key: MetricKey,
     unit: Option<Unit>,
+    formatter: Option<ValueFormatter>,
+    axis_format: AxisFormat,
+    focus: Option<Instant>,
+    note: String,
+    paused: bool,
+    open_settings: bool,
+    /// The metric was deleted from the registry, and its handle is orphaned.
+    expired: bool,
+    events: Option<EventTrack>,
+    recording: Option<PlotRecording>,
     data: MetricPlotData,
 }
 
-#[allow(clippy::large_enum_variant)]
+/// The samples of a line plot on which a counter increased, drawn as a strip
+/// under the plot. See [`MetricPlot::set_event_counter`].
+pub(crate) struct EventTrack {
+    key: MetricKey,
+    source: Arc<AtomicU64>,
+    expired: bool,
+    last_value: Option<u64>,
+    /// The sample number and frame of each increase, oldest first.
+    events: VecDeque<(u64, Option<u32>)>,
+}
+
+impl EventTrack {
+    /// Record whether the counter increased on the plot's sample number
+    /// `sample`, and forget increases older than `first_sample`.
+    fn update(
+        &mut self,
+        snapshot: Option<&FrameSnapshot>,
+        sample: u64,
+        frame: Option<u32>,
+        first_sample: u64,
+    ) {
+        let value = snapshot
+            .and_then(|s| s.counter(&self.key.key))
+            .unwrap_or_else(|| self.source.load(Ordering::Relaxed));
+        if self
+            .last_value
+            .is_some_and(|last| counter_delta(last, value) > 0)
+        {
+            self.events.push_back((sample, frame));
+        }
+        self.last_value = Some(value);
+        while self.events.front().is_some_and(|&(s, _)| s < first_sample) {
+            self.events.pop_front();
+        }
+    }
+
+    /// The X coordinates of the increases, matching [`make_plot_points`].
+    fn xs(&self, by_frame: bool) -> impl Iterator<Item = f64> + '_ {
+        self.events.iter().map(move |&(sample, frame)| {
+            let frame = frame.filter(|_| by_frame);
+            frame.map_or(sample as f64, f64::from)
+        })
+    }
+}
+
 enum MetricPlotData {
     Counter(CounterData),
     Gauge(GaugeData),
-    Histogram(HistogramData),
+    Histogram(Box<HistogramData>),
+    Custom(CustomData),
+}
+
+struct CustomData {
+    config: CustomPlotConfig,
+    plot: Box<dyn PlotKind>,
+}
+
+impl MetricPlotData {
+    /// Data plotted with `config`, falling back to the default config for
+    /// the kind of `key` if `config` is custom without a factory.
+    fn new(
+        registry: &MetricsRegistry,
+        key: &MetricKey,
+        unit: Option<Unit>,
+        config: MetricPlotConfig,
+    ) -> Self {
+        match config {
+            MetricPlotConfig::Counter(config) => {
+                let source = registry.get_or_create_counter(&key.key);
+                Self::Counter(CounterData::new(config, source))
+            }
+            MetricPlotConfig::Gauge(config) => {
+                let source = registry.get_or_create_gauge(&key.key);
+                Self::Gauge(GaugeData::new(config, source))
+            }
+            MetricPlotConfig::Histogram(bar_config) => {
+                let source = if key.kind == MetricKind::Gauge {
+                    HistogramSource::Gauge(registry.get_or_create_gauge(&key.key))
+                } else {
+                    let bucket = registry.get_or_create_histogram(&key.key);
+                    HistogramSource::Bucket(bucket, BucketCursor::new(registry))
+                };
+                Self::Histogram(Box::new(HistogramData::new(*bar_config, source, unit)))
+            }
+            MetricPlotConfig::Custom(config) => match config.factory.clone() {
+                Some(factory) => Self::Custom(CustomData {
+                    plot: factory(registry, key, unit),
+                    config,
+                }),
+                None => {
+                    let config = MetricPlotConfig::default_for_kind(key.kind);
+                    Self::new(registry, key, unit, config)
+                }
+            },
+        }
+    }
+
+    fn kind(&self) -> MetricKind {
+        match self {
+            Self::Counter(_) => MetricKind::Counter,
+            Self::Gauge(_) => MetricKind::Gauge,
+            Self::Histogram(_) => MetricKind::Histogram,
+            Self::Custom(data) => data.config.metric_kind,
+        }
+    }
+
+    fn plot_kind_mut(&mut self) -> &mut dyn PlotKind {
+        match self {
+            Self::Counter(data) => data,
+            Self::Gauge(data) => data,
+            Self::Histogram(data) => &mut **data,
+            Self::Custom(data) => &mut *data.plot,
+        }
+    }
+
+    /// Get the handles to the metric from `registry` again, after it was
+    /// recreated. Custom plots start over.
+    fn resolve_sources(&mut self, registry: &MetricsRegistry, key: &MetricKey, unit: Option<Unit>) {
+        match self {
+            Self::Counter(data) => data.source = registry.get_or_create_counter(&key.key),
+            Self::Gauge(data) => data.source = registry.get_or_create_gauge(&key.key),
+            Self::Histogram(data) => {
+                data.source = match data.source {
+                    HistogramSource::Gauge(_) => {
+                        HistogramSource::Gauge(registry.get_or_create_gauge(&key.key))
+                    }
+                    HistogramSource::Bucket(..) => HistogramSource::Bucket(
+                        registry.get_or_create_histogram(&key.key),
+                        BucketCursor::new(registry),
+                    ),
+                };
+            }
+            Self::Custom(data) => {
+                if let Some(factory) = &data.config.factory {
+                    data.plot = factory(registry, key, unit);
+                }
+            }
+        }
+    }
+
+    /// Switch a gauge between a line and a histogram of its values, with a
+    /// default config.
+    fn plot_gauge_as_histogram(&mut self, as_histogram: bool, unit: Option<Unit>) {
+        match self {
+            Self::Gauge(data) if as_histogram => {
+                let source = HistogramSource::Gauge(data.source.clone());
+                *self = Self::Histogram(Box::new(HistogramData::new(default(), source, unit)));
+            }
+            Self::Histogram(data) if !as_histogram => {
+                if let HistogramSource::Gauge(source) = &data.source {
+                    *self = Self::Gauge(GaugeData::new(default(), source.clone()));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The points of this data as a line, if it's plotted as one.
+    fn line_points(&self, by_frame: bool) -> Option<Vec<PlotPoint>> {
+        match self {
+            Self::Counter(data) => Some(data.plot_points(by_frame)),
+            Self::Gauge(data) => Some(data.plot_points(by_frame)),
+            Self::Histogram(data) => data
+                .percentile_ring
+                .as_ref()
+                .map(|ring| make_plot_points(ring, by_frame)),
+            Self::Custom(_) => None,
+        }
+    }
+
+    /// The X ranges of gaps in sampling between the line points, see
+    /// [`ring_gaps`].
+    fn gaps(&self, by_frame: bool) -> Vec<(f64, f64)> {
+        match self {
+            Self::Counter(data) => ring_gaps(&data.ring, by_frame),
+            Self::Gauge(data) => ring_gaps(&data.ring, by_frame),
+            Self::Histogram(data) => data
+                .percentile_ring
+                .as_ref()
+                .map_or_else(Vec::new, |ring| ring_gaps(ring, by_frame)),
+            Self::Custom(_) => Vec::new(),
+        }
+    }
+
+    /// The X coordinate of the first line point recorded at or after `time`,
+    /// or of the latest point if there is none.
+    fn x_at_time(&self, time: Instant, by_frame: bool) -> Option<f64> {
+        match self {
+            Self::Counter(data) => ring_x_at_time(&data.ring, time, by_frame),
+            Self::Gauge(data) => ring_x_at_time(&data.ring, time, by_frame),
+            Self::Histogram(data) => data
+                .percentile_ring
+                .as_ref()
+                .and_then(|ring| ring_x_at_time(ring, time, by_frame)),
+            Self::Custom(_) => None,
+        }
+    }
+}
+
+/// The time at which the element plotted at `x` was pushed, if any.
+fn ring_time_at_x<T>(ring: &Ring<T>, x: f64, by_frame: bool) -> Option<Instant> {
+    (ring.first_sample_number()..)
+        .zip(ring.iter_timestamped().zip(ring.iter_with_frame()))
+        .find(|(i, (_, (frame, _)))| {
+            let frame = frame.filter(|_| by_frame);
+            frame.map_or(*i as f64, f64::from) == x
+        })
+        .map(|(_, ((time, _), _))| time)
+}
+
+fn ring_x_at_time<T>(ring: &Ring<T>, time: Instant, by_frame: bool) -> Option<f64> {
+    let i = ring
+        .partition_point_by_time(time)
+        .min(ring.len().checked_sub(1)?);
+    let frame = by_frame
+        .then(|| ring.iter_with_frame().nth(i).and_then(|(frame, _)| frame))
+        .flatten();
+    Some(frame.map_or((ring.first_sample_number() + i as u64) as f64, f64::from))
 }
 
 struct CounterData {
     source: Arc<AtomicU64>,
     ring: Ring<u64>,
+    /// The number of times the counter wrapped around past `u64::MAX`.
+    wraps: u64,
+    /// Whether the latest value is stuck at `u64::MAX`.
+    saturated: bool,
     config: CounterPlotConfig,
 }
 
 impl CounterData {
+    /// The plotted points: increments, derivative, or raw values.
+    ///
+    /// Increments and the derivative bridge wraparounds and resets of the
+    /// counter, see [`counter_delta`].
+    fn plot_points(&self, by_frame: bool) -> Vec<PlotPoint> {
+        let points = make_plot_points(&self.ring, by_frame);
+        if !self.config.increment_bars && !self.config.derivative {
+            return points;
+        }
+        let values: Vec<u64> = self.ring.iter_chronological().copied().collect();
+        points
+            .windows(2)
+            .zip(values.windows(2))
+            .map(|(p, v)| {
+                let delta = counter_delta(v[0], v[1]) as f64;
+                if self.config.increment_bars {
+                    PlotPoint::new(p[1].x, delta)
+                } else {
+                    PlotPoint::new(p[0].x, delta / (p[1].x - p[0].x))
+                }
+            })
+            .collect()
+    }
+
     fn new(config: CounterPlotConfig, source: Arc<AtomicU64>) -> Self {
         let CounterPlotConfig { window_size, .. } = config;
         Self {
             source,
             ring: Ring::new(window_size),
+            wraps: 0,
+            saturated: false,
             config,
         }
     }
@@ -191,24 +802,41 @@ impl CounterData {
         if let Some(window_size) = dash_config.global_window_size {
             self.config.window_size = window_size;
             self.ring.set_max_len(self.config.window_size);
+        } else if let Some(duration) = self.config.auto_window {
+            self.config.window_size = fit_window(&mut self.ring, duration);
         }
     }
 
-    fn configure_ui(&mut self, enable_window_size: bool, ui: &mut Ui) {
-        ui.checkbox(&mut self.config.derivative, "Derivative");
+    fn settings_ui(&mut self, enable_window_size: bool, ui: &mut Ui) {
+        ui.checkbox(
+            &mut self.config.increment_bars,
+            tr(ui, "Increments per Frame"),
+        );
+        ui.add_enabled(
+            !self.config.increment_bars,
+            Checkbox::new(&mut self.config.derivative, tr(ui, "Derivative")),
+        );
 
         if enable_window_size
+            && !auto_window_ui(&mut self.config.auto_window, ui)
             && ui
-                .add(window_size_slider(&mut self.config.window_size))
+                .add(window_size_slider(&mut self.config.window_size, ui))
                 .changed()
         {
             self.ring.set_max_len(self.config.window_size);
         }
     }
 
-    fn update(&mut self) {
-        let value = self.source.load(Ordering::Relaxed);
-        self.ring.push(value);
+    /// Push the `snapshot` value, or else the live value.
+    fn sample(&mut self, snapshot: Option<u64>, frame: Option<u32>) {
+        let value = snapshot.unwrap_or_else(|| self.source.load(Ordering::Relaxed));
+        if let Some(&last) = self.ring.latest() {
+            if value < last && counter_wrapped(last) {
+                self.wraps += 1;
+            }
+        }
+        self.saturated = value == u64::MAX;
+        self.ring.push_with_frame(value, frame);
     }
 }
 
@@ -220,6 +848,33 @@ struct GaugeData {
 }
 
 impl GaugeData {
+    fn plot_points(&self, by_frame: bool) -> Vec<PlotPoint> {
+        let mut points = make_plot_points(&self.ring, by_frame);
+        if self.config.derivative {
+            derivative(&mut points);
+        }
+        points
+    }
+
+    /// The least-squares slope per minute of the samples plotted within
+    /// `x_range`, by the time they were recorded.
+    fn trend_per_minute(
+        &self,
+        x_range: Option<RangeInclusive<f64>>,
+        by_frame: bool,
+    ) -> Option<f64> {
+        let samples = make_plot_points(&self.ring, by_frame)
+            .into_iter()
+            .zip(self.ring.iter_timestamped())
+            .filter(|(point, _)| x_range.as_ref().is_none_or(|r| r.contains(&point.x)));
+        let mut start = None;
+        let samples = samples.map(|(point, (time, _))| {
+            let start = *start.get_or_insert(time);
+            (time.saturating_duration_since(start).as_secs_f64(), point.y)
+        });
+        least_squares_slope(samples).map(|per_second| 60.0 * per_second)
+    }
+
     fn new(config: GaugePlotConfig, source: Arc<AtomicU64>) -> Self {
         let GaugePlotConfig {
             window_size,
@@ -238,64 +893,131 @@ impl GaugeData {
         if let Some(window_size) = dash_config.global_window_size {
             self.config.window_size = window_size;
             self.ring.set_max_len(self.config.window_size);
+        } else if let Some(duration) = self.config.auto_window {
+            self.config.window_size = fit_window(&mut self.ring, duration);
         }
     }
 
-    fn configure_ui(&mut self, enable_window_size: bool, ui: &mut Ui) {
-        ui.checkbox(&mut self.config.derivative, "Derivative");
+    fn settings_ui(&mut self, enable_window_size: bool, ui: &mut Ui) {
+        ui.checkbox(&mut self.config.derivative, tr(ui, "Derivative"));
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.config.trend, tr(ui, "Trend"))
+                .on_hover_text(tr(
+                    ui,
+                    "Fit a line to the visible samples, e.g. to spot leaks",
+                ));
+            if self.config.trend {
+                ui.add(
+                    DragValue::new(&mut self.config.leak_threshold_per_minute)
+                        .prefix(tr(ui, "Warn above: "))
+                        .suffix("/min"),
+                );
+            }
+        });
 
         if enable_window_size
+            && !auto_window_ui(&mut self.config.auto_window, ui)
             && ui
-                .add(window_size_slider(&mut self.config.window_size))
+                .add(window_size_slider(&mut self.config.window_size, ui))
                 .changed()
         {
             self.ring.set_max_len(self.config.window_size);
         }
 
-        ui.add(Slider::new(&mut self.config.smoothing_weight, 0.0..=1.0).text("Smoothing Weight"));
+        ui.add(
+            Slider::new(&mut self.config.smoothing_weight, 0.0..=1.0)
+                .text(tr(ui, "Smoothing Weight")),
+        );
         self.smoother.weight = self.config.smoothing_weight;
     }
 
-    fn update(&mut self) {
-        let value = f64::from_bits(self.source.load(Ordering::Relaxed));
+    /// Push the `snapshot` value, or else the live value.
+    fn sample(&mut self, snapshot: Option<f64>, frame: Option<u32>) {
+        let value = snapshot.unwrap_or_else(|| f64::from_bits(self.source.load(Ordering::Relaxed)));
         self.smoother.add(value);
-        self.ring.push(self.smoother.smoothed_value());
+        self.ring
+            .push_with_frame(self.smoother.smoothed_value(), frame);
     }
 }
 
+/// Where a [`HistogramData`] gets its samples.
+enum HistogramSource {
+    Bucket(Arc<AtomicBucket<f64>>, BucketCursor),
+    /// One sample of a gauge's value per update.
+    Gauge(Arc<AtomicU64>),
+}
+
 struct HistogramData {
-    source: Arc<AtomicBucket<f64>>,
+    source: HistogramSource,
     ring: Option<Ring<f64>>,
     bucket_counts: CountsVec,
+    /// Bucket counts frozen for comparison, drawn as outlines behind the
+    /// live ones.
+    baseline: Option<CountsVec>,
+    percentile_ring: Option<Ring<f64>>,
+    overlay_value: Option<f64>,
+    summary: Option<HistogramSummary>,
+    /// Values collected to fit the bucket range, while waiting for enough of
+    /// them.
+    auto_range_values: Option<Vec<f64>>,
     config: HistogramPlotConfig,
 }
 
 impl HistogramData {
-    fn new(config: HistogramPlotConfig, source: Arc<AtomicBucket<f64>>) -> Self {
+    /// The number of values used to fit the bucket range.
+    const AUTO_RANGE_SAMPLES: usize = 30;
+
+    fn new(config: HistogramPlotConfig, source: HistogramSource, unit: Option<Unit>) -> Self {
         let n_buckets = config.buckets.bounds.len() + 1;
 
         Self {
             source,
             ring: None,
             bucket_counts: smallvec![0; n_buckets],
+            baseline: None,
+            percentile_ring: None,
+            overlay_value: None,
+            summary: config.summary_readouts.then(HistogramSummary::new),
+            auto_range_values: Self::auto_range_values(&config, unit),
             config,
         }
     }
 
-    fn configure_ui(&mut self, ui: &mut Ui) {
+    fn auto_range_values(config: &HistogramPlotConfig, unit: Option<Unit>) -> Option<Vec<f64>> {
+        let is_duration = unit.is_some_and(|u| seconds_per_unit(u).is_some());
+        (config.auto_range && is_duration).then(Vec::new)
+    }
+
+    /// Collect `values` until there are enough to fit the bucket range.
+    ///
+    /// Returns all collected values once the range is fitted.
+    fn fit_range(&mut self, values: &[f64]) -> Option<Vec<f64>> {
+        let collected = self.auto_range_values.as_mut()?;
+        collected.extend_from_slice(values);
+        if collected.len() < Self::AUTO_RANGE_SAMPLES {
+            return None;
+        }
+        let collected = self.auto_range_values.take()?;
+        self.config.buckets.range_input.fit(&collected);
+        self.config.auto_range = false;
+        self.update_bounds_from_input();
+        Some(collected)
+    }
+
+    fn settings_ui(&mut self, ui: &mut Ui) {
         let mut update = false;
         ui.horizontal(|ui| {
             update |= ui
                 .add(
                     DragValue::new(&mut self.config.buckets.range_input.n_buckets)
-                        .prefix("Buckets: ")
+                        .prefix(tr(ui, "Buckets: "))
                         .speed(0.1),
                 )
                 .changed();
             update |= ui
                 .add(
                     DragValue::new(&mut self.config.buckets.range_input.min)
-                        .prefix("Min: ")
+                        .prefix(tr(ui, "Min: "))
                         .speed(0.1),
                 )
                 .changed();
@@ -303,13 +1025,44 @@ impl HistogramData {
             update |= ui
                 .add(
                     DragValue::new(&mut self.config.buckets.range_input.max)
-                        .prefix("Max: ")
+                        .prefix(tr(ui, "Max: "))
                         .speed(0.1),
                 )
                 .changed();
             self.config.buckets.range_input.clamp_min();
+            update |= ui
+                .checkbox(
+                    &mut self.config.buckets.range_input.log_scale,
+                    tr(ui, "Log Scale"),
+                )
+                .changed();
+        });
+        if ui
+            .button(tr(ui, "Fit Range"))
+            .on_hover_text(tr(ui, "Fit the bucket range to the next recorded values"))
+            .clicked()
+        {
+            self.auto_range_values = Some(Vec::new());
+        }
+        ui.checkbox(&mut self.config.normalize, tr(ui, "Percent of Samples"))
+            .on_hover_text(tr(
+                ui,
+                "Draw bars as a percentage of all samples instead of counts",
+            ));
+        ui.horizontal(|ui| {
+            ui.label(tr(ui, "Empty Buckets:"));
+            for mode in EmptyBuckets::ALL {
+                ui.selectable_value(&mut self.config.empty_buckets, mode, tr(ui, mode.label()))
+                    .on_hover_text(match mode {
+                        EmptyBuckets::Show => tr(ui, "Draw every bucket"),
+                        EmptyBuckets::Trim => tr(ui, "Hide empty buckets at both ends"),
+                        EmptyBuckets::Merge => tr(ui, "Also collapse runs of empty buckets"),
+                    });
+            }
         });
         if update {
+            self.config.auto_range = false;
+            self.auto_range_values = None;
             self.update_bounds_from_input();
         }
 
@@ -317,7 +1070,7 @@ impl HistogramData {
 
         let mut use_sliding_window = self.config.window_size.is_some();
         if ui
-            .checkbox(&mut use_sliding_window, "Sliding Window")
+            .checkbox(&mut use_sliding_window, tr(ui, "Sliding Window"))
             .changed()
             && !use_sliding_window
         {
@@ -327,10 +1080,72 @@ impl HistogramData {
         }
         if use_sliding_window {
             let window_size = self.config.window_size.get_or_insert(500);
-            if ui.add(window_size_slider(window_size)).changed() {
+            if !auto_window_ui(&mut self.config.auto_window, ui)
+                && ui.add(window_size_slider(window_size, ui)).changed()
+            {
                 self.ring = Some(Ring::new(*window_size));
             }
         }
+
+        ui.separator();
+
+        let mut plot_percentile = matches!(self.config.mode, HistogramPlotMode::Percentile { .. });
+        if ui
+            .checkbox(&mut plot_percentile, tr(ui, "Plot Percentile"))
+            .changed()
+        {
+            self.config.mode = if plot_percentile {
+                HistogramPlotMode::Percentile {
+                    percentile: 99.0,
+                    history_size: 500,
+                }
+            } else {
+                HistogramPlotMode::Buckets
+            };
+            self.percentile_ring = None;
+        }
+        if let HistogramPlotMode::Percentile {
+            percentile,
+            history_size,
+        } = &mut self.config.mode
+        {
+            ui.add(Slider::new(percentile, 0.0..=100.0).text(tr(ui, "Percentile")));
+            if ui.add(window_size_slider(history_size, ui)).changed() {
+                if let Some(ring) = &mut self.percentile_ring {
+                    ring.set_max_len(*history_size);
+                }
+            }
+        } else {
+            let mut overlay = self.config.overlay_percentile.is_some();
+            if ui
+                .checkbox(&mut overlay, tr(ui, "Percentile Marker"))
+                .changed()
+                && !overlay
+            {
+                self.config.overlay_percentile = None;
+                self.overlay_value = None;
+            }
+            if overlay {
+                let percentile = self.config.overlay_percentile.get_or_insert(99.0);
+                ui.add(Slider::new(percentile, 0.0..=100.0).text(tr(ui, "Percentile")));
+            }
+        }
+
+        ui.separator();
+
+        if ui
+            .checkbox(
+                &mut self.config.summary_readouts,
+                tr(ui, "Summary Readouts"),
+            )
+            .on_hover_text(tr(
+                ui,
+                "Show the count, sum, rate, and quantiles of all samples since now",
+            ))
+            .changed()
+        {
+            self.summary = self.config.summary_readouts.then(HistogramSummary::new);
+        }
     }
 
     fn update_bounds_from_input(&mut self) {
@@ -339,37 +1154,57 @@ impl HistogramData {
         };
 
         self.config.buckets.bounds = new_bounds;
+        // The baseline was counted in the old buckets.
+        self.baseline = None;
 
         self.bucket_counts
             .resize(self.config.buckets.bounds.len() + 1, 0);
         self.bucket_counts.fill(0);
     }
 
-    fn make_bar_chart(&self) -> BarChart {
-        assert_eq!(
-            self.bucket_counts.len(),
-            self.config.buckets.bounds.len() + 1
-        );
+    /// A "start,end,count" CSV of every bucket. The first and last buckets
+    /// are unbounded.
+    fn buckets_csv(&self) -> String {
+        let bounds = &self.config.buckets.bounds;
+        let mut csv = String::from("start,end,count\n");
+        for (i, count) in self.bucket_counts.iter().enumerate() {
+            let start = i.checked_sub(1).map_or(f64::NEG_INFINITY, |i| bounds[i]);
+            let end = bounds.get(i).copied().unwrap_or(f64::INFINITY);
+            let _ = writeln!(csv, "{start},{end},{count}");
+        }
+        csv
+    }
 
-        let mut bars: Vec<_> = self
-            .bucket_counts
+    /// The average width of the bounded buckets.
+    fn avg_bucket_width(&self) -> f64 {
+        let bounds = &self.config.buckets.bounds;
+        (bounds[bounds.len() - 1] - bounds[0]) / (bounds.len() - 1) as f64
+    }
+
+    /// The bars of `counts`, one per bucket.
+    fn bucket_bars(&self, counts: &[u32]) -> Vec<Bar> {
+        assert_eq!(counts.len(), self.config.buckets.bounds.len() + 1);
+
+        let total: u32 = counts.iter().sum();
+        let scale = if self.config.normalize && total > 0 {
+            100.0 / total as f64
+        } else {
+            1.0
+        };
+        let mut bars: Vec<_> = counts
             .iter()
-            .map(|&c| Bar::new(0.0, c as f64))
+            .map(|&c| Bar::new(0.0, scale * c as f64))
             .collect();
 
-        let mut avg_bar_width = 0.0;
         for (window_i, edges) in self.config.buckets.bounds.windows(2).enumerate() {
             let start = edges[0];
             let end = edges[1];
             let bar_i = window_i + 1;
-            let width = end - start;
-            let center = 0.5 * (start + end);
             let bar = &mut bars[bar_i];
-            bar.argument = center;
-            bar.bar_width = width;
-            avg_bar_width += width;
+            bar.argument = 0.5 * (start + end);
+            bar.bar_width = end - start;
         }
-        avg_bar_width /= (self.config.buckets.bounds.len() - 1) as f64;
+        let avg_bar_width = self.avg_bucket_width();
 
         let start = self.config.buckets.bounds[0];
         let end = *self.config.buckets.bounds.last().unwrap();
@@ -382,46 +1217,168 @@ impl HistogramData {
         last_bar.argument = end + 0.5 * avg_bar_width;
         last_bar.bar_width = avg_bar_width;
         last_bar.fill = Color32::BLUE;
+        bars
+    }
+
+    /// The bar chart of all buckets, the outlined bars of the frozen
+    /// baseline if there is one, and the mapping of their X axis to values if
+    /// empty buckets are merged.
+    fn make_bar_chart(&self) -> (BarChart, Option<BarChart>, Option<BucketAxis>) {
+        let mut bars = self.bucket_bars(&self.bucket_counts);
+        let mut baseline = self
+            .baseline
+            .as_ref()
+            .filter(|counts| counts.len() == self.bucket_counts.len())
+            .map(|counts| self.bucket_bars(counts));
+        let baseline_chart = |bars: Vec<Bar>| {
+            let stroke = Stroke::new(1.5, Color32::GRAY);
+            let bars = bars
+                .into_iter()
+                .map(|bar| bar.fill(Color32::TRANSPARENT).stroke(stroke))
+                .collect();
+            BarChart::new(bars).name("baseline")
+        };
+
+        if self.config.empty_buckets == EmptyBuckets::Show {
+            return (BarChart::new(bars), baseline.map(baseline_chart), None);
+        }
+        // Buckets occupied in either the live data or the baseline.
+        let occupied: Vec<_> = match &baseline {
+            Some(baseline) => bars
+                .iter()
+                .zip(baseline)
+                .map(|(bar, base)| bar.value != 0.0 || base.value != 0.0)
+                .collect(),
+            None => bars.iter().map(|bar| bar.value != 0.0).collect(),
+        };
+        if let (Some(first), Some(last)) = (
+            occupied.iter().position(|&o| o),
+            occupied.iter().rposition(|&o| o),
+        ) {
+            for bars in std::iter::once(&mut bars).chain(baseline.as_mut()) {
+                bars.truncate(last + 1);
+                bars.drain(..first);
+            }
+        }
+        if self.config.empty_buckets == EmptyBuckets::Trim {
+            return (BarChart::new(bars), baseline.map(baseline_chart), None);
+        }
 
-        BarChart::new(bars)
+        let gap_width = 0.5 * self.avg_bucket_width();
+        let Some(baseline) = baseline else {
+            let (bars, axis) = BucketAxis::merge_empty(bars, gap_width);
+            return (BarChart::new(bars), None, Some(axis));
+        };
+        // Lay out both charts by the buckets occupied in either of them.
+        let combined: Vec<_> = bars
+            .iter()
+            .zip(&baseline)
+            .map(|(bar, base)| {
+                Bar::new(bar.argument, bar.value.max(base.value)).width(bar.bar_width)
+            })
+            .collect();
+        let (_, axis) = BucketAxis::merge_empty(combined, gap_width);
+        let remap = |bars: Vec<Bar>| -> Vec<Bar> {
+            bars.into_iter()
+                .filter(|bar| bar.value != 0.0)
+                .map(|mut bar| {
+                    bar.argument = axis.x_at(bar.argument);
+                    bar
+                })
+                .collect()
+        };
+        let bars = remap(bars);
+        let baseline = remap(baseline);
+        (
+            BarChart::new(bars),
+            Some(baseline_chart(baseline)),
+            Some(axis),
+        )
     }
 
-    fn update(&mut self) {
-        if let Some(window_size) = self.config.window_size {
+    /// Read new samples, preferring `gauge_snapshot` for gauge sources.
+    fn sample(&mut self, gauge_snapshot: Option<f64>, frame: Option<u32>) {
+        let mut new_values = Vec::new();
+        match &mut self.source {
+            HistogramSource::Bucket(bucket, cursor) => {
+                cursor.read_new(bucket, |value| new_values.push(value));
+            }
+            HistogramSource::Gauge(gauge) => new_values.push(
+                gauge_snapshot.unwrap_or_else(|| f64::from_bits(gauge.load(Ordering::Relaxed))),
+            ),
+        }
+        if let Some(summary) = &mut self.summary {
+            for &value in &new_values {
+                summary.add(value);
+            }
+        }
+        if let Some(collected) = self.fit_range(&new_values) {
+            // Recount everything seen so far with the fitted buckets.
+            if self.config.window_size.is_none() {
+                new_values = collected;
+            }
+        }
+
+        let Self {
+            ring,
+            bucket_counts,
+            percentile_ring,
+            overlay_value,
+            summary,
+            config,
+            ..
+        } = self;
+        let needs_values = matches!(config.mode, HistogramPlotMode::Percentile { .. })
+            || config.overlay_percentile.is_some();
+        let mut window_values = Vec::new();
+        if let Some(window_size) = config.window_size {
             // We are only counting within a sliding window, so clear
             // counts first.
-            self.bucket_counts.fill(0);
-            // We only need to take the latest values up to the ring's
-            // capacity. The histogram doesn't actually care about the order
-            // of elements, it just needs to always have the most recent
-            // N elements.
-            let ring = self.ring.get_or_insert_with(|| Ring::new(window_size));
-            let mut taken = 0;
-            self.source.data_with(|block| {
-                let mut block_iter = block.iter().rev().copied();
-                while taken < ring.max_len() {
-                    if let Some(value) = block_iter.next() {
-                        ring.push(value);
-                        taken += 1;
-                    } else {
-                        break;
-                    }
-                }
-            });
+            bucket_counts.fill(0);
+            let ring = ring.get_or_insert_with(|| Ring::new(window_size));
+            for value in new_values {
+                ring.push(value);
+            }
+            if let Some(duration) = config.auto_window {
+                config.window_size = Some(fit_window(ring, duration));
+            }
             for &value in ring.iter_chronological() {
-                add_value_to_bucket(&self.config.buckets.bounds, value, &mut self.bucket_counts);
+                add_value_to_bucket(&config.buckets.bounds, value, bucket_counts);
+            }
+            if needs_values {
+                window_values.extend(ring.iter_chronological().copied());
             }
         } else {
             // Keep adding to the existing buckets.
-            self.source.data_with(|block| {
-                for &value in block {
-                    add_value_to_bucket(
-                        &self.config.buckets.bounds,
-                        value,
-                        &mut self.bucket_counts,
-                    );
-                }
-            });
+            for &value in &new_values {
+                add_value_to_bucket(&config.buckets.bounds, value, bucket_counts);
+            }
+            if needs_values {
+                window_values = new_values;
+            }
+        }
+
+        if let Some(p) = config.overlay_percentile {
+            let summary = summary.as_ref().filter(|_| config.window_size.is_none());
+            let value = match summary {
+                Some(summary) => summary.quantile(p / 100.0),
+                None => percentile(&mut window_values, p),
+            };
+            if let Some(value) = value {
+                *overlay_value = Some(value);
+            }
+        }
+
+        if let HistogramPlotMode::Percentile {
+            percentile: p,
+            history_size,
+        } = config.mode
+        {
+            if let Some(value) = percentile(&mut window_values, p) {
+                percentile_ring
+                    .get_or_insert_with(|| Ring::new(history_size))
+                    .push_with_frame(value, frame);
+            }
         }
     }
 }
@@ -438,156 +1395,2254 @@ impl MetricPlot {
         unit: Option<Unit>,
         config: MetricPlotConfig,
     ) -> Self {
-        let data = match config {
-            MetricPlotConfig::Counter(config) => {
-                let source = registry.get_or_create_counter(&key.key);
-                MetricPlotData::Counter(CounterData::new(config, source))
-            }
-            MetricPlotConfig::Gauge(config) => {
-                let source = registry.get_or_create_gauge(&key.key);
-                MetricPlotData::Gauge(GaugeData::new(config, source))
+        let data = MetricPlotData::new(registry, &key, unit, config);
+        Self {
+            registry: registry.clone(),
+            name: name.into(),
+            key,
+            unit,
+            formatter: None,
+            axis_format: default(),
+            focus: None,
+            note: String::new(),
+            paused: false,
+            open_settings: false,
+            expired: false,
+            events: None,
+            recording: None,
+            data,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn set_name(&mut self, name: impl Into<String>) {
+        self.name = name.into();
+    }
+
+    /// Free text shown under the plot's header, e.g. "expected to spike
+    /// during loading". Empty by default.
+    pub fn note(&self) -> &str {
+        &self.note
+    }
+
+    pub fn set_note(&mut self, note: impl Into<String>) {
+        self.note = note.into();
+    }
+
+    pub fn key(&self) -> &MetricKey {
+        &self.key
+    }
+
+    pub fn unit(&self) -> Option<Unit> {
+        self.unit
+    }
+
+    /// Replace the plot configuration, resetting all plotted data.
+    ///
+    /// Configs that don't [support](MetricPlotConfig::supports_kind) this
+    /// plot's [`MetricKind`] are ignored.
+    pub fn set_config(&mut self, config: MetricPlotConfig) {
+        if self.key.kind == MetricKind::Gauge {
+            let as_histogram = matches!(config, MetricPlotConfig::Histogram(_));
+            self.data.plot_gauge_as_histogram(as_histogram, self.unit);
+        }
+        match (&mut self.data, config) {
+            (MetricPlotData::Counter(data), MetricPlotConfig::Counter(config)) => {
+                *data = CounterData::new(config, data.source.clone());
+            }
+            (MetricPlotData::Gauge(data), MetricPlotConfig::Gauge(config)) => {
+                *data = GaugeData::new(config, data.source.clone());
+            }
+            (MetricPlotData::Histogram(data), MetricPlotConfig::Histogram(config)) => {
+                let n_buckets = config.buckets.bounds.len() + 1;
+                data.ring = None;
+                data.bucket_counts = smallvec![0; n_buckets];
+                data.percentile_ring = None;
+                data.overlay_value = None;
+                // The baseline was counted in the old buckets.
+                data.baseline = None;
+                data.auto_range_values = HistogramData::auto_range_values(&config, self.unit);
+                data.summary = config.summary_readouts.then(HistogramSummary::new);
+                data.config = *config;
+            }
+            (data, config @ MetricPlotConfig::Custom(_))
+            | (data @ MetricPlotData::Custom(_), config)
+                if config.supports_kind(self.key.kind) =>
+            {
+                *data = MetricPlotData::new(&self.registry, &self.key, self.unit, config);
+            }
+            _ => {}
+        }
+        // Sample numbers start over with the new data.
+        if let Some(events) = &mut self.events {
+            events.events.clear();
+        }
+    }
+
+    /// Mark the samples on which the counter identified by `key` increased,
+    /// in a strip under this plot, e.g. to see whether spikes line up with
+    /// garbage collections or level streaming. `None` removes the strip.
+    ///
+    /// Only counters can be chosen, and only counter and gauge line plots
+    /// draw the strip. Returns `false` if `key` isn't a counter.
+    pub fn set_event_counter(
+        &mut self,
+        registry: &MetricsRegistry,
+        key: Option<MetricKey>,
+    ) -> bool {
+        self.events = match key {
+            Some(key) if key.kind != MetricKind::Counter => return false,
+            Some(key) => Some(EventTrack {
+                source: registry.get_or_create_counter(&key.key),
+                key,
+                expired: false,
+                last_value: None,
+                events: VecDeque::new(),
+            }),
+            None => None,
+        };
+        true
+    }
+
+    pub fn with_event_counter(mut self, registry: &MetricsRegistry, key: MetricKey) -> Self {
+        self.set_event_counter(registry, Some(key));
+        self
+    }
+
+    /// The counter marked under this plot, if any.
+    pub fn event_counter(&self) -> Option<&MetricKey> {
+        self.events.as_ref().map(|events| &events.key)
+    }
+
+    /// The X coordinates of the samples on which the
+    /// [event counter](Self::set_event_counter) increased, oldest first.
+    pub fn event_xs(&self, by_frame: bool) -> Vec<f64> {
+        self.events
+            .as_ref()
+            .map_or_else(Vec::new, |events| events.xs(by_frame).collect())
+    }
+
+    /// Returns `true` if this plot draws a histogram rather than a line, so it
+    /// has no event strip.
+    pub(crate) fn is_histogram(&self) -> bool {
+        matches!(self.data, MetricPlotData::Histogram(_))
+    }
+
+    /// A paused plot keeps its samples but stops pulling new ones. See
+    /// [`DashboardConfig::paused`] to pause every plot.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    /// Toggle between plotting a counter or gauge and its derivative.
+    ///
+    /// Returns `false` if this plot has no derivative, i.e. it's a histogram
+    /// or a custom [`PlotKind`].
+    pub fn toggle_derivative(&mut self) -> bool {
+        let derivative = match &mut self.data {
+            MetricPlotData::Counter(data) => &mut data.config.derivative,
+            MetricPlotData::Gauge(data) => &mut data.config.derivative,
+            MetricPlotData::Histogram(_) | MetricPlotData::Custom(_) => return false,
+        };
+        *derivative = !*derivative;
+        true
+    }
+
+    /// Expand the plot's settings the next time it's drawn.
+    pub fn open_settings(&mut self) {
+        self.open_settings = true;
+    }
+
+    /// Returns `true` if [`Self::open_settings`] was called since the plot
+    /// was last drawn, so the header containing the plot should open too.
+    pub fn settings_requested(&self) -> bool {
+        self.open_settings
+    }
+
+    /// Override the formatters passed to [`Self::draw`] for this plot.
+    pub fn set_formatter(&mut self, formatter: Option<ValueFormatter>) {
+        self.formatter = formatter;
+    }
+
+    pub fn axis_format(&self) -> AxisFormat {
+        self.axis_format
+    }
+
+    /// Choose how the value axis ticks are labeled.
+    pub fn set_axis_format(&mut self, axis_format: AxisFormat) {
+        self.axis_format = axis_format;
+    }
+
+    /// Center the X axis on the sample recorded at `time` the next time the
+    /// plot is drawn, and stop following the latest samples.
+    ///
+    /// Samples that have left the plot's window are gone, so earlier times
+    /// show the oldest sample instead.
+    pub fn focus_on(&mut self, time: Instant) {
+        self.focus = Some(time);
+    }
+
+    /// Apply the config and formatter of `template`.
+    pub fn apply_template(&mut self, template: &PlotTemplate) {
+        self.set_config(template.config.clone());
+        self.set_formatter(template.formatter.clone());
+    }
+
+    /// The most recently plotted value.
+    ///
+    /// Histograms only have a latest value in
+    /// [`HistogramPlotMode::Percentile`].
+    pub fn latest_value(&self) -> Option<f64> {
+        match &self.data {
+            MetricPlotData::Counter(data) => data.latest_value(),
+            MetricPlotData::Gauge(data) => data.latest_value(),
+            MetricPlotData::Histogram(data) => data.latest_value(),
+            MetricPlotData::Custom(data) => data.plot.latest_value(),
+        }
+    }
+
+    /// The points currently plotted as a line or bars, or `None` for
+    /// histograms that aren't plotted over time.
+    ///
+    /// X coordinates are sample numbers, or frames if `by_frame` and the
+    /// samples were taken from a [`FrameSnapshot`].
+    pub fn line_points(&self, by_frame: bool) -> Option<Vec<PlotPoint>> {
+        self.data.line_points(by_frame)
+    }
+
+    /// The X ranges where samples are missing, e.g. because the plot was
+    /// paused or the app was minimized. Lines are broken and shaded there.
+    pub fn gaps(&self, by_frame: bool) -> Vec<(f64, f64)> {
+        self.data.gaps(by_frame)
+    }
+
+    /// The summary of a histogram plot with
+    /// [`summary_readouts`](HistogramPlotConfig::summary_readouts).
+    pub fn histogram_summary(&self) -> Option<&HistogramSummary> {
+        match &self.data {
+            MetricPlotData::Histogram(data) => data.summary.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// The bucket counts of a histogram plot, oldest bucket first.
+    pub fn histogram_bucket_counts(&self) -> Option<&[u32]> {
+        match &self.data {
+            MetricPlotData::Histogram(data) => Some(&data.bucket_counts),
+            _ => None,
+        }
+    }
+
+    /// Keep the current bucket counts of a histogram plot as its baseline,
+    /// drawn as outlines behind the live buckets until cleared or the buckets
+    /// change. Returns `false` for other kinds of plots.
+    pub fn freeze_histogram_baseline(&mut self) -> bool {
+        match &mut self.data {
+            MetricPlotData::Histogram(data) => {
+                data.baseline = Some(data.bucket_counts.clone());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn clear_histogram_baseline(&mut self) {
+        if let MetricPlotData::Histogram(data) = &mut self.data {
+            data.baseline = None;
+        }
+    }
+
+    /// The bucket counts frozen by [`Self::freeze_histogram_baseline`].
+    pub fn histogram_baseline(&self) -> Option<&[u32]> {
+        match &self.data {
+            MetricPlotData::Histogram(data) => data.baseline.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// The slope per minute of a line fitted to all samples of a gauge, or
+    /// `None` for other kinds or fewer than two samples.
+    pub fn trend_per_minute(&self) -> Option<f64> {
+        match &self.data {
+            MetricPlotData::Gauge(data) => data.trend_per_minute(None, false),
+            _ => None,
+        }
+    }
+
+    pub fn clone_config(&self) -> MetricPlotConfig {
+        match &self.data {
+            MetricPlotData::Counter(data) => MetricPlotConfig::Counter(data.config.clone()),
+            MetricPlotData::Gauge(data) => MetricPlotConfig::Gauge(data.config.clone()),
+            MetricPlotData::Histogram(data) => {
+                MetricPlotConfig::Histogram(Box::new(data.config.clone()))
+            }
+            MetricPlotData::Custom(data) => MetricPlotConfig::Custom(data.config.clone()),
+        }
+    }
+
+    /// Write every sample of the metric to a CSV file at `path` on each
+    /// update, until [`Self::stop_recording`], regardless of the plot's window.
+    ///
+    /// The columns are `frame,seconds,value`. Counters and gauges are
+    /// recorded once per update, before smoothing, and histograms once per
+    /// sample. Samples are recorded while the plot or its dashboard is paused,
+    /// and the recording is finished when the plot is removed from its
+    /// dashboard.
+    pub fn start_recording(&mut self, path: impl Into<PathBuf>) -> io::Result<()> {
+        self.stop_recording()?;
+        self.recording = Some(PlotRecording::start(path.into())?);
+        Ok(())
+    }
+
+    /// Flush and close the file of [`Self::start_recording`], if any.
+    pub fn stop_recording(&mut self) -> io::Result<()> {
+        self.recording.take().map_or(Ok(()), PlotRecording::finish)
+    }
+
+    /// The file being recorded to, if any.
+    pub fn recording_path(&self) -> Option<&Path> {
+        self.recording.as_ref().map(PlotRecording::path)
+    }
+
+    /// Pull metric data from the source.
+    ///
+    /// This should run in the [`Last`](bevy::prelude::Last) schedule **before**
+    /// [`ClearBucketsSystem`](crate::ClearBucketsSystem) to ensure no data
+    /// is missed.
+    pub fn update(&mut self) {
+        self.update_with(None);
+    }
+
+    /// Like [`Self::update`], but counters and gauges are read from
+    /// `snapshot`, so that all plots see values from the same instant.
+    ///
+    /// Metrics missing from the snapshot are read from the source.
+    pub fn update_from_snapshot(&mut self, snapshot: &FrameSnapshot) {
+        self.update_with(Some(snapshot));
+    }
+
+    /// Follow the deletion of the metric identified by `key` from the
+    /// registry, e.g. on a [`MetricExpired`](crate::MetricExpired) event.
+    ///
+    /// If it's the plotted metric or the event counter, the plot keeps its
+    /// samples and reads the metric again once it's recreated.
+    pub fn metric_expired(&mut self, key: &MetricKey) {
+        if *key == self.key {
+            self.expired = true;
+        }
+        if let Some(events) = self.events.as_mut().filter(|e| e.key == *key) {
+            events.expired = true;
+        }
+    }
+
+    fn resolve_expired(&mut self) {
+        if self.expired && self.registry.contains(&self.key) {
+            (self.data).resolve_sources(&self.registry, &self.key, self.unit);
+            self.expired = false;
+        }
+        if let Some(events) = self.events.as_mut().filter(|e| e.expired) {
+            if self.registry.contains(&events.key) {
+                events.source = self.registry.get_or_create_counter(&events.key.key);
+                events.expired = false;
+            }
+        }
+    }
+
+    /// Write the current sample to the recording, if any, without updating
+    /// the plot, e.g. while its dashboard is paused.
+    pub(crate) fn record(&mut self, snapshot: Option<&FrameSnapshot>) {
+        if let Some(recording) = &mut self.recording {
+            if let Err(e) = recording.record(&self.registry, &self.key, snapshot) {
+                let path = recording.path().display();
+                bevy::log::error!("Stopped recording {} to {path}: {e}", self.name);
+                self.recording = None;
+            }
+        }
+    }
+
+    pub(crate) fn update_with(&mut self, snapshot: Option<&FrameSnapshot>) {
+        self.resolve_expired();
+        self.record(snapshot);
+        if self.paused {
+            return;
+        }
+        self.data.plot_kind_mut().update(&self.key, snapshot);
+
+        let (n_pushed, first_sample) = match &self.data {
+            MetricPlotData::Counter(data) => {
+                (data.ring.n_pushed(), data.ring.first_sample_number())
+            }
+            MetricPlotData::Gauge(data) => (data.ring.n_pushed(), data.ring.first_sample_number()),
+            MetricPlotData::Histogram(_) | MetricPlotData::Custom(_) => return,
+        };
+        if let Some(events) = &mut self.events {
+            let frame = snapshot.and_then(FrameSnapshot::frame);
+            events.update(snapshot, n_pushed - 1, frame, first_sample);
+        }
+    }
+
+    /// Draw the plot using `ui`.
+    ///
+    /// Values are displayed with this plot's formatter, or else the matching
+    /// formatter from `formatters`, if any. The plot settings offer the
+    /// matching `templates`. The series is drawn in its `palette` color.
+    pub fn draw(
+        &mut self,
+        dash_config: &DashboardConfig,
+        formatters: &ValueFormatters,
+        templates: &PlotTemplates,
+        palette: &Palette,
+        ui: &mut Ui,
+    ) {
+        let Self {
+            name,
+            key,
+            unit,
+            formatter,
+            axis_format,
+            focus,
+            note,
+            open_settings,
+            events,
+            recording,
+            data,
+            ..
+        } = self;
+
+        if std::mem::take(open_settings) {
+            // The ID of the "Settings" header drawn by `draw_plot`.
+            let id = ui.make_persistent_id(tr(ui, "Settings"));
+            let mut state = CollapsingState::load_with_default_open(ui.ctx(), id, false);
+            state.set_open(true);
+            state.store(ui.ctx());
+        }
+        let formatter = formatter.as_ref().or_else(|| formatters.get(key, *unit));
+        let templates: Vec<_> = templates.matching(data.kind(), *unit).collect();
+        let ctx = PlotContext {
+            dash_config,
+            name,
+            plot_id: ui.make_persistent_id(&*name),
+            unit: *unit,
+            formatter,
+            axis_format: *axis_format,
+            color: palette.color_for(key),
+            events: events.as_ref().map(|e| (e, palette.color_for(&e.key))),
+        };
+        let settings = PlotSettings {
+            axis_format,
+            focus,
+            note: Some(note),
+            can_plot_as_histogram: key.kind == MetricKind::Gauge,
+            templates: &templates,
+            recording: Some((&*key, recording)),
+        };
+        let selected = draw_plot(&ctx, settings, data, ui);
+        if let Some(template) = selected.and_then(|i| templates.get(i)).cloned() {
+            self.apply_template(template);
+        }
+    }
+}
+
+/// The Y axis that a series of a [`MultiSeriesPlot`] is measured on.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum YAxisSide {
+    #[default]
+    Left,
+    Right,
+}
+
+/// A line chart of several metrics, each measured on the left or right Y
+/// axis, e.g. FPS on the left and entity count on the right.
+///
+/// Series on the right axis are scaled to span the same height as those on
+/// the left, and the right axis is labeled with their original values.
+/// Histograms are only plotted in [`HistogramPlotMode::Percentile`].
+pub struct MultiSeriesPlot {
+    name: String,
+    series: Vec<(MetricPlot, YAxisSide)>,
+    focus: Option<Instant>,
+    stacked: bool,
+    shared_hover: bool,
+}
+
+impl MultiSeriesPlot {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            series: Vec::new(),
+            focus: None,
+            stacked: false,
+            shared_hover: true,
+        }
+    }
+
+    /// A [stacked](Self::with_stacked) plot of every series of the counter
+    /// `name`, i.e. with any labels, e.g. draw calls per camera.
+    ///
+    /// Each series is named after its labels.
+    pub fn stacked_counters(registry: &MetricsRegistry, name: impl Into<String>) -> Self {
+        let name = name.into();
+        let mut results = registry.find_by_name(&name, MetricKind::Counter);
+        results.sort_by(|a, b| a.key.key.cmp(&b.key.key));
+        let mut plot = Self::new(name).with_stacked(true);
+        for result in results {
+            let labels = labels_text(&result.key.key, ", ");
+            let series_name = if labels.is_empty() {
+                result.key.title(None, 0)
+            } else {
+                labels
+            };
+            let unit = result.description.and_then(|d| d.unit);
+            let config = MetricPlotConfig::default_for_kind(MetricKind::Counter);
+            let series = MetricPlot::new(registry, series_name, result.key, unit, config);
+            plot.add_series(series, YAxisSide::Left);
+        }
+        plot
+    }
+
+    /// Draw the series as areas stacked on top of each other, so the chart
+    /// shows both their sum and how much each contributes to it.
+    ///
+    /// This ignores the [`YAxisSide`] of each series.
+    pub fn with_stacked(mut self, stacked: bool) -> Self {
+        self.stacked = stacked;
+        self
+    }
+
+    pub fn is_stacked(&self) -> bool {
+        self.stacked
+    }
+
+    pub fn set_stacked(&mut self, stacked: bool) {
+        self.stacked = stacked;
+    }
+
+    /// While hovering the chart, show the values of every series at the
+    /// hovered X in one tooltip, instead of only the nearest point. This is
+    /// on by default.
+    pub fn with_shared_hover(mut self, shared_hover: bool) -> Self {
+        self.shared_hover = shared_hover;
+        self
+    }
+
+    pub fn has_shared_hover(&self) -> bool {
+        self.shared_hover
+    }
+
+    pub fn set_shared_hover(&mut self, shared_hover: bool) {
+        self.shared_hover = shared_hover;
+    }
+
+    /// Add a series plotted against the `axis` side.
+    pub fn with_series(mut self, plot: MetricPlot, axis: YAxisSide) -> Self {
+        self.add_series(plot, axis);
+        self
+    }
+
+    /// Add a series of the metric `key` with the default config for its kind.
+    pub fn with_metric(
+        self,
+        registry: &MetricsRegistry,
+        key: MetricKey,
+        unit: Option<Unit>,
+        axis: YAxisSide,
+    ) -> Self {
+        let name = key.title(None, 0);
+        let config = MetricPlotConfig::default_for_kind(key.kind);
+        self.with_series(MetricPlot::new(registry, name, key, unit, config), axis)
+    }
+
+    pub fn add_series(&mut self, plot: MetricPlot, axis: YAxisSide) {
+        self.series.push((plot, axis));
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn series(&self) -> &[(MetricPlot, YAxisSide)] {
+        &self.series
+    }
+
+    pub fn series_mut(&mut self) -> &mut [(MetricPlot, YAxisSide)] {
+        &mut self.series
+    }
+
+    /// See [`MetricPlot::focus_on`].
+    pub fn focus_on(&mut self, time: Instant) {
+        self.focus = Some(time);
+    }
+
+    /// The value of every series at the point nearest to `x`, skipping
+    /// series without points on both sides of `x`.
+    ///
+    /// `x` is a sample number, or a frame if `by_frame`, like the X axis of
+    /// the chart.
+    pub fn values_at(&self, x: f64, by_frame: bool) -> Vec<(&MetricPlot, f64)> {
+        self.series
+            .iter()
+            .filter_map(|(plot, _)| {
+                let points = plot.data.line_points(by_frame)?;
+                let (first, last) = (points.first()?, points.last()?);
+                if x < first.x || x > last.x {
+                    return None;
+                }
+                let i = points.partition_point(|p| p.x < x);
+                let nearest = match (i.checked_sub(1).map(|i| &points[i]), points.get(i)) {
+                    (Some(before), Some(after)) if x - before.x < after.x - x => before,
+                    (_, Some(after)) => after,
+                    (before, None) => before?,
+                };
+                Some((plot, nearest.y))
+            })
+            .collect()
+    }
+
+    /// Pull metric data into every series. See [`MetricPlot::update`].
+    pub fn update(&mut self) {
+        self.update_with(None);
+    }
+
+    /// Like [`Self::update`], but counters and gauges are read from
+    /// `snapshot`.
+    pub fn update_from_snapshot(&mut self, snapshot: &FrameSnapshot) {
+        self.update_with(Some(snapshot));
+    }
+
+    pub(crate) fn update_with(&mut self, snapshot: Option<&FrameSnapshot>) {
+        for (plot, _) in &mut self.series {
+            plot.update_with(snapshot);
+        }
+    }
+
+    /// See [`MetricPlot::metric_expired`].
+    pub fn metric_expired(&mut self, key: &MetricKey) {
+        for (plot, _) in &mut self.series {
+            plot.metric_expired(key);
+        }
+    }
+
+    /// Draw the chart using `ui`.
+    ///
+    /// Values are displayed with each series' formatter, or else the matching
+    /// formatter from `formatters`, if any.
+    pub fn draw(
+        &mut self,
+        dash_config: &DashboardConfig,
+        formatters: &ValueFormatters,
+        palette: &Palette,
+        ui: &mut Ui,
+    ) {
+        let by_frame = dash_config.align_by_frame;
+        let mut lines = Vec::new();
+        for (plot, side) in &self.series {
+            let Some(points) = plot.data.line_points(by_frame) else {
+                continue;
+            };
+            let formatter = plot
+                .formatter
+                .as_ref()
+                .or_else(|| formatters.get(&plot.key, plot.unit));
+            if let Some(latest) = points.last() {
+                let value = formatter.map_or_else(|| format!("{:.3}", latest.y), |f| f(latest.y));
+                ui.label(format!("{} = {value}", plot.name));
+            }
+            lines.push((plot, *side, points));
+        }
+        let gaps: Vec<_> = lines
+            .iter()
+            .flat_map(|(plot, _, _)| plot.data.gaps(by_frame))
+            .collect();
+        if self.stacked && lines.len() > 1 {
+            let total: f64 = lines
+                .iter()
+                .filter_map(|(_, _, p)| p.last())
+                .map(|p| p.y)
+                .sum();
+            ui.label(format!("{} = {total:.3}", tr(ui, "total")));
+        }
+
+        let range_of = |side| {
+            let ys = lines
+                .iter()
+                .filter(|(_, s, _)| *s == side)
+                .flat_map(|(_, _, points)| points.iter().map(|p| p.y));
+            ys.fold(None, |range: Option<(f64, f64)>, y| {
+                Some(range.map_or((y, y), |(min, max)| (min.min(y), max.max(y))))
+            })
+        };
+        let to_left = AxisMap::fit(range_of(YAxisSide::Right), range_of(YAxisSide::Left));
+
+        let axis_label = |side| {
+            let mut units: Vec<String> = Vec::new();
+            for (plot, _, _) in lines.iter().filter(|(_, s, _)| *s == side) {
+                if let Some(unit) = plot.unit.map(|u| tr_unit(ui, u)) {
+                    if !units.contains(&unit) {
+                        units.push(unit);
+                    }
+                }
+            }
+            units.join(", ")
+        };
+        let left_axis = AxisHints::new_y().label(axis_label(YAxisSide::Left));
+        let right_axis = AxisHints::new_y()
+            .label(axis_label(YAxisSide::Right))
+            .placement(HPlacement::Right)
+            .formatter(move |mark, _| {
+                let step = mark.step_size / to_left.scale;
+                let num_decimals = -step.log10().round() as usize;
+                let value = to_left.inverse(mark.value);
+                emath::format_with_decimals_in_range(value, num_decimals..=num_decimals)
+            });
+
+        let right_names: Vec<String> = lines
+            .iter()
+            .filter(|(_, s, _)| *s == YAxisSide::Right)
+            .map(|(plot, _, _)| plot.name.clone())
+            .collect();
+        let plot_id = ui.make_persistent_id(("multi_series", &self.name));
+        if let Some(time) = self.focus {
+            let x = self
+                .series
+                .iter()
+                .find_map(|(plot, _)| plot.data.x_at_time(time, by_frame));
+            if x.is_none_or(|x| focus_x(plot_id, x, ui)) {
+                self.focus = None;
+            }
+        }
+        let shared_hover = self.shared_hover;
+        let mut hovered_x = None;
+        let mut hover = |plot_ui: &mut PlotUi| {
+            if !shared_hover || !plot_ui.response().hovered() {
+                return;
+            }
+            if let Some(pointer) = plot_ui.pointer_coordinate() {
+                let x = pointer.x.round();
+                plot_ui.vline(VLine::new(x).color(Color32::GRAY).width(1.0));
+                hovered_x = Some(x);
+            }
+        };
+        let plot = Plot::new(&self.name)
+            .id(plot_id)
+            .allow_scroll(false)
+            .view_aspect(2.0)
+            .auto_bounds([true, true].into())
+            .legend(Legend::default())
+            .x_axis_label(tr(ui, "frame"))
+            .show_x(!shared_hover)
+            .show_y(!shared_hover);
+        if self.stacked {
+            let units = [axis_label(YAxisSide::Left), axis_label(YAxisSide::Right)];
+            let units: Vec<_> = units.into_iter().filter(|u| !u.is_empty()).collect();
+            let plot = plot.y_axis_label(units.join(", "));
+            // The top of the stack at each X coordinate.
+            let mut tops = HashMap::<i64, f64>::default();
+            let mut areas = Vec::new();
+            let mut top_lines = Vec::new();
+            for (series, _, points) in lines {
+                let color = palette.color_for(&series.key);
+                let series_gaps = series.data.gaps(by_frame);
+                let crosses_gap = |x0: f64, x1: f64| {
+                    series_gaps
+                        .iter()
+                        .any(|&(start, end)| x0 <= start && x1 >= end)
+                };
+                let mut top_points = Vec::with_capacity(points.len());
+                let mut previous: Option<(f64, f64, f64)> = None;
+                for point in points {
+                    let top = tops.entry(point.x as i64).or_default();
+                    let (base, new_top) = (*top, *top + point.y);
+                    *top = new_top;
+                    if let Some((x0, base0, top0)) =
+                        previous.filter(|&(x0, ..)| !crosses_gap(x0, point.x))
+                    {
+                        // Polygons are only filled correctly when they're
+                        // convex, so fill between each pair of samples.
+                        let quad =
+                            vec![[x0, base0], [point.x, base], [point.x, new_top], [x0, top0]];
+                        areas.push(
+                            Polygon::new(PlotPoints::from(quad))
+                                .fill_color(color.gamma_multiply(0.5))
+                                .allow_hover(false),
+                        );
+                    }
+                    previous = Some((point.x, base, new_top));
+                    top_points.push(PlotPoint::new(point.x, new_top));
+                }
+                for segment in split_at_gaps(top_points, &series_gaps) {
+                    let line = Line::new(PlotPoints::Owned(segment));
+                    top_lines.push(line.name(&series.name).color(color));
+                }
+            }
+            show_plot(plot, plot_id, dash_config.redraw_interval, ui, |plot_ui| {
+                for area in areas {
+                    plot_ui.polygon(area);
+                }
+                for line in top_lines {
+                    plot_ui.line(line);
+                }
+                hover(plot_ui);
+            });
+        } else {
+            let plot = plot
+                .custom_y_axes(vec![left_axis, right_axis])
+                .label_formatter(move |name, point| {
+                    let y = if right_names.iter().any(|n| n == name) {
+                        to_left.inverse(point.y)
+                    } else {
+                        point.y
+                    };
+                    format!("{name}\nx = {:.0}\ny = {y:.3}", point.x)
+                });
+            let mut all_points = Vec::new();
+            let lines: Vec<_> = lines
+                .into_iter()
+                .flat_map(|(plot, side, mut points)| {
+                    if side == YAxisSide::Right {
+                        for point in &mut points {
+                            point.y = to_left.apply(point.y);
+                        }
+                    }
+                    all_points.extend_from_slice(&points);
+                    let color = palette.color_for(&plot.key);
+                    split_at_gaps(points, &plot.data.gaps(by_frame))
+                        .into_iter()
+                        .map(move |segment| {
+                            Line::new(PlotPoints::Owned(segment))
+                                .name(&plot.name)
+                                .color(color)
+                        })
+                })
+                .collect();
+            show_plot(plot, plot_id, dash_config.redraw_interval, ui, |plot_ui| {
+                shade_gaps(&gaps, &all_points, plot_ui);
+                for line in lines {
+                    plot_ui.line(line);
+                }
+                hover(plot_ui);
+            });
+        }
+        if let Some(x) = hovered_x {
+            self.shared_tooltip(x, by_frame, formatters, palette, plot_id, ui);
+        }
+        ui.horizontal(|ui| {
+            follow_toggle(plot_id, ui);
+            if !dash_config.read_only {
+                ui.checkbox(&mut self.stacked, tr(ui, "Stacked"));
+                ui.checkbox(&mut self.shared_hover, tr(ui, "Shared Tooltip"));
+            }
+        });
+
+        for (plot, _) in &mut self.series {
+            if let MetricPlotData::Counter(data) = &mut plot.data {
+                data.handle_global_config(dash_config);
+            } else if let MetricPlotData::Gauge(data) = &mut plot.data {
+                data.handle_global_config(dash_config);
+            }
+        }
+    }
+
+    /// Show every series' value at `x`, with its unit and labels, in a
+    /// tooltip at the pointer.
+    fn shared_tooltip(
+        &self,
+        x: f64,
+        by_frame: bool,
+        formatters: &ValueFormatters,
+        palette: &Palette,
+        plot_id: Id,
+        ui: &Ui,
+    ) {
+        let values = self.values_at(x, by_frame);
+        if values.is_empty() {
+            return;
+        }
+        show_tooltip_at_pointer(
+            ui.ctx(),
+            ui.layer_id(),
+            plot_id.with("shared_hover"),
+            |ui| {
+                ui.label(RichText::new(format!("x = {x:.0}")).strong());
+                Grid::new(plot_id.with("shared_hover_grid"))
+                    .num_columns(3)
+                    .show(ui, |ui| {
+                        for (plot, y) in values {
+                            let formatter = plot
+                                .formatter
+                                .as_ref()
+                                .or_else(|| formatters.get(&plot.key, plot.unit));
+                            let value = match (formatter, plot.unit) {
+                                (Some(formatter), _) => formatter(y),
+                                (None, Some(unit)) => format!("{y:.3} {}", tr_unit(ui, unit)),
+                                (None, None) => format!("{y:.3}"),
+                            };
+                            let labels = labels_text(&plot.key.key, ", ");
+                            ui.label(RichText::new("⏺").color(palette.color_for(&plot.key)));
+                            ui.horizontal(|ui| {
+                                ui.label(&plot.name);
+                                if !labels.is_empty() {
+                                    ui.label(RichText::new(labels).weak());
+                                }
+                            });
+                            ui.label(RichText::new(value).monospace());
+                            ui.end_row();
+                        }
+                    });
+            },
+        );
+    }
+}
+
+/// A linear map from the range of right axis values onto the left axis.
+#[derive(Clone, Copy)]
+struct AxisMap {
+    scale: f64,
+    offset: f64,
+}
+
+impl AxisMap {
+    /// Map `from` onto `to`, or the identity if either range is unknown.
+    fn fit(from: Option<(f64, f64)>, to: Option<(f64, f64)>) -> Self {
+        let (Some((from_min, from_max)), Some((to_min, to_max))) = (from, to) else {
+            return Self {
+                scale: 1.0,
+                offset: 0.0,
+            };
+        };
+        // Flat lines still need a nonzero span.
+        let span = |min: f64, max: f64| (max - min).max(1e-9);
+        let scale = span(to_min, to_max) / span(from_min, from_max);
+        Self {
+            scale,
+            offset: to_min - from_min * scale,
+        }
+    }
+
+    fn apply(self, y: f64) -> f64 {
+        y * self.scale + self.offset
+    }
+
+    fn inverse(self, y: f64) -> f64 {
+        (y - self.offset) / self.scale
+    }
+}
+
+/// Where a [`CustomSeries`] gets its values.
+pub enum SeriesSource {
+    /// Called once per update.
+    Fn(Arc<dyn Fn() -> f64 + Send + Sync>),
+    /// Every value received since the last update is plotted.
+    Channel(Mutex<Receiver<f64>>),
+}
+
+/// A gauge-like plot of values from a [`SeriesSource`] instead of the
+/// registry, for quick one-off values that aren't worth defining a metric.
+pub struct CustomSeries {
+    name: String,
+    unit: Option<Unit>,
+    axis_format: AxisFormat,
+    focus: Option<Instant>,
+    source: SeriesSource,
+    /// Always [`MetricPlotData::Gauge`], with an unused atomic source.
+    data: MetricPlotData,
+}
+
+impl CustomSeries {
+    pub fn new(name: impl Into<String>, source: SeriesSource) -> Self {
+        Self {
+            name: name.into(),
+            unit: None,
+            axis_format: default(),
+            focus: None,
+            source,
+            data: MetricPlotData::Gauge(GaugeData::new(default(), default())),
+        }
+    }
+
+    /// Plot the value returned by `f` on every update.
+    pub fn from_fn(name: impl Into<String>, f: impl Fn() -> f64 + Send + Sync + 'static) -> Self {
+        Self::new(name, SeriesSource::Fn(Arc::new(f)))
+    }
+
+    /// Plot every value sent to `receiver`.
+    pub fn from_receiver(name: impl Into<String>, receiver: Receiver<f64>) -> Self {
+        Self::new(name, SeriesSource::Channel(Mutex::new(receiver)))
+    }
+
+    /// Plot the value of metric `a` minus the value of metric `b` on every
+    /// update, e.g. produced minus consumed items to see a queue grow.
+    ///
+    /// Counters are compared by their totals. Returns `None` if either metric
+    /// is a histogram.
+    pub fn difference(registry: &MetricsRegistry, a: &MetricKey, b: &MetricKey) -> Option<Self> {
+        let read_a = scalar_reader(registry, a)?;
+        let read_b = scalar_reader(registry, b)?;
+        let name = format!("{} - {}", a.title(None, 0), b.title(None, 0));
+        Some(Self::from_fn(name, move || read_a() - read_b()))
+    }
+
+    pub fn with_unit(mut self, unit: Unit) -> Self {
+        self.unit = Some(unit);
+        self
+    }
+
+    pub fn with_axis_format(mut self, axis_format: AxisFormat) -> Self {
+        self.axis_format = axis_format;
+        self
+    }
+
+    /// See [`MetricPlot::focus_on`].
+    pub fn focus_on(&mut self, time: Instant) {
+        self.focus = Some(time);
+    }
+
+    /// Replace the plot configuration, resetting all plotted data.
+    pub fn with_config(mut self, config: GaugePlotConfig) -> Self {
+        self.data = MetricPlotData::Gauge(GaugeData::new(config, default()));
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn unit(&self) -> Option<Unit> {
+        self.unit
+    }
+
+    /// The most recently plotted value.
+    pub fn latest_value(&self) -> Option<f64> {
+        match &self.data {
+            MetricPlotData::Gauge(data) => data.ring.latest().copied(),
+            _ => None,
+        }
+    }
+
+    /// Pull values from the source.
+    pub fn update(&mut self) {
+        let MetricPlotData::Gauge(data) = &mut self.data else {
+            return;
+        };
+        match &self.source {
+            SeriesSource::Fn(f) => data.sample(Some(f()), None),
+            SeriesSource::Channel(receiver) => {
+                let receiver = receiver.lock().unwrap();
+                while let Ok(value) = receiver.try_recv() {
+                    data.sample(Some(value), None);
+                }
+            }
+        }
+    }
+
+    /// Draw the plot using `ui`.
+    ///
+    /// Values are displayed with the formatter for this series' unit from
+    /// `formatters`, if any.
+    pub fn draw(
+        &mut self,
+        dash_config: &DashboardConfig,
+        formatters: &ValueFormatters,
+        palette: &Palette,
+        ui: &mut Ui,
+    ) {
+        let formatter = self.unit.and_then(|u| formatters.get_for_unit(u));
+        let ctx = PlotContext {
+            dash_config,
+            name: &self.name,
+            plot_id: ui.make_persistent_id(&self.name),
+            unit: self.unit,
+            formatter,
+            axis_format: self.axis_format,
+            color: palette.color_for_name(&self.name),
+            events: None,
+        };
+        let settings = PlotSettings {
+            axis_format: &mut self.axis_format,
+            focus: &mut self.focus,
+            note: None,
+            can_plot_as_histogram: false,
+            templates: &[],
+            recording: None,
+        };
+        draw_plot(&ctx, settings, &mut self.data, ui);
+    }
+}
+
+/// Reads the current value of a counter or gauge.
+fn scalar_reader(registry: &MetricsRegistry, key: &MetricKey) -> Option<impl Fn() -> f64> {
+    let (atomic, is_counter) = match key.kind {
+        MetricKind::Counter => (registry.get_or_create_counter(&key.key), true),
+        MetricKind::Gauge => (registry.get_or_create_gauge(&key.key), false),
+        MetricKind::Histogram => return None,
+    };
+    Some(move || {
+        let bits = atomic.load(Ordering::Relaxed);
+        if is_counter {
+            bits as f64
+        } else {
+            f64::from_bits(bits)
+        }
+    })
+}
+
+/// The slope of the least-squares line through `(x, y)` samples, or `None`
+/// if there are fewer than two distinct `x`.
+fn least_squares_slope(samples: impl Iterator<Item = (f64, f64)>) -> Option<f64> {
+    let (mut n, mut sum_x, mut sum_y, mut sum_xx, mut sum_xy) = (0.0, 0.0, 0.0, 0.0, 0.0);
+    for (x, y) in samples {
+        n += 1.0;
+        sum_x += x;
+        sum_y += y;
+        sum_xx += x * x;
+        sum_xy += x * y;
+    }
+    let denominator = n * sum_xx - sum_x * sum_x;
+    (n >= 2.0 && denominator > f64::EPSILON).then(|| (n * sum_xy - sum_x * sum_y) / denominator)
+}
+
+fn trend_label(slope: f64, leak_threshold: f64, formatter: Option<&ValueFormatter>, ui: &mut Ui) {
+    let value = formatter.map_or_else(|| format!("{slope:+.3}"), |f| f(slope));
+    let text = format!("{} = {value}/min", tr(ui, "trend"));
+    if slope > leak_threshold {
+        ui.colored_label(ui.visuals().warn_fg_color, format!("⚠ {text}"))
+            .on_hover_text(tr(ui, "Steadily increasing, possibly leaking"));
+    } else {
+        ui.label(text);
+    }
+}
+
+/// The nearest-rank `p`th percentile of `values`, or `None` if empty.
+fn percentile(values: &mut [f64], p: f64) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable_by(f64::total_cmp);
+    let rank = (p.clamp(0.0, 100.0) / 100.0 * values.len() as f64).ceil() as usize;
+    Some(values[rank.saturating_sub(1)])
+}
+
+fn add_value_to_bucket(bucket_bounds: &[f64], value: f64, bucket_counts: &mut [u32]) {
+    let bucket_i = match bucket_bounds.binary_search_by_key(&FloatOrd(value), |&d| FloatOrd(d)) {
+        Ok(i) => i,
+        Err(i) => i,
+    };
+    bucket_counts[bucket_i] += 1;
+}
+
+/// What the settings of a plot drawn by [`draw_plot`] edit or offer.
+struct PlotSettings<'a> {
+    axis_format: &'a mut AxisFormat,
+    focus: &'a mut Option<Instant>,
+    note: Option<&'a mut String>,
+    can_plot_as_histogram: bool,
+    templates: &'a [&'a PlotTemplate],
+    recording: Option<(&'a MetricKey, &'a mut Option<PlotRecording>)>,
+}
+
+fn draw_plot(
+    ctx: &PlotContext,
+    settings: PlotSettings,
+    data: &mut MetricPlotData,
+    ui: &mut Ui,
+) -> Option<usize> {
+    let PlotSettings {
+        axis_format,
+        focus,
+        note,
+        can_plot_as_histogram,
+        templates,
+        recording,
+    } = settings;
+    let dash_config = ctx.dash_config;
+    if let Some(time) = *focus {
+        let x = data.x_at_time(time, dash_config.align_by_frame);
+        // Keep trying until the plot has been shown once.
+        if x.is_none_or(|x| focus_x(ctx.plot_id, x, ui)) {
+            *focus = None;
+        }
+    }
+    data.plot_kind_mut().draw(ctx, ui);
+    if dash_config.read_only {
+        return None;
+    }
+
+    // Whether the plot is a histogram, if it can switch.
+    let as_histogram = match data {
+        MetricPlotData::Gauge(_) if can_plot_as_histogram => Some(false),
+        MetricPlotData::Histogram(data) => {
+            matches!(data.source, HistogramSource::Gauge(_)).then_some(true)
+        }
+        _ => None,
+    };
+    let mut selected = None;
+    let mut plot_as_histogram = None;
+    ui.collapsing(tr(ui, "Settings"), |ui| {
+        selected = template_menu(templates, ui);
+        if let Some(note) = note {
+            note_ui(note, ui);
+        }
+        if let Some(as_histogram) = as_histogram {
+            plot_as_histogram = plot_as_ui(as_histogram, ui);
+        }
+        axis_format_ui(axis_format, ui);
+        if let Some((key, recording)) = recording {
+            recording_ui(key, recording, ui);
+        }
+        data.plot_kind_mut().configure_ui(ctx, ui);
+    });
+    if let Some(as_histogram) = plot_as_histogram {
+        data.plot_gauge_as_histogram(as_histogram, ctx.unit);
+    }
+    selected
+}
+
+impl PlotKind for CounterData {
+    fn update(&mut self, key: &MetricKey, snapshot: Option<&FrameSnapshot>) {
+        let frame = snapshot.and_then(FrameSnapshot::frame);
+        self.sample(snapshot.and_then(|s| s.counter(&key.key)), frame);
+    }
+
+    fn draw(&mut self, ctx: &PlotContext, ui: &mut Ui) {
+        let PlotContext {
+            dash_config,
+            name,
+            plot_id,
+            unit,
+            formatter,
+            axis_format,
+            color,
+            events,
+        } = *ctx;
+        if let Some(&latest) = self.ring.latest() {
+            if let Some(formatter) = formatter {
+                ui.label(format!(
+                    "{} = {}",
+                    tr(ui, "latest"),
+                    formatter(latest as f64)
+                ));
+            } else {
+                ui.label(format!("{} = {latest:.3}", tr(ui, "latest")));
+            }
+        }
+        if self.saturated {
+            let text = tr(ui, "Saturated at the maximum value");
+            ui.colored_label(ui.visuals().warn_fg_color, text);
+        }
+        if self.wraps > 0 {
+            let text = format!("{} ({})", tr(ui, "Wrapped around"), self.wraps);
+            ui.colored_label(ui.visuals().warn_fg_color, text);
+        }
+
+        let mut plot = ctx.new_plot().x_axis_label(tr(ui, "frame"));
+        if let Some(unit) = unit {
+            plot = plot.y_axis_label(tr_unit(ui, unit));
+        }
+        let plot = format_value_axis(plot, Axis::Y, unit, axis_format);
+        // Only make points when the plot is actually redrawn.
+        let plotted = &*self;
+        let mut measurement = Measurement::load(plot_id, ui);
+        let response = show_plot(plot, plot_id, dash_config.redraw_interval, ui, |plot_ui| {
+            let plot_points = plotted.plot_points(dash_config.align_by_frame);
+            let gaps = ring_gaps(&plotted.ring, dash_config.align_by_frame);
+            lock_y_axis(plotted.config.y_lock.range(&plot_points), plot_ui);
+            shade_gaps(&gaps, &plot_points, plot_ui);
+            if plotted.config.increment_bars {
+                let bars = plot_points.iter().map(|p| Bar::new(p.x, p.y)).collect();
+                plot_ui.bar_chart(BarChart::new(bars).width(1.0).color(color))
+            } else {
+                for segment in split_at_gaps(plot_points, &gaps) {
+                    plot_ui.line(Line::new(PlotPoints::Owned(segment)).color(color));
+                }
+            }
+            measurement.draw(plot_ui);
+        });
+        let clicked = measurement.clicked_x(response.as_ref());
+        if dash_config.show_minimaps {
+            let points = self.plot_points(dash_config.align_by_frame);
+            minimap(plot_id, &points, color, ui);
+        }
+        if let Some((events, color)) = events {
+            event_strip(plot_id, events, dash_config.align_by_frame, color, ui);
+        }
+        ui.horizontal(|ui| {
+            follow_toggle(plot_id, ui);
+            measurement.toggle(ui);
+            copy_values_button(ui, |ui| {
+                let points = self.plot_points(dash_config.align_by_frame);
+                points_csv(name, &points, visible_x_range(plot_id, ui))
+            });
+        });
+        measurement.update(
+            clicked,
+            || self.plot_points(dash_config.align_by_frame),
+            |x| ring_time_at_x(&self.ring, x, dash_config.align_by_frame),
+            formatter,
+            dash_config.align_by_frame,
+            ui,
+        );
+        measurement.store(plot_id, ui);
+
+        self.handle_global_config(dash_config);
+    }
+
+    fn configure_ui(&mut self, ctx: &PlotContext, ui: &mut Ui) {
+        y_lock_ui(&mut self.config.y_lock, ctx.plot_id, ui);
+        self.settings_ui(ctx.dash_config.global_window_size.is_none(), ui);
+    }
+
+    fn latest_value(&self) -> Option<f64> {
+        self.ring.latest().map(|&v| v as f64)
+    }
+}
+
+impl PlotKind for GaugeData {
+    fn update(&mut self, key: &MetricKey, snapshot: Option<&FrameSnapshot>) {
+        let frame = snapshot.and_then(FrameSnapshot::frame);
+        self.sample(snapshot.and_then(|s| s.gauge(&key.key)), frame);
+    }
+
+    fn draw(&mut self, ctx: &PlotContext, ui: &mut Ui) {
+        let PlotContext {
+            dash_config,
+            name,
+            plot_id,
+            unit,
+            formatter,
+            axis_format,
+            color,
+            events,
+        } = *ctx;
+        if let Some(&latest) = self.ring.latest() {
+            if let Some(formatter) = formatter {
+                ui.label(format!("{} = {}", tr(ui, "latest"), formatter(latest)));
+            } else {
+                ui.label(format!("{} = {latest:.3}", tr(ui, "latest")));
+            }
+        }
+
+        let mut plot = ctx.new_plot().x_axis_label(tr(ui, "frame"));
+        if let Some(unit) = unit {
+            plot = plot.y_axis_label(tr_unit(ui, unit));
+        }
+        let plot = format_value_axis(plot, Axis::Y, unit, axis_format);
+        let plotted = &*self;
+        let mut measurement = Measurement::load(plot_id, ui);
+        let response = show_plot(plot, plot_id, dash_config.redraw_interval, ui, |plot_ui| {
+            let points = plotted.plot_points(dash_config.align_by_frame);
+            let gaps = ring_gaps(&plotted.ring, dash_config.align_by_frame);
+            lock_y_axis(plotted.config.y_lock.range(&points), plot_ui);
+            shade_gaps(&gaps, &points, plot_ui);
+            for segment in split_at_gaps(points, &gaps) {
+                plot_ui.line(Line::new(PlotPoints::Owned(segment)).color(color));
+            }
+            measurement.draw(plot_ui);
+        });
+        let clicked = measurement.clicked_x(response.as_ref());
+        if dash_config.show_minimaps {
+            let points = self.plot_points(dash_config.align_by_frame);
+            minimap(plot_id, &points, color, ui);
+        }
+        if let Some((events, color)) = events {
+            event_strip(plot_id, events, dash_config.align_by_frame, color, ui);
+        }
+        ui.horizontal(|ui| {
+            follow_toggle(plot_id, ui);
+            measurement.toggle(ui);
+            copy_values_button(ui, |ui| {
+                let points = self.plot_points(dash_config.align_by_frame);
+                points_csv(name, &points, visible_x_range(plot_id, ui))
+            });
+        });
+        measurement.update(
+            clicked,
+            || self.plot_points(dash_config.align_by_frame),
+            |x| ring_time_at_x(&self.ring, x, dash_config.align_by_frame),
+            formatter,
+            dash_config.align_by_frame,
+            ui,
+        );
+        measurement.store(plot_id, ui);
+        if self.config.trend {
+            let x_range = visible_x_range(plot_id, ui);
+            if let Some(slope) = self.trend_per_minute(x_range, dash_config.align_by_frame) {
+                trend_label(slope, self.config.leak_threshold_per_minute, formatter, ui);
+            }
+        }
+
+        self.handle_global_config(dash_config);
+    }
+
+    fn configure_ui(&mut self, ctx: &PlotContext, ui: &mut Ui) {
+        y_lock_ui(&mut self.config.y_lock, ctx.plot_id, ui);
+        self.settings_ui(ctx.dash_config.global_window_size.is_none(), ui);
+    }
+
+    fn latest_value(&self) -> Option<f64> {
+        self.ring.latest().copied()
+    }
+}
+
+impl PlotKind for HistogramData {
+    fn update(&mut self, key: &MetricKey, snapshot: Option<&FrameSnapshot>) {
+        let gauge = match self.source {
+            HistogramSource::Gauge(_) => snapshot.and_then(|s| s.gauge(&key.key)),
+            HistogramSource::Bucket(..) => None,
+        };
+        self.sample(gauge, snapshot.and_then(FrameSnapshot::frame));
+    }
+
+    fn draw(&mut self, ctx: &PlotContext, ui: &mut Ui) {
+        let PlotContext {
+            dash_config,
+            name,
+            plot_id,
+            unit,
+            formatter,
+            axis_format,
+            color,
+            ..
+        } = *ctx;
+        if let Some(summary) = &self.summary {
+            summary_readouts(summary, formatter, ui);
+        }
+        if let HistogramPlotMode::Percentile { percentile, .. } = self.config.mode {
+            let latest = self.percentile_ring.as_ref().and_then(|r| r.latest());
+            if let Some(&latest) = latest {
+                if let Some(formatter) = formatter {
+                    ui.label(format!("p{percentile} = {}", formatter(latest)));
+                } else {
+                    ui.label(format!("p{percentile} = {latest:.3}"));
+                }
             }
-            MetricPlotConfig::Histogram(bar_config) => {
-                let source = registry.get_or_create_histogram(&key.key);
-                MetricPlotData::Histogram(HistogramData::new(bar_config, source))
+
+            let plot_points = self
+                .percentile_ring
+                .as_ref()
+                .map(|ring| make_plot_points(ring, dash_config.align_by_frame))
+                .unwrap_or_default();
+            let magnitude = latest.copied().unwrap_or_default();
+            let plot = ctx.new_plot().x_axis_label(tr(ui, "frame"));
+            let plot = label_value_axis(plot, Axis::Y, unit, magnitude, axis_format, ui);
+            let gaps = self
+                .percentile_ring
+                .as_ref()
+                .map_or_else(Vec::new, |ring| ring_gaps(ring, dash_config.align_by_frame));
+            let segments = split_at_gaps(plot_points.clone(), &gaps);
+            show_plot(plot, plot_id, dash_config.redraw_interval, ui, |plot_ui| {
+                shade_gaps(&gaps, &plot_points, plot_ui);
+                for segment in segments {
+                    plot_ui.line(Line::new(PlotPoints::Owned(segment)).color(color));
+                }
+            });
+            if dash_config.show_minimaps {
+                minimap(plot_id, &plot_points, color, ui);
             }
-        };
+            ui.horizontal(|ui| {
+                follow_toggle(plot_id, ui);
+                copy_values_button(ui, |ui| {
+                    let header = format!("{name} p{percentile}");
+                    points_csv(&header, &plot_points, visible_x_range(plot_id, ui))
+                });
+            });
+        } else {
+            let (chart, baseline, bucket_axis) = self.make_bar_chart();
+            let chart = chart.color(color);
+            let magnitude = self.config.buckets.range_input.max;
+            let plot = if self.config.normalize {
+                ctx.new_plot().y_axis_label(tr_unit(ui, Unit::Percent))
+            } else {
+                let plot = ctx.new_plot().y_axis_label(tr(ui, "count"));
+                format_value_axis(plot, Axis::Y, None, axis_format)
+            };
+            let mut plot = label_value_axis(plot, Axis::X, unit, magnitude, axis_format, ui);
+            if let Some(bucket_axis) = bucket_axis.clone() {
+                plot = remap_x_axis(plot, bucket_axis, unit, magnitude, axis_format);
+            }
+            let overlay =
+                self.config
+                    .overlay_percentile
+                    .zip(self.overlay_value)
+                    .map(|(p, value)| {
+                        let x = bucket_axis.as_ref().map_or(value, |a| a.x_at(value));
+                        VLine::new(x).name(format!("p{p}"))
+                    });
+            show_plot(plot, plot_id, dash_config.redraw_interval, ui, |plot_ui| {
+                if let Some(baseline) = baseline {
+                    plot_ui.bar_chart(baseline);
+                }
+                plot_ui.bar_chart(chart);
+                if let Some(overlay) = overlay {
+                    plot_ui.vline(overlay);
+                }
+            });
+            ui.horizontal(|ui| {
+                copy_values_button(ui, |_| self.buckets_csv());
+                if self.baseline.is_none() {
+                    if ui
+                        .button(tr(ui, "Freeze Baseline"))
+                        .on_hover_text(tr(
+                            ui,
+                            "Keep the current buckets as outlines, to compare with later data",
+                        ))
+                        .clicked()
+                    {
+                        self.baseline = Some(self.bucket_counts.clone());
+                    }
+                } else if ui.button(tr(ui, "Clear Baseline")).clicked() {
+                    self.baseline = None;
+                }
+            });
+        }
+    }
 
+    fn configure_ui(&mut self, _ctx: &PlotContext, ui: &mut Ui) {
+        self.settings_ui(ui);
+    }
+
+    /// Only in [`HistogramPlotMode::Percentile`].
+    fn latest_value(&self) -> Option<f64> {
+        self.percentile_ring
+            .as_ref()
+            .and_then(|r| r.latest())
+            .copied()
+    }
+}
+
+/// Count, sum, and quantiles of every sample of a histogram plot since its
+/// [`summary_readouts`](HistogramPlotConfig::summary_readouts) were enabled.
+///
+/// Quantiles come from a [`Summary`] sketch, so they're accurate to within
+/// 0.01% of the value in constant memory, however many samples there are.
+pub struct HistogramSummary {
+    summary: Summary,
+    sum: f64,
+    /// The low-order bits lost from `sum`.
+    compensation: f64,
+    first_sample: Option<Instant>,
+}
+
+impl HistogramSummary {
+    fn new() -> Self {
         Self {
-            name: name.into(),
-            key,
-            unit,
-            data,
+            summary: Summary::with_defaults(),
+            sum: 0.0,
+            compensation: 0.0,
+            first_sample: None,
         }
     }
 
-    pub fn name(&self) -> &str {
-        &self.name
+    fn add(&mut self, value: f64) {
+        self.summary.add(value);
+        // Neumaier summation, so adding many small samples to a large sum
+        // doesn't lose them.
+        let sum = self.sum + value;
+        self.compensation += if self.sum.abs() >= value.abs() {
+            (self.sum - sum) + value
+        } else {
+            (value - sum) + self.sum
+        };
+        self.sum = sum;
+        self.first_sample.get_or_insert_with(Instant::now);
     }
 
-    pub fn key(&self) -> &MetricKey {
-        &self.key
+    pub fn count(&self) -> usize {
+        self.summary.count()
     }
 
-    pub fn clone_config(&self) -> MetricPlotConfig {
-        match &self.data {
-            MetricPlotData::Counter(data) => MetricPlotConfig::Counter(data.config.clone()),
-            MetricPlotData::Gauge(data) => MetricPlotConfig::Gauge(data.config.clone()),
-            MetricPlotData::Histogram(data) => MetricPlotConfig::Histogram(data.config.clone()),
+    pub fn sum(&self) -> f64 {
+        self.sum + self.compensation
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        let count = self.count();
+        (count > 0).then(|| self.sum() / count as f64)
+    }
+
+    /// The `q`th quantile, in the range `[0, 1]`.
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        self.summary.quantile(q)
+    }
+
+    /// The number of samples per second since the first one.
+    pub fn rate_per_second(&self) -> Option<f64> {
+        let elapsed = self.first_sample?.elapsed().as_secs_f64();
+        (elapsed > 0.0).then(|| self.count() as f64 / elapsed)
+    }
+}
+
+fn summary_readouts(summary: &HistogramSummary, formatter: Option<&ValueFormatter>, ui: &mut Ui) {
+    let format = |value: f64| formatter.map_or_else(|| format!("{value:.3}"), |f| f(value));
+    let mut text = format!("n = {}, sum = {}", summary.count(), format(summary.sum()));
+    if let Some(rate) = summary.rate_per_second() {
+        let _ = write!(text, ", {rate:.1}/s");
+    }
+    for q in [0.5, 0.99] {
+        if let Some(value) = summary.quantile(q) {
+            let _ = write!(text, ", p{} = {}", 100.0 * q, format(value));
         }
     }
+    ui.label(text);
+}
 
-    /// Pull metric data from the source.
-    ///
-    /// This should run in the [`Last`](bevy::prelude::Last) schedule **before**
-    /// [`ClearBucketsSystem`](crate::ClearBucketsSystem) to ensure no data
-    /// is missed.
-    pub fn update(&mut self) {
-        match &mut self.data {
-            MetricPlotData::Counter(data) => {
-                data.update();
-            }
-            MetricPlotData::Gauge(data) => {
-                data.update();
-            }
-            MetricPlotData::Histogram(data) => {
-                data.update();
+/// Maps the X axis of a bar chart whose empty buckets are merged to the
+/// values of its buckets.
+///
+/// The mapping is piecewise linear between knots at bucket edges, and has a
+/// slope of 1 outside of them.
+#[derive(Clone)]
+struct BucketAxis {
+    /// `(x, value)` pairs, increasing in both.
+    knots: Vec<(f64, f64)>,
+}
+
+impl BucketAxis {
+    /// Lay out `bars` side by side, replacing every run of at least two empty
+    /// bars with a gap of `gap_width`.
+    fn merge_empty(bars: Vec<Bar>, gap_width: f64) -> (Vec<Bar>, Self) {
+        let Some(first) = bars.first() else {
+            return (bars, Self { knots: Vec::new() });
+        };
+        let mut x = first.argument - 0.5 * first.bar_width;
+        let mut knots = vec![(x, x)];
+        let mut merged = Vec::with_capacity(bars.len());
+        let mut i = 0;
+        while i < bars.len() {
+            let run = bars[i..].iter().take_while(|b| b.value == 0.0).count();
+            if run >= 2 {
+                let last = &bars[i + run - 1];
+                x += gap_width;
+                knots.push((x, last.argument + 0.5 * last.bar_width));
+                i += run;
+                continue;
             }
+            let mut bar = bars[i].clone();
+            let value_end = bar.argument + 0.5 * bar.bar_width;
+            bar.argument = x + 0.5 * bar.bar_width;
+            x += bar.bar_width;
+            knots.push((x, value_end));
+            merged.push(bar);
+            i += 1;
         }
+        (merged, Self { knots })
     }
 
-    /// Draw the plot using `ui`.
-    pub fn draw(&mut self, dash_config: &DashboardConfig, ui: &mut Ui) {
-        let Self {
-            name, unit, data, ..
-        } = self;
+    fn value_at(&self, x: f64) -> f64 {
+        interpolate(self.knots.iter().copied(), x)
+    }
 
-        draw_plot(dash_config, name, *unit, data, ui);
+    fn x_at(&self, value: f64) -> f64 {
+        interpolate(self.knots.iter().map(|&(x, v)| (v, x)), value)
     }
 }
 
-fn add_value_to_bucket(bucket_bounds: &[f64], value: f64, bucket_counts: &mut [u32]) {
-    let bucket_i = match bucket_bounds.binary_search_by_key(&FloatOrd(value), |&d| FloatOrd(d)) {
-        Ok(i) => i,
-        Err(i) => i,
+/// Evaluate the piecewise linear function through `knots`, sorted by their
+/// first coordinate, at `x`.
+fn interpolate(knots: impl Iterator<Item = (f64, f64)> + Clone, x: f64) -> f64 {
+    let mut prev: Option<(f64, f64)> = None;
+    for (kx, ky) in knots.clone() {
+        if x <= kx {
+            return match prev {
+                Some((px, py)) if kx > px => py + (ky - py) * (x - px) / (kx - px),
+                _ => ky - (kx - x),
+            };
+        }
+        prev = Some((kx, ky));
+    }
+    prev.map_or(x, |(px, py)| py + (x - px))
+}
+
+/// The meshes painted by a plot on its last full redraw.
+#[derive(Clone)]
+struct PlotPaintCache {
+    drawn_at: Instant,
+    /// The plot and its axes.
+    rect: Rect,
+    available_width: f32,
+    pixels_per_point: f32,
+    primitives: Arc<Vec<ClippedPrimitive>>,
+}
+
+/// Show `plot`, or repaint its cached meshes if it was drawn less than
+/// `redraw_interval` ago.
+///
+/// Repainting skips laying out and tessellating the plot. The plot is always
+/// redrawn while hovered, so tooltips and panning stay responsive.
+///
+/// Returns the response of the plot, or `None` if it was repainted.
+pub(crate) fn show_plot<'a>(
+    plot: Plot<'a>,
+    plot_id: Id,
+    redraw_interval: Option<Duration>,
+    ui: &mut Ui,
+    build: impl FnOnce(&mut PlotUi) + 'a,
+) -> Option<PlotResponse<()>> {
+    let Some(redraw_interval) = redraw_interval else {
+        return Some(plot.show(ui, build));
     };
-    bucket_counts[bucket_i] += 1;
+    let cache_id = plot_id.with("paint_cache");
+    let pixels_per_point = ui.ctx().pixels_per_point();
+
+    let cache: Option<PlotPaintCache> = ui.data(|d| d.get_temp(cache_id));
+    if let Some(cache) = cache {
+        let rect = Rect::from_min_size(ui.available_rect_before_wrap().min, cache.rect.size());
+        let reusable = cache.drawn_at.elapsed() < redraw_interval
+            && cache.pixels_per_point == pixels_per_point
+            && (ui.available_size_before_wrap().x - cache.available_width).abs() < 0.5
+            && !ui.rect_contains_pointer(rect);
+        if reusable {
+            let (rect, _) = ui.allocate_exact_size(rect.size(), Sense::hover());
+            let offset = rect.min - cache.rect.min;
+            for primitive in cache.primitives.iter() {
+                let Primitive::Mesh(mesh) = &primitive.primitive else {
+                    continue;
+                };
+                let mut mesh = mesh.clone();
+                mesh.translate(offset);
+                let clip_rect = primitive.clip_rect.translate(offset);
+                ui.painter()
+                    .with_clip_rect(clip_rect.intersect(ui.clip_rect()))
+                    .add(mesh);
+            }
+            return None;
+        }
+    }
+
+    let layer_id = ui.layer_id();
+    let first_shape = ui
+        .ctx()
+        .graphics(|g| g.get(layer_id).map_or(0, |list| list.next_idx().0));
+    let available_width = ui.available_size_before_wrap().x;
+    // The plot's response only covers its frame, so measure the axes too.
+    let scope = ui.scope(|ui| plot.show(ui, build));
+    let (response, rect) = (scope.inner, scope.response.rect);
+    if ui.rect_contains_pointer(rect) {
+        // Don't cache hover decorations.
+        ui.data_mut(|d| d.remove::<PlotPaintCache>(cache_id));
+        return Some(response);
+    }
+    let shapes = ui.ctx().graphics(|g| {
+        g.get(layer_id).map_or_else(Vec::new, |list| {
+            list.all_entries().skip(first_shape).cloned().collect()
+        })
+    });
+    let primitives = ui.ctx().tessellate(shapes, pixels_per_point);
+    ui.data_mut(|d| {
+        d.insert_temp(
+            cache_id,
+            PlotPaintCache {
+                drawn_at: Instant::now(),
+                rect,
+                available_width,
+                pixels_per_point,
+                primitives: Arc::new(primitives),
+            },
+        )
+    });
+    Some(response)
 }
 
-fn draw_plot(
-    dash_config: &DashboardConfig,
-    name: &str,
+/// Label the `axis` of plotted values with `unit`.
+///
+/// Durations are shown in the unit that best fits `magnitude`, e.g. a range
+/// up to 0.02 seconds is labeled in milliseconds. Other values are formatted
+/// with `axis_format`.
+fn label_value_axis<'a>(
+    plot: Plot<'a>,
+    axis: Axis,
     unit: Option<Unit>,
-    data: &mut MetricPlotData,
-    ui: &mut Ui,
-) {
-    let new_plot = || {
-        Plot::new(name)
-            .allow_scroll(false)
-            .view_aspect(2.0)
-            .auto_bounds([true, true].into())
+    magnitude: f64,
+    axis_format: AxisFormat,
+    ui: &Ui,
+) -> Plot<'a> {
+    let Some(unit) = unit else {
+        return format_value_axis(plot, axis, None, axis_format);
+    };
+    let (display_unit, factor) = display_duration_unit(unit, magnitude).unwrap_or((unit, 1.0));
+    let label = tr_unit(ui, display_unit);
+    let formatter = move |mark: GridMark, _: &RangeInclusive<f64>| {
+        let num_decimals = -(mark.step_size * factor).log10().round() as usize;
+        emath::format_with_decimals_in_range(mark.value * factor, num_decimals..=num_decimals)
     };
+    match (axis, factor == 1.0) {
+        (Axis::X, true) => {
+            format_value_axis(plot.x_axis_label(label), axis, Some(unit), axis_format)
+        }
+        (Axis::Y, true) => {
+            format_value_axis(plot.y_axis_label(label), axis, Some(unit), axis_format)
+        }
+        (Axis::X, false) => plot.x_axis_label(label).x_axis_formatter(formatter),
+        (Axis::Y, false) => plot.y_axis_label(label).y_axis_formatter(formatter),
+    }
+}
 
-    match data {
-        MetricPlotData::Counter(data) => {
-            if let Some(latest) = data.ring.latest() {
-                ui.label(format!("latest = {latest:.3}"));
+/// Label the X axis of a bar chart laid out by `bucket_axis` with bucket
+/// values, formatted like [`label_value_axis`].
+fn remap_x_axis<'a>(
+    plot: Plot<'a>,
+    bucket_axis: BucketAxis,
+    unit: Option<Unit>,
+    magnitude: f64,
+    axis_format: AxisFormat,
+) -> Plot<'a> {
+    let factor = unit
+        .and_then(|u| display_duration_unit(u, magnitude))
+        .map_or(1.0, |(_, factor)| factor);
+    plot.x_axis_formatter(move |mark: GridMark, _: &RangeInclusive<f64>| {
+        let value = bucket_axis.value_at(mark.value);
+        if factor == 1.0 {
+            if let Some(text) = axis_format.format(value, unit) {
+                return text;
             }
+        }
+        emath::format_with_decimals_in_range(value * factor, 0..=2)
+    })
+}
 
-            let mut plot_points = data.ring.make_plot_points();
-            if data.config.derivative {
-                derivative(&mut plot_points);
-            }
-            let line = Line::new(PlotPoints::Owned(plot_points));
-            let mut plot = new_plot().x_axis_label("frame");
-            if let Some(unit) = unit {
-                plot = plot.y_axis_label(unit_str(unit));
-            }
-            plot.show(ui, |plot_ui| plot_ui.line(line));
+/// Format the tick labels of `axis`, whose values are measured in `unit`.
+fn format_value_axis<'a>(
+    plot: Plot<'a>,
+    axis: Axis,
+    unit: Option<Unit>,
+    axis_format: AxisFormat,
+) -> Plot<'a> {
+    if axis_format == AxisFormat::Plain {
+        return plot;
+    }
+    let formatter = move |mark: GridMark, _: &RangeInclusive<f64>| {
+        axis_format.format(mark.value, unit).unwrap_or_default()
+    };
+    match axis {
+        Axis::X => plot.x_axis_formatter(formatter),
+        Axis::Y => plot.y_axis_formatter(formatter),
+    }
+}
 
-            data.handle_global_config(dash_config);
-            ui.collapsing("Settings", |ui| {
-                data.configure_ui(dash_config.global_window_size.is_none(), ui);
-            });
+/// Choose between plotting a gauge as a line or as a histogram of its values.
+///
+/// Returns the new choice if it changed.
+fn plot_as_ui(as_histogram: bool, ui: &mut Ui) -> Option<bool> {
+    let mut selected = as_histogram;
+    ui.horizontal(|ui| {
+        ui.label(tr(ui, "Plot As:"));
+        ui.selectable_value(&mut selected, false, tr(ui, "Line"));
+        ui.selectable_value(&mut selected, true, tr(ui, "Histogram"))
+            .on_hover_text(tr(ui, "Sample the value into buckets every frame"));
+    });
+    (selected != as_histogram).then_some(selected)
+}
+
+fn note_ui(note: &mut String, ui: &mut Ui) {
+    ui.horizontal(|ui| {
+        ui.label(tr(ui, "Note:"));
+        ui.add(TextEdit::singleline(note).hint_text(tr(ui, "Shown under the header")));
+    });
+}
+
+/// Fix the Y axis of the plot to `range`, if any, while the X axis keeps
+/// following the samples.
+fn lock_y_axis(range: Option<RangeInclusive<f64>>, plot_ui: &mut PlotUi) {
+    let Some(range) = range else {
+        return;
+    };
+    let bounds = plot_ui.plot_bounds();
+    let follow = plot_ui.auto_bounds().x;
+    plot_ui.set_plot_bounds(PlotBounds::from_min_max(
+        [bounds.min()[0], *range.start()],
+        [bounds.max()[0], *range.end()],
+    ));
+    plot_ui.set_auto_bounds([follow, false].into());
+}
+
+/// Lock either end of the Y axis, starting from the visible range.
+fn y_lock_ui(lock: &mut YAxisLock, plot_id: Id, ui: &mut Ui) {
+    let memory = PlotMemory::load(ui.ctx(), plot_id);
+    let visible = memory.as_ref().map(|m| *m.bounds());
+    let was_locked = lock.is_locked();
+    ui.horizontal(|ui| {
+        let min = visible.map(|b| b.min()[1]);
+        bound_lock_ui(&mut lock.min, tr(ui, "Lock Y Min"), min, ui);
+        let max = visible.map(|b| b.max()[1]);
+        bound_lock_ui(&mut lock.max, tr(ui, "Lock Y Max"), max, ui);
+    });
+    if was_locked && !lock.is_locked() {
+        // Go back to fitting the Y axis to the samples.
+        if let Some(mut memory) = memory {
+            memory.auto_bounds.y = true;
+            memory.store(ui.ctx(), plot_id);
         }
-        MetricPlotData::Gauge(data) => {
-            if let Some(latest) = data.ring.latest() {
-                ui.label(format!("latest = {latest:.3}"));
-            }
+    }
+}
 
-            let mut plot_points = data.ring.make_plot_points();
-            if data.config.derivative {
-                derivative(&mut plot_points);
-            }
-            let line = Line::new(PlotPoints::Owned(plot_points));
-            let mut plot = new_plot().x_axis_label("frame");
-            if let Some(unit) = unit {
-                plot = plot.y_axis_label(unit_str(unit));
-            }
-            plot.show(ui, |plot_ui| plot_ui.line(line));
+fn bound_lock_ui(bound: &mut Option<f64>, label: String, visible: Option<f64>, ui: &mut Ui) {
+    let mut locked = bound.is_some();
+    if ui.checkbox(&mut locked, label).changed() {
+        *bound = locked.then(|| visible.unwrap_or_default());
+    }
+    if let Some(value) = bound {
+        let speed = 0.01 * value.abs().max(1e-3);
+        ui.add(DragValue::new(value).speed(speed));
+    }
+}
 
-            data.handle_global_config(dash_config);
-            ui.collapsing("Settings", |ui| {
-                data.configure_ui(dash_config.global_window_size.is_none(), ui);
-            });
+fn axis_format_ui(axis_format: &mut AxisFormat, ui: &mut Ui) {
+    let mut compact = *axis_format == AxisFormat::Compact;
+    if ui
+        .checkbox(&mut compact, tr(ui, "Compact Axis Labels"))
+        .on_hover_text(tr(
+            ui,
+            "Label large values like 1.5M and sizes like 1.5 MiB",
+        ))
+        .changed()
+    {
+        *axis_format = if compact {
+            AxisFormat::Compact
+        } else {
+            AxisFormat::Plain
+        };
+    }
+}
+
+/// The number of seconds in one `unit`, if it's a duration.
+fn seconds_per_unit(unit: Unit) -> Option<f64> {
+    match unit {
+        Unit::Seconds => Some(1.0),
+        Unit::Milliseconds => Some(1e-3),
+        Unit::Microseconds => Some(1e-6),
+        Unit::Nanoseconds => Some(1e-9),
+        _ => None,
+    }
+}
+
+/// The duration unit that shows `magnitude` (measured in `unit`) with the
+/// fewest digits, and the factor converting from `unit` to it.
+fn display_duration_unit(unit: Unit, magnitude: f64) -> Option<(Unit, f64)> {
+    let per_unit = seconds_per_unit(unit)?;
+    let seconds = magnitude.abs() * per_unit;
+    if seconds == 0.0 {
+        return Some((unit, 1.0));
+    }
+    let (display_unit, per_display_unit) = [
+        Unit::Seconds,
+        Unit::Milliseconds,
+        Unit::Microseconds,
+        Unit::Nanoseconds,
+    ]
+    .into_iter()
+    .filter_map(|u| Some((u, seconds_per_unit(u)?)))
+    .find(|&(_, per_u)| seconds >= per_u)
+    .unwrap_or((Unit::Nanoseconds, 1e-9));
+    Some((display_unit, per_unit / per_display_unit))
+}
+
+/// A button that copies the CSV made by `csv` to the clipboard, for pasting
+/// into a spreadsheet.
+fn copy_values_button(ui: &mut Ui, csv: impl FnOnce(&Ui) -> String) {
+    if ui
+        .button(tr(ui, "Copy Values"))
+        .on_hover_text(tr(ui, "Copy the visible samples to the clipboard as CSV"))
+        .clicked()
+    {
+        ui.ctx().copy_text(csv(ui));
+    }
+}
+
+/// The X range currently shown by the plot `plot_id`.
+fn visible_x_range(plot_id: Id, ui: &Ui) -> Option<RangeInclusive<f64>> {
+    PlotMemory::load(ui.ctx(), plot_id).map(|memory| memory.bounds().range_x())
+}
+
+/// A "sample,value" CSV of the `points` within `x_range`.
+fn points_csv(
+    value_header: &str,
+    points: &[PlotPoint],
+    x_range: Option<RangeInclusive<f64>>,
+) -> String {
+    let mut csv = format!("sample,{}\n", csv_field(value_header));
+    for point in points {
+        if x_range.as_ref().is_none_or(|r| r.contains(&point.x)) {
+            let _ = writeln!(csv, "{},{}", point.x, point.y);
         }
-        MetricPlotData::Histogram(data) => {
-            let chart = data.make_bar_chart();
-            let mut plot = new_plot().y_axis_label("count");
-            if let Some(unit) = unit {
-                plot = plot.x_axis_label(unit_str(unit));
+    }
+    csv
+}
+
+/// Quote `field` if it contains CSV delimiters.
+pub(crate) fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+/// Center the X axis of the plot `plot_id` on `x`, keeping its zoom, and stop
+/// following the latest samples.
+///
+/// Returns `false` if the plot hasn't been shown yet.
+fn focus_x(plot_id: Id, x: f64, ui: &Ui) -> bool {
+    let Some(mut memory) = PlotMemory::load(ui.ctx(), plot_id) else {
+        return false;
+    };
+    let mut bounds = *memory.bounds();
+    bounds.set_x_center_width(x, bounds.width());
+    memory.set_bounds(bounds);
+    memory.auto_bounds = [false, true].into();
+    memory.store(ui.ctx(), plot_id);
+    // Don't wait for the redraw interval to show the new bounds.
+    ui.data_mut(|d| d.remove::<PlotPaintCache>(plot_id.with("paint_cache")));
+    true
+}
+
+const MINIMAP_HEIGHT: f32 = 24.0;
+
+/// A strip showing all `points` of the plot `plot_id`, with the visible
+/// region highlighted, so zooming in doesn't lose the context of the whole
+/// window. Clicking or dragging the strip pans the plot there.
+fn minimap(plot_id: Id, points: &[PlotPoint], color: Color32, ui: &mut Ui) {
+    let (Some(first), Some(last)) = (points.first(), points.last()) else {
+        return;
+    };
+    let (rect, response) = ui.allocate_exact_size(
+        vec2(ui.available_width(), MINIMAP_HEIGHT),
+        Sense::click_and_drag(),
+    );
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+
+    let x_span = (last.x - first.x).max(f64::EPSILON);
+    let (min_y, max_y) = points
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| {
+            (lo.min(p.y), hi.max(p.y))
+        });
+    let y_span = (max_y - min_y).max(f64::EPSILON);
+    let screen_x = |x: f64| rect.left() + rect.width() * ((x - first.x) / x_span) as f32;
+    let to_screen = |p: &PlotPoint| -> Pos2 {
+        pos2(
+            screen_x(p.x),
+            rect.bottom() - rect.height() * ((p.y - min_y) / y_span) as f32,
+        )
+    };
+    // There's no point in drawing more than one point per pixel.
+    let stride = (points.len() / rect.width().max(1.0) as usize).max(1);
+    let line: Vec<Pos2> = points.iter().step_by(stride).map(to_screen).collect();
+    painter.add(Shape::line(line, Stroke::new(1.0, color)));
+
+    if let Some(memory) = PlotMemory::load(ui.ctx(), plot_id) {
+        let visible = memory.bounds().range_x();
+        let left = screen_x(*visible.start()).max(rect.left());
+        let right = screen_x(*visible.end()).min(rect.right());
+        if left < right {
+            let view = Rect::from_x_y_ranges(left..=right, rect.y_range());
+            let selection = ui.visuals().selection;
+            painter.rect_filled(view, 0.0, selection.bg_fill.gamma_multiply(0.3));
+            painter.rect_stroke(view, 0.0, selection.stroke);
+        }
+    }
+
+    if let Some(pointer) = response.interact_pointer_pos() {
+        let t = ((pointer.x - rect.left()) / rect.width()).clamp(0.0, 1.0);
+        focus_x(plot_id, first.x + f64::from(t) * x_span, ui);
+    }
+}
+
+const EVENT_STRIP_HEIGHT: f32 = 8.0;
+
+/// A strip with a tick on each sample where the counter of `events`
+/// increased, aligned with the X axis of the plot `plot_id`.
+fn event_strip(plot_id: Id, events: &EventTrack, by_frame: bool, color: Color32, ui: &mut Ui) {
+    let Some(memory) = PlotMemory::load(ui.ctx(), plot_id) else {
+        return;
+    };
+    let transform = memory.transform();
+    let (rect, response) = ui.allocate_exact_size(
+        vec2(ui.available_width(), EVENT_STRIP_HEIGHT),
+        Sense::hover(),
+    );
+    // Only span the plot's frame, not its axes.
+    let strip = Rect::from_x_y_ranges(transform.frame().x_range(), rect.y_range());
+    let painter = ui.painter_at(strip);
+    painter.rect_filled(strip, 2.0, ui.visuals().extreme_bg_color);
+    for x in events.xs(by_frame) {
+        let x = transform.position_from_point_x(x);
+        if strip.x_range().contains(x) {
+            painter.vline(x, strip.y_range(), Stroke::new(1.0, color));
+        }
+    }
+    response.on_hover_text(events.key.title(None, 0));
+}
+
+/// Oscilloscope-style cursors that measure the change between two samples of
+/// a line plot, e.g. to quantify a ramp or a leak.
+///
+/// While measuring, each click on the plot places a cursor on the nearest
+/// sample. A third click starts a new pair.
+#[derive(Clone, Default)]
+struct Measurement {
+    active: bool,
+    cursors: SmallVec<[f64; 2]>,
+}
+
+impl Measurement {
+    fn load(plot_id: Id, ui: &Ui) -> Self {
+        ui.data(|d| d.get_temp(plot_id.with("measurement")))
+            .unwrap_or_default()
+    }
+
+    fn store(self, plot_id: Id, ui: &Ui) {
+        ui.data_mut(|d| d.insert_temp(plot_id.with("measurement"), self));
+    }
+
+    fn draw(&self, plot_ui: &mut PlotUi) {
+        for &x in &self.cursors {
+            let line = VLine::new(x).color(Color32::WHITE);
+            plot_ui.vline(line.style(LineStyle::dashed_loose()));
+        }
+    }
+
+    /// The X coordinate of a click on the plot, from its `response`, that
+    /// should place a cursor.
+    fn clicked_x(&self, response: Option<&PlotResponse<()>>) -> Option<f64> {
+        let response = response.filter(|r| self.active && r.response.clicked())?;
+        let pos = response.response.interact_pointer_pos()?;
+        Some(response.transform.value_from_position(pos).x)
+    }
+
+    fn toggle(&mut self, ui: &mut Ui) {
+        if ui
+            .toggle_value(&mut self.active, tr(ui, "Measure"))
+            .on_hover_text(tr(
+                ui,
+                "Click two samples to measure the change between them",
+            ))
+            .changed()
+        {
+            self.cursors.clear();
+        }
+    }
+
+    /// Place a cursor at `clicked`, and show the change between the cursors
+    /// on the plotted `points`. The point at X coordinate `x` was recorded at
+    /// `time_at(x)`.
+    fn update(
+        &mut self,
+        clicked: Option<f64>,
+        points: impl FnOnce() -> Vec<PlotPoint>,
+        time_at: impl Fn(f64) -> Option<Instant>,
+        formatter: Option<&ValueFormatter>,
+        by_frame: bool,
+        ui: &mut Ui,
+    ) {
+        if !self.active {
+            return;
+        }
+        let points = points();
+        let nearest = |x: f64| {
+            points
+                .iter()
+                .min_by(|a, b| (a.x - x).abs().total_cmp(&(b.x - x).abs()))
+        };
+        if let Some(point) = clicked.and_then(nearest) {
+            if self.cursors.len() == 2 {
+                self.cursors.clear();
             }
-            plot.show(ui, |plot_ui| plot_ui.bar_chart(chart));
+            self.cursors.push(point.x);
+        }
 
-            ui.collapsing("Settings", |ui| {
-                data.configure_ui(ui);
-            });
+        let [a, b] = self.cursors[..] else {
+            ui.weak(tr(ui, "Click two samples to measure"));
+            return;
+        };
+        let (Some(a), Some(b)) = (nearest(a), nearest(b)) else {
+            return;
+        };
+        let (a, b) = if a.x <= b.x { (a, b) } else { (b, a) };
+        let dy = b.y - a.y;
+        let dy_text = formatter.map_or_else(|| format!("{dy:.3}"), |f| f(dy));
+        let dx_label = if by_frame { "Δframes" } else { "Δsamples" };
+        let mut text = format!("Δ = {dy_text}, {} = {}", tr(ui, dx_label), b.x - a.x);
+        if let (Some(t_a), Some(t_b)) = (time_at(a.x), time_at(b.x)) {
+            let dt = t_b.saturating_duration_since(t_a).as_secs_f64();
+            let _ = write!(text, ", Δt = {dt:.3} s");
+            if dt > 0.0 {
+                let _ = write!(text, ", {} = {:.3}/s", tr(ui, "rate"), dy / dt);
+            }
+        }
+        ui.label(text);
+    }
+}
+
+/// Toggles whether the plot `plot_id` follows the latest samples.
+///
+/// Panning or zooming a plot stops following, so earlier samples can be
+/// inspected without the view snapping back.
+fn follow_toggle(plot_id: Id, ui: &mut Ui) {
+    let Some(mut memory) = PlotMemory::load(ui.ctx(), plot_id) else {
+        return;
+    };
+    let mut follow = memory.auto_bounds.x;
+    if ui
+        .toggle_value(&mut follow, tr(ui, "Follow"))
+        .on_hover_text(tr(
+            ui,
+            "Follow the latest samples. Pan or zoom the plot to stop following.",
+        ))
+        .changed()
+    {
+        memory.auto_bounds = follow.into();
+        memory.store(ui.ctx(), plot_id);
+    }
+}
+
+/// Returns the index of the template chosen from the menu, if any.
+fn template_menu(templates: &[&PlotTemplate], ui: &mut Ui) -> Option<usize> {
+    if templates.is_empty() {
+        return None;
+    }
+    let mut selected = None;
+    ui.menu_button(tr(ui, "Apply Template"), |ui| {
+        for (i, template) in templates.iter().enumerate() {
+            if ui.button(&template.name).clicked() {
+                selected = Some(i);
+                ui.close_menu();
+            }
         }
+    });
+    selected
+}
+
+/// Draw small colored chips showing the metric `kind` and `unit`.
+pub fn draw_metric_chips(kind: MetricKind, unit: Option<Unit>, ui: &mut Ui) {
+    let kind_color = match kind {
+        MetricKind::Counter => Color32::LIGHT_BLUE,
+        MetricKind::Gauge => Color32::LIGHT_GREEN,
+        MetricKind::Histogram => Color32::GOLD,
+    };
+    chip(metric_kind_str(kind), kind_color, ui);
+    if let Some(unit) = unit {
+        chip(&tr_unit(ui, unit), Color32::LIGHT_GRAY, ui);
     }
 }
 
-pub(crate) fn window_size_slider(size: &mut usize) -> Slider {
-    Slider::new(size, 100..=5000).text("Window Size")
+pub(crate) fn chip(text: &str, fill: Color32, ui: &mut Ui) {
+    Frame::none()
+        .fill(fill)
+        .rounding(4.0)
+        .inner_margin(Margin::symmetric(4.0, 0.0))
+        .show(ui, |ui| {
+            ui.label(RichText::new(text).small().color(Color32::BLACK));
+        });
+}
+
+pub(crate) fn window_size_slider<'a>(size: &'a mut usize, ui: &Ui) -> Slider<'a> {
+    Slider::new(size, 100..=5000).text(tr(ui, "Window Size"))
 }
 
 struct Smoother {
@@ -613,6 +3668,151 @@ impl Smoother {
     }
 }
 
+/// Plot points with either frames or sample numbers on the X axis.
+fn make_plot_points<T>(ring: &Ring<T>, by_frame: bool) -> Vec<PlotPoint>
+where
+    T: Clone + num_traits::NumCast,
+{
+    if by_frame {
+        ring.make_frame_plot_points()
+    } else {
+        ring.make_plot_points()
+    }
+}
+
+/// The window sizes chosen for an `auto_window`, e.g.
+/// [`CounterPlotConfig::auto_window`].
+const AUTO_WINDOW_SIZES: RangeInclusive<usize> = 10..=50_000;
+
+/// Resize `ring` to hold about `duration` of elements at its measured push
+/// rate, returning its new maximum length.
+fn fit_window<T>(ring: &mut Ring<T>, duration: Duration) -> usize {
+    let current = ring.max_len();
+    let Some(rate) = ring.push_rate() else {
+        return current;
+    };
+    let target = (rate * duration.as_secs_f64()).round() as usize;
+    let target = target.clamp(*AUTO_WINDOW_SIZES.start(), *AUTO_WINDOW_SIZES.end());
+    // Small changes in the rate would otherwise trim the oldest elements on
+    // every frame.
+    if target.abs_diff(current) * 10 <= current {
+        return current;
+    }
+    ring.set_max_len(target);
+    target
+}
+
+/// A checkbox to choose an `auto_window` instead of a fixed window size,
+/// with the duration to cover. Returns whether it's checked.
+fn auto_window_ui(auto_window: &mut Option<Duration>, ui: &mut Ui) -> bool {
+    ui.horizontal(|ui| {
+        let mut auto = auto_window.is_some();
+        ui.checkbox(&mut auto, tr(ui, "Auto Window"))
+            .on_hover_text(tr(
+                ui,
+                "Keep a duration of samples, at the rate they're measured",
+            ));
+        if !auto {
+            *auto_window = None;
+            return false;
+        }
+        let duration = auto_window.get_or_insert(Duration::from_secs(10));
+        let mut seconds = duration.as_secs_f64();
+        if ui
+            .add(
+                DragValue::new(&mut seconds)
+                    .range(1.0..=3600.0)
+                    .suffix(" s"),
+            )
+            .changed()
+        {
+            *duration = Duration::from_secs_f64(seconds);
+        }
+        true
+    })
+    .inner
+}
+
+/// Samples are this many times further apart than usual after a gap, see
+/// [`Ring::gap_indices`].
+const GAP_FACTOR: f64 = 4.0;
+
+/// The shortest time without samples that counts as a gap.
+const MIN_GAP: Duration = Duration::from_millis(250);
+
+/// The X ranges between consecutive points of `ring` with a gap in sampling
+/// between them.
+fn ring_gaps<T>(ring: &Ring<T>, by_frame: bool) -> Vec<(f64, f64)>
+where
+    T: Clone + num_traits::NumCast,
+{
+    let gaps = ring.gap_indices(GAP_FACTOR, MIN_GAP);
+    if gaps.is_empty() {
+        return Vec::new();
+    }
+    let points = make_plot_points(ring, by_frame);
+    gaps.into_iter()
+        .map(|i| (points[i - 1].x, points[i].x))
+        .collect()
+}
+
+/// Split a line wherever it would connect points across one of `gaps`, so
+/// missing samples aren't mistaken for a steady change.
+fn split_at_gaps(points: Vec<PlotPoint>, gaps: &[(f64, f64)]) -> Vec<Vec<PlotPoint>> {
+    let mut segments = vec![Vec::new()];
+    for point in points {
+        let segment = segments.last_mut().unwrap();
+        let crosses_gap = segment.last().is_some_and(|last: &PlotPoint| {
+            gaps.iter()
+                .any(|&(start, end)| last.x <= start && point.x >= end)
+        });
+        if crosses_gap {
+            segments.push(vec![point]);
+        } else {
+            segment.push(point);
+        }
+    }
+    segments
+}
+
+/// Shade each of `gaps` over the Y range of `points`.
+fn shade_gaps(gaps: &[(f64, f64)], points: &[PlotPoint], plot_ui: &mut PlotUi) {
+    let Some((min, max)) = points.iter().fold(None, |range: Option<(f64, f64)>, p| {
+        Some(range.map_or((p.y, p.y), |(min, max)| (min.min(p.y), max.max(p.y))))
+    }) else {
+        return;
+    };
+    for &(start, end) in gaps {
+        let corners = vec![[start, min], [end, min], [end, max], [start, max]];
+        let polygon = Polygon::new(PlotPoints::new(corners))
+            .fill_color(Color32::from_gray(128).gamma_multiply(0.25))
+            .stroke(Stroke::NONE)
+            .allow_hover(false);
+        plot_ui.polygon(polygon);
+    }
+}
+
+/// Whether a counter that was at `last` and then decreased most likely
+/// wrapped around, rather than being reset.
+fn counter_wrapped(last: u64) -> bool {
+    last > u64::MAX / 2
+}
+
+/// How much a counter increased from `last` to `value`.
+///
+/// A decrease is either a wraparound past `u64::MAX`, if `last` was close to
+/// it, or else a reset to zero, e.g. by a restarted server. Neither counts as
+/// a huge negative increase.
+fn counter_delta(last: u64, value: u64) -> u64 {
+    if value >= last {
+        value - last
+    } else if counter_wrapped(last) {
+        value.wrapping_sub(last)
+    } else {
+        value
+    }
+}
+
 fn derivative(points: &mut Vec<PlotPoint>) {
     if points.is_empty() {
         return;
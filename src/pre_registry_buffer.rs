@@ -0,0 +1,124 @@
+use crate::registry::MetricsRegistry;
+use metrics::{
+    set_global_recorder, Counter, Gauge, Histogram, Key, KeyName, Metadata, Recorder,
+    SetRecorderError, SharedString, Unit,
+};
+use std::sync::{atomic::Ordering, Arc, OnceLock};
+
+/// A global recorder that buffers metrics until the real [`MetricsRegistry`]
+/// exists.
+///
+/// The [`RegistryPlugin`](crate::RegistryPlugin) normally installs the global
+/// recorder when it is built, so any `describe_*!` calls or metric writes that
+/// happen earlier (e.g. while other plugins are being built) are dropped.
+/// Installing this buffer at the very start of `main` captures them instead:
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_metrics_dashboard::{PreRegistryBuffer, RegistryPlugin};
+/// let buffer = PreRegistryBuffer::install().unwrap();
+/// App::new().add_plugins(RegistryPlugin::with_pre_registry_buffer(buffer));
+/// ```
+///
+/// Once [`replay_into`](Self::replay_into) is called, all buffered data is
+/// copied into the registry and every subsequent call is forwarded to it.
+/// Metric handles that were obtained before the replay and kept alive will
+/// continue writing into the buffer, not the registry.
+#[derive(Clone)]
+pub struct PreRegistryBuffer {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    buffer: MetricsRegistry,
+    target: OnceLock<MetricsRegistry>,
+}
+
+impl Default for PreRegistryBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PreRegistryBuffer {
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                buffer: MetricsRegistry::new(),
+                target: OnceLock::new(),
+            }),
+        }
+    }
+
+    /// Create a new buffer and install it as the global recorder.
+    pub fn install() -> Result<Self, SetRecorderError<Self>> {
+        let buffer = Self::new();
+        set_global_recorder(buffer.clone())?;
+        Ok(buffer)
+    }
+
+    /// Copy all buffered descriptions and metrics into `registry` and forward
+    /// all future calls to it.
+    ///
+    /// Returns `false` if this buffer was already replayed into a registry,
+    /// in which case nothing happens.
+    pub fn replay_into(&self, registry: &MetricsRegistry) -> bool {
+        if self.inner.target.set(registry.clone()).is_err() {
+            return false;
+        }
+
+        let buffer = &self.inner.buffer;
+        for (key, description) in buffer.all_descriptions() {
+            registry.add_description_if_missing(key, description);
+        }
+        buffer.visit_counters(|key, counter| {
+            registry
+                .get_or_create_counter(key)
+                .fetch_add(counter.load(Ordering::Relaxed), Ordering::Relaxed);
+        });
+        buffer.visit_gauges(|key, gauge| {
+            registry
+                .get_or_create_gauge(key)
+                .store(gauge.load(Ordering::Relaxed), Ordering::Relaxed);
+        });
+        buffer.visit_histograms(|key, histogram| {
+            let target = registry.get_or_create_histogram(key);
+            histogram.clear_with(|block| {
+                for &value in block {
+                    target.push(value);
+                }
+            });
+        });
+        true
+    }
+
+    fn recorder(&self) -> &MetricsRegistry {
+        self.inner.target.get().unwrap_or(&self.inner.buffer)
+    }
+}
+
+impl Recorder for PreRegistryBuffer {
+    fn describe_counter(&self, key: KeyName, unit: Option<Unit>, description: SharedString) {
+        self.recorder().describe_counter(key, unit, description);
+    }
+
+    fn describe_gauge(&self, key: KeyName, unit: Option<Unit>, description: SharedString) {
+        self.recorder().describe_gauge(key, unit, description);
+    }
+
+    fn describe_histogram(&self, key: KeyName, unit: Option<Unit>, description: SharedString) {
+        self.recorder().describe_histogram(key, unit, description);
+    }
+
+    fn register_counter(&self, key: &Key, metadata: &Metadata<'_>) -> Counter {
+        self.recorder().register_counter(key, metadata)
+    }
+
+    fn register_gauge(&self, key: &Key, metadata: &Metadata<'_>) -> Gauge {
+        self.recorder().register_gauge(key, metadata)
+    }
+
+    fn register_histogram(&self, key: &Key, metadata: &Metadata<'_>) -> Histogram {
+        self.recorder().register_histogram(key, metadata)
+    }
+}
@@ -1,18 +1,30 @@
 //! The process-global metrics registry.
 
-use crate::{metric_kind_str, unit_str};
+use crate::{
+    locale::DashboardLocale,
+    metric_kind_str,
+    metric_storage::{MetricStorage, Stored},
+    multi_recorder::{fan_out_counter, fan_out_gauge, fan_out_histogram},
+    sharded_counters::{ShardedCounter, ShardedCounters},
+    write_coalescing::{CoalescedCounter, CoalescedGauge, CoalescedWrites, Target},
+    write_counting::{WriteCounts, WriteStats},
+    WriteCoalescing,
+};
 use bevy::{
     prelude::{default, Res, Resource},
     utils::HashMap,
 };
 use bevy_egui::egui::{text::LayoutJob, Color32, TextFormat};
 use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
-use metrics::{Counter, Gauge, Histogram, Key, KeyName, Metadata, Recorder, SharedString, Unit};
+use metrics::{
+    Counter, Gauge, Histogram, Key, KeyName, Label, Metadata, Recorder, SharedString, Unit,
+};
 use metrics_util::{
-    registry::{AtomicStorage, Registry},
-    storage::AtomicBucket, MetricKind,
+    registry::{Generation, Registry},
+    storage::AtomicBucket,
+    MetricKind,
 };
-use std::sync::atomic::AtomicU64;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, RwLock};
 
 /// Tracks all metrics in the current process.
@@ -26,10 +38,57 @@ pub struct MetricsRegistry {
 }
 
 struct Inner {
-    registry: Registry<Key, AtomicStorage>,
+    registry: Registry<Key, MetricStorage>,
     descriptions: RwLock<HashMap<DescriptionKey, MetricDescription>>,
+    /// Sorted by descending namespace length, so the most specific comes
+    /// first.
+    namespace_descriptions: RwLock<Vec<(String, MetricDescription)>>,
+    targets: RwLock<HashMap<DescriptionKey, String>>,
+    clear_generation: AtomicU64,
+    downstream: Option<Arc<dyn Recorder + Send + Sync>>,
+    coalesce_counters: AtomicBool,
+    coalesce_gauges: AtomicBool,
+    coalesced_writes: Arc<CoalescedWrites>,
+    shard_counters: AtomicBool,
+    sharded_counters: ShardedCounters,
+    count_writes: AtomicBool,
+    write_counts: Arc<WriteCounts>,
+    has_added_hooks: AtomicBool,
+    added_hooks: RwLock<Vec<Arc<MetricAddedFn>>>,
+    recency: RwLock<RecencyState>,
+}
+
+/// What [`MetricsRegistry::upkeep`] has seen of every metric.
+#[derive(Default)]
+struct RecencyState {
+    generation: u64,
+    /// Indexed by [`kind_index`].
+    keys: [HashMap<Key, Recency>; 3],
+}
+
+struct Recency {
+    /// The metric's write generation seen by the last upkeep.
+    observed: Generation,
+    last_update: u64,
+}
+
+fn kind_index(kind: MetricKind) -> usize {
+    match kind {
+        MetricKind::Counter => 0,
+        MetricKind::Gauge => 1,
+        MetricKind::Histogram => 2,
+    }
 }
 
+const KINDS: [MetricKind; 3] = [
+    MetricKind::Counter,
+    MetricKind::Gauge,
+    MetricKind::Histogram,
+];
+
+/// A callback passed to [`MetricsRegistry::on_metric_added`].
+pub type MetricAddedFn = dyn Fn(&MetricKey) + Send + Sync;
+
 #[derive(Clone)]
 pub struct MetricDescription {
     pub unit: Option<Unit>,
@@ -37,10 +96,24 @@ pub struct MetricDescription {
 }
 
 impl Inner {
-    fn new() -> Self {
+    fn new(downstream: Option<Arc<dyn Recorder + Send + Sync>>) -> Self {
         Self {
-            registry: Registry::atomic(),
+            registry: Registry::new(MetricStorage::new()),
             descriptions: RwLock::new(Default::default()),
+            namespace_descriptions: RwLock::new(Vec::new()),
+            targets: RwLock::new(Default::default()),
+            clear_generation: AtomicU64::new(0),
+            downstream,
+            coalesce_counters: AtomicBool::new(false),
+            coalesce_gauges: AtomicBool::new(false),
+            coalesced_writes: Arc::new(CoalescedWrites::new()),
+            shard_counters: AtomicBool::new(false),
+            sharded_counters: default(),
+            count_writes: AtomicBool::new(false),
+            write_counts: default(),
+            has_added_hooks: AtomicBool::new(false),
+            added_hooks: RwLock::new(Vec::new()),
+            recency: RwLock::new(default()),
         }
     }
 }
@@ -48,52 +121,310 @@ impl Inner {
 impl MetricsRegistry {
     pub fn new() -> Self {
         Self {
-            inner: Arc::new(Inner::new()),
+            inner: Arc::new(Inner::new(None)),
+        }
+    }
+
+    /// A registry that also forwards all descriptions, registrations, and
+    /// samples to `recorder`.
+    ///
+    /// Install this registry as the global recorder to keep using another
+    /// recorder (e.g. an exporter) alongside the dashboard.
+    pub fn with_downstream(recorder: impl Recorder + Send + Sync + 'static) -> Self {
+        Self {
+            inner: Arc::new(Inner::new(Some(Arc::new(recorder)))),
         }
     }
 
     pub fn get_or_create_counter(&self, key: &Key) -> Arc<AtomicU64> {
-        self.inner.registry.get_or_create_counter(key, Arc::clone)
+        let (counter, added) = (self.inner.registry)
+            .get_or_create_counter(key, |c| (c.atomic().clone(), c.claim_added()));
+        self.notify_if_added(added, key, MetricKind::Counter);
+        counter
     }
     pub fn get_or_create_gauge(&self, key: &Key) -> Arc<AtomicU64> {
-        self.inner.registry.get_or_create_gauge(key, Arc::clone)
+        let (gauge, added) = (self.inner.registry)
+            .get_or_create_gauge(key, |g| (g.atomic().clone(), g.claim_added()));
+        self.notify_if_added(added, key, MetricKind::Gauge);
+        gauge
     }
     pub fn get_or_create_histogram(&self, key: &Key) -> Arc<AtomicBucket<f64>> {
-        self.inner.registry.get_or_create_histogram(key, Arc::clone)
+        let (histogram, added) = (self.inner.registry)
+            .get_or_create_histogram(key, |h| (h.atomic().clone(), h.claim_added()));
+        self.notify_if_added(added, key, MetricKind::Histogram);
+        histogram
     }
+
+    /// Returns `true` if the metric identified by `key` exists, without
+    /// creating it.
+    pub fn contains(&self, key: &MetricKey) -> bool {
+        let reg = &self.inner.registry;
+        match key.kind {
+            MetricKind::Counter => reg.get_counter(&key.key).is_some(),
+            MetricKind::Gauge => reg.get_gauge(&key.key).is_some(),
+            MetricKind::Histogram => reg.get_histogram(&key.key).is_some(),
+        }
+    }
+
+    /// Call `callback` whenever a new metric (or series of a metric with new
+    /// labels) is created in this registry, so UIs and exporters don't need
+    /// to rescan the registry.
+    ///
+    /// `callback` runs on the thread that created the metric, so it should be
+    /// quick, e.g. sending to a channel. It's called once per metric, even
+    /// when the metric is created concurrently on several threads.
+    ///
+    /// Until the first callback is added, creating metrics has no overhead.
+    pub fn on_metric_added(&self, callback: impl Fn(&MetricKey) + Send + Sync + 'static) {
+        self.inner
+            .added_hooks
+            .write()
+            .unwrap()
+            .push(Arc::new(callback));
+        self.inner.has_added_hooks.store(true, Ordering::Release);
+    }
+
+    /// Run the [`Self::on_metric_added`] callbacks if the metric was `added`
+    /// by the caller, after the registry has released its lock, so they can
+    /// add more.
+    fn notify_if_added(&self, added: bool, key: &Key, kind: MetricKind) {
+        if !added || !self.inner.has_added_hooks.load(Ordering::Acquire) {
+            return;
+        }
+        let hooks = self.inner.added_hooks.read().unwrap().clone();
+        let key = MetricKey::new(key.clone(), kind);
+        for hook in hooks {
+            hook(&key);
+        }
+    }
+
+    /// The description of a metric, or else the description of its
+    /// namespace from [`Self::describe_namespace`].
     pub fn get_description(&self, key: &DescriptionKey) -> Option<MetricDescription> {
-        self.inner.descriptions.read().unwrap().get(key).cloned()
+        let descriptions = self.inner.descriptions.read().unwrap();
+        let namespaces = self.inner.namespace_descriptions.read().unwrap();
+        lookup_description(&descriptions, &namespaces, key)
+    }
+
+    /// Describe every metric named under `namespace`, e.g. `net::bytes` for
+    /// `net::bytes::sent` and `net::bytes::received`, regardless of kind.
+    ///
+    /// This only applies to metrics that aren't described themselves, which
+    /// is useful for third-party crates that don't call the `describe_*`
+    /// macros. If several namespaces match, the longest one is used. A
+    /// trailing `::*` is ignored.
+    pub fn describe_namespace(
+        &self,
+        namespace: impl Into<String>,
+        unit: Option<Unit>,
+        text: impl Into<SharedString>,
+    ) {
+        let mut namespace = namespace.into();
+        if let Some(trimmed) = namespace.strip_suffix("::*") {
+            namespace.truncate(trimmed.len());
+        }
+        let description = MetricDescription {
+            unit,
+            text: text.into(),
+        };
+        let mut namespaces = self.inner.namespace_descriptions.write().unwrap();
+        namespaces.retain(|(n, _)| *n != namespace);
+        namespaces.push((namespace, description));
+        namespaces.sort_by_key(|(n, _)| std::cmp::Reverse(n.len()));
+    }
+
+    /// The [`Metadata::target`] that a metric was first registered with,
+    /// usually the path of the module that wrote it.
+    pub fn get_target(&self, key: &DescriptionKey) -> Option<String> {
+        self.inner.targets.read().unwrap().get(key).cloned()
+    }
+
+    /// Record the target of a metric on its first registration.
+    fn record_target(
+        &self,
+        registered: bool,
+        key: &Key,
+        kind: MetricKind,
+        metadata: &Metadata<'_>,
+    ) {
+        if !registered {
+            return;
+        }
+        let key = DescriptionKey {
+            name: key.name_shared(),
+            kind,
+        };
+        let mut targets = self.inner.targets.write().unwrap();
+        targets
+            .entry(key)
+            .or_insert_with(|| metadata.target().to_owned());
+    }
+
+    /// A new handle writing to `counter`, sharded or coalesced if chosen.
+    fn counter_handle(&self, counter: &Stored<AtomicU64, Counter>) -> Counter {
+        let target = counter.handle();
+        if self.counter_sharding() {
+            let sharded = &self.inner.sharded_counters;
+            let stripes = counter.stripes(|| sharded.add_stripes(target));
+            Counter::from_arc(Arc::new(ShardedCounter::new(stripes.clone())))
+        } else if self.inner.coalesce_counters.load(Ordering::Relaxed) {
+            Counter::from_arc(Arc::new(CoalescedCounter {
+                writes: self.inner.coalesced_writes.clone(),
+                target,
+            }))
+        } else {
+            target.into()
+        }
+    }
+
+    /// A new handle writing to `target`, coalesced if chosen.
+    fn gauge_handle(&self, target: Target) -> Gauge {
+        if self.inner.coalesce_gauges.load(Ordering::Relaxed) {
+            Gauge::from_arc(Arc::new(CoalescedGauge {
+                writes: self.inner.coalesced_writes.clone(),
+                target,
+            }))
+        } else {
+            target.into()
+        }
+    }
+
+    /// Visit every counter in the registry.
+    ///
+    /// Do not create metrics in this same registry from `visit`, since the
+    /// registry is locked for reading.
+    pub fn visit_counters(&self, mut visit: impl FnMut(&Key, &Arc<AtomicU64>)) {
+        (self.inner.registry).visit_counters(|key, c| visit(key, c.atomic()));
+    }
+
+    /// Visit every gauge in the registry.
+    ///
+    /// Do not create metrics in this same registry from `visit`, since the
+    /// registry is locked for reading.
+    pub fn visit_gauges(&self, mut visit: impl FnMut(&Key, &Arc<AtomicU64>)) {
+        (self.inner.registry).visit_gauges(|key, g| visit(key, g.atomic()));
+    }
+
+    /// Visit every histogram in the registry.
+    ///
+    /// Do not create metrics in this same registry from `visit`, since the
+    /// registry is locked for reading.
+    pub fn visit_histograms(&self, mut visit: impl FnMut(&Key, &Arc<AtomicBucket<f64>>)) {
+        (self.inner.registry).visit_histograms(|key, h| visit(key, h.atomic()));
+    }
+
+    /// Returns all metric descriptions, including those of metrics that have
+    /// not been created yet, but not those from [`Self::describe_namespace`].
+    pub fn all_descriptions(&self) -> Vec<(DescriptionKey, MetricDescription)> {
+        let descriptions = self.inner.descriptions.read().unwrap();
+        descriptions
+            .iter()
+            .map(|(k, d)| (k.clone(), d.clone()))
+            .collect()
+    }
+
+    /// Count the number of series (label combinations) of each metric name.
+    pub fn series_counts_by_name(&self) -> HashMap<DescriptionKey, usize> {
+        let mut counts = HashMap::default();
+        let mut count = |kind, key: &Key| {
+            let desc_key = DescriptionKey {
+                name: KeyName::from(key.name().to_owned()),
+                kind,
+            };
+            *counts.entry(desc_key).or_default() += 1;
+        };
+        let reg = &self.inner.registry;
+        reg.visit_counters(|key, _| count(MetricKind::Counter, key));
+        reg.visit_gauges(|key, _| count(MetricKind::Gauge, key));
+        reg.visit_histograms(|key, _| count(MetricKind::Histogram, key));
+        counts
+    }
+
+    /// Returns `true` if `self` and `other` are handles to the same registry.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
     }
 
-    /// Search the registry for metrics whose name matches `input`.
+    /// Search the registry for metrics whose name fuzzy-matches `input`.
     ///
     /// Empty `input` will match everything.
     ///
-    /// Results are not returned in any particular order.
+    /// Results are sorted by descending match score, and alphabetically by
+    /// name among equal scores.
     pub fn fuzzy_search_by_name(&self, input: &str) -> Vec<SearchResult> {
+        self.search_by_name(input, &FuzzyNameMatcher::default())
+    }
+
+    /// Search the registry for metrics whose name is scored by `matcher`.
+    ///
+    /// `input` is split into space-separated terms, and names must match
+    /// every term, scoring the sum of their scores. Names containing a term
+    /// prefixed with `-`, ignoring case, are excluded, so `"time -gpu"`
+    /// matches `"render::time"` but not `"gpu::time"`.
+    ///
+    /// Results are sorted like [`Self::fuzzy_search_by_name`].
+    pub fn search_by_name(&self, input: &str, matcher: &dyn NameMatcher) -> Vec<SearchResult> {
+        let terms = SearchTerms::parse(input);
+        self.search_filtered(|name| terms.score(name, name, matcher))
+    }
+
+    /// Like [`Self::search_by_name`], only searching metrics in `namespace`,
+    /// e.g. `"render"` for `"render::passes"`.
+    ///
+    /// Names are scored without the namespace prefix, so matches inside the
+    /// prefix don't count. Excluded terms are searched in the whole name.
+    pub fn search_in_namespace(
+        &self,
+        namespace: &str,
+        input: &str,
+        matcher: &dyn NameMatcher,
+    ) -> Vec<SearchResult> {
+        let terms = SearchTerms::parse(input);
+        self.search_filtered(|name| {
+            let rest = name.strip_prefix(namespace)?.strip_prefix("::")?;
+            terms.score(name, rest, matcher)
+        })
+    }
+
+    fn search_filtered(&self, score: impl Fn(&str) -> Option<i64>) -> Vec<SearchResult> {
         let mut results = Vec::new();
-        let matcher = SkimMatcherV2::default();
         let reg = &self.inner.registry;
         let descriptions = self.inner.descriptions.read().unwrap();
-        reg.visit_counters(|key, _| {
-            if matcher.fuzzy_match(key.name(), input).is_some() {
-                results.push(make_search_result(MetricKind::Counter, key, &descriptions));
-            }
-        });
-        reg.visit_gauges(|key, _| {
-            if matcher.fuzzy_match(key.name(), input).is_some() {
-                results.push(make_search_result(MetricKind::Gauge, key, &descriptions));
+        let namespaces = self.inner.namespace_descriptions.read().unwrap();
+        let mut visit = |kind, key: &Key| {
+            if let Some(score) = score(key.name()) {
+                let mut result = make_search_result(kind, key, &descriptions, &namespaces);
+                result.score = Some(score);
+                results.push(result);
             }
+        };
+        reg.visit_counters(|key, _| visit(MetricKind::Counter, key));
+        reg.visit_gauges(|key, _| visit(MetricKind::Gauge, key));
+        reg.visit_histograms(|key, _| visit(MetricKind::Histogram, key));
+        results.sort_by(|r1, r2| {
+            r2.score
+                .cmp(&r1.score)
+                .then_with(|| r1.key.key.name().cmp(r2.key.key.name()))
         });
-        reg.visit_histograms(|key, _| {
-            if matcher.fuzzy_match(key.name(), input).is_some() {
-                results.push(make_search_result(
-                    MetricKind::Histogram,
-                    key,
-                    &descriptions,
-                ));
+        results
+    }
+
+    /// Every series of the metric `name`, i.e. with any labels.
+    pub fn find_by_name(&self, name: &str, kind: MetricKind) -> Vec<SearchResult> {
+        let mut results = Vec::new();
+        let descriptions = self.inner.descriptions.read().unwrap();
+        let namespaces = self.inner.namespace_descriptions.read().unwrap();
+        let mut visit = |key: &Key| {
+            if key.name() == name {
+                results.push(make_search_result(kind, key, &descriptions, &namespaces));
             }
-        });
+        };
+        let reg = &self.inner.registry;
+        match kind {
+            MetricKind::Counter => reg.visit_counters(|key, _| visit(key)),
+            MetricKind::Gauge => reg.visit_gauges(|key, _| visit(key)),
+            MetricKind::Histogram => reg.visit_histograms(|key, _| visit(key)),
+        }
         results
     }
 
@@ -101,23 +432,39 @@ impl MetricsRegistry {
         let mut results = Vec::new();
         let reg = &self.inner.registry;
         let descriptions = self.inner.descriptions.read().unwrap();
+        let namespaces = self.inner.namespace_descriptions.read().unwrap();
         reg.visit_counters(|key, _| {
-            results.push(make_search_result(MetricKind::Counter, key, &descriptions));
+            results.push(make_search_result(
+                MetricKind::Counter,
+                key,
+                &descriptions,
+                &namespaces,
+            ));
         });
         reg.visit_gauges(|key, _| {
-            results.push(make_search_result(MetricKind::Gauge, key, &descriptions));
+            results.push(make_search_result(
+                MetricKind::Gauge,
+                key,
+                &descriptions,
+                &namespaces,
+            ));
         });
         reg.visit_histograms(|key, _| {
             results.push(make_search_result(
                 MetricKind::Histogram,
                 key,
                 &descriptions,
+                &namespaces,
             ));
         });
         results
     }
 
-    fn add_description_if_missing(&self, key: DescriptionKey, description: MetricDescription) {
+    pub(crate) fn add_description_if_missing(
+        &self,
+        key: DescriptionKey,
+        description: MetricDescription,
+    ) {
         let mut descriptions = self.inner.descriptions.write().unwrap();
         descriptions.entry(key).or_insert(description);
     }
@@ -125,12 +472,289 @@ impl MetricsRegistry {
     /// Clear all atomic buckets used for storing histogram data.
     pub fn clear_atomic_buckets(&self) {
         self.inner.registry.visit_histograms(|_, h| {
-            h.clear();
+            h.atomic().clear();
         });
+        self.inner.clear_generation.fetch_add(1, Ordering::Release);
+    }
+
+    /// Clear all atomic buckets like [`Self::clear_atomic_buckets`], passing
+    /// their samples to `drain` first.
+    ///
+    /// `drain` may be called more than once per histogram.
+    pub fn drain_atomic_buckets(&self, mut drain: impl FnMut(&Key, &[f64])) {
+        self.inner.registry.visit_histograms(|key, h| {
+            h.atomic().clear_with(|values| drain(key, values));
+        });
+        self.inner.clear_generation.fetch_add(1, Ordering::Release);
+    }
+
+    /// The number of times [`Self::clear_atomic_buckets`] or
+    /// [`Self::drain_atomic_buckets`] has been called.
+    pub fn clear_generation(&self) -> u64 {
+        self.inner.clear_generation.load(Ordering::Acquire)
+    }
+
+    /// Choose which handles registered from now on buffer their writes until
+    /// [`Self::flush_coalesced_writes`].
+    ///
+    /// The [`RegistryPlugin`](crate::RegistryPlugin) flushes once per frame,
+    /// before any dashboard reads the registry.
+    pub fn set_write_coalescing(&self, coalescing: WriteCoalescing) {
+        let inner = &self.inner;
+        let WriteCoalescing { counters, gauges } = coalescing;
+        inner.coalesce_counters.store(counters, Ordering::Relaxed);
+        inner.coalesce_gauges.store(gauges, Ordering::Relaxed);
+    }
+
+    pub fn write_coalescing(&self) -> WriteCoalescing {
+        WriteCoalescing {
+            counters: self.inner.coalesce_counters.load(Ordering::Relaxed),
+            gauges: self.inner.coalesce_gauges.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Choose whether counter handles registered from now on are sharded:
+    /// each thread increments its own stripe of the counter, on its own cache
+    /// line, and the stripes are added to the registry by
+    /// [`Self::flush_coalesced_writes`].
+    ///
+    /// This avoids cache-line contention on counters incremented from many
+    /// threads at once, e.g. in the inner loops of parallel systems, without
+    /// the per-write lookup of [`WriteCoalescing`]. Sharding takes precedence
+    /// over coalescing counters. Hot metrics can be found with
+    /// [`Self::write_stats`].
+    pub fn set_counter_sharding(&self, enabled: bool) {
+        self.inner.shard_counters.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn counter_sharding(&self) -> bool {
+        self.inner.shard_counters.load(Ordering::Relaxed)
+    }
+
+    /// Write all values buffered by coalesced handles, and the stripes of
+    /// sharded counters, on any thread, to the registry.
+    pub fn flush_coalesced_writes(&self) {
+        self.inner.coalesced_writes.flush();
+        self.inner.sharded_counters.aggregate();
+    }
+
+    /// The number of metrics with writes buffered by coalesced handles or
+    /// sharded counters, on any thread, that haven't been flushed yet.
+    ///
+    /// Dropping the last clone of a registry with pending writes logs a
+    /// warning, since those writes are lost.
+    pub fn pending_coalesced_writes(&self) -> usize {
+        self.inner.coalesced_writes.pending() + self.inner.sharded_counters.pending()
     }
 
-    pub(crate) fn clear_atomic_buckets_system(registry: Res<Self>) {
-        registry.clear_atomic_buckets();
+    pub(crate) fn flush_coalesced_writes_system(registry: Res<Self>) {
+        registry.flush_coalesced_writes();
+    }
+
+    /// Choose whether metrics registered from now on count their writes, for
+    /// [`Self::sample_write_counts`].
+    ///
+    /// A counting metric builds its handle once, on its first registration,
+    /// so it keeps the [`WriteCoalescing`] and sharding chosen then.
+    pub fn set_write_counting(&self, enabled: bool) {
+        self.inner.count_writes.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn write_counting(&self) -> bool {
+        self.inner.count_writes.load(Ordering::Relaxed)
+    }
+
+    /// Update the [`WriteStats`] of every metric with a counting handle, and
+    /// choose whether to sample the next frame.
+    ///
+    /// This should run once per frame, after all metrics are written. Only
+    /// every `interval`th frame is sampled, and handles don't count writes
+    /// in other frames, which the [`RegistryPlugin`](crate::RegistryPlugin)
+    /// does when built
+    /// [`with_write_counting`](crate::RegistryPlugin::with_write_counting).
+    pub fn sample_write_counts(&self, interval: u32) {
+        self.inner.write_counts.sample(interval);
+    }
+
+    /// How often the metric identified by `key` was written, or `None` if it
+    /// has no counting handles or no frame has been sampled yet.
+    pub fn write_stats(&self, key: &MetricKey) -> Option<WriteStats> {
+        self.inner.write_counts.stats(key)
+    }
+
+    /// The [`WriteStats`] of every metric with a counting handle.
+    pub fn all_write_stats(&self) -> Vec<(MetricKey, WriteStats)> {
+        self.inner.write_counts.all_stats()
+    }
+
+    /// Start a new upkeep generation, recording which metrics were written
+    /// since the previous one.
+    ///
+    /// A metric is updated by any write through a handle, even one that
+    /// leaves its value unchanged, like setting a gauge to its current value.
+    /// This should run once per frame after all metrics are written, which
+    /// the [`RegistryPlugin`](crate::RegistryPlugin) does when built
+    /// [`with_recency`](crate::RegistryPlugin::with_recency).
+    pub fn upkeep(&self) {
+        let mut state = self.inner.recency.write().unwrap();
+        state.generation += 1;
+        let generation = state.generation;
+
+        let [counters, gauges, histograms] = &mut state.keys;
+        let reg = &self.inner.registry;
+        reg.visit_counters(|key, c| observe(counters, key, c.generation(), generation));
+        reg.visit_gauges(|key, g| observe(gauges, key, g.generation(), generation));
+        reg.visit_histograms(|key, h| observe(histograms, key, h.generation(), generation));
+    }
+
+    /// The number of times [`Self::upkeep`] has been called.
+    pub fn upkeep_generation(&self) -> u64 {
+        self.inner.recency.read().unwrap().generation
+    }
+
+    /// The [`Self::upkeep_generation`] in which the metric identified by
+    /// `key` was last updated, or `None` if no upkeep has seen it.
+    pub fn last_update_generation(&self, key: &MetricKey) -> Option<u64> {
+        let state = self.inner.recency.read().unwrap();
+        state.keys[kind_index(key.kind)]
+            .get(&key.key)
+            .map(|r| r.last_update)
+    }
+
+    /// The number of upkeep generations since the metric identified by `key`
+    /// was last updated, so `0` if it was updated in the latest one.
+    pub fn generations_since_update(&self, key: &MetricKey) -> Option<u64> {
+        let state = self.inner.recency.read().unwrap();
+        state.keys[kind_index(key.kind)]
+            .get(&key.key)
+            .map(|r| state.generation - r.last_update)
+    }
+
+    /// Sort `results` by recency, so recently updated metrics come first.
+    ///
+    /// The sort is stable, so results updated in the same generation keep
+    /// their order, e.g. by search score. Metrics never seen by
+    /// [`Self::upkeep`] come last.
+    pub fn sort_by_recency(&self, results: &mut [SearchResult]) {
+        results.sort_by_cached_key(|r| self.generations_since_update(&r.key).unwrap_or(u64::MAX));
+    }
+
+    /// Delete every metric that hasn't been updated for more than
+    /// `max_idle` upkeep generations, returning their keys.
+    ///
+    /// Handles to deleted metrics keep working, but their writes are no
+    /// longer visible in this registry. Calling the `metrics` macros again
+    /// creates a new metric with the same key, so readers holding the old
+    /// atomics should get them again once the registry
+    /// [contains](Self::contains) it.
+    pub fn remove_idle(&self, max_idle: u64) -> Vec<MetricKey> {
+        let mut state = self.inner.recency.write().unwrap();
+        let generation = state.generation;
+        let reg = &self.inner.registry;
+        let mut removed = Vec::new();
+        for (keys, kind) in state.keys.iter_mut().zip(KINDS) {
+            keys.retain(|key, recency| {
+                if generation - recency.last_update <= max_idle {
+                    return true;
+                }
+                // Don't lose writes since the last upkeep.
+                let written = match kind {
+                    MetricKind::Counter => reg.get_counter(key).map(|c| c.generation()),
+                    MetricKind::Gauge => reg.get_gauge(key).map(|g| g.generation()),
+                    MetricKind::Histogram => reg.get_histogram(key).map(|h| h.generation()),
+                }
+                .is_some_and(|g| g != recency.observed);
+                if written {
+                    return true;
+                }
+                match kind {
+                    MetricKind::Counter => reg.delete_counter(key),
+                    MetricKind::Gauge => reg.delete_gauge(key),
+                    MetricKind::Histogram => reg.delete_histogram(key),
+                };
+                let key = MetricKey::new(key.clone(), kind);
+                self.inner.write_counts.remove(&key);
+                removed.push(key);
+                false
+            });
+        }
+        removed
+    }
+}
+
+fn observe(keys: &mut HashMap<Key, Recency>, key: &Key, observed: Generation, generation: u64) {
+    // Avoid cloning keys we've already seen.
+    if let Some(recency) = keys.get_mut(key) {
+        if recency.observed != observed {
+            recency.observed = observed;
+            recency.last_update = generation;
+        }
+    } else {
+        keys.insert(
+            key.clone(),
+            Recency {
+                observed,
+                last_update: generation,
+            },
+        );
+    }
+}
+
+/// Tracks which samples of an atomic bucket have already been read.
+///
+/// This lets a consumer read each histogram sample once, even when buckets
+/// are not cleared every frame (see
+/// [`ClearBucketsPolicy`](crate::ClearBucketsPolicy)).
+///
+/// The cursor assumes that samples are only removed from the bucket by
+/// clearing it entirely. If samples are recorded concurrently with a read,
+/// a few of them may be skipped or read twice.
+pub struct BucketCursor {
+    registry: MetricsRegistry,
+    generation: u64,
+    n_seen: usize,
+}
+
+impl BucketCursor {
+    pub fn new(registry: &MetricsRegistry) -> Self {
+        Self {
+            registry: registry.clone(),
+            generation: registry.clear_generation(),
+            n_seen: 0,
+        }
+    }
+
+    /// Call `f` on every sample in `bucket` that has not been read by this
+    /// cursor, in the order they were recorded.
+    pub fn read_new(&mut self, bucket: &AtomicBucket<f64>, mut f: impl FnMut(f64)) {
+        let mut n_total = 0;
+        bucket.data_with(|block| n_total += block.len());
+
+        let generation = self.registry.clear_generation();
+        if generation != self.generation || n_total < self.n_seen {
+            // The bucket was cleared since the last read.
+            self.generation = generation;
+            self.n_seen = 0;
+        }
+
+        // Blocks are visited from newest to oldest, so the new samples are
+        // found in the first blocks.
+        let mut n_remaining = n_total - self.n_seen;
+        let mut new_chunks = Vec::new();
+        bucket.data_with(|block| {
+            if n_remaining == 0 {
+                return;
+            }
+            let take = n_remaining.min(block.len());
+            new_chunks.push(block[block.len() - take..].to_vec());
+            n_remaining -= take;
+        });
+        for chunk in new_chunks.iter().rev() {
+            for &value in chunk {
+                f(value);
+            }
+        }
+        self.n_seen = n_total;
     }
 }
 
@@ -138,11 +762,144 @@ fn make_search_result(
     kind: MetricKind,
     key: &Key,
     descriptions: &HashMap<DescriptionKey, MetricDescription>,
+    namespaces: &[(String, MetricDescription)],
 ) -> SearchResult {
     let key = MetricKey::new(key.clone(), kind);
     let desc_key = DescriptionKey::from(&key);
-    let description = descriptions.get(&desc_key).cloned();
-    SearchResult { key, description }
+    let description = lookup_description(descriptions, namespaces, &desc_key);
+    SearchResult {
+        key,
+        description,
+        score: None,
+    }
+}
+
+fn lookup_description(
+    descriptions: &HashMap<DescriptionKey, MetricDescription>,
+    namespaces: &[(String, MetricDescription)],
+    key: &DescriptionKey,
+) -> Option<MetricDescription> {
+    if let Some(description) = descriptions.get(key) {
+        return Some(description.clone());
+    }
+    let name: &str = key.name.as_str();
+    namespaces
+        .iter()
+        .find(|(namespace, _)| {
+            name.strip_prefix(namespace.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+        })
+        .map(|(_, description)| description.clone())
+}
+
+/// Scores how well a metric name matches a search input, for
+/// [`MetricsRegistry::search_by_name`].
+pub trait NameMatcher: Send + Sync {
+    /// A higher score is a better match, and `None` is no match.
+    fn score(&self, name: &str, input: &str) -> Option<i64>;
+}
+
+/// The terms of a search input, see [`MetricsRegistry::search_by_name`].
+struct SearchTerms<'a> {
+    include: Vec<&'a str>,
+    /// Lowercase.
+    exclude: Vec<String>,
+}
+
+impl<'a> SearchTerms<'a> {
+    fn parse(input: &'a str) -> Self {
+        let mut terms = Self {
+            include: Vec::new(),
+            exclude: Vec::new(),
+        };
+        for term in input.split_whitespace() {
+            match term.strip_prefix('-') {
+                Some(excluded) if !excluded.is_empty() => {
+                    terms.exclude.push(excluded.to_lowercase())
+                }
+                _ => terms.include.push(term),
+            }
+        }
+        terms
+    }
+
+    /// Score `scored`, the part of `name` that included terms are matched in.
+    fn score(&self, name: &str, scored: &str, matcher: &dyn NameMatcher) -> Option<i64> {
+        if !self.exclude.is_empty() {
+            let name = name.to_lowercase();
+            if self.exclude.iter().any(|term| name.contains(term.as_str())) {
+                return None;
+            }
+        }
+        if self.include.is_empty() {
+            return matcher.score(scored, "");
+        }
+        self.include
+            .iter()
+            .map(|term| matcher.score(scored, term))
+            .sum()
+    }
+}
+
+/// Matches names that contain the characters of the input in order, not
+/// necessarily adjacent, ignoring case unless the input has uppercase letters.
+///
+/// Matches in the last `::`-separated component of a name score higher than
+/// matches scattered along its namespace, so `"time"` ranks `"render::time"`
+/// above `"timeline::events::count"`.
+pub struct FuzzyNameMatcher {
+    matcher: SkimMatcherV2,
+    last_component_weight: i64,
+}
+
+impl Default for FuzzyNameMatcher {
+    fn default() -> Self {
+        Self {
+            matcher: default(),
+            last_component_weight: 1,
+        }
+    }
+}
+
+impl FuzzyNameMatcher {
+    /// Add `weight` times the score of matching only the last component of a
+    /// name to the score of matching the whole name. Defaults to 1, and 0
+    /// scores whole names only.
+    pub fn with_last_component_weight(mut self, weight: i64) -> Self {
+        self.last_component_weight = weight;
+        self
+    }
+
+    pub fn last_component_weight(&self) -> i64 {
+        self.last_component_weight
+    }
+}
+
+impl NameMatcher for FuzzyNameMatcher {
+    fn score(&self, name: &str, input: &str) -> Option<i64> {
+        let score = self.matcher.fuzzy_match(name, input)?;
+        let last_component = name.rsplit("::").next().unwrap_or(name);
+        let last_score = self.matcher.fuzzy_match(last_component, input);
+        Some(score + self.last_component_weight * last_score.unwrap_or(0))
+    }
+}
+
+/// Matches names that start with the input, preferring the shortest names.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PrefixNameMatcher {
+    pub case_sensitive: bool,
+}
+
+impl NameMatcher for PrefixNameMatcher {
+    fn score(&self, name: &str, input: &str) -> Option<i64> {
+        let is_match = if self.case_sensitive {
+            name.starts_with(input)
+        } else {
+            name.get(..input.len())
+                .is_some_and(|prefix| prefix.eq_ignore_ascii_case(input))
+        };
+        is_match.then(|| -((name.len() - input.len()) as i64))
+    }
 }
 
 impl Default for MetricsRegistry {
@@ -176,6 +933,26 @@ impl MetricKey {
     }
 }
 
+/// A label as `k=v`.
+pub fn label_text(label: &Label) -> String {
+    format!("{}={}", label.key(), label.value())
+}
+
+/// The labels of `key` as `k=v`, joined by `separator`.
+pub fn labels_text(key: &Key, separator: &str) -> String {
+    let labels: Vec<_> = key.labels().map(label_text).collect();
+    labels.join(separator)
+}
+
+/// The metric's name followed by its labels, like `name{k=v}`.
+pub fn key_text(key: &Key) -> String {
+    if key.labels().next().is_none() {
+        key.name().to_owned()
+    } else {
+        format!("{}{{{}}}", key.name(), labels_text(key, ","))
+    }
+}
+
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct DescriptionKey {
     pub name: KeyName,
@@ -195,6 +972,9 @@ impl From<&MetricKey> for DescriptionKey {
 pub struct SearchResult {
     pub key: MetricKey,
     pub description: Option<MetricDescription>,
+    /// The [`NameMatcher::score`] of a search, or `None` if the result didn't
+    /// come from a search.
+    pub score: Option<i64>,
 }
 
 impl SearchResult {
@@ -203,6 +983,15 @@ impl SearchResult {
     /// `display_path` will override the key's name, which is used for removing
     /// layers of namespacing.
     pub fn detailed_text(&self, display_path: Option<&str>) -> LayoutJob {
+        self.localized_detailed_text(display_path, &DashboardLocale::default())
+    }
+
+    /// Like [`Self::detailed_text`], with unit names from `locale`.
+    pub fn localized_detailed_text(
+        &self,
+        display_path: Option<&str>,
+        locale: &DashboardLocale,
+    ) -> LayoutJob {
         let mut job = LayoutJob::default();
         job.append(
             &self.key.title(display_path, 0),
@@ -215,7 +1004,7 @@ impl SearchResult {
         );
         if let Some(unit) = self.description.as_ref().and_then(|d| d.unit) {
             job.append(
-                &format!(" [{}]", unit_str(unit)),
+                &format!(" [{}]", locale.unit_str(unit)),
                 0.0,
                 TextFormat {
                     color: Color32::LIGHT_BLUE,
@@ -226,7 +1015,7 @@ impl SearchResult {
         for label in self.key.key.labels() {
             job.append("\n", 0.0, default());
             job.append(
-                &format!("{}={}", label.key(), label.value()),
+                &label_text(label),
                 0.0,
                 TextFormat {
                     color: Color32::YELLOW,
@@ -252,6 +1041,9 @@ impl SearchResult {
 
 impl Recorder for MetricsRegistry {
     fn describe_counter(&self, key_name: KeyName, unit: Option<Unit>, description: SharedString) {
+        if let Some(downstream) = &self.inner.downstream {
+            downstream.describe_counter(key_name.clone(), unit, description.clone());
+        }
         self.add_description_if_missing(
             DescriptionKey {
                 name: key_name,
@@ -265,6 +1057,9 @@ impl Recorder for MetricsRegistry {
     }
 
     fn describe_gauge(&self, key_name: KeyName, unit: Option<Unit>, description: SharedString) {
+        if let Some(downstream) = &self.inner.downstream {
+            downstream.describe_gauge(key_name.clone(), unit, description.clone());
+        }
         self.add_description_if_missing(
             DescriptionKey {
                 name: key_name,
@@ -278,6 +1073,9 @@ impl Recorder for MetricsRegistry {
     }
 
     fn describe_histogram(&self, key_name: KeyName, unit: Option<Unit>, description: SharedString) {
+        if let Some(downstream) = &self.inner.downstream {
+            downstream.describe_histogram(key_name.clone(), unit, description.clone());
+        }
         self.add_description_if_missing(
             DescriptionKey {
                 name: key_name,
@@ -290,21 +1088,70 @@ impl Recorder for MetricsRegistry {
         );
     }
 
-    fn register_counter(&self, key: &Key, _metadata: &Metadata<'_>) -> Counter {
-        self.inner
-            .registry
-            .get_or_create_counter(key, |c| c.clone().into())
+    fn register_counter(&self, key: &Key, metadata: &Metadata<'_>) -> Counter {
+        let (counter, added, registered) = self.inner.registry.get_or_create_counter(key, |c| {
+            let new_handle = || self.counter_handle(c);
+            let counted = c.counted_handle(|| {
+                self.write_counting().then(|| {
+                    let key = MetricKey::new(key.clone(), MetricKind::Counter);
+                    self.inner.write_counts.counter(key, new_handle())
+                })
+            });
+            let counter = counted.cloned().unwrap_or_else(new_handle);
+            (counter, c.claim_added(), c.claim_registered())
+        });
+        self.record_target(registered, key, MetricKind::Counter, metadata);
+        self.notify_if_added(added, key, MetricKind::Counter);
+        match &self.inner.downstream {
+            Some(downstream) => {
+                fan_out_counter(vec![counter, downstream.register_counter(key, metadata)])
+            }
+            None => counter,
+        }
     }
 
-    fn register_gauge(&self, key: &Key, _metadata: &Metadata<'_>) -> Gauge {
-        self.inner
-            .registry
-            .get_or_create_gauge(key, |c| c.clone().into())
+    fn register_gauge(&self, key: &Key, metadata: &Metadata<'_>) -> Gauge {
+        let (gauge, added, registered) = self.inner.registry.get_or_create_gauge(key, |g| {
+            let new_handle = || self.gauge_handle(g.handle());
+            let counted = g.counted_handle(|| {
+                self.write_counting().then(|| {
+                    let key = MetricKey::new(key.clone(), MetricKind::Gauge);
+                    self.inner.write_counts.gauge(key, new_handle())
+                })
+            });
+            let gauge = counted.cloned().unwrap_or_else(new_handle);
+            (gauge, g.claim_added(), g.claim_registered())
+        });
+        self.record_target(registered, key, MetricKind::Gauge, metadata);
+        self.notify_if_added(added, key, MetricKind::Gauge);
+        match &self.inner.downstream {
+            Some(downstream) => {
+                fan_out_gauge(vec![gauge, downstream.register_gauge(key, metadata)])
+            }
+            None => gauge,
+        }
     }
 
-    fn register_histogram(&self, key: &Key, _metadata: &Metadata<'_>) -> Histogram {
-        self.inner
-            .registry
-            .get_or_create_histogram(key, |c| c.clone().into())
+    fn register_histogram(&self, key: &Key, metadata: &Metadata<'_>) -> Histogram {
+        let (histogram, added, registered) =
+            self.inner.registry.get_or_create_histogram(key, |h| {
+                let counted = h.counted_handle(|| {
+                    self.write_counting().then(|| {
+                        let key = MetricKey::new(key.clone(), MetricKind::Histogram);
+                        self.inner.write_counts.histogram(key, h.handle().into())
+                    })
+                });
+                let histogram = counted.cloned().unwrap_or_else(|| h.handle().into());
+                (histogram, h.claim_added(), h.claim_registered())
+            });
+        self.record_target(registered, key, MetricKind::Histogram, metadata);
+        self.notify_if_added(added, key, MetricKind::Histogram);
+        match &self.inner.downstream {
+            Some(downstream) => fan_out_histogram(vec![
+                histogram,
+                downstream.register_histogram(key, metadata),
+            ]),
+            None => histogram,
+        }
     }
 }
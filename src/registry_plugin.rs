@@ -1,24 +1,310 @@
-use crate::registry::MetricsRegistry;
-use bevy::prelude::*;
-use metrics::set_global_recorder;
+use crate::{
+    histogram_sketch::HistogramSketches,
+    multi_recorder::MultiRecorder,
+    ordering_diagnostics::OrderingDiagnostics,
+    registry::{MetricKey, MetricsRegistry},
+    DashboardWindow, FrameSnapshot, PreRegistryBuffer, ProduceMetricsSystem, WriteCoalescing,
+};
+use bevy::{ecs::schedule::ScheduleLabel, prelude::*, utils::HashMap};
+use metrics::{set_global_recorder, Recorder};
+use std::sync::{mpsc, Arc, Mutex};
 
 /// Installs and garbage collects a [`MetricsRegistry`].
 ///
-/// This plugin runs a garbage collector every frame in the
-/// [`ClearBucketsSystem`]. Direct consumers of atomic buckets (e.g. plots)
-/// should read the documentation of [`ClearBucketsSystem`].
+/// This plugin runs a garbage collector in the [`ClearBucketsSystem`], by
+/// default every frame (see [`ClearBucketsPolicy`]). Direct consumers of
+/// atomic buckets (e.g. plots) should read the documentation of
+/// [`ClearBucketsSystem`].
 #[derive(Default)]
 pub struct RegistryPlugin {
     registry: Option<MetricsRegistry>,
+    pre_registry_buffer: Option<PreRegistryBuffer>,
+    clear_policy: ClearBucketsPolicy,
+    chained_recorders: MultiRecorder,
+    write_coalescing: WriteCoalescing,
+    sharded_counters: bool,
+    write_count_interval: Option<u32>,
+    registration_events: bool,
+    recency: bool,
+    max_idle_generations: Option<u64>,
+    final_flush: bool,
+    ordering_diagnostics: bool,
 }
 
+/// Sent in the [`First`] schedule for every metric created in the
+/// [`MetricsRegistry`] since the previous frame.
+///
+/// Enable these with [`RegistryPlugin::with_registration_events`].
+#[derive(Clone, Debug, Event)]
+pub struct MetricRegistered {
+    pub key: MetricKey,
+}
+
+/// Sent in the [`Last`] schedule for every metric deleted from the
+/// [`MetricsRegistry`] because it stopped updating.
+///
+/// Enable expiry with [`RegistryPlugin::with_metric_expiry`].
+#[derive(Clone, Debug, Event)]
+pub struct MetricExpired {
+    pub key: MetricKey,
+}
+
+/// Sent in the [`Last`] schedule, before the [`ClearBucketsSystem`], when the
+/// app exits or the [`MetricsRegistry`] resource is replaced.
+///
+/// Consumers that only report periodically, like the
+/// [`TerminalMetricsPlugin`](crate::terminal::TerminalMetricsPlugin), report
+/// `registry` one last time, so the end of the session isn't lost.
+///
+/// Enable these with [`RegistryPlugin::with_final_flush`].
+#[derive(Clone, Event)]
+pub struct FinalFlush {
+    /// The registry that is going away. Its coalesced writes are already
+    /// flushed.
+    pub registry: MetricsRegistry,
+    pub reason: FinalFlushReason,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FinalFlushReason {
+    AppExit,
+    /// Another registry was inserted as the [`MetricsRegistry`] resource.
+    Replaced,
+}
+
+/// The [`SystemSet`] sending [`FinalFlush`] events.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, SystemSet)]
+pub struct FinalFlushSystem;
+
 /// The [`SystemSet`] from which atomic buckets are cleared.
 ///
 /// Histogram consumer systems should run in the [`Last`] schedule **before**
 /// this set to avoid missing samples.
+///
+/// Unless the [`ClearBucketsPolicy`] is [`EveryFrame`](ClearBucketsPolicy::EveryFrame),
+/// consumers will see the same samples on multiple frames, and they should use
+/// a [`BucketCursor`](crate::registry::BucketCursor) to only read new samples.
 #[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, SystemSet)]
 pub struct ClearBucketsSystem;
 
+/// Determines when the [`ClearBucketsSystem`] clears histogram buckets.
+///
+/// The active policy is available as a resource.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Resource)]
+pub enum ClearBucketsPolicy {
+    /// Clear all buckets every frame.
+    #[default]
+    EveryFrame,
+    /// Clear all buckets once every `n` frames. `0` is treated like `1`.
+    EveryNFrames(u32),
+    /// Never clear buckets, so they grow without bound and every
+    /// [`BucketCursor`](crate::registry::BucketCursor) read walks all of their
+    /// samples. This is only meant for short sessions that keep every sample,
+    /// e.g. tests.
+    ///
+    /// Consumers must not drain buckets themselves, e.g. with
+    /// [`AtomicBucket::clear_with`](metrics_util::storage::AtomicBucket::clear_with),
+    /// since the cursors of other consumers would skip or repeat samples. To
+    /// clear buckets once every consumer has read them, use
+    /// [`WhenConsumed`](Self::WhenConsumed).
+    Never,
+    /// Clear all buckets once every consumer registered in
+    /// [`HistogramConsumers`] has marked them as read.
+    WhenConsumed,
+}
+
+/// Whether histogram samples are kept in their buckets, as determined by the
+/// [`ClearBucketsPolicy`], or summarized in [`HistogramSketches`].
+///
+/// The active retention is available as a resource. The
+/// [`DashboardPlugin`](crate::DashboardPlugin) switches it to
+/// [`Sketch`](Self::Sketch) while in the background (see
+/// [`DashboardActivity`](crate::DashboardActivity)).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Resource)]
+pub enum HistogramRetention {
+    #[default]
+    Buckets,
+    /// Drain the buckets into [`HistogramSketches`] instead of clearing
+    /// them, when the [`ClearBucketsPolicy`] says to clear them. Consumers
+    /// still see every sample first, and samples older than that only take
+    /// the space of their sketch.
+    Sketch,
+}
+
+/// Coordinates multiple histogram consumers under
+/// [`ClearBucketsPolicy::WhenConsumed`].
+///
+/// Each consumer registers once and then calls
+/// [`mark_read`](Self::mark_read) after it has read the buckets. Buckets are
+/// cleared on the first frame that every registered consumer has done so.
+///
+/// This is cheap to clone, so consumers outside of the ECS can keep their own
+/// handle.
+#[derive(Clone, Default, Resource)]
+pub struct HistogramConsumers {
+    inner: Arc<Mutex<ConsumersState>>,
+}
+
+#[derive(Default)]
+struct ConsumersState {
+    next_id: u64,
+    has_read: HashMap<u64, bool>,
+}
+
+/// Identifies a consumer registered in [`HistogramConsumers`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct HistogramConsumerId(u64);
+
+impl HistogramConsumers {
+    pub fn register(&self) -> HistogramConsumerId {
+        let mut state = self.inner.lock().unwrap();
+        let id = state.next_id;
+        state.next_id += 1;
+        state.has_read.insert(id, false);
+        HistogramConsumerId(id)
+    }
+
+    pub fn unregister(&self, id: HistogramConsumerId) {
+        self.inner.lock().unwrap().has_read.remove(&id.0);
+    }
+
+    /// Indicate that consumer `id` has read all buckets since they were last
+    /// cleared.
+    pub fn mark_read(&self, id: HistogramConsumerId) {
+        if let Some(has_read) = self.inner.lock().unwrap().has_read.get_mut(&id.0) {
+            *has_read = true;
+        }
+    }
+
+    /// Returns `true` and resets all consumers if they have all read the
+    /// buckets.
+    fn take_all_read(&self) -> bool {
+        let mut state = self.inner.lock().unwrap();
+        if state.has_read.values().all(|&r| r) {
+            state.has_read.values_mut().for_each(|r| *r = false);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// [`App`] extension methods for plugins that read histogram buckets.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_metrics_dashboard::{registry::MetricsRegistry, HistogramConsumerAppExt};
+/// fn read_buckets(registry: Res<MetricsRegistry>) {
+///     registry.visit_histograms(|key, bucket| bucket.data_with(|samples| {}));
+/// }
+///
+/// App::new().add_histogram_consumer(read_buckets);
+/// ```
+pub trait HistogramConsumerAppExt {
+    /// Add systems to [`Last`] that read histogram buckets after all metrics
+    /// are written, and before the buckets are cleared.
+    ///
+    /// The systems are registered in [`HistogramConsumers`], and mark the
+    /// buckets as read once they have all run.
+    fn add_histogram_consumer<M>(&mut self, systems: impl IntoSystemConfigs<M>) -> &mut Self;
+
+    /// Like [`Self::add_histogram_consumer`], in another schedule that runs
+    /// before [`Last`] clears the buckets.
+    fn add_histogram_consumer_to<M>(
+        &mut self,
+        schedule: impl ScheduleLabel,
+        systems: impl IntoSystemConfigs<M>,
+    ) -> &mut Self;
+}
+
+impl HistogramConsumerAppExt for App {
+    fn add_histogram_consumer<M>(&mut self, systems: impl IntoSystemConfigs<M>) -> &mut Self {
+        self.add_histogram_consumer_to(Last, systems)
+    }
+
+    fn add_histogram_consumer_to<M>(
+        &mut self,
+        schedule: impl ScheduleLabel,
+        systems: impl IntoSystemConfigs<M>,
+    ) -> &mut Self {
+        let consumer_id = self
+            .init_resource::<HistogramConsumers>()
+            .world()
+            .resource::<HistogramConsumers>()
+            .register();
+        self.add_systems(
+            schedule,
+            (systems, move |consumers: Res<HistogramConsumers>| {
+                consumers.mark_read(consumer_id)
+            })
+                .chain()
+                .after(ProduceMetricsSystem)
+                .after(MetricsRegistry::flush_coalesced_writes_system)
+                .before(ClearBucketsSystem),
+        )
+    }
+}
+
+fn final_flush(
+    registry: Res<MetricsRegistry>,
+    mut exits: EventReader<AppExit>,
+    mut flushes: EventWriter<FinalFlush>,
+    mut previous: Local<Option<MetricsRegistry>>,
+) {
+    let mut flush = |registry: &MetricsRegistry, reason| {
+        registry.flush_coalesced_writes();
+        let (mut counters, mut gauges, mut histograms, mut samples) = (0, 0, 0, 0);
+        registry.visit_counters(|_, _| counters += 1);
+        registry.visit_gauges(|_, _| gauges += 1);
+        registry.visit_histograms(|_, bucket| {
+            histograms += 1;
+            bucket.data_with(|block| samples += block.len());
+        });
+        info!(
+            "Final metrics flush ({reason:?}): {counters} counters, {gauges} gauges, \
+            {histograms} histograms with {samples} buffered samples"
+        );
+        flushes.send(FinalFlush {
+            registry: registry.clone(),
+            reason,
+        });
+    };
+    if let Some(old) = previous.take().filter(|old| !old.ptr_eq(&registry)) {
+        flush(&old, FinalFlushReason::Replaced);
+    }
+    *previous = Some(registry.clone());
+    if exits.read().next().is_some() {
+        flush(&registry, FinalFlushReason::AppExit);
+    }
+}
+
+fn clear_buckets(
+    registry: Res<MetricsRegistry>,
+    policy: Res<ClearBucketsPolicy>,
+    retention: Res<HistogramRetention>,
+    consumers: Res<HistogramConsumers>,
+    mut sketches: ResMut<HistogramSketches>,
+    mut frames_since_clear: Local<u32>,
+) {
+    *frames_since_clear += 1;
+    let clear = match *policy {
+        ClearBucketsPolicy::EveryFrame => true,
+        ClearBucketsPolicy::EveryNFrames(n) => *frames_since_clear >= n,
+        ClearBucketsPolicy::Never => false,
+        ClearBucketsPolicy::WhenConsumed => consumers.take_all_read(),
+    };
+    if !clear {
+        return;
+    }
+    match *retention {
+        HistogramRetention::Buckets => registry.clear_atomic_buckets(),
+        HistogramRetention::Sketch => {
+            registry.drain_atomic_buckets(|key, values| sketches.insert(key, values))
+        }
+    }
+    *frames_since_clear = 0;
+}
+
 impl RegistryPlugin {
     pub fn new() -> Self {
         Self::default()
@@ -32,25 +318,225 @@ impl RegistryPlugin {
     pub fn with_registry(registry: MetricsRegistry) -> Self {
         Self {
             registry: Some(registry),
+            ..default()
+        }
+    }
+
+    /// Replay everything captured by `buffer` into the new registry, instead
+    /// of installing the registry as the global recorder.
+    ///
+    /// `buffer` should already be installed as the global recorder, e.g. with
+    /// [`PreRegistryBuffer::install`].
+    pub fn with_pre_registry_buffer(buffer: PreRegistryBuffer) -> Self {
+        Self {
+            pre_registry_buffer: Some(buffer),
+            ..default()
+        }
+    }
+
+    /// Install a [`MultiRecorder`] of the registry and `recorder` as the
+    /// global recorder, so both receive all metrics.
+    ///
+    /// Use this if your app has another recorder, e.g. an exporter. That
+    /// recorder must not already be installed, because the global recorder
+    /// can only be set once.
+    pub fn with_chained_recorder(
+        mut self,
+        recorder: impl Recorder + Send + Sync + 'static,
+    ) -> Self {
+        self.chained_recorders.push(recorder);
+        self
+    }
+
+    /// Choose when histogram buckets are cleared.
+    pub fn with_clear_buckets_policy(mut self, policy: ClearBucketsPolicy) -> Self {
+        self.clear_policy = policy;
+        self
+    }
+
+    /// Buffer writes to counters and/or gauges per thread, flushing them to
+    /// the registry once per frame after the [`ProduceMetricsSystem`].
+    ///
+    /// See [`WriteCoalescing`].
+    pub fn with_write_coalescing(mut self, coalescing: WriteCoalescing) -> Self {
+        self.write_coalescing = coalescing;
+        self
+    }
+
+    /// Shard counters into per-thread stripes, adding them to the registry
+    /// once per frame after the [`ProduceMetricsSystem`].
+    ///
+    /// See [`MetricsRegistry::set_counter_sharding`].
+    pub fn with_sharded_counters(mut self) -> Self {
+        self.sharded_counters = true;
+        self
+    }
+
+    /// Count the writes to every metric in one of every `sample_interval`
+    /// frames, to find metrics that are never written or written far too
+    /// often. See [`MetricsRegistry::sample_write_counts`].
+    ///
+    /// Dashboards show the counts when their
+    /// [`write_diagnostics`](crate::DashboardConfig::write_diagnostics) are
+    /// enabled.
+    pub fn with_write_counting(mut self, sample_interval: u32) -> Self {
+        self.write_count_interval = Some(sample_interval.max(1));
+        self
+    }
+
+    /// Send a [`MetricRegistered`] event for every new metric.
+    pub fn with_registration_events(mut self) -> Self {
+        self.registration_events = true;
+        self
+    }
+
+    /// Call [`MetricsRegistry::upkeep`] every frame, so the registry knows
+    /// when each metric was last updated.
+    ///
+    /// This lets searches list recently active metrics first and dashboards
+    /// mark stalled plots.
+    pub fn with_recency(mut self) -> Self {
+        self.recency = true;
+        self
+    }
+
+    /// Like [`Self::with_recency`], also deleting metrics that haven't been
+    /// updated for more than `max_idle_frames` frames and sending a
+    /// [`MetricExpired`] event for each.
+    ///
+    /// This bounds the registry for apps that create many short-lived label
+    /// combinations. Dashboards keep the plots of expired metrics, and follow
+    /// them again when they're recreated. See [`MetricsRegistry::remove_idle`].
+    pub fn with_metric_expiry(mut self, max_idle_frames: u64) -> Self {
+        self.recency = true;
+        self.max_idle_generations = Some(max_idle_frames);
+        self
+    }
+
+    /// Send a [`FinalFlush`] event and log how many metrics were recorded
+    /// when the app exits or the registry is replaced.
+    pub fn with_final_flush(mut self) -> Self {
+        self.final_flush = true;
+        self
+    }
+
+    /// Check the [`Last`] schedule once, after it first runs, for systems
+    /// ordered so that histogram samples are dropped, logging a warning with
+    /// the names of the systems for each [`OrderingProblem`](crate::OrderingProblem).
+    ///
+    /// The problems found are kept in the [`OrderingDiagnostics`] resource.
+    /// This is meant for debug builds.
+    pub fn with_ordering_diagnostics(mut self) -> Self {
+        self.ordering_diagnostics = true;
+        self
+    }
+
+    fn install(&self, registry: &MetricsRegistry) -> Result<(), String> {
+        if self.chained_recorders.is_empty() {
+            set_global_recorder(registry.clone()).map_err(|e| e.to_string())
+        } else {
+            let mut recorder = self.chained_recorders.clone();
+            recorder.push(registry.clone());
+            set_global_recorder(recorder).map_err(|e| e.to_string())
         }
     }
 }
 
 impl Plugin for RegistryPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        let registry = if let Some(registry) = &self.registry {
-            _ = set_global_recorder(registry.clone());
+        let registry = if let Some(buffer) = &self.pre_registry_buffer {
+            let registry = self.registry.clone().unwrap_or_default();
+            if !buffer.replay_into(&registry) {
+                error!("PreRegistryBuffer was already replayed into another registry");
+            }
+            if !self.chained_recorders.is_empty() {
+                warn!("Chained recorders are ignored when using a PreRegistryBuffer");
+            }
+            registry
+        } else if let Some(registry) = &self.registry {
+            _ = self.install(registry);
             registry.clone()
         } else {
             let registry = MetricsRegistry::default();
-            if let Err(e) = set_global_recorder(registry.clone()) {
-                error!("Failed to set global recorder: {e}");
+            if let Err(e) = self.install(&registry) {
+                error!(
+                    "Failed to set global recorder: {e}. The dashboard will not receive any \
+                    metrics. If another recorder is installed, pass it to \
+                    RegistryPlugin::with_chained_recorder instead of installing it yourself."
+                );
             }
             registry
         };
-        app.insert_resource(registry).add_systems(
-            Last,
-            MetricsRegistry::clear_atomic_buckets_system.in_set(ClearBucketsSystem),
-        );
+        if self.write_coalescing != WriteCoalescing::default() || self.sharded_counters {
+            registry.set_write_coalescing(self.write_coalescing);
+            registry.set_counter_sharding(self.sharded_counters);
+            app.add_systems(
+                Last,
+                MetricsRegistry::flush_coalesced_writes_system
+                    .after(ProduceMetricsSystem)
+                    .before(FrameSnapshot::capture_system)
+                    .before(DashboardWindow::update_all)
+                    .before(ClearBucketsSystem),
+            );
+        }
+        if let Some(interval) = self.write_count_interval {
+            registry.set_write_counting(true);
+            app.add_systems(
+                Last,
+                (move |registry: Res<MetricsRegistry>| registry.sample_write_counts(interval))
+                    .after(ProduceMetricsSystem)
+                    .before(ClearBucketsSystem),
+            );
+        }
+        app.add_event::<MetricRegistered>();
+        if self.registration_events {
+            let (sender, receiver) = mpsc::channel();
+            registry.on_metric_added(move |key| {
+                let _ = sender.send(key.clone());
+            });
+            let receiver = Mutex::new(receiver);
+            app.add_systems(First, move |mut events: EventWriter<MetricRegistered>| {
+                let receiver = receiver.lock().unwrap();
+                events.send_batch(receiver.try_iter().map(|key| MetricRegistered { key }));
+            });
+        }
+        app.add_event::<MetricExpired>();
+        if self.recency {
+            let max_idle = self.max_idle_generations;
+            app.add_systems(
+                Last,
+                (move |registry: Res<MetricsRegistry>, mut events: EventWriter<MetricExpired>| {
+                    registry.upkeep();
+                    if let Some(max_idle) = max_idle {
+                        let expired = registry.remove_idle(max_idle);
+                        events.send_batch(expired.into_iter().map(|key| MetricExpired { key }));
+                    }
+                })
+                .after(ProduceMetricsSystem)
+                .after(MetricsRegistry::flush_coalesced_writes_system)
+                .before(ClearBucketsSystem),
+            );
+        }
+        app.add_event::<FinalFlush>();
+        if self.final_flush {
+            app.add_systems(
+                Last,
+                final_flush
+                    .in_set(FinalFlushSystem)
+                    .after(ProduceMetricsSystem)
+                    .after(MetricsRegistry::flush_coalesced_writes_system)
+                    .before(ClearBucketsSystem),
+            );
+        }
+        if self.ordering_diagnostics {
+            app.init_resource::<OrderingDiagnostics>()
+                .add_systems(First, OrderingDiagnostics::check_system);
+        }
+        app.insert_resource(registry)
+            .insert_resource(self.clear_policy)
+            .init_resource::<HistogramRetention>()
+            .init_resource::<HistogramSketches>()
+            .init_resource::<HistogramConsumers>()
+            .add_systems(Last, clear_buckets.in_set(ClearBucketsSystem));
     }
 }
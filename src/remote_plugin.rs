@@ -0,0 +1,287 @@
+//! [Bevy Remote Protocol](bevy_remote) methods for reading and driving the
+//! dashboard from external tools.
+
+use crate::{
+    metric_kind_str, parse_metric_kind,
+    registry::{DescriptionKey, MetricKey, MetricsRegistry},
+    unit_str, HistogramConsumerAppExt, RequestPlot,
+};
+use bevy::{prelude::*, utils::HashMap};
+use bevy_remote::{error_codes, BrpError, BrpResult, RemoteMethodSystemId, RemoteMethods};
+use metrics::{Key, Label};
+use metrics_util::MetricKind;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::atomic::Ordering;
+
+/// Lists metrics in the [`MetricsRegistry`].
+///
+/// Params: optional [`ListMetricsParams`]. Result: a list of [`MetricInfo`].
+pub const LIST_METHOD: &str = "metrics_dashboard/list";
+/// Reads the current values of metrics in the [`MetricsRegistry`].
+///
+/// Params: optional [`SnapshotParams`]. Result: a list of [`MetricSnapshot`].
+pub const SNAPSHOT_METHOD: &str = "metrics_dashboard/snapshot";
+/// Opens a plot of a metric in all dashboard windows.
+///
+/// Params: [`MetricId`]. Result: `null`.
+pub const REQUEST_PLOT_METHOD: &str = "metrics_dashboard/request_plot";
+
+/// Registers Bevy Remote Protocol methods for listing metrics, reading
+/// snapshots, and requesting plots, so external tools can drive and read the
+/// dashboard over the wire.
+///
+/// This must be added after [`RemotePlugin`](bevy_remote::RemotePlugin).
+pub struct DashboardRemotePlugin;
+
+impl Plugin for DashboardRemotePlugin {
+    fn build(&self, app: &mut App) {
+        if !app.world().contains_resource::<RemoteMethods>() {
+            warn!("DashboardRemotePlugin requires RemotePlugin to be added first");
+            return;
+        }
+        app.add_event::<RequestPlot>()
+            .init_resource::<HistogramSummaries>()
+            .add_histogram_consumer(HistogramSummaries::update);
+
+        let world = app.world_mut();
+        let list = world.register_system(list_metrics);
+        let snapshot = world.register_system(snapshot_metrics);
+        let request_plot = world.register_system(request_plot);
+        let mut methods = world.resource_mut::<RemoteMethods>();
+        methods.insert(LIST_METHOD, RemoteMethodSystemId::Instant(list));
+        methods.insert(SNAPSHOT_METHOD, RemoteMethodSystemId::Instant(snapshot));
+        methods.insert(
+            REQUEST_PLOT_METHOD,
+            RemoteMethodSystemId::Instant(request_plot),
+        );
+    }
+}
+
+/// Identifies a single metric series.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MetricId {
+    pub name: String,
+    /// One of "counter", "gauge", or "histogram".
+    pub kind: String,
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct ListMetricsParams {
+    /// Only list metrics whose name fuzzy-matches this string.
+    #[serde(default)]
+    pub search: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MetricInfo {
+    #[serde(flatten)]
+    pub id: MetricId,
+    pub unit: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct SnapshotParams {
+    /// Only include metrics with these names. All metrics are included when
+    /// empty.
+    #[serde(default)]
+    pub names: Vec<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MetricSnapshot {
+    #[serde(flatten)]
+    pub id: MetricId,
+    pub value: SnapshotValue,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum SnapshotValue {
+    Scalar(f64),
+    Histogram(HistogramSummary),
+}
+
+/// Summary of the histogram samples recorded since the buckets were last
+/// cleared by the [`ClearBucketsSystem`](crate::ClearBucketsSystem), every
+/// frame by default.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct HistogramSummary {
+    pub count: usize,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+}
+
+/// Histogram summaries captured before buckets are cleared, since remote
+/// requests are processed after
+/// [`ClearBucketsSystem`](crate::ClearBucketsSystem).
+#[derive(Default, Resource)]
+struct HistogramSummaries(HashMap<Key, HistogramSummary>);
+
+impl HistogramSummaries {
+    fn update(mut summaries: ResMut<Self>, registry: Res<MetricsRegistry>) {
+        summaries.0.clear();
+        registry.visit_histograms(|key, bucket| {
+            let mut summary = HistogramSummary {
+                count: 0,
+                min: f64::INFINITY,
+                max: f64::NEG_INFINITY,
+                mean: 0.0,
+            };
+            bucket.data_with(|block| {
+                for &value in block {
+                    summary.count += 1;
+                    summary.min = summary.min.min(value);
+                    summary.max = summary.max.max(value);
+                    summary.mean += value;
+                }
+            });
+            if summary.count > 0 {
+                summary.mean /= summary.count as f64;
+                summaries.0.insert(key.clone(), summary);
+            }
+        });
+    }
+}
+
+fn list_metrics(In(params): In<Option<Value>>, registry: Res<MetricsRegistry>) -> BrpResult {
+    let params: ListMetricsParams = parse_params_or_default(params)?;
+    let results = if let Some(search) = params.search {
+        registry.fuzzy_search_by_name(&search)
+    } else {
+        registry.all_metrics()
+    };
+    let infos: Vec<_> = results
+        .into_iter()
+        .map(|result| MetricInfo {
+            id: MetricId::from(&result.key),
+            unit: result
+                .description
+                .as_ref()
+                .and_then(|d| d.unit)
+                .map(|u| unit_str(u).to_owned()),
+            description: result.description.map(|d| d.text.to_string()),
+        })
+        .collect();
+    serde_json::to_value(infos).map_err(BrpError::internal)
+}
+
+fn snapshot_metrics(
+    In(params): In<Option<Value>>,
+    registry: Res<MetricsRegistry>,
+    summaries: Res<HistogramSummaries>,
+) -> BrpResult {
+    let params: SnapshotParams = parse_params_or_default(params)?;
+    let included =
+        |key: &Key| params.names.is_empty() || params.names.iter().any(|n| n == key.name());
+
+    let mut snapshots = Vec::new();
+    registry.visit_counters(|key, counter| {
+        if included(key) {
+            snapshots.push(MetricSnapshot {
+                id: MetricId::from(&MetricKey::new(key.clone(), MetricKind::Counter)),
+                value: SnapshotValue::Scalar(counter.load(Ordering::Relaxed) as f64),
+            });
+        }
+    });
+    registry.visit_gauges(|key, gauge| {
+        if included(key) {
+            snapshots.push(MetricSnapshot {
+                id: MetricId::from(&MetricKey::new(key.clone(), MetricKind::Gauge)),
+                value: SnapshotValue::Scalar(f64::from_bits(gauge.load(Ordering::Relaxed))),
+            });
+        }
+    });
+    for (key, summary) in &summaries.0 {
+        if included(key) {
+            snapshots.push(MetricSnapshot {
+                id: MetricId::from(&MetricKey::new(key.clone(), MetricKind::Histogram)),
+                value: SnapshotValue::Histogram(*summary),
+            });
+        }
+    }
+    serde_json::to_value(snapshots).map_err(BrpError::internal)
+}
+
+fn request_plot(
+    In(params): In<Option<Value>>,
+    registry: Res<MetricsRegistry>,
+    mut requests: EventWriter<RequestPlot>,
+) -> BrpResult {
+    let Some(params) = params else {
+        return Err(invalid_params("missing params"));
+    };
+    let id: MetricId = serde_json::from_value(params).map_err(invalid_params)?;
+    let requested = MetricKey::try_from(id)?;
+    // Label order is not significant to clients, so prefer the existing key
+    // with the same set of labels.
+    let key = registry
+        .all_metrics()
+        .into_iter()
+        .map(|result| result.key)
+        .find(|key| same_series(key, &requested))
+        .unwrap_or(requested);
+    let unit = registry
+        .get_description(&DescriptionKey::from(&key))
+        .and_then(|d| d.unit);
+    requests.send(RequestPlot::new(key, unit));
+    Ok(Value::Null)
+}
+
+impl From<&MetricKey> for MetricId {
+    fn from(key: &MetricKey) -> Self {
+        Self {
+            name: key.key.name().to_owned(),
+            kind: metric_kind_str(key.kind).to_owned(),
+            labels: key
+                .key
+                .labels()
+                .map(|l| (l.key().to_owned(), l.value().to_owned()))
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<MetricId> for MetricKey {
+    type Error = BrpError;
+
+    fn try_from(id: MetricId) -> Result<Self, Self::Error> {
+        let Some(kind) = parse_metric_kind(&id.kind) else {
+            return Err(invalid_params(format!("unknown metric kind `{}`", id.kind)));
+        };
+        let labels: Vec<_> = id
+            .labels
+            .into_iter()
+            .map(|(k, v)| Label::new(k, v))
+            .collect();
+        Ok(MetricKey::new(Key::from_parts(id.name, labels), kind))
+    }
+}
+
+fn same_series(a: &MetricKey, b: &MetricKey) -> bool {
+    a.kind == b.kind
+        && a.key.name() == b.key.name()
+        && a.key.labels().len() == b.key.labels().len()
+        && a.key.labels().all(|l| b.key.labels().any(|m| l == m))
+}
+
+fn parse_params_or_default<T: Default + for<'de> Deserialize<'de>>(
+    params: Option<Value>,
+) -> Result<T, BrpError> {
+    match params {
+        Some(params) => serde_json::from_value(params).map_err(invalid_params),
+        None => Ok(T::default()),
+    }
+}
+
+fn invalid_params(error: impl ToString) -> BrpError {
+    BrpError {
+        code: error_codes::INVALID_PARAMS,
+        message: error.to_string(),
+        data: None,
+    }
+}
@@ -1,3 +1,4 @@
+use crate::MetricProducerAppExt;
 use bevy::{prelude::*, render::view::VisibleEntities};
 use metrics::{describe_gauge, gauge, Unit};
 
@@ -6,8 +7,8 @@ pub struct RenderMetricsPlugin;
 
 impl Plugin for RenderMetricsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, describe_render_metrics)
-            .add_systems(Update, update_render_metrics);
+        app.describe_metrics(describe_render_metrics)
+            .add_metric_producer(update_render_metrics);
     }
 }
 
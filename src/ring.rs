@@ -1,17 +1,47 @@
+//! A ring buffer of timestamped samples.
+
 use egui_plot::PlotPoint;
-use std::collections::VecDeque;
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
 
 /// A resizable ring buffer.
+///
+/// Every element is stored with the time it was pushed, so elements can be
+/// looked up by age, and optionally the frame it was recorded on.
+///
+/// ```
+/// # use bevy_metrics_dashboard::ring::Ring;
+/// use std::time::{Duration, Instant};
+///
+/// let start = Instant::now();
+/// let mut ring = Ring::new(3);
+/// for i in 0..5 {
+///     ring.push_at(i, start + Duration::from_secs(i));
+/// }
+///
+/// // The two oldest elements have been overwritten.
+/// assert_eq!(ring.iter_chronological().copied().collect::<Vec<_>>(), [2, 3, 4]);
+/// assert_eq!(ring.latest(), Some(&4));
+///
+/// // Find the first element pushed at or after 3 seconds.
+/// let i = ring.partition_point_by_time(start + Duration::from_secs(3));
+/// assert_eq!(ring.get(i), Some(&3));
+/// ```
 pub struct Ring<T> {
-    elements: VecDeque<T>,
+    /// Chronological order, oldest first.
+    elements: VecDeque<(Instant, Option<u32>, T)>,
     max_len: usize,
+    n_pushed: u64,
 }
 
-impl<T: Clone + Default> Ring<T> {
+impl<T> Ring<T> {
     pub fn new(max_len: usize) -> Self {
         Self {
             elements: VecDeque::with_capacity(max_len),
             max_len,
+            n_pushed: 0,
         }
     }
 
@@ -19,6 +49,8 @@ impl<T: Clone + Default> Ring<T> {
         self.max_len
     }
 
+    /// Change the maximum length, discarding the oldest elements if there are
+    /// too many.
     pub fn set_max_len(&mut self, new_max_len: usize) {
         self.max_len = new_max_len;
 
@@ -27,33 +59,201 @@ impl<T: Clone + Default> Ring<T> {
             self.elements.reserve(add_cap);
         }
         if self.max_len < self.elements.len() {
-            self.elements.truncate(self.max_len);
+            let n_remove = self.elements.len() - self.max_len;
+            self.elements.drain(..n_remove);
         }
     }
 
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// The number of elements ever pushed, including those overwritten or
+    /// cleared.
+    pub fn n_pushed(&self) -> u64 {
+        self.n_pushed
+    }
+
+    /// The sample number of the oldest element, counting every element ever
+    /// pushed. This stays the same for an element as newer ones are pushed.
+    pub fn first_sample_number(&self) -> u64 {
+        self.n_pushed - self.elements.len() as u64
+    }
+
+    pub fn clear(&mut self) {
+        self.elements.clear();
+    }
+
     pub fn latest(&self) -> Option<&T> {
-        self.elements.front()
+        self.elements.back().map(|(_, _, e)| e)
     }
 
+    /// The time at which the latest element was pushed.
+    pub fn latest_time(&self) -> Option<Instant> {
+        self.elements.back().map(|&(t, _, _)| t)
+    }
+
+    /// Get the element at chronological `index`, where `0` is the oldest.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.elements.get(index).map(|(_, _, e)| e)
+    }
+
+    /// Push `elem` with the current time.
+    ///
     /// Overwrites the oldest element if capacity limit is reached.
     pub fn push(&mut self, elem: T) {
+        self.push_at(elem, Instant::now());
+    }
+
+    /// Push `elem` as if it was recorded at `time`.
+    ///
+    /// `time` should not be earlier than [`Self::latest_time`], otherwise the
+    /// lookups by time will give nonsensical results.
+    pub fn push_at(&mut self, elem: T, time: Instant) {
+        self.push_entry(time, None, elem);
+    }
+
+    /// Push `elem` with the current time and the `frame` it was recorded on,
+    /// if known.
+    pub fn push_with_frame(&mut self, elem: T, frame: Option<u32>) {
+        self.push_entry(Instant::now(), frame, elem);
+    }
+
+    fn push_entry(&mut self, time: Instant, frame: Option<u32>, elem: T) {
+        self.n_pushed += 1;
+        if self.max_len == 0 {
+            return;
+        }
         while self.elements.len() >= self.max_len {
-            self.elements.pop_back();
+            self.elements.pop_front();
         }
-        self.elements.push_front(elem);
+        self.elements.push_back((time, frame, elem));
     }
 
     pub fn iter_chronological(&self) -> impl Iterator<Item = &T> {
-        self.elements.iter().rev()
+        self.elements.iter().map(|(_, _, e)| e)
     }
 
+    /// Iterate over elements in chronological order, with the time at which
+    /// they were pushed.
+    pub fn iter_timestamped(&self) -> impl Iterator<Item = (Instant, &T)> {
+        self.elements.iter().map(|(t, _, e)| (*t, e))
+    }
+
+    /// Iterate over elements in chronological order, with the frame they
+    /// were recorded on, if known.
+    pub fn iter_with_frame(&self) -> impl Iterator<Item = (Option<u32>, &T)> {
+        self.elements.iter().map(|(_, f, e)| (*f, e))
+    }
+
+    /// Iterate over elements in chronological order, with their
+    /// chronological index and their age relative to now.
+    pub fn iter_with_age(&self) -> impl Iterator<Item = (usize, Duration, &T)> {
+        let now = Instant::now();
+        self.elements
+            .iter()
+            .enumerate()
+            .map(move |(i, (t, _, e))| (i, now.saturating_duration_since(*t), e))
+    }
+
+    /// Binary search for the element pushed at exactly `time`.
+    ///
+    /// Like [`slice::binary_search`], this returns `Ok` with the element's
+    /// chronological index if found, or `Err` with the index where an element
+    /// pushed at `time` would be inserted.
+    pub fn binary_search_by_time(&self, time: Instant) -> Result<usize, usize> {
+        self.elements.binary_search_by(|(t, _, _)| t.cmp(&time))
+    }
+
+    /// The chronological index of the first element pushed at or after
+    /// `time`, or [`Self::len`] if there is none.
+    pub fn partition_point_by_time(&self, time: Instant) -> usize {
+        self.elements.partition_point(|(t, _, _)| *t < time)
+    }
+
+    /// The average number of elements pushed per second, measured over the
+    /// elements in the ring, or `None` if they span no time.
+    pub fn push_rate(&self) -> Option<f64> {
+        let (first, _, _) = self.elements.front()?;
+        let (last, _, _) = self.elements.back()?;
+        let span = last.saturating_duration_since(*first).as_secs_f64();
+        (span > 0.0).then(|| (self.elements.len() - 1) as f64 / span)
+    }
+
+    /// Iterate over the elements pushed within `max_age` of now, in
+    /// chronological order.
+    pub fn iter_newer_than(&self, max_age: Duration) -> impl Iterator<Item = &T> {
+        let start = Instant::now()
+            .checked_sub(max_age)
+            .map_or(0, |time| self.partition_point_by_time(time));
+        self.elements.range(start..).map(|(_, _, e)| e)
+    }
+
+    /// Chronological indices of the elements pushed after a gap in sampling,
+    /// e.g. while a plot was paused or the app was minimized.
+    ///
+    /// An element follows a gap if it was recorded more than `factor` times
+    /// the median number of frames after the previous element, or more than
+    /// `factor` times the median interval and at least `min_gap` after it.
+    /// The minimum keeps single slow frames from counting as gaps.
+    pub fn gap_indices(&self, factor: f64, min_gap: Duration) -> Vec<usize> {
+        if self.elements.len() < 3 {
+            return Vec::new();
+        }
+        let pairs = || self.elements.iter().zip(self.elements.iter().skip(1));
+        let median = |mut values: Vec<f64>| {
+            values.sort_unstable_by(f64::total_cmp);
+            values.get(values.len() / 2).copied()
+        };
+        let frame_steps: Vec<_> = pairs()
+            .map(|((_, f1, _), (_, f2, _))| Some(f64::from((*f2)?.checked_sub((*f1)?)?)))
+            .collect();
+        let intervals: Vec<_> = pairs()
+            .map(|((t1, ..), (t2, ..))| t2.saturating_duration_since(*t1).as_secs_f64())
+            .collect();
+        let median_step = median(frame_steps.iter().flatten().copied().collect());
+        let median_interval = median(intervals.clone()).unwrap_or_default();
+        let max_interval = (factor * median_interval).max(min_gap.as_secs_f64());
+        (1..self.elements.len())
+            .filter(|&i| {
+                let skipped_frames = frame_steps[i - 1]
+                    .zip(median_step)
+                    .is_some_and(|(step, median)| step > factor * median.max(1.0));
+                skipped_frames || intervals[i - 1] > max_interval
+            })
+            .collect()
+    }
+
+    /// Plot points with the sample number of each element on the X axis, so
+    /// points don't move as newer elements are pushed.
     pub fn make_plot_points(&self) -> Vec<PlotPoint>
     where
-        T: num_traits::NumCast,
+        T: Clone + num_traits::NumCast,
     {
-        (0..)
+        (self.first_sample_number()..)
             .zip(self.iter_chronological().cloned())
-            .map(|(i, y)| [i.into(), num_traits::cast(y).unwrap()].into())
+            .map(|(i, y)| [i as f64, num_traits::cast(y).unwrap()].into())
+            .collect()
+    }
+
+    /// Plot points with the frame each element was recorded on as the X
+    /// axis, so plots sampled over different ranges of frames line up.
+    ///
+    /// Elements pushed without a frame use their sample number instead.
+    pub fn make_frame_plot_points(&self) -> Vec<PlotPoint>
+    where
+        T: Clone + num_traits::NumCast,
+    {
+        (self.first_sample_number()..)
+            .zip(self.iter_with_frame())
+            .map(|(i, (frame, y))| {
+                let x = frame.map_or(i as f64, f64::from);
+                [x, num_traits::cast(y.clone()).unwrap()].into()
+            })
             .collect()
     }
 }
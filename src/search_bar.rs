@@ -2,14 +2,30 @@
 
 use crate::{
     dropdown_list::dropdown_list,
-    registry::{MetricsRegistry, SearchResult},
+    locale::{tr, DashboardLocale},
+    registry::{FuzzyNameMatcher, MetricsRegistry, NameMatcher, SearchResult},
 };
 use bevy::tasks::{block_on, AsyncComputeTaskPool, Task};
 use bevy_egui::egui::{TextEdit, Ui};
-use std::time::{Duration, Instant};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-/// A widget that searches the [`MetricsRegistry`] with fuzzy string matching.
+/// A widget that searches the [`MetricsRegistry`] with fuzzy string matching,
+/// or any other [`NameMatcher`].
+///
+/// The input is split into space-separated terms that must all match, and
+/// terms prefixed with `-` exclude names containing them, e.g. `"time -gpu"`.
+///
+/// Results are listed in order of best match, optionally with recently
+/// active metrics first (see [`Self::with_recently_active_first`]).
 pub struct SearchBar {
+    matcher: Arc<dyn NameMatcher>,
+    recently_active_first: bool,
+    debounce: Duration,
+    max_results: Option<usize>,
+    max_dropdown_height: f32,
     search_input: String,
     input_dirty: bool,
     last_search_time: Instant,
@@ -26,6 +42,11 @@ impl Default for SearchBar {
 impl SearchBar {
     pub fn new() -> Self {
         Self {
+            matcher: Arc::new(FuzzyNameMatcher::default()),
+            recently_active_first: false,
+            debounce: Duration::from_millis(250),
+            max_results: None,
+            max_dropdown_height: 200.0,
             search_input: Default::default(),
             input_dirty: true,
             last_search_time: Instant::now(),
@@ -34,6 +55,94 @@ impl SearchBar {
         }
     }
 
+    /// Score metric names with `matcher` instead of a [`FuzzyNameMatcher`],
+    /// e.g. a [`PrefixNameMatcher`](crate::registry::PrefixNameMatcher).
+    pub fn with_matcher(mut self, matcher: impl NameMatcher + 'static) -> Self {
+        self.set_matcher(matcher);
+        self
+    }
+
+    pub fn set_matcher(&mut self, matcher: impl NameMatcher + 'static) {
+        self.matcher = Arc::new(matcher);
+        self.input_dirty = true;
+    }
+
+    /// List the results updated most recently first, keeping the best
+    /// matches first among those updated at the same time.
+    ///
+    /// This needs the [`RegistryPlugin`](crate::RegistryPlugin) to be built
+    /// [`with_recency`](crate::RegistryPlugin::with_recency).
+    pub fn with_recently_active_first(mut self, recently_active_first: bool) -> Self {
+        self.set_recently_active_first(recently_active_first);
+        self
+    }
+
+    pub fn recently_active_first(&self) -> bool {
+        self.recently_active_first
+    }
+
+    pub fn set_recently_active_first(&mut self, recently_active_first: bool) {
+        self.recently_active_first = recently_active_first;
+        self.input_dirty = true;
+    }
+
+    /// Wait at least `debounce` between searches while the input is changing.
+    ///
+    /// Defaults to 250 ms.
+    pub fn with_debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    /// Only show the `max_results` best matches in the dropdown.
+    ///
+    /// Defaults to showing all matches.
+    pub fn with_max_results(mut self, max_results: usize) -> Self {
+        self.max_results = Some(max_results);
+        self
+    }
+
+    /// Scroll the dropdown once it's taller than `max_height` points.
+    ///
+    /// Defaults to 200.
+    pub fn with_max_dropdown_height(mut self, max_height: f32) -> Self {
+        self.max_dropdown_height = max_height;
+        self
+    }
+
+    pub fn debounce(&self) -> Duration {
+        self.debounce
+    }
+
+    pub fn set_debounce(&mut self, debounce: Duration) {
+        self.debounce = debounce;
+    }
+
+    pub fn max_results(&self) -> Option<usize> {
+        self.max_results
+    }
+
+    pub fn set_max_results(&mut self, max_results: Option<usize>) {
+        self.max_results = max_results;
+    }
+
+    pub fn max_dropdown_height(&self) -> f32 {
+        self.max_dropdown_height
+    }
+
+    pub fn set_max_dropdown_height(&mut self, max_height: f32) {
+        self.max_dropdown_height = max_height;
+    }
+
+    /// The results of the latest search, or nothing if the input is empty.
+    pub fn results(&self) -> &[SearchResult] {
+        if self.search_input.is_empty() {
+            &[]
+        } else {
+            &self.search_results
+        }
+    }
+
     /// Draw the widget and accept user input.
     ///
     /// If the user selects one of the search results, it will be returned.
@@ -41,20 +150,31 @@ impl SearchBar {
         // Draw search box.
         let maybe_selected = ui
             .horizontal(|ui| {
-                ui.label("Search:");
+                ui.label(tr(ui, "Search:"));
                 let response = TextEdit::singleline(&mut self.search_input)
-                    .hint_text("metric name")
+                    .hint_text(tr(ui, "metric name"))
                     .show(ui)
-                    .response;
+                    .response
+                    .on_hover_text(tr(
+                        ui,
+                        "Names must match every word. Prefix a word with - to exclude names \
+                        containing it.",
+                    ));
                 if response.changed() {
                     self.input_dirty = true;
                 }
+                let n_shown = self.max_results.map_or(self.search_results.len(), |max| {
+                    max.min(self.search_results.len())
+                });
+                let locale = DashboardLocale::get(ui.ctx());
                 dropdown_list(
                     response,
                     ui,
                     "metric-search-dropdown",
-                    self.search_results.iter(),
-                    |&s| s.detailed_text(None),
+                    self.search_results[..n_shown].iter(),
+                    self.search_results.len() - n_shown,
+                    self.max_dropdown_height,
+                    |&s| s.localized_detailed_text(None, &locale),
                 )
                 .cloned()
             })
@@ -64,8 +184,6 @@ impl SearchBar {
         if let Some(task) = self.search_task.take() {
             if task.is_finished() {
                 self.search_results = block_on(task);
-                self.search_results
-                    .sort_by(|r1, r2| r1.key.key.name().cmp(r2.key.key.name()));
             } else {
                 self.search_task = Some(task);
             }
@@ -73,15 +191,22 @@ impl SearchBar {
 
         if self.input_dirty
             && !self.search_input.is_empty()
-            && self.last_search_time.elapsed() > Duration::from_millis(250)
+            && self.last_search_time.elapsed() > self.debounce
         {
             // Spawn task to search the registry, just to avoid long frame times
             // when searching a large registry.
             self.last_search_time = Instant::now();
             let search_input = self.search_input.clone();
             let task_registry = registry.clone();
-            let task = AsyncComputeTaskPool::get()
-                .spawn(async move { task_registry.fuzzy_search_by_name(&search_input) });
+            let matcher = self.matcher.clone();
+            let recently_active_first = self.recently_active_first;
+            let task = AsyncComputeTaskPool::get().spawn(async move {
+                let mut results = task_registry.search_by_name(&search_input, &*matcher);
+                if recently_active_first {
+                    task_registry.sort_by_recency(&mut results);
+                }
+                results
+            });
             self.search_task = Some(task);
             self.input_dirty = false;
         }
@@ -0,0 +1,133 @@
+//! A window that identifies the running session and build.
+
+use crate::{dashboard_target::DashboardTarget, locale::tr};
+use bevy::{core::FrameCount, prelude::*};
+use bevy_egui::{
+    egui::{self, Ui},
+    EguiContexts,
+};
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// An `egui` window showing the wall clock, app uptime, [`FrameCount`], build
+/// profile, and optionally a git hash, so screenshots and exports of the
+/// dashboard can be tied to a specific build and time.
+///
+/// This requires the [`FrameCountPlugin`],
+/// which is part of the `DefaultPlugins`.
+#[derive(Component)]
+pub struct SessionInfoWindow {
+    title: String,
+    id: egui::Id,
+    git_hash: Option<String>,
+}
+
+impl SessionInfoWindow {
+    pub fn new(title: impl Into<String>) -> Self {
+        static WINDOW_ID: AtomicU64 = AtomicU64::new(0);
+        let id = WINDOW_ID.fetch_add(1, Ordering::Relaxed);
+        let title = title.into();
+        let id = format!("{title} {id}").into();
+        Self {
+            title,
+            id,
+            git_hash: None,
+        }
+    }
+
+    /// Show `git_hash`, e.g. from `env!("GIT_HASH")` set by your build script.
+    pub fn with_git_hash(mut self, git_hash: impl Into<String>) -> Self {
+        self.git_hash = Some(git_hash.into());
+        self
+    }
+
+    pub(crate) fn draw_all(
+        mut commands: Commands,
+        time: Res<Time<Real>>,
+        frame: Res<FrameCount>,
+        mut ctxts: EguiContexts,
+        windows: Query<(Entity, &Self, Option<&DashboardTarget>)>,
+    ) {
+        for (entity, window, target) in &windows {
+            let Some(ctxt) = DashboardTarget::ctx(target, &mut ctxts) else {
+                continue;
+            };
+            let mut open = true;
+            DashboardTarget::constrain(target, egui::Window::new(&window.title))
+                .id(window.id)
+                .open(&mut open)
+                .show(ctxt, |ui| {
+                    window.draw(time.elapsed(), frame.0, ui);
+                });
+            if !open {
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+
+    /// Draw the widget for an app that has been running for `uptime` and is
+    /// on `frame`.
+    pub fn draw(&self, uptime: Duration, frame: u32, ui: &mut Ui) {
+        let build_profile = if cfg!(debug_assertions) {
+            "debug"
+        } else {
+            "release"
+        };
+        egui::Grid::new(self.id.with("grid"))
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label(tr(ui, "Clock (UTC)"));
+                ui.label(format_utc(SystemTime::now()));
+                ui.end_row();
+                ui.label(tr(ui, "Uptime"));
+                ui.label(format_duration(uptime));
+                ui.end_row();
+                ui.label(tr(ui, "Frame"));
+                ui.label(frame.to_string());
+                ui.end_row();
+                ui.label(tr(ui, "Profile"));
+                ui.label(build_profile);
+                ui.end_row();
+                if let Some(git_hash) = &self.git_hash {
+                    ui.label(tr(ui, "Git Hash"));
+                    ui.label(git_hash);
+                    ui.end_row();
+                }
+            });
+    }
+}
+
+pub(crate) fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+/// Formats as `YYYY-MM-DD HH:MM:SS`.
+fn format_utc(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let (year, month, day) = civil_from_days((secs / 86400) as i64);
+    let time_of_day = format_duration(Duration::from_secs(secs % 86400));
+    format!("{year:04}-{month:02}-{day:02} {time_of_day}")
+}
+
+/// Converts days since the Unix epoch to a (year, month, day) of the
+/// proleptic Gregorian calendar.
+///
+/// From Howard Hinnant's `civil_from_days` algorithm.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
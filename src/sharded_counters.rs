@@ -0,0 +1,136 @@
+use crate::write_coalescing::Target;
+use bevy::{log::warn, prelude::default};
+use metrics::CounterFn;
+use std::{
+    num::NonZeroUsize,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+/// An atomic on its own cache line, so stripes written by different threads
+/// don't contend.
+#[repr(align(128))]
+#[derive(Default)]
+struct Stripe(AtomicU64);
+
+static NEXT_THREAD_INDEX: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    /// Picks this thread's stripe of every sharded counter.
+    static THREAD_INDEX: usize = NEXT_THREAD_INDEX.fetch_add(1, Ordering::Relaxed);
+}
+
+/// The per-thread increments of one counter, not yet added to its atomic.
+pub(crate) struct Stripes {
+    target: Target,
+    stripes: Box<[Stripe]>,
+}
+
+impl Stripes {
+    fn new(target: Target, n_stripes: usize) -> Self {
+        Self {
+            target,
+            stripes: (0..n_stripes).map(|_| Stripe::default()).collect(),
+        }
+    }
+
+    fn increment(&self, value: u64) {
+        let i = THREAD_INDEX.with(|i| *i) % self.stripes.len();
+        self.stripes[i].0.fetch_add(value, Ordering::Relaxed);
+    }
+
+    fn pending(&self) -> bool {
+        self.stripes.iter().any(|s| s.0.load(Ordering::Relaxed) > 0)
+    }
+
+    fn aggregate(&self) {
+        let sum = self.stripes.iter().fold(0u64, |sum, s| {
+            sum.wrapping_add(s.0.swap(0, Ordering::Relaxed))
+        });
+        if sum > 0 {
+            (self.target).with_increment(|t| t.fetch_add(sum, Ordering::AcqRel));
+        }
+    }
+}
+
+/// The stripes of every sharded counter of a registry, to aggregate them.
+pub(crate) struct ShardedCounters {
+    n_stripes: usize,
+    counters: Mutex<Vec<Arc<Stripes>>>,
+}
+
+impl Default for ShardedCounters {
+    fn default() -> Self {
+        // More stripes than threads only costs memory.
+        let n_threads = std::thread::available_parallelism().map_or(8, NonZeroUsize::get);
+        Self {
+            n_stripes: n_threads.next_power_of_two().min(64),
+            counters: default(),
+        }
+    }
+}
+
+impl ShardedCounters {
+    /// New stripes of the counter writing to `target`. Each counter should
+    /// only have one set of stripes, kept with the counter in the registry,
+    /// so registering its handles doesn't lock all of them.
+    pub fn add_stripes(&self, target: Target) -> Arc<Stripes> {
+        let stripes = Arc::new(Stripes::new(target, self.n_stripes));
+        self.counters.lock().unwrap().push(stripes.clone());
+        stripes
+    }
+
+    /// Add the increments of every stripe to their counters.
+    pub fn aggregate(&self) {
+        let mut counters = self.counters.lock().unwrap();
+        for stripes in counters.iter() {
+            stripes.aggregate();
+        }
+        // Forget counters without handles that were removed from the
+        // registry, once their increments are added.
+        counters.retain(|stripes| Arc::strong_count(stripes) > 1);
+    }
+
+    /// The number of counters with increments that haven't been aggregated.
+    pub fn pending(&self) -> usize {
+        let counters = self.counters.lock().unwrap();
+        counters.iter().filter(|s| s.pending()).count()
+    }
+}
+
+impl Drop for ShardedCounters {
+    fn drop(&mut self) {
+        let pending = self.pending();
+        if pending > 0 {
+            warn!(
+                "Dropped a registry with unflushed increments of {pending} sharded counters. \
+                Call MetricsRegistry::flush_coalesced_writes before replacing it."
+            );
+        }
+    }
+}
+
+pub(crate) struct ShardedCounter {
+    stripes: Arc<Stripes>,
+}
+
+impl ShardedCounter {
+    pub fn new(stripes: Arc<Stripes>) -> Self {
+        Self { stripes }
+    }
+}
+
+impl CounterFn for ShardedCounter {
+    fn increment(&self, value: u64) {
+        self.stripes.increment(value);
+    }
+
+    fn absolute(&self, value: u64) {
+        // Increments that come before this must not be added on top of it.
+        self.stripes.aggregate();
+        let target = &self.stripes.target;
+        target.with_increment(|t| t.fetch_max(value, Ordering::AcqRel));
+    }
+}
@@ -0,0 +1,293 @@
+//! Capture the state of other metrics when a gauge spikes.
+
+use crate::{
+    dashboard_target::DashboardTarget,
+    frame_snapshot::FrameSnapshot,
+    locale::tr,
+    metrics_table::SampledMetric,
+    registry::{MetricKey, MetricsRegistry},
+    search_bar::SearchBar,
+    value_formatters::{ValueFormatter, ValueFormatters},
+};
+use bevy::{core::FrameCount, prelude::*};
+use bevy_egui::{
+    egui::{self, DragValue, Ui},
+    EguiContexts,
+};
+use metrics::Unit;
+use std::{
+    collections::VecDeque,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// An `egui` window that logs every frame on which a trigger metric (usually a
+/// gauge) exceeds a threshold, e.g. frame time spikes.
+///
+/// Each log entry records the [`FrameCount`] of the spike and the values of a
+/// set of watched metrics on that frame, next to their mean over the
+/// preceding frames, so it's easy to see what else was abnormal.
+///
+/// This requires the [`FrameCountPlugin`],
+/// which is part of the `DefaultPlugins`.
+#[derive(Component)]
+pub struct SpikeLogWindow {
+    title: String,
+    id: egui::Id,
+    trigger: SampledMetric,
+    threshold: f64,
+    watched: Vec<SampledMetric>,
+    history_len: usize,
+    max_entries: usize,
+    entries: VecDeque<SpikeEntry>,
+    search_bar: SearchBar,
+}
+
+/// The state of watched metrics on one spiky frame.
+pub struct SpikeEntry {
+    pub frame: u32,
+    pub trigger_value: f64,
+    pub metrics: Vec<SpikeMetric>,
+}
+
+pub struct SpikeMetric {
+    pub key: MetricKey,
+    pub unit: Option<Unit>,
+    /// The value on the spiky frame, if one was recorded.
+    pub value: Option<f64>,
+    /// The mean of the preceding values, if any.
+    pub recent_mean: Option<f64>,
+}
+
+impl SpikeLogWindow {
+    /// Log spikes of the metric identified by `trigger` above `threshold`.
+    pub fn new(
+        title: impl Into<String>,
+        registry: &MetricsRegistry,
+        trigger: MetricKey,
+        unit: Option<Unit>,
+        threshold: f64,
+    ) -> Self {
+        static WINDOW_ID: AtomicU64 = AtomicU64::new(0);
+        let id = WINDOW_ID.fetch_add(1, Ordering::Relaxed);
+        let title = title.into();
+        let id = format!("{title} {id}").into();
+        let history_len = 30;
+        Self {
+            title,
+            id,
+            trigger: SampledMetric::new(registry, trigger, unit, 1),
+            threshold,
+            watched: Vec::new(),
+            history_len,
+            max_entries: 100,
+            entries: VecDeque::new(),
+            search_bar: default(),
+        }
+    }
+
+    /// Set the number of frames before a spike that are averaged for
+    /// comparison.
+    pub fn with_history_len(mut self, history_len: usize) -> Self {
+        self.history_len = history_len.max(1);
+        for metric in &mut self.watched {
+            metric.ring.set_max_len(self.history_len + 1);
+        }
+        self
+    }
+
+    /// Set the number of entries kept in the log. The oldest are discarded
+    /// first.
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    pub fn set_threshold(&mut self, threshold: f64) {
+        self.threshold = threshold;
+    }
+
+    /// Record the metric identified by `key` in every log entry.
+    pub fn watch(&mut self, registry: &MetricsRegistry, key: MetricKey, unit: Option<Unit>) {
+        if self.watched.iter().any(|m| m.key == key) {
+            return;
+        }
+        self.watched.push(SampledMetric::new(
+            registry,
+            key,
+            unit,
+            self.history_len + 1,
+        ));
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &SpikeEntry> {
+        self.entries.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub(crate) fn update_all(
+        frame: Res<FrameCount>,
+        snapshot: Res<FrameSnapshot>,
+        mut windows: Query<&mut Self>,
+    ) {
+        for mut window in &mut windows {
+            window.update(frame.0, &snapshot);
+        }
+    }
+
+    fn update(&mut self, frame: u32, snapshot: &FrameSnapshot) {
+        let n_pushed = self.trigger.ring.n_pushed();
+        self.trigger.update(snapshot);
+        for metric in &mut self.watched {
+            metric.update(snapshot);
+        }
+
+        // Without a new sample, the latest one was already checked.
+        if self.trigger.ring.n_pushed() == n_pushed {
+            return;
+        }
+        let Some(&trigger_value) = self.trigger.ring.latest() else {
+            return;
+        };
+        if trigger_value <= self.threshold {
+            return;
+        }
+
+        let metrics = self
+            .watched
+            .iter()
+            .map(|m| {
+                let value = m.ring.latest().copied();
+                let n_previous = m.ring.len().saturating_sub(1);
+                let recent_mean = (n_previous > 0).then(|| {
+                    m.ring.iter_chronological().take(n_previous).sum::<f64>() / n_previous as f64
+                });
+                SpikeMetric {
+                    key: m.key.clone(),
+                    unit: m.unit,
+                    value,
+                    recent_mean,
+                }
+            })
+            .collect();
+        while self.entries.len() >= self.max_entries.max(1) {
+            self.entries.pop_back();
+        }
+        self.entries.push_front(SpikeEntry {
+            frame,
+            trigger_value,
+            metrics,
+        });
+    }
+
+    pub(crate) fn draw_all(
+        mut commands: Commands,
+        registry: Res<MetricsRegistry>,
+        formatters: Res<ValueFormatters>,
+        mut ctxts: EguiContexts,
+        mut windows: Query<(Entity, &mut Self, Option<&DashboardTarget>)>,
+    ) {
+        for (entity, mut window, target) in &mut windows {
+            let Some(ctxt) = DashboardTarget::ctx(target, &mut ctxts) else {
+                continue;
+            };
+            let mut open = true;
+            DashboardTarget::constrain(target, egui::Window::new(&window.title))
+                .id(window.id)
+                .open(&mut open)
+                .show(ctxt, |ui| {
+                    window.draw(&registry, &formatters, ui);
+                });
+            if !open {
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+
+    /// Draw the widget and accept user input.
+    pub fn draw(&mut self, registry: &MetricsRegistry, formatters: &ValueFormatters, ui: &mut Ui) {
+        let trigger_formatter = formatters.get(&self.trigger.key, self.trigger.unit);
+        let format = |formatter: Option<&ValueFormatter>, v: f64| {
+            formatter.map_or_else(|| format!("{v:.3}"), |f| f(v))
+        };
+
+        ui.horizontal(|ui| {
+            ui.label(format!(
+                "{}: {} >",
+                tr(ui, "Trigger"),
+                self.trigger.key.title(None, 0)
+            ));
+            ui.add(DragValue::new(&mut self.threshold).speed(0.1));
+        });
+        ui.collapsing(tr(ui, "Watched Metrics"), |ui| {
+            if let Some(selected) = self.search_bar.draw(registry, ui) {
+                self.watch(
+                    registry,
+                    selected.key,
+                    selected.description.and_then(|d| d.unit),
+                );
+            }
+            let mut remove = None;
+            for (i, metric) in self.watched.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label(metric.key.title(None, 0));
+                    if ui.small_button(tr(ui, "Remove")).clicked() {
+                        remove = Some(i);
+                    }
+                });
+            }
+            if let Some(i) = remove {
+                self.watched.remove(i);
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label(format!("{} {}", self.entries.len(), tr(ui, "spikes")));
+            if ui.button(tr(ui, "Clear")).clicked() {
+                self.entries.clear();
+            }
+        });
+        ui.separator();
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for entry in &self.entries {
+                let header = format!(
+                    "{} {}: {}",
+                    tr(ui, "Frame"),
+                    entry.frame,
+                    format(trigger_formatter, entry.trigger_value)
+                );
+                egui::CollapsingHeader::new(header)
+                    .id_salt(self.id.with(entry.frame))
+                    .show(ui, |ui| {
+                        egui::Grid::new(self.id.with(("grid", entry.frame)))
+                            .striped(true)
+                            .show(ui, |ui| {
+                                ui.strong(tr(ui, "Metric"));
+                                ui.strong(tr(ui, "Value"));
+                                ui.strong(tr(ui, "Recent Mean"));
+                                ui.end_row();
+                                for metric in &entry.metrics {
+                                    let formatter = formatters.get(&metric.key, metric.unit);
+                                    ui.label(metric.key.title(None, 0));
+                                    ui.label(
+                                        metric
+                                            .value
+                                            .map(|v| format(formatter, v))
+                                            .unwrap_or_default(),
+                                    );
+                                    ui.label(
+                                        metric
+                                            .recent_mean
+                                            .map(|v| format(formatter, v))
+                                            .unwrap_or_default(),
+                                    );
+                                    ui.end_row();
+                                }
+                            });
+                    });
+            }
+        });
+    }
+}
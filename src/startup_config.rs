@@ -0,0 +1,252 @@
+//! Configure the dashboard when the app starts, from environment variables or
+//! from the app's own command line parsing.
+//!
+//! This lets QA enable and set up the dashboard on builds without code
+//! changes, e.g. `BMD_AUTOSPAWN=1 BMD_PAUSE_KEY=F3 ./game`.
+//!
+//! ```no_run
+//! # use bevy::prelude::*;
+//! # use bevy_metrics_dashboard::{startup_config::StartupConfig, DashboardPlugin, RegistryPlugin};
+//! App::new().add_plugins((
+//!     DefaultPlugins,
+//!     RegistryPlugin::default(),
+//!     DashboardPlugin::new().with_startup_config(StartupConfig::from_env()),
+//! ));
+//! ```
+
+use crate::{
+    namespace_tree::NamespaceTreeWindow, registry::MetricsRegistry, DashboardWindow, PlotTemplates,
+};
+use bevy::prelude::*;
+use std::path::{Path, PathBuf};
+
+/// The variables read by [`StartupConfig::from_env`].
+const ENV_VARS: [&str; 4] = [
+    "BMD_AUTOSPAWN",
+    "BMD_LAYOUT",
+    "BMD_PAUSE_KEY",
+    "BMD_READ_ONLY",
+];
+
+/// Dashboard settings chosen when the app starts.
+///
+/// Apps that parse their own command line, e.g. with `clap`, can fill this in
+/// directly instead of reading [`Self::from_env`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct StartupConfig {
+    /// Spawn a [`DashboardWindow`] and a [`NamespaceTreeWindow`] on startup.
+    pub autospawn: bool,
+    /// A file with a `LayoutExport` as JSON, spawned as windows on startup.
+    /// A `DashboardExport`, or an array of them, also works.
+    ///
+    /// This needs the `serde` feature.
+    pub layout: Option<PathBuf>,
+    /// See [`DashboardPlugin::with_pause_key`](crate::DashboardPlugin::with_pause_key).
+    pub pause_key: Option<KeyCode>,
+    /// See [`DashboardPlugin::with_read_only`](crate::DashboardPlugin::with_read_only).
+    pub read_only: Option<bool>,
+}
+
+impl StartupConfig {
+    /// Read the config from the environment variables:
+    ///
+    /// - `BMD_AUTOSPAWN`: `1` or `true` to spawn windows.
+    /// - `BMD_LAYOUT`: the path of a layout file.
+    /// - `BMD_PAUSE_KEY`: a key like `F3`, `P`, or `Backquote`.
+    /// - `BMD_READ_ONLY`: `1` or `true` to make dashboards read-only.
+    ///
+    /// Invalid values are logged and ignored, and empty values count as unset.
+    pub fn from_env() -> Self {
+        Self::from_vars(ENV_VARS.into_iter().filter_map(|name| {
+            match std::env::var_os(name)?.into_string() {
+                Ok(value) => Some((name.to_owned(), value)),
+                Err(_) => {
+                    warn!("Ignoring {name}: not valid Unicode");
+                    None
+                }
+            }
+        }))
+    }
+
+    /// Like [`Self::from_env`], reading `(name, value)` pairs instead of
+    /// the environment.
+    pub fn from_vars(vars: impl IntoIterator<Item = (String, String)>) -> Self {
+        let mut config = Self::default();
+        for (name, value) in vars {
+            match name.as_str() {
+                "BMD_AUTOSPAWN" if !value.is_empty() => {
+                    if let Some(autospawn) = parse_bool(&name, &value) {
+                        config.autospawn = autospawn;
+                    }
+                }
+                "BMD_LAYOUT" if !value.is_empty() => config.layout = Some(value.into()),
+                "BMD_PAUSE_KEY" if !value.is_empty() => match parse_key_code(&value) {
+                    Some(key) => config.pause_key = Some(key),
+                    None => warn!("Ignoring {name}: unknown key \"{value}\""),
+                },
+                "BMD_READ_ONLY" if !value.is_empty() => {
+                    config.read_only = parse_bool(&name, &value);
+                }
+                _ => {}
+            }
+        }
+        config
+    }
+
+    /// Spawn the windows chosen by [`Self::autospawn`] and [`Self::layout`].
+    pub(crate) fn spawn_windows(
+        &self,
+        commands: &mut Commands,
+        registry: &MetricsRegistry,
+        templates: &PlotTemplates,
+    ) {
+        if self.autospawn {
+            commands.spawn(DashboardWindow::new("Metrics Dashboard"));
+            commands.spawn(NamespaceTreeWindow::new("Namespace Viewer"));
+        }
+        if let Some(path) = &self.layout {
+            spawn_layout(path, commands, registry, templates);
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+fn spawn_layout(
+    path: &Path,
+    commands: &mut Commands,
+    registry: &MetricsRegistry,
+    templates: &PlotTemplates,
+) {
+    use crate::window_export::LayoutExport;
+
+    let json = match std::fs::read_to_string(path) {
+        Ok(json) => json,
+        Err(e) => {
+            error!("Failed to read layout {}: {e}", path.display());
+            return;
+        }
+    };
+    match LayoutExport::from_json(&json) {
+        Ok(layout) => {
+            for export in &layout.dashboards {
+                commands.spawn(DashboardWindow::import(registry, templates, export));
+            }
+            for export in &layout.namespace_trees {
+                commands.spawn(NamespaceTreeWindow::import(export));
+            }
+        }
+        Err(e) => error!("Failed to parse layout {}: {e}", path.display()),
+    }
+}
+
+#[cfg(not(feature = "serde"))]
+fn spawn_layout(path: &Path, _: &mut Commands, _: &MetricsRegistry, _: &PlotTemplates) {
+    warn!(
+        "Ignoring layout {}: loading layouts needs the \"serde\" feature",
+        path.display()
+    );
+}
+
+fn parse_bool(name: &str, value: &str) -> Option<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Some(true),
+        "" | "0" | "false" | "no" | "off" => Some(false),
+        _ => {
+            warn!("Ignoring {name}: expected 1 or 0, not \"{value}\"");
+            None
+        }
+    }
+}
+
+/// The key named like `F3`, `P`, `7`, or a [`KeyCode`] variant like `Backquote`.
+fn parse_key_code(name: &str) -> Option<KeyCode> {
+    const LETTERS: [KeyCode; 26] = [
+        KeyCode::KeyA,
+        KeyCode::KeyB,
+        KeyCode::KeyC,
+        KeyCode::KeyD,
+        KeyCode::KeyE,
+        KeyCode::KeyF,
+        KeyCode::KeyG,
+        KeyCode::KeyH,
+        KeyCode::KeyI,
+        KeyCode::KeyJ,
+        KeyCode::KeyK,
+        KeyCode::KeyL,
+        KeyCode::KeyM,
+        KeyCode::KeyN,
+        KeyCode::KeyO,
+        KeyCode::KeyP,
+        KeyCode::KeyQ,
+        KeyCode::KeyR,
+        KeyCode::KeyS,
+        KeyCode::KeyT,
+        KeyCode::KeyU,
+        KeyCode::KeyV,
+        KeyCode::KeyW,
+        KeyCode::KeyX,
+        KeyCode::KeyY,
+        KeyCode::KeyZ,
+    ];
+    const DIGITS: [KeyCode; 10] = [
+        KeyCode::Digit0,
+        KeyCode::Digit1,
+        KeyCode::Digit2,
+        KeyCode::Digit3,
+        KeyCode::Digit4,
+        KeyCode::Digit5,
+        KeyCode::Digit6,
+        KeyCode::Digit7,
+        KeyCode::Digit8,
+        KeyCode::Digit9,
+    ];
+    const FUNCTION_KEYS: [KeyCode; 12] = [
+        KeyCode::F1,
+        KeyCode::F2,
+        KeyCode::F3,
+        KeyCode::F4,
+        KeyCode::F5,
+        KeyCode::F6,
+        KeyCode::F7,
+        KeyCode::F8,
+        KeyCode::F9,
+        KeyCode::F10,
+        KeyCode::F11,
+        KeyCode::F12,
+    ];
+
+    let name = name.trim();
+    let key = name.strip_prefix("Key").unwrap_or(name);
+    let key = key.strip_prefix("Digit").unwrap_or(key);
+    if let [c] = key.as_bytes() {
+        if c.is_ascii_alphabetic() {
+            return Some(LETTERS[(c.to_ascii_uppercase() - b'A') as usize]);
+        }
+        if c.is_ascii_digit() {
+            return Some(DIGITS[(c - b'0') as usize]);
+        }
+    }
+    if let Some(n) = name
+        .strip_prefix(['F', 'f'])
+        .and_then(|n| n.parse::<usize>().ok())
+    {
+        return FUNCTION_KEYS.get(n.checked_sub(1)?).copied();
+    }
+    let key = match name {
+        "Backquote" => KeyCode::Backquote,
+        "Backslash" => KeyCode::Backslash,
+        "Delete" => KeyCode::Delete,
+        "End" => KeyCode::End,
+        "Escape" => KeyCode::Escape,
+        "Home" => KeyCode::Home,
+        "Insert" => KeyCode::Insert,
+        "PageDown" => KeyCode::PageDown,
+        "PageUp" => KeyCode::PageUp,
+        "Pause" => KeyCode::Pause,
+        "ScrollLock" => KeyCode::ScrollLock,
+        "Space" => KeyCode::Space,
+        "Tab" => KeyCode::Tab,
+        _ => return None,
+    };
+    Some(key)
+}
@@ -0,0 +1,107 @@
+use crate::MetricProducerAppExt;
+use bevy::{app::MainScheduleOrder, ecs::schedule::ScheduleLabel, prelude::*};
+use metrics::{describe_gauge, gauge, Unit};
+use std::time::{Duration, Instant};
+
+/// Records how long the app took to start, as gauges that keep their value
+/// for the rest of the run, so startup regressions can be inspected in the
+/// dashboard later.
+///
+/// - gauge "startup_plugin_time": from building this plugin until the startup
+///   schedules begin, which covers building all later plugins
+/// - gauge "startup_schedule_time": the run time of each startup schedule,
+///   labeled with `schedule` set to "PreStartup", "Startup", or "PostStartup"
+/// - gauge "startup_time_to_first_frame": from building this plugin until the
+///   end of the first frame
+///
+/// Add this plugin before all others to measure as much of the startup as
+/// possible.
+pub struct StartupMetricsPlugin;
+
+/// Marks the boundaries of the startup schedules.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, ScheduleLabel)]
+enum StartupMark {
+    Begin,
+    PreStartupDone,
+    StartupDone,
+    PostStartupDone,
+}
+
+#[derive(Resource)]
+struct StartupTimes {
+    built_at: Instant,
+    marks: Vec<Instant>,
+}
+
+impl StartupTimes {
+    fn mark(mut times: ResMut<Self>) {
+        times.marks.push(Instant::now());
+    }
+
+    fn record(times: Res<Self>) {
+        let [begin, pre_startup, startup, post_startup] = times.marks[..] else {
+            return;
+        };
+        gauge!("startup_plugin_time").set(ms(begin - times.built_at));
+        let phases = [
+            ("PreStartup", begin, pre_startup),
+            ("Startup", pre_startup, startup),
+            ("PostStartup", startup, post_startup),
+        ];
+        for (schedule, start, end) in phases {
+            gauge!("startup_schedule_time", "schedule" => schedule).set(ms(end - start));
+        }
+    }
+}
+
+fn ms(duration: Duration) -> f64 {
+    1000.0 * duration.as_secs_f64()
+}
+
+impl Plugin for StartupMetricsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(StartupTimes {
+            built_at: Instant::now(),
+            marks: Vec::new(),
+        });
+
+        let mut order = app.world_mut().resource_mut::<MainScheduleOrder>();
+        order.insert_startup_before(PreStartup, StartupMark::Begin);
+        order.insert_startup_after(PreStartup, StartupMark::PreStartupDone);
+        order.insert_startup_after(Startup, StartupMark::StartupDone);
+        order.insert_startup_after(PostStartup, StartupMark::PostStartupDone);
+
+        app.add_systems(StartupMark::Begin, StartupTimes::mark)
+            .add_systems(StartupMark::PreStartupDone, StartupTimes::mark)
+            .add_systems(StartupMark::StartupDone, StartupTimes::mark)
+            .add_systems(
+                StartupMark::PostStartupDone,
+                (StartupTimes::mark, StartupTimes::record).chain(),
+            )
+            .describe_metrics(describe_startup_metrics)
+            .add_metric_producer(|times: Res<StartupTimes>, mut recorded: Local<bool>| {
+                if !*recorded {
+                    *recorded = true;
+                    gauge!("startup_time_to_first_frame").set(ms(times.built_at.elapsed()));
+                }
+            });
+    }
+}
+
+fn describe_startup_metrics() {
+    describe_gauge!(
+        "startup_plugin_time",
+        Unit::Milliseconds,
+        "Time spent building plugins before the startup schedules"
+    );
+    describe_gauge!(
+        "startup_schedule_time",
+        Unit::Milliseconds,
+        "Run time of a startup schedule"
+    );
+    describe_gauge!(
+        "startup_time_to_first_frame",
+        Unit::Milliseconds,
+        "Time from startup until the end of the first frame"
+    );
+}
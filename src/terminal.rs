@@ -0,0 +1,270 @@
+//! A `top`-style table of metrics, printed periodically for headless servers
+//! and CI logs.
+//!
+//! ```no_run
+//! # use bevy::prelude::*;
+//! # use bevy_metrics_dashboard::{terminal::TerminalMetricsPlugin, RegistryPlugin};
+//! # use std::time::Duration;
+//! App::new().add_plugins((
+//!     MinimalPlugins,
+//!     RegistryPlugin::default(),
+//!     TerminalMetricsPlugin::new()
+//!         .with_period(Duration::from_secs(10))
+//!         .with_metric("frame_time"),
+//! ));
+//! ```
+
+use crate::{
+    registry::{key_text, BucketCursor, DescriptionKey, MetricsRegistry},
+    unit_str, FinalFlush, FinalFlushReason, FinalFlushSystem, HistogramConsumerAppExt,
+};
+use bevy::{prelude::*, utils::HashMap};
+use metrics::{Key, KeyName};
+use metrics_util::MetricKind;
+use std::{
+    fmt::Write,
+    sync::atomic::Ordering,
+    time::{Duration, Instant},
+};
+
+/// Where the [`TerminalMetricsPlugin`] writes its table.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum TerminalOutput {
+    #[default]
+    Stdout,
+    /// One `info!` message per table.
+    Log,
+}
+
+#[derive(Default)]
+struct HistogramStats {
+    count: usize,
+    sum: f64,
+    max: f64,
+}
+
+/// Accumulates values between printed tables.
+///
+/// Counters are shown with their total and rate since the previous table,
+/// gauges with their current value, and histograms with the count, mean, and
+/// max of the samples recorded since the previous table.
+#[derive(Default, Resource)]
+pub struct TerminalTable {
+    /// Metric names to show, or every metric if empty.
+    names: Vec<String>,
+    last_counters: HashMap<Key, u64>,
+    histograms: HashMap<Key, HistogramStats>,
+    cursors: HashMap<Key, BucketCursor>,
+}
+
+impl TerminalTable {
+    /// Show only metrics named in `names`, or every metric if `names` is
+    /// empty.
+    pub fn new(names: Vec<String>) -> Self {
+        Self { names, ..default() }
+    }
+
+    /// Read the histogram samples recorded since the last call.
+    pub fn sample(&mut self, registry: &MetricsRegistry) {
+        let Self {
+            names,
+            histograms,
+            cursors,
+            ..
+        } = self;
+        registry.visit_histograms(|key, bucket| {
+            if !is_shown(names, key) {
+                return;
+            }
+            let cursor = cursors
+                .entry(key.clone())
+                .or_insert_with(|| BucketCursor::new(registry));
+            let stats = histograms.entry(key.clone()).or_default();
+            cursor.read_new(bucket, |value| {
+                stats.max = if stats.count == 0 {
+                    value
+                } else {
+                    stats.max.max(value)
+                };
+                stats.count += 1;
+                stats.sum += value;
+            });
+        });
+    }
+
+    /// Format the table of every shown metric, sorted by name, and start
+    /// accumulating the next one.
+    ///
+    /// `elapsed` is the time since the previous table, used for counter
+    /// rates.
+    pub fn render(&mut self, registry: &MetricsRegistry, elapsed: Duration) -> String {
+        let mut rows = Vec::new();
+        let unit = |key: &Key, kind| {
+            let description_key = DescriptionKey {
+                name: KeyName::from(key.name().to_owned()),
+                kind,
+            };
+            registry
+                .get_description(&description_key)
+                .and_then(|d| d.unit)
+                .map_or("", unit_str)
+        };
+
+        registry.visit_counters(|key, counter| {
+            if !is_shown(&self.names, key) {
+                return;
+            }
+            let value = counter.load(Ordering::Relaxed);
+            let detail = match self.last_counters.insert(key.clone(), value) {
+                Some(last) if !elapsed.is_zero() => {
+                    let rate = value.saturating_sub(last) as f64 / elapsed.as_secs_f64();
+                    format!("{rate:.1}/s")
+                }
+                _ => String::new(),
+            };
+            let value = with_unit(value.to_string(), unit(key, MetricKind::Counter));
+            rows.push((key.clone(), value, detail));
+        });
+        registry.visit_gauges(|key, gauge| {
+            if !is_shown(&self.names, key) {
+                return;
+            }
+            let value = f64::from_bits(gauge.load(Ordering::Relaxed));
+            let value = with_unit(format!("{value:.3}"), unit(key, MetricKind::Gauge));
+            rows.push((key.clone(), value, String::new()));
+        });
+        for (key, stats) in self.histograms.iter_mut() {
+            let stats = std::mem::take(stats);
+            let (value, detail) = if stats.count == 0 {
+                ("-".to_owned(), "n=0".to_owned())
+            } else {
+                let mean = stats.sum / stats.count as f64;
+                (
+                    with_unit(format!("{mean:.3}"), unit(key, MetricKind::Histogram)),
+                    format!("n={} max={:.3}", stats.count, stats.max),
+                )
+            };
+            rows.push((key.clone(), value, detail));
+        }
+
+        rows.sort_by(|a, b| a.0.cmp(&b.0));
+        let titles: Vec<_> = rows.iter().map(|(key, ..)| key_text(key)).collect();
+        let title_width = titles.iter().map(String::len).max().unwrap_or(0).max(6);
+        let value_width = rows
+            .iter()
+            .map(|(_, v, _)| v.len())
+            .max()
+            .unwrap_or(0)
+            .max(5);
+
+        let mut table = format!("{:title_width$}  {:>value_width$}\n", "METRIC", "VALUE");
+        for (title, (_, value, detail)) in titles.iter().zip(&rows) {
+            let _ = writeln!(
+                table,
+                "{title:title_width$}  {value:>value_width$}  {detail}"
+            );
+        }
+        table
+    }
+}
+
+fn is_shown(names: &[String], key: &Key) -> bool {
+    names.is_empty() || names.iter().any(|n| n == key.name())
+}
+
+fn with_unit(mut value: String, unit: &str) -> String {
+    if !unit.is_empty() {
+        value.push(' ');
+        value.push_str(unit);
+    }
+    value
+}
+
+/// Prints a [`TerminalTable`] of metrics every period, without any UI.
+///
+/// This doesn't need `egui` or the [`DashboardPlugin`](crate::DashboardPlugin),
+/// so it works on headless servers and in CI.
+///
+/// With [`RegistryPlugin::with_final_flush`](crate::RegistryPlugin::with_final_flush),
+/// a last table is printed on every [`FinalFlush`], however recently the
+/// previous one was.
+pub struct TerminalMetricsPlugin {
+    period: Duration,
+    names: Vec<String>,
+    output: TerminalOutput,
+}
+
+impl Default for TerminalMetricsPlugin {
+    fn default() -> Self {
+        Self {
+            period: Duration::from_secs(5),
+            names: Vec::new(),
+            output: default(),
+        }
+    }
+}
+
+impl TerminalMetricsPlugin {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Print a table every `period`, 5 seconds by default.
+    pub fn with_period(mut self, period: Duration) -> Self {
+        self.period = period;
+        self
+    }
+
+    /// Show every series of the metrics named `name`. Without any, all
+    /// metrics are shown.
+    pub fn with_metric(mut self, name: impl Into<String>) -> Self {
+        self.names.push(name.into());
+        self
+    }
+
+    pub fn with_output(mut self, output: TerminalOutput) -> Self {
+        self.output = output;
+        self
+    }
+}
+
+impl Plugin for TerminalMetricsPlugin {
+    fn build(&self, app: &mut App) {
+        let period = self.period;
+        let output = self.output;
+        let mut last_print = Instant::now();
+        app.insert_resource(TerminalTable::new(self.names.clone()))
+            .add_histogram_consumer(
+                (move |mut table: ResMut<TerminalTable>,
+                       registry: Res<MetricsRegistry>,
+                       mut flushes: EventReader<FinalFlush>| {
+                    let print = |text: String| match output {
+                        TerminalOutput::Stdout => println!("{text}"),
+                        TerminalOutput::Log => info!("\n{text}"),
+                    };
+                    let mut flushed_current = false;
+                    for flush in flushes.read() {
+                        table.sample(&flush.registry);
+                        print(table.render(&flush.registry, last_print.elapsed()));
+                        last_print = Instant::now();
+                        flushed_current |= flush.registry.ptr_eq(&registry);
+                        if flush.reason == FinalFlushReason::Replaced {
+                            // Cursors and counter totals belong to the old registry.
+                            *table = TerminalTable::new(std::mem::take(&mut table.names));
+                        }
+                    }
+                    if flushed_current {
+                        return;
+                    }
+                    table.sample(&registry);
+                    let elapsed = last_print.elapsed();
+                    if elapsed < period {
+                        return;
+                    }
+                    last_print = Instant::now();
+                    print(table.render(&registry, elapsed));
+                })
+                .after(FinalFlushSystem),
+            );
+    }
+}
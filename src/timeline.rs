@@ -0,0 +1,186 @@
+//! A ribbon showing the app's uptime, marked with [`Annotation`] events.
+
+use crate::{locale::tr, session_info::format_duration};
+use bevy::prelude::*;
+use bevy_egui::egui::{self, Color32, Rect, Sense, Ui};
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// Marks a moment or a span of time on the [`SessionTimeline`], e.g. a level
+/// load or an alert firing.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_metrics_dashboard::{bevy_egui::egui::Color32, timeline::Annotation};
+/// fn on_level_loaded(mut annotations: EventWriter<Annotation>) {
+///     annotations.send(Annotation::new("level loaded").with_color(Color32::GREEN));
+/// }
+/// ```
+#[derive(Clone, Debug, Event)]
+pub struct Annotation {
+    pub label: String,
+    pub color: Color32,
+    /// How long the annotated span lasted, ending when the event is read.
+    /// Zero marks a single moment.
+    pub duration: Duration,
+}
+
+impl Annotation {
+    pub fn new(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            color: Color32::LIGHT_BLUE,
+            duration: Duration::ZERO,
+        }
+    }
+
+    pub fn with_color(mut self, color: Color32) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+}
+
+/// An [`Annotation`] placed on the [`SessionTimeline`].
+#[derive(Clone, Debug)]
+pub struct TimelineSegment {
+    pub label: String,
+    pub color: Color32,
+    pub start: Instant,
+    pub end: Instant,
+}
+
+/// The annotated history of the session, drawn as a ribbon at the top of
+/// every [`DashboardWindow`](crate::DashboardWindow).
+///
+/// Clicking the ribbon pauses the dashboard and jumps its plots to that
+/// moment with [`Dashboard::jump_to`](crate::Dashboard::jump_to).
+///
+/// Inserted by the [`DashboardPlugin`](crate::DashboardPlugin), which adds
+/// a segment for every [`Annotation`] event.
+#[derive(Resource)]
+pub struct SessionTimeline {
+    start: Instant,
+    segments: VecDeque<TimelineSegment>,
+    max_segments: usize,
+}
+
+impl Default for SessionTimeline {
+    fn default() -> Self {
+        Self::new(Instant::now())
+    }
+}
+
+const RIBBON_HEIGHT: f32 = 8.0;
+const MIN_SEGMENT_WIDTH: f32 = 2.0;
+
+impl SessionTimeline {
+    /// A timeline of a session that started at `start`.
+    pub fn new(start: Instant) -> Self {
+        Self {
+            start,
+            segments: default(),
+            max_segments: 1000,
+        }
+    }
+
+    /// Keep at most `max_segments`, forgetting the oldest.
+    pub fn with_max_segments(mut self, max_segments: usize) -> Self {
+        self.max_segments = max_segments;
+        let n_remove = self.segments.len().saturating_sub(max_segments);
+        self.segments.drain(..n_remove);
+        self
+    }
+
+    pub fn start(&self) -> Instant {
+        self.start
+    }
+
+    /// All segments, oldest first.
+    pub fn segments(&self) -> impl Iterator<Item = &TimelineSegment> {
+        self.segments.iter()
+    }
+
+    /// Add a segment for `annotation`, ending at `now`.
+    pub fn annotate(&mut self, annotation: &Annotation, now: Instant) {
+        let start = now.checked_sub(annotation.duration).unwrap_or(now);
+        self.push(TimelineSegment {
+            label: annotation.label.clone(),
+            color: annotation.color,
+            start: start.max(self.start),
+            end: now,
+        });
+    }
+
+    pub fn push(&mut self, segment: TimelineSegment) {
+        if self.segments.len() >= self.max_segments {
+            self.segments.pop_front();
+        }
+        if self.max_segments > 0 {
+            self.segments.push_back(segment);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.segments.clear();
+    }
+
+    /// Draw the ribbon from the start of the session until now.
+    ///
+    /// Returns the moment that was clicked, which is the start of the segment
+    /// under the pointer, if any.
+    pub fn draw(&self, ui: &mut Ui) -> Option<Instant> {
+        let uptime = self.start.elapsed().as_secs_f32().max(f32::EPSILON);
+        let (rect, response) = ui.allocate_exact_size(
+            egui::vec2(ui.available_width(), RIBBON_HEIGHT),
+            Sense::click(),
+        );
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+
+        let x_of = |time: Instant| {
+            let t = time.saturating_duration_since(self.start).as_secs_f32() / uptime;
+            rect.left() + rect.width() * t.min(1.0)
+        };
+        let pointer = response.hover_pos();
+        let mut hovered = None;
+        for segment in &self.segments {
+            let left = x_of(segment.start);
+            let right = x_of(segment.end).max(left + MIN_SEGMENT_WIDTH);
+            let segment_rect = Rect::from_x_y_ranges(left..=right, rect.y_range());
+            painter.rect_filled(segment_rect, 0.0, segment.color);
+            if pointer.is_some_and(|p| segment_rect.contains(p)) {
+                hovered = Some(segment);
+            }
+        }
+
+        let pointer_time = pointer.map(|p| {
+            let t = ((p.x - rect.left()) / rect.width()).clamp(0.0, 1.0);
+            self.start + Duration::from_secs_f32(uptime * t)
+        });
+        let target = hovered.map(|s| s.start).or(pointer_time);
+        let clicked = response.clicked();
+        if let Some(target) = target {
+            let uptime = format_duration(target.saturating_duration_since(self.start));
+            let text = match hovered {
+                Some(segment) => format!("{}: {uptime}", segment.label),
+                None => format!("{} {uptime}", tr(ui, "Uptime")),
+            };
+            response.on_hover_text_at_pointer(text);
+        }
+        target.filter(|_| clicked)
+    }
+
+    pub(crate) fn record_system(mut timeline: ResMut<Self>, mut events: EventReader<Annotation>) {
+        let now = Instant::now();
+        for annotation in events.read() {
+            timeline.annotate(annotation, now);
+        }
+    }
+}
@@ -0,0 +1,148 @@
+//! Custom formatting of metric values.
+
+use crate::registry::MetricKey;
+use bevy::{prelude::Resource, utils::HashMap};
+use metrics::Unit;
+use std::sync::Arc;
+
+/// Converts a metric value into display text.
+pub type ValueFormatter = Arc<dyn Fn(f64) -> String + Send + Sync>;
+
+/// Formatters used for latest-value labels and hover tooltips of plots.
+///
+/// A formatter registered for a specific [`MetricKey`] takes precedence over
+/// one registered for the metric's [`Unit`]. Metrics without any formatter
+/// use the default number formatting.
+///
+/// ```
+/// # use bevy_metrics_dashboard::ValueFormatters;
+/// # use bevy_metrics_dashboard::metrics::Unit;
+/// let mut formatters = ValueFormatters::default();
+/// formatters.set_for_unit(Unit::Milliseconds, |ms| {
+///     format!("{ms:.1} ms ({:.0} FPS)", 1000.0 / ms)
+/// });
+/// ```
+#[derive(Clone, Default, Resource)]
+pub struct ValueFormatters {
+    by_key: HashMap<MetricKey, ValueFormatter>,
+    by_unit: HashMap<Unit, ValueFormatter>,
+}
+
+impl ValueFormatters {
+    pub fn set_for_key(
+        &mut self,
+        key: MetricKey,
+        formatter: impl Fn(f64) -> String + Send + Sync + 'static,
+    ) {
+        self.by_key.insert(key, Arc::new(formatter));
+    }
+
+    pub fn set_for_unit(
+        &mut self,
+        unit: Unit,
+        formatter: impl Fn(f64) -> String + Send + Sync + 'static,
+    ) {
+        self.by_unit.insert(unit, Arc::new(formatter));
+    }
+
+    pub fn remove_for_key(&mut self, key: &MetricKey) -> Option<ValueFormatter> {
+        self.by_key.remove(key)
+    }
+
+    pub fn remove_for_unit(&mut self, unit: Unit) -> Option<ValueFormatter> {
+        self.by_unit.remove(&unit)
+    }
+
+    pub fn get_for_unit(&self, unit: Unit) -> Option<&ValueFormatter> {
+        self.by_unit.get(&unit)
+    }
+
+    /// Find the formatter for a metric, preferring one registered for `key`
+    /// over one registered for `unit`.
+    pub fn get(&self, key: &MetricKey, unit: Option<Unit>) -> Option<&ValueFormatter> {
+        self.by_key
+            .get(key)
+            .or_else(|| unit.and_then(|u| self.get_for_unit(u)))
+    }
+}
+
+/// How the tick labels of a plot's value axis are formatted.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum AxisFormat {
+    /// Full numbers, e.g. "1500000".
+    #[default]
+    Plain,
+    /// Numbers with metric suffixes, e.g. "1.5M", and byte units in binary
+    /// multiples, e.g. "1.5 MiB". See [`compact_number`] and
+    /// [`binary_bytes`].
+    Compact,
+}
+
+impl AxisFormat {
+    /// Format `value`, measured in `unit`, or `None` for the plot's default
+    /// formatting.
+    pub fn format(self, value: f64, unit: Option<Unit>) -> Option<String> {
+        match self {
+            Self::Plain => None,
+            Self::Compact => Some(match unit.and_then(bytes_per_unit) {
+                Some(bytes) => binary_bytes(value * bytes),
+                None => compact_number(value),
+            }),
+        }
+    }
+}
+
+/// Format `value` with a k, M, G, or T suffix and at most two decimals, e.g.
+/// "1.5M" for 1500000.
+///
+/// ```
+/// # use bevy_metrics_dashboard::value_formatters::compact_number;
+/// assert_eq!(compact_number(1_500_000.0), "1.5M");
+/// assert_eq!(compact_number(-2_000.0), "-2k");
+/// assert_eq!(compact_number(999.0), "999");
+/// ```
+pub fn compact_number(value: f64) -> String {
+    const SUFFIXES: [(f64, &str); 4] = [(1e12, "T"), (1e9, "G"), (1e6, "M"), (1e3, "k")];
+    scaled_number(value, &SUFFIXES, "")
+}
+
+/// Format a number of bytes in binary multiples, e.g. "1.5 MiB" for
+/// 1572864.
+///
+/// ```
+/// # use bevy_metrics_dashboard::value_formatters::binary_bytes;
+/// assert_eq!(binary_bytes(1_572_864.0), "1.5 MiB");
+/// assert_eq!(binary_bytes(512.0), "512 B");
+/// ```
+pub fn binary_bytes(bytes: f64) -> String {
+    const SUFFIXES: [(f64, &str); 4] = [
+        (1_099_511_627_776.0, " TiB"),
+        (1_073_741_824.0, " GiB"),
+        (1_048_576.0, " MiB"),
+        (1024.0, " KiB"),
+    ];
+    scaled_number(bytes, &SUFFIXES, " B")
+}
+
+fn scaled_number(value: f64, suffixes: &[(f64, &str)], unscaled_suffix: &str) -> String {
+    let (scale, suffix) = suffixes
+        .iter()
+        .copied()
+        .find(|&(scale, _)| value.abs() >= scale)
+        .unwrap_or((1.0, unscaled_suffix));
+    let number = format!("{:.2}", value / scale);
+    let number = number.trim_end_matches('0').trim_end_matches('.');
+    format!("{number}{suffix}")
+}
+
+/// The number of bytes in one `unit`, if it's a size.
+fn bytes_per_unit(unit: Unit) -> Option<f64> {
+    match unit {
+        Unit::Bytes => Some(1.0),
+        Unit::Kibibytes => Some(1024.0),
+        Unit::Mebibytes => Some(1_048_576.0),
+        Unit::Gibibytes => Some(1_073_741_824.0),
+        Unit::Tebibytes => Some(1_099_511_627_776.0),
+        _ => None,
+    }
+}
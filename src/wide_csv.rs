@@ -0,0 +1,282 @@
+//! Sample a chosen set of metrics on the same frames into one wide CSV, with
+//! a column per metric, e.g. for regression analysis in pandas.
+//!
+//! ```no_run
+//! # use bevy::prelude::*;
+//! # use bevy_metrics_dashboard::{
+//! #     metrics::Key, metrics_util::MetricKind, registry::MetricKey, wide_csv::WideCsvPlugin,
+//! #     RegistryPlugin,
+//! # };
+//! App::new().add_plugins((
+//!     DefaultPlugins,
+//!     RegistryPlugin::default(),
+//!     WideCsvPlugin::new()
+//!         .with_metric(MetricKey::new(Key::from_static_name("fps"), MetricKind::Gauge))
+//!         .with_metric(MetricKey::new(Key::from_static_name("entities"), MetricKind::Gauge))
+//!         .with_output_path("metrics.csv"),
+//! ));
+//! ```
+
+use crate::{
+    locale::tr,
+    metric_picker::MetricPicker,
+    plots::csv_field,
+    registry::{key_text, BucketCursor, MetricKey, MetricsRegistry},
+    HistogramConsumerAppExt,
+};
+use bevy::{core::FrameCount, prelude::*, utils::HashMap};
+use bevy_egui::egui::{self, Ui};
+use metrics::Key;
+use metrics_util::MetricKind;
+use std::{
+    fmt::Write,
+    path::{Path, PathBuf},
+    sync::atomic::Ordering,
+    time::Duration,
+};
+
+/// One line of the CSV.
+struct Row {
+    frame: u32,
+    seconds: f64,
+    /// By column. Columns added after this row was sampled are missing.
+    values: Vec<Option<f64>>,
+}
+
+/// Records the values of the chosen metrics on every sample, as the rows of a
+/// CSV with one column per metric.
+///
+/// Counters and gauges are sampled at their current value. Histograms are
+/// sampled at the mean of the samples recorded since the previous sample,
+/// and left empty if there are none.
+///
+/// Every row is kept until [`Self::reset`], so memory grows with the length
+/// of the recording.
+#[derive(Default, Resource)]
+pub struct WideCsvRecorder {
+    columns: Vec<MetricKey>,
+    rows: Vec<Row>,
+    cursors: HashMap<Key, BucketCursor>,
+    paused: bool,
+    picker: MetricPicker,
+}
+
+impl WideCsvRecorder {
+    pub fn new(metrics: impl IntoIterator<Item = MetricKey>) -> Self {
+        let mut recorder = Self::default();
+        for key in metrics {
+            recorder.add_metric(key);
+        }
+        recorder
+    }
+
+    /// The metrics sampled, in column order.
+    pub fn metrics(&self) -> &[MetricKey] {
+        &self.columns
+    }
+
+    /// Add a column for `key`, unless it already has one. Rows sampled
+    /// before have an empty cell in this column.
+    pub fn add_metric(&mut self, key: MetricKey) {
+        if !self.columns.contains(&key) {
+            self.columns.push(key);
+        }
+    }
+
+    /// Remove the column of `key`, from every row.
+    pub fn remove_metric(&mut self, key: &MetricKey) {
+        let Some(index) = self.columns.iter().position(|k| k == key) else {
+            return;
+        };
+        self.columns.remove(index);
+        for row in &mut self.rows {
+            if index < row.values.len() {
+                row.values.remove(index);
+            }
+        }
+        self.cursors.remove(&key.key);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Stop adding rows until unpaused.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    /// Add a row with the value of every metric in `registry`, sampled on
+    /// `frame` at `timestamp` since the start of the session.
+    pub fn sample(&mut self, registry: &MetricsRegistry, frame: u32, timestamp: Duration) {
+        if self.paused || self.columns.is_empty() {
+            return;
+        }
+        let values = self
+            .columns
+            .iter()
+            .map(|key| match key.kind {
+                MetricKind::Counter => {
+                    let counter = registry.get_or_create_counter(&key.key);
+                    Some(counter.load(Ordering::Relaxed) as f64)
+                }
+                MetricKind::Gauge => {
+                    let gauge = registry.get_or_create_gauge(&key.key);
+                    Some(f64::from_bits(gauge.load(Ordering::Relaxed)))
+                }
+                MetricKind::Histogram => {
+                    let bucket = registry.get_or_create_histogram(&key.key);
+                    let cursor = self
+                        .cursors
+                        .entry(key.key.clone())
+                        .or_insert_with(|| BucketCursor::new(registry));
+                    let (mut count, mut sum) = (0, 0.0);
+                    cursor.read_new(&bucket, |value| {
+                        count += 1;
+                        sum += value;
+                    });
+                    (count > 0).then(|| sum / count as f64)
+                }
+            })
+            .collect();
+        self.rows.push(Row {
+            frame,
+            seconds: timestamp.as_secs_f64(),
+            values,
+        });
+    }
+
+    /// The number of rows recorded.
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Everything recorded so far, with a "frame,seconds" column followed by
+    /// one column per metric, named like `name{k=v}`.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("frame,seconds");
+        for key in &self.columns {
+            let _ = write!(csv, ",{}", csv_field(&key_text(&key.key)));
+        }
+        csv.push('\n');
+        for row in &self.rows {
+            let _ = write!(csv, "{},{}", row.frame, row.seconds);
+            for i in 0..self.columns.len() {
+                csv.push(',');
+                if let Some(value) = row.values.get(i).copied().flatten() {
+                    let _ = write!(csv, "{value}");
+                }
+            }
+            csv.push('\n');
+        }
+        csv
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_csv())
+    }
+
+    /// Forget every row recorded so far, keeping the columns.
+    pub fn reset(&mut self) {
+        self.rows.clear();
+    }
+
+    /// Draw controls to choose the metrics, pause, and copy the CSV.
+    pub fn configure_ui(&mut self, registry: &MetricsRegistry, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.paused, tr(ui, "Pause"));
+            ui.label(format!("{} {}", self.rows.len(), tr(ui, "rows")));
+            if ui.button(tr(ui, "Clear")).clicked() {
+                self.reset();
+            }
+            if ui
+                .button(tr(ui, "Copy CSV"))
+                .on_hover_text(tr(ui, "Copy every row to the clipboard as CSV"))
+                .clicked()
+            {
+                ui.ctx().copy_text(self.to_csv());
+            }
+        });
+        let mut remove = None;
+        for key in &self.columns {
+            ui.horizontal(|ui| {
+                if ui.small_button("🗙").clicked() {
+                    remove = Some(key.clone());
+                }
+                ui.label(key.title(None, 0));
+            });
+        }
+        if let Some(key) = remove {
+            self.remove_metric(&key);
+        }
+        egui::CollapsingHeader::new(tr(ui, "Add Column")).show(ui, |ui| {
+            if let Some(picked) = self.picker.draw(registry, ui) {
+                self.add_metric(picked.key);
+            }
+        });
+    }
+
+    fn sample_system(
+        mut recorder: ResMut<Self>,
+        registry: Res<MetricsRegistry>,
+        frame: Res<FrameCount>,
+        time: Res<Time<Real>>,
+    ) {
+        recorder.sample(&registry, frame.0, time.elapsed());
+    }
+}
+
+/// Samples the metrics of the [`WideCsvRecorder`] resource once per frame,
+/// optionally saving the CSV to a file when the app exits.
+///
+/// Timestamps are the [`Time<Real>`] elapsed since startup.
+#[derive(Default)]
+pub struct WideCsvPlugin {
+    metrics: Vec<MetricKey>,
+    output_path: Option<PathBuf>,
+}
+
+impl WideCsvPlugin {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a column for `key` from the start. More can be added to the
+    /// [`WideCsvRecorder`] later.
+    pub fn with_metric(mut self, key: MetricKey) -> Self {
+        self.metrics.push(key);
+        self
+    }
+
+    /// Save the CSV to `path` on [`AppExit`].
+    pub fn with_output_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.output_path = Some(path.into());
+        self
+    }
+}
+
+impl Plugin for WideCsvPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(WideCsvRecorder::new(self.metrics.iter().cloned()))
+            .add_histogram_consumer(WideCsvRecorder::sample_system);
+
+        if let Some(path) = self.output_path.clone() {
+            app.add_systems(
+                Last,
+                (move |mut exits: EventReader<AppExit>, recorder: Res<WideCsvRecorder>| {
+                    if exits.read().next().is_none() {
+                        return;
+                    }
+                    if let Err(e) = recorder.save(&path) {
+                        error!("Failed to save CSV to {}: {e}", path.display());
+                    }
+                })
+                .after(WideCsvRecorder::sample_system),
+            );
+        }
+    }
+}
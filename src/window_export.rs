@@ -0,0 +1,324 @@
+//! Export and import the plots of a single dashboard window, and the settings
+//! of a namespace viewer.
+//!
+//! This lets you move one curated window between projects, e.g. by pasting
+//! its JSON into another app.
+
+use crate::{
+    locale::tr,
+    metric_kind_str,
+    namespace_tree::{NamespaceTreeWindow, TreeGrouping},
+    parse_metric_kind,
+    plots::MetricPlotConfig,
+    registry::{MetricKey, MetricsRegistry},
+    Dashboard, DashboardWindow, PlotTemplates,
+};
+use bevy::log::warn;
+use bevy_egui::egui::{TextEdit, Ui};
+use metrics::{Key, Label, Unit};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// The serializable plots and configs of one [`Dashboard`].
+#[derive(Clone, Default, Deserialize, Serialize)]
+pub struct DashboardExport {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub global_window_size: Option<usize>,
+    /// Where the window was, in `egui` points.
+    #[serde(default)]
+    pub position: Option<[f32; 2]>,
+    /// The size of the window's contents, in `egui` points.
+    #[serde(default)]
+    pub size: Option<[f32; 2]>,
+    pub plots: Vec<PlotExport>,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct PlotExport {
+    pub name: String,
+    /// One of "counter", "gauge", or "histogram".
+    pub kind: String,
+    /// Labels in the order they were recorded.
+    #[serde(default)]
+    pub labels: Vec<(String, String)>,
+    #[serde(default)]
+    pub unit: Option<String>,
+    #[serde(default)]
+    pub note: String,
+    pub config: MetricPlotConfig,
+}
+
+impl DashboardExport {
+    pub fn from_dashboard(dashboard: &Dashboard) -> Self {
+        let plots = dashboard
+            .plots()
+            .iter()
+            .map(|plot| PlotExport {
+                name: plot.key().key.name().to_owned(),
+                kind: metric_kind_str(plot.key().kind).to_owned(),
+                labels: plot
+                    .key()
+                    .key
+                    .labels()
+                    .map(|l| (l.key().to_owned(), l.value().to_owned()))
+                    .collect(),
+                unit: plot.unit().map(|u| u.as_str().to_owned()),
+                note: plot.note().to_owned(),
+                config: plot.clone_config(),
+            })
+            .collect();
+        Self {
+            title: None,
+            global_window_size: dashboard.config().global_window_size,
+            position: None,
+            size: None,
+            plots,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    /// Add all exported plots to `dashboard`.
+    ///
+    /// Plots of unknown metric kinds, or whose config doesn't match their
+    /// kind, are skipped. Custom plot kinds are found in `templates`, see
+    /// [`CustomPlotConfig::resolve_factory`](crate::plots::CustomPlotConfig::resolve_factory).
+    pub fn add_to_dashboard(
+        &self,
+        registry: &MetricsRegistry,
+        templates: &PlotTemplates,
+        dashboard: &mut Dashboard,
+    ) {
+        if self.global_window_size.is_some() {
+            dashboard.config_mut().global_window_size = self.global_window_size;
+        }
+        for plot in &self.plots {
+            let Some(kind) = parse_metric_kind(&plot.kind) else {
+                warn!(
+                    "Skipping plot of {} with unknown kind {}",
+                    plot.name, plot.kind
+                );
+                continue;
+            };
+            if !plot.config.supports_kind(kind) {
+                warn!("Skipping plot of {} with mismatched config", plot.name);
+                continue;
+            }
+            let labels: Vec<_> = plot
+                .labels
+                .iter()
+                .map(|(k, v)| Label::new(k.clone(), v.clone()))
+                .collect();
+            let key = MetricKey::new(Key::from_parts(plot.name.clone(), labels), kind);
+            let unit = plot.unit.as_deref().and_then(Unit::from_string);
+            let mut config = plot.config.clone();
+            if let MetricPlotConfig::Custom(custom) = &mut config {
+                if !custom.resolve_factory(templates) {
+                    warn!(
+                        "Plotting {} with the default config: no plot kind {} is registered",
+                        plot.name, custom.name
+                    );
+                }
+            }
+            if let MetricPlotConfig::Histogram(histogram) = &mut config {
+                if histogram.buckets.sanitize() {
+                    warn!("Fixed invalid histogram buckets of {}", plot.name);
+                }
+            }
+            dashboard
+                .add_plot(registry, key, unit, config)
+                .set_note(plot.note.clone());
+        }
+    }
+}
+
+impl DashboardWindow {
+    /// Export the plots and configs of only this window, and where it was
+    /// last drawn.
+    pub fn export(&self) -> DashboardExport {
+        DashboardExport {
+            title: Some(self.title().to_owned()),
+            position: self
+                .rect()
+                .map(|r| r.min)
+                .or(self.default_pos())
+                .map(Into::into),
+            size: self.content_size().or(self.default_size()).map(Into::into),
+            ..DashboardExport::from_dashboard(self.dashboard())
+        }
+    }
+
+    /// Create a window from an export, titled "Dashboard" if the export has no
+    /// title.
+    ///
+    /// The window is placed where it was exported from, unless `egui` already
+    /// remembers a position for a window with the same title.
+    pub fn import(
+        registry: &MetricsRegistry,
+        templates: &PlotTemplates,
+        export: &DashboardExport,
+    ) -> Self {
+        let title = export.title.as_deref().unwrap_or("Dashboard");
+        let mut window = Self::new(title);
+        if let Some(pos) = export.position {
+            window.set_default_pos(pos.into());
+        }
+        if let Some(size) = export.size {
+            window.set_default_size(size.into());
+        }
+        export.add_to_dashboard(registry, templates, window.dashboard_mut());
+        window
+    }
+}
+
+/// The serializable settings of a [`NamespaceTreeWindow`].
+#[derive(Clone, Deserialize, Serialize)]
+pub struct NamespaceTreeExport {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub filter: String,
+    #[serde(default)]
+    pub grouping: TreeGrouping,
+    /// Seconds between refreshes, or `None` to only refresh manually.
+    #[serde(default = "default_refresh_period_secs")]
+    pub refresh_period_secs: Option<f64>,
+}
+
+fn default_refresh_period_secs() -> Option<f64> {
+    Some(5.0)
+}
+
+impl NamespaceTreeExport {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+}
+
+impl NamespaceTreeWindow {
+    pub fn export(&self) -> NamespaceTreeExport {
+        let tree = self.tree();
+        NamespaceTreeExport {
+            title: Some(self.title().to_owned()),
+            filter: tree.filter().to_owned(),
+            grouping: tree.grouping(),
+            refresh_period_secs: tree.refresh_period().map(|p| p.as_secs_f64()),
+        }
+    }
+
+    /// Create a window from an export, titled "Namespace Viewer" if the
+    /// export has no title.
+    pub fn import(export: &NamespaceTreeExport) -> Self {
+        let title = export.title.as_deref().unwrap_or("Namespace Viewer");
+        let mut window = Self::new(title);
+        window.set_filter(export.filter.clone());
+        window.set_grouping(export.grouping);
+        match export.refresh_period_secs.map(Duration::try_from_secs_f64) {
+            Some(Ok(period)) => window.set_refresh_period(period),
+            Some(Err(e)) => warn!("Ignoring refresh period of {title}: {e}"),
+            None => window.set_manual_refresh(),
+        }
+        window
+    }
+}
+
+/// The windows of a layout file.
+#[derive(Clone, Default, Deserialize, Serialize)]
+pub struct LayoutExport {
+    #[serde(default)]
+    pub dashboards: Vec<DashboardExport>,
+    #[serde(default)]
+    pub namespace_trees: Vec<NamespaceTreeExport>,
+}
+
+impl LayoutExport {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    /// Parse a layout, or a [`DashboardExport`] or an array of them.
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        if let Ok(dashboards) = serde_json::from_str::<Vec<DashboardExport>>(json) {
+            return Ok(Self {
+                dashboards,
+                ..Default::default()
+            });
+        }
+        if let Ok(dashboard) = DashboardExport::from_json(json) {
+            return Ok(Self {
+                dashboards: vec![dashboard],
+                ..Default::default()
+            });
+        }
+        serde_json::from_str(json)
+    }
+}
+
+/// "Export Window" and "Import Window" actions, using the clipboard for
+/// export and a pasted JSON string for import.
+pub(crate) fn export_import_ui(
+    dashboard: &mut Dashboard,
+    registry: &MetricsRegistry,
+    templates: &PlotTemplates,
+    ui: &mut Ui,
+) {
+    let input_id = ui.make_persistent_id("import_window_input");
+    let mut input: Option<String> = ui.data_mut(|d| d.get_temp(input_id));
+
+    ui.horizontal(|ui| {
+        if ui
+            .button(tr(ui, "Export Window"))
+            .on_hover_text(tr(ui, "Copy this window's plots to the clipboard as JSON"))
+            .clicked()
+        {
+            ui.ctx()
+                .copy_text(DashboardExport::from_dashboard(dashboard).to_json());
+        }
+        if ui.button(tr(ui, "Import Window")).clicked() {
+            input = input.is_none().then(String::new);
+        }
+    });
+
+    if let Some(text) = &mut input {
+        ui.add(
+            TextEdit::multiline(text)
+                .hint_text(tr(ui, "Paste exported JSON"))
+                .desired_rows(3),
+        );
+        let mut done = false;
+        ui.horizontal(|ui| {
+            if ui.button(tr(ui, "Load")).clicked() {
+                match DashboardExport::from_json(text) {
+                    Ok(export) => {
+                        export.add_to_dashboard(registry, templates, dashboard);
+                        done = true;
+                    }
+                    Err(e) => warn!("Failed to import window: {e}"),
+                }
+            }
+            if ui.button(tr(ui, "Cancel")).clicked() {
+                done = true;
+            }
+        });
+        if done {
+            input = None;
+        }
+    }
+
+    ui.data_mut(|d| match input {
+        Some(text) => d.insert_temp(input_id, text),
+        None => d.remove::<String>(input_id),
+    });
+}
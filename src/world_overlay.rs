@@ -0,0 +1,386 @@
+//! Draw metrics in the 3D scene, at the entities they're labeled with, e.g.
+//! the mesh time of each chunk or the AI time of each NPC.
+//!
+//! Each series of an overlaid metric is drawn at the entity named by one of
+//! its labels, as a bar drawn with [`Gizmos`] and/or a floating value drawn
+//! with `egui`.
+//!
+//! ```no_run
+//! # use bevy::prelude::*;
+//! # use bevy_metrics_dashboard::{
+//! #     bevy_egui::EguiPlugin,
+//! #     metrics_util::MetricKind,
+//! #     world_overlay::{WorldOverlay, WorldOverlayPlugin},
+//! #     RegistryPlugin,
+//! # };
+//! fn think(npcs: Query<Entity, With<Name>>) {
+//!     for npc in &npcs {
+//!         // The default `entity` label is formatted like `Entity`'s `Display`.
+//!         metrics::histogram!("npc_think_time", "entity" => npc.to_string()).record(0.1);
+//!     }
+//! }
+//!
+//! App::new()
+//!     .add_plugins((
+//!         DefaultPlugins,
+//!         EguiPlugin,
+//!         RegistryPlugin::default(),
+//!         WorldOverlayPlugin::new()
+//!             .with_overlay(WorldOverlay::new("npc_think_time", MetricKind::Histogram)),
+//!     ))
+//!     .add_systems(Update, think);
+//! ```
+
+use crate::{
+    dashboard_window::parse_entity,
+    registry::{BucketCursor, DescriptionKey, MetricKey, MetricsRegistry},
+    unit_str, HistogramConsumerAppExt, Palette, ValueFormatters,
+};
+use bevy::{ecs::system::SystemParam, prelude::*, utils::HashMap};
+use bevy_egui::{egui, EguiContexts};
+use metrics::Key;
+use metrics_util::MetricKind;
+use std::sync::atomic::Ordering;
+
+/// How a [`WorldOverlay`] draws each value.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum OverlayStyle {
+    #[default]
+    BarAndText,
+    Bar,
+    Text,
+}
+
+/// A metric drawn at the world position of each entity it's labeled with.
+#[derive(Clone, Debug)]
+pub struct WorldOverlay {
+    pub name: String,
+    pub kind: MetricKind,
+    /// The label naming the entity of each series, `entity` by default.
+    ///
+    /// Values formatted like an [`Entity`], e.g. `12v1`, are looked up
+    /// directly. Any other value is matched against the entities' [`Name`].
+    pub label: String,
+    pub style: OverlayStyle,
+    /// The value drawn as a full bar. Values above are drawn as a full bar in
+    /// red. If `None`, the largest value of the overlay is the full bar.
+    pub max: Option<f64>,
+    /// The height of a full bar in world units.
+    pub bar_height: f32,
+    /// Where the overlay is drawn relative to the entity's translation.
+    pub offset: Vec3,
+    /// If `None`, the [`Palette`] color of the metric is used.
+    pub color: Option<Color>,
+}
+
+impl WorldOverlay {
+    pub fn new(name: impl Into<String>, kind: MetricKind) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+            label: "entity".into(),
+            style: default(),
+            max: None,
+            bar_height: 1.0,
+            offset: Vec3::new(0.0, 1.0, 0.0),
+            color: None,
+        }
+    }
+
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = label.into();
+        self
+    }
+
+    pub fn with_style(mut self, style: OverlayStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn with_max(mut self, max: f64) -> Self {
+        self.max = Some(max);
+        self
+    }
+
+    pub fn with_bar_height(mut self, height: f32) -> Self {
+        self.bar_height = height;
+        self
+    }
+
+    pub fn with_offset(mut self, offset: Vec3) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+}
+
+/// The latest value of one series of a [`WorldOverlay`].
+struct OverlayValue {
+    key: MetricKey,
+    /// The value of the overlay's label.
+    target: String,
+    value: f64,
+}
+
+/// The [`WorldOverlay`]s drawn by the [`WorldOverlayPlugin`], with the
+/// latest value of each of their series.
+///
+/// Counters and gauges are sampled at their current value. Histograms are
+/// sampled at the mean of the samples recorded since the previous sample,
+/// keeping the previous mean on frames without samples.
+#[derive(Default, Resource)]
+pub struct WorldOverlays {
+    overlays: Vec<WorldOverlay>,
+    /// By overlay.
+    values: Vec<Vec<OverlayValue>>,
+    cursors: HashMap<Key, BucketCursor>,
+    camera: Option<Entity>,
+}
+
+impl WorldOverlays {
+    pub fn new(overlays: impl IntoIterator<Item = WorldOverlay>) -> Self {
+        let mut this = Self::default();
+        for overlay in overlays {
+            this.add(overlay);
+        }
+        this
+    }
+
+    pub fn overlays(&self) -> &[WorldOverlay] {
+        &self.overlays
+    }
+
+    /// Add `overlay`, replacing any overlay of the same metric.
+    pub fn add(&mut self, overlay: WorldOverlay) {
+        self.remove(&overlay.name, overlay.kind);
+        self.overlays.push(overlay);
+        self.values.push(Vec::new());
+    }
+
+    pub fn remove(&mut self, name: &str, kind: MetricKind) {
+        if let Some(i) = self
+            .overlays
+            .iter()
+            .position(|o| o.name == name && o.kind == kind)
+        {
+            self.overlays.remove(i);
+            for value in self.values.remove(i) {
+                self.cursors.remove(&value.key.key);
+            }
+        }
+    }
+
+    /// The camera that values are projected with. If `None`, the active
+    /// camera with the highest order is used.
+    pub fn camera(&self) -> Option<Entity> {
+        self.camera
+    }
+
+    pub fn set_camera(&mut self, camera: Option<Entity>) {
+        self.camera = camera;
+    }
+
+    /// The latest value of the series of metric `name` labeled with `target`.
+    pub fn value(&self, name: &str, target: &str) -> Option<f64> {
+        let i = self.overlays.iter().position(|o| o.name == name)?;
+        let value = self.values[i].iter().find(|v| v.target == target)?;
+        Some(value.value)
+    }
+
+    /// Read the latest value of every series of every overlay from
+    /// `registry`.
+    pub fn sample(&mut self, registry: &MetricsRegistry) {
+        for (overlay, values) in self.overlays.iter().zip(&mut self.values) {
+            let mut sampled = Vec::new();
+            for result in registry.find_by_name(&overlay.name, overlay.kind) {
+                let key = result.key;
+                let Some(target) = key
+                    .key
+                    .labels()
+                    .find(|l| l.key() == overlay.label)
+                    .map(|l| l.value().to_owned())
+                else {
+                    continue;
+                };
+                let previous = values.iter().find(|v| v.key == key).map(|v| v.value);
+                let value = match key.kind {
+                    MetricKind::Counter => {
+                        let counter = registry.get_or_create_counter(&key.key);
+                        Some(counter.load(Ordering::Relaxed) as f64)
+                    }
+                    MetricKind::Gauge => {
+                        let gauge = registry.get_or_create_gauge(&key.key);
+                        Some(f64::from_bits(gauge.load(Ordering::Relaxed)))
+                    }
+                    MetricKind::Histogram => {
+                        let bucket = registry.get_or_create_histogram(&key.key);
+                        let cursor = self
+                            .cursors
+                            .entry(key.key.clone())
+                            .or_insert_with(|| BucketCursor::new(registry));
+                        let (mut count, mut sum) = (0, 0.0);
+                        cursor.read_new(&bucket, |value| {
+                            count += 1;
+                            sum += value;
+                        });
+                        if count > 0 {
+                            Some(sum / count as f64)
+                        } else {
+                            previous
+                        }
+                    }
+                };
+                if let Some(value) = value {
+                    sampled.push(OverlayValue { key, target, value });
+                }
+            }
+            *values = sampled;
+        }
+    }
+
+    fn sample_system(mut overlays: ResMut<Self>, registry: Res<MetricsRegistry>) {
+        overlays.sample(&registry);
+    }
+
+    fn draw_system(
+        overlays: Res<Self>,
+        registry: Res<MetricsRegistry>,
+        palette: Option<Res<Palette>>,
+        formatters: Option<Res<ValueFormatters>>,
+        scene: OverlayScene,
+        mut gizmos: Gizmos,
+        mut contexts: EguiContexts,
+    ) {
+        if overlays.overlays.is_empty() {
+            return;
+        }
+        let OverlayScene { cameras, targets } = scene;
+        let default_palette = Palette::default();
+        let palette = palette.as_deref().unwrap_or(&default_palette);
+        let camera = match overlays.camera {
+            Some(camera) => cameras.get(camera).ok(),
+            None => cameras
+                .iter()
+                .filter(|(_, camera, _)| camera.is_active)
+                .max_by_key(|(_, camera, _)| camera.order),
+        };
+        let painter = contexts
+            .try_ctx_mut()
+            .map(|ctx| ctx.layer_painter(egui::LayerId::background()));
+
+        let names: HashMap<&str, Entity> = targets
+            .iter()
+            .filter_map(|(entity, _, name)| Some((name?.as_str(), entity)))
+            .collect();
+        let position = |target: &str| {
+            let entity = parse_entity(target).or_else(|| names.get(target).copied())?;
+            targets
+                .get(entity)
+                .ok()
+                .map(|(_, transform, _)| transform.translation())
+        };
+
+        for (overlay, values) in overlays.overlays.iter().zip(&overlays.values) {
+            let max = overlay
+                .max
+                .unwrap_or_else(|| values.iter().map(|v| v.value).fold(f64::EPSILON, f64::max));
+            let unit = values.first().and_then(|v| {
+                let description = registry.get_description(&DescriptionKey::from(&v.key))?;
+                description.unit
+            });
+            for value in values {
+                let Some(origin) = position(&value.target) else {
+                    continue;
+                };
+                let origin = origin + overlay.offset;
+                let color = overlay.color.unwrap_or_else(|| {
+                    let c = palette.color_for(&value.key);
+                    Color::srgba_u8(c.r(), c.g(), c.b(), c.a())
+                });
+
+                let t = (value.value / max.max(f64::EPSILON)).max(0.0);
+                let top = origin + Vec3::Y * overlay.bar_height * t.min(1.0) as f32;
+                if overlay.style != OverlayStyle::Text {
+                    let color = if t > 1.0 {
+                        Color::srgb(1.0, 0.2, 0.2)
+                    } else {
+                        color
+                    };
+                    gizmos.line(origin, top, color);
+                    let full = origin + Vec3::Y * overlay.bar_height;
+                    gizmos.line(top, full, color.with_alpha(0.2));
+                }
+
+                if overlay.style == OverlayStyle::Bar {
+                    continue;
+                }
+                let (Some((_, camera, camera_transform)), Some(painter)) = (camera, &painter)
+                else {
+                    continue;
+                };
+                let Ok(viewport_pos) = camera.world_to_viewport(camera_transform, top) else {
+                    continue;
+                };
+                let offset = camera.logical_viewport_rect().map_or(Vec2::ZERO, |r| r.min);
+                let scale = camera.target_scaling_factor().unwrap_or(1.0)
+                    / painter.ctx().pixels_per_point();
+                let pos = (viewport_pos + offset) * scale;
+
+                let text = match formatters.as_ref().and_then(|f| f.get(&value.key, unit)) {
+                    Some(formatter) => formatter(value.value),
+                    None => format!("{:.3} {}", value.value, unit.map_or("", unit_str)),
+                };
+                let c = color.to_srgba().to_u8_array();
+                painter.text(
+                    egui::pos2(pos.x, pos.y),
+                    egui::Align2::CENTER_BOTTOM,
+                    text,
+                    egui::FontId::monospace(12.0),
+                    egui::Color32::from_rgba_unmultiplied(c[0], c[1], c[2], c[3]),
+                );
+            }
+        }
+    }
+}
+
+/// The cameras and entities that [`WorldOverlays`] are drawn with.
+#[derive(SystemParam)]
+struct OverlayScene<'w, 's> {
+    cameras: Query<'w, 's, (Entity, &'static Camera, &'static GlobalTransform)>,
+    targets: Query<'w, 's, (Entity, &'static GlobalTransform, Option<&'static Name>)>,
+}
+
+/// Samples the [`WorldOverlays`] resource once per frame, and draws them.
+///
+/// Bars are drawn with [`Gizmos`], so their appearance follows the default
+/// [`GizmoConfigGroup`]. Text is drawn on the primary window's `egui`
+/// context, below every window.
+#[derive(Default)]
+pub struct WorldOverlayPlugin {
+    overlays: Vec<WorldOverlay>,
+}
+
+impl WorldOverlayPlugin {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Draw `overlay` from the start. More can be added to the
+    /// [`WorldOverlays`] later.
+    pub fn with_overlay(mut self, overlay: WorldOverlay) -> Self {
+        self.overlays.push(overlay);
+        self
+    }
+}
+
+impl Plugin for WorldOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(WorldOverlays::new(self.overlays.iter().cloned()))
+            .add_systems(Update, WorldOverlays::draw_system)
+            .add_histogram_consumer(WorldOverlays::sample_system);
+    }
+}
@@ -0,0 +1,231 @@
+use bevy::{log::warn, utils::HashMap};
+use metrics::{CounterFn, GaugeFn};
+use metrics_util::registry::Generational;
+use std::{
+    cell::RefCell,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+/// Which metric handles buffer their writes on the writing thread until the
+/// next [`MetricsRegistry::flush_coalesced_writes`](crate::registry::MetricsRegistry::flush_coalesced_writes).
+///
+/// This avoids contention on shared atomics when the same metric is written
+/// many times per frame, e.g. from inner loops of parallel systems. Counter
+/// increments are summed, and only the last value set on a gauge is kept.
+/// When several threads set the same gauge in one frame, which of them wins
+/// is unspecified.
+///
+/// Coalescing only applies to handles registered after it's enabled, and the
+/// savings come from reusing a handle, so hot loops should keep the result of
+/// [`counter!`](metrics::counter) or [`gauge!`](metrics::gauge) instead of
+/// calling the macro on every iteration. Histograms are never coalesced.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct WriteCoalescing {
+    pub counters: bool,
+    pub gauges: bool,
+}
+
+impl WriteCoalescing {
+    /// Coalesce writes to counters and gauges.
+    pub fn all() -> Self {
+        Self {
+            counters: true,
+            gauges: true,
+        }
+    }
+}
+
+/// The registry's atomic of a metric, counting the writes flushed to it.
+pub(crate) type Target = Generational<Arc<AtomicU64>>;
+
+#[derive(Clone, Copy)]
+enum GaugeWrite {
+    Set(f64),
+    Add(f64),
+}
+
+#[derive(Default)]
+struct WriteBuffer {
+    counters: HashMap<usize, (Target, u64)>,
+    gauges: HashMap<usize, (Target, Option<GaugeWrite>)>,
+}
+
+impl WriteBuffer {
+    /// The number of metrics with writes that haven't been flushed.
+    fn pending(&self) -> usize {
+        let counters = self.counters.values().filter(|(_, p)| *p > 0).count();
+        let gauges = self.gauges.values().filter(|(_, p)| p.is_some()).count();
+        counters + gauges
+    }
+
+    /// Write and forget every buffered value, so metrics removed from the
+    /// registry aren't kept alive by the buffer.
+    fn flush(&mut self) {
+        for (target, pending) in self.counters.drain().map(|(_, entry)| entry) {
+            flush_counter(&target, pending);
+        }
+        for (target, pending) in self.gauges.drain().map(|(_, entry)| entry) {
+            match pending {
+                Some(GaugeWrite::Set(value)) => {
+                    target.with_increment(|t| t.store(value.to_bits(), Ordering::Release));
+                }
+                Some(GaugeWrite::Add(delta)) => target.with_increment(|t| add_to_gauge(t, delta)),
+                None => {}
+            }
+        }
+    }
+}
+
+fn flush_counter(target: &Target, pending: u64) {
+    if pending > 0 {
+        target.with_increment(|t| t.fetch_add(pending, Ordering::AcqRel));
+    }
+}
+
+fn add_to_gauge(target: &AtomicU64, delta: f64) {
+    let _ = target.fetch_update(Ordering::AcqRel, Ordering::Relaxed, |bits| {
+        Some((f64::from_bits(bits) + delta).to_bits())
+    });
+}
+
+fn target_id(target: &Target) -> usize {
+    Arc::as_ptr(target.get_inner()) as usize
+}
+
+thread_local! {
+    /// This thread's buffer for each registry, by `CoalescedWrites::id`.
+    static BUFFERS: RefCell<Vec<(u64, Arc<Mutex<WriteBuffer>>)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// The buffers of every thread that has written to a registry's coalesced
+/// handles.
+pub(crate) struct CoalescedWrites {
+    id: u64,
+    buffers: Mutex<Vec<Arc<Mutex<WriteBuffer>>>>,
+}
+
+impl CoalescedWrites {
+    pub fn new() -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            buffers: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn with_buffer(&self, f: impl FnOnce(&mut WriteBuffer)) {
+        BUFFERS.with_borrow_mut(|buffers| {
+            // Forget the buffers of dropped registries.
+            buffers.retain(|(_, buffer)| Arc::strong_count(buffer) > 1);
+            let buffer = match buffers.iter().find(|(id, _)| *id == self.id) {
+                Some((_, buffer)) => buffer,
+                None => {
+                    let buffer = Arc::<Mutex<WriteBuffer>>::default();
+                    self.buffers.lock().unwrap().push(buffer.clone());
+                    buffers.push((self.id, buffer));
+                    &buffers.last().unwrap().1
+                }
+            };
+            // Only contended while flushing.
+            f(&mut buffer.lock().unwrap());
+        });
+    }
+
+    /// Write all buffered values to their atomics.
+    pub fn flush(&self) {
+        let mut buffers = self.buffers.lock().unwrap();
+        for buffer in buffers.iter() {
+            buffer.lock().unwrap().flush();
+        }
+        // Forget the buffers of threads that have exited.
+        buffers.retain(|buffer| Arc::strong_count(buffer) > 1);
+    }
+
+    /// The number of metrics with writes that haven't been flushed, summed
+    /// over all threads.
+    pub fn pending(&self) -> usize {
+        let buffers = self.buffers.lock().unwrap();
+        buffers.iter().map(|b| b.lock().unwrap().pending()).sum()
+    }
+}
+
+impl Drop for CoalescedWrites {
+    fn drop(&mut self) {
+        let pending = self.pending();
+        if pending > 0 {
+            warn!(
+                "Dropped a registry with unflushed writes to {pending} coalesced metrics. \
+                Call MetricsRegistry::flush_coalesced_writes before replacing it."
+            );
+        }
+    }
+}
+
+pub(crate) struct CoalescedCounter {
+    pub writes: Arc<CoalescedWrites>,
+    pub target: Target,
+}
+
+impl CounterFn for CoalescedCounter {
+    fn increment(&self, value: u64) {
+        self.writes.with_buffer(|buffer| {
+            buffer
+                .counters
+                .entry(target_id(&self.target))
+                .or_insert_with(|| (self.target.clone(), 0))
+                .1 += value;
+        });
+    }
+
+    fn absolute(&self, value: u64) {
+        self.writes.with_buffer(|buffer| {
+            // Increments that come before this must not be added on top of it.
+            if let Some((target, pending)) = buffer.counters.remove(&target_id(&self.target)) {
+                flush_counter(&target, pending);
+            }
+            (self.target).with_increment(|t| t.fetch_max(value, Ordering::AcqRel));
+        });
+    }
+}
+
+pub(crate) struct CoalescedGauge {
+    pub writes: Arc<CoalescedWrites>,
+    pub target: Target,
+}
+
+impl CoalescedGauge {
+    fn write(&self, write: GaugeWrite) {
+        self.writes.with_buffer(|buffer| {
+            let (_, pending) = buffer
+                .gauges
+                .entry(target_id(&self.target))
+                .or_insert_with(|| (self.target.clone(), None));
+            *pending = Some(match (*pending, write) {
+                (Some(GaugeWrite::Set(value)), GaugeWrite::Add(delta)) => {
+                    GaugeWrite::Set(value + delta)
+                }
+                (Some(GaugeWrite::Add(sum)), GaugeWrite::Add(delta)) => {
+                    GaugeWrite::Add(sum + delta)
+                }
+                (_, write) => write,
+            });
+        });
+    }
+}
+
+impl GaugeFn for CoalescedGauge {
+    fn increment(&self, value: f64) {
+        self.write(GaugeWrite::Add(value));
+    }
+
+    fn decrement(&self, value: f64) {
+        self.write(GaugeWrite::Add(-value));
+    }
+
+    fn set(&self, value: f64) {
+        self.write(GaugeWrite::Set(value));
+    }
+}
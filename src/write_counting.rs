@@ -0,0 +1,202 @@
+use crate::registry::MetricKey;
+use bevy::utils::HashMap;
+use metrics::{Counter, CounterFn, Gauge, GaugeFn, Histogram, HistogramFn};
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    Arc, RwLock,
+};
+
+/// How often a metric was written, as sampled by
+/// [`MetricsRegistry::sample_write_counts`](crate::registry::MetricsRegistry::sample_write_counts).
+///
+/// Every call to a handle counts as one write, e.g. `counter.increment(5)` or
+/// `histogram.record(x)`, regardless of the value.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct WriteStats {
+    /// The number of writes in the latest sampled frame.
+    pub writes_per_frame: u64,
+    /// The most writes in any sampled frame.
+    pub max_writes_per_frame: u64,
+    /// The number of consecutive samples, up to the latest, without writes.
+    pub idle_samples: u64,
+    /// The number of frames sampled since the metric was registered.
+    pub samples: u64,
+}
+
+/// When dashboards flag a metric's [`WriteStats`] as suspicious, see
+/// [`DashboardConfig::write_diagnostics`](crate::DashboardConfig::write_diagnostics).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct WriteDiagnostics {
+    /// Flag metrics written at least this many times in a sampled frame.
+    pub hot_writes_per_frame: u64,
+    /// Flag metrics without writes in this many consecutive samples.
+    pub dead_after_samples: u64,
+}
+
+impl Default for WriteDiagnostics {
+    fn default() -> Self {
+        Self {
+            hot_writes_per_frame: 1000,
+            dead_after_samples: 30,
+        }
+    }
+}
+
+impl WriteDiagnostics {
+    pub fn is_hot(&self, stats: &WriteStats) -> bool {
+        stats.writes_per_frame >= self.hot_writes_per_frame
+    }
+
+    pub fn is_dead(&self, stats: &WriteStats) -> bool {
+        stats.idle_samples >= self.dead_after_samples
+    }
+}
+
+/// The write counts of every counting handle of a registry.
+///
+/// Handles only count while a frame is being sampled, so the cost of other
+/// frames is one relaxed load per write.
+#[derive(Default)]
+pub(crate) struct WriteCounts {
+    counting: AtomicBool,
+    ticks: AtomicU64,
+    counts: RwLock<HashMap<MetricKey, Arc<AtomicU64>>>,
+    stats: RwLock<HashMap<MetricKey, WriteStats>>,
+}
+
+impl WriteCounts {
+    fn count(&self, count: &AtomicU64) {
+        if self.counting.load(Ordering::Relaxed) {
+            count.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn get_or_create(&self, key: MetricKey) -> Arc<AtomicU64> {
+        if let Some(count) = self.counts.read().unwrap().get(&key) {
+            return count.clone();
+        }
+        self.counts.write().unwrap().entry(key).or_default().clone()
+    }
+
+    pub fn counter(self: &Arc<Self>, key: MetricKey, inner: Counter) -> Counter {
+        Counter::from_arc(Arc::new(CountedCounter {
+            counts: self.clone(),
+            count: self.get_or_create(key),
+            inner,
+        }))
+    }
+
+    pub fn gauge(self: &Arc<Self>, key: MetricKey, inner: Gauge) -> Gauge {
+        Gauge::from_arc(Arc::new(CountedGauge {
+            counts: self.clone(),
+            count: self.get_or_create(key),
+            inner,
+        }))
+    }
+
+    pub fn histogram(self: &Arc<Self>, key: MetricKey, inner: Histogram) -> Histogram {
+        Histogram::from_arc(Arc::new(CountedHistogram {
+            counts: self.clone(),
+            count: self.get_or_create(key),
+            inner,
+        }))
+    }
+
+    /// Record the writes counted since the previous call, if that frame was
+    /// sampled, and sample the next frame if it's the `interval`th.
+    pub fn sample(&self, interval: u32) {
+        let tick = self.ticks.fetch_add(1, Ordering::Relaxed) + 1;
+        if self.counting.swap(false, Ordering::Relaxed) {
+            let counts = self.counts.read().unwrap();
+            let mut all_stats = self.stats.write().unwrap();
+            for (key, count) in counts.iter() {
+                let writes = count.swap(0, Ordering::Relaxed);
+                let stats = all_stats.entry(key.clone()).or_default();
+                stats.writes_per_frame = writes;
+                stats.max_writes_per_frame = stats.max_writes_per_frame.max(writes);
+                stats.idle_samples = if writes == 0 {
+                    stats.idle_samples + 1
+                } else {
+                    0
+                };
+                stats.samples += 1;
+            }
+        }
+        if tick.is_multiple_of(u64::from(interval.max(1))) {
+            self.counting.store(true, Ordering::Relaxed);
+        }
+    }
+
+    pub fn stats(&self, key: &MetricKey) -> Option<WriteStats> {
+        self.stats.read().unwrap().get(key).copied()
+    }
+
+    pub fn all_stats(&self) -> Vec<(MetricKey, WriteStats)> {
+        let stats = self.stats.read().unwrap();
+        stats.iter().map(|(k, s)| (k.clone(), *s)).collect()
+    }
+
+    pub fn remove(&self, key: &MetricKey) {
+        self.counts.write().unwrap().remove(key);
+        self.stats.write().unwrap().remove(key);
+    }
+}
+
+struct CountedCounter {
+    counts: Arc<WriteCounts>,
+    count: Arc<AtomicU64>,
+    inner: Counter,
+}
+
+impl CounterFn for CountedCounter {
+    fn increment(&self, value: u64) {
+        self.counts.count(&self.count);
+        self.inner.increment(value);
+    }
+
+    fn absolute(&self, value: u64) {
+        self.counts.count(&self.count);
+        self.inner.absolute(value);
+    }
+}
+
+struct CountedGauge {
+    counts: Arc<WriteCounts>,
+    count: Arc<AtomicU64>,
+    inner: Gauge,
+}
+
+impl GaugeFn for CountedGauge {
+    fn increment(&self, value: f64) {
+        self.counts.count(&self.count);
+        self.inner.increment(value);
+    }
+
+    fn decrement(&self, value: f64) {
+        self.counts.count(&self.count);
+        self.inner.decrement(value);
+    }
+
+    fn set(&self, value: f64) {
+        self.counts.count(&self.count);
+        self.inner.set(value);
+    }
+}
+
+struct CountedHistogram {
+    counts: Arc<WriteCounts>,
+    count: Arc<AtomicU64>,
+    inner: Histogram,
+}
+
+impl HistogramFn for CountedHistogram {
+    fn record(&self, value: f64) {
+        self.counts.count(&self.count);
+        self.inner.record(value);
+    }
+
+    fn record_many(&self, value: f64, count: usize) {
+        self.counts.count(&self.count);
+        self.inner.record_many(value, count);
+    }
+}
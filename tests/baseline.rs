@@ -0,0 +1,52 @@
+#![cfg(feature = "serde")]
+
+use bevy_metrics_dashboard::{
+    baseline::{Baseline, BaselineRecorder, Tolerances},
+    metrics::Key,
+    registry::MetricsRegistry,
+};
+use std::sync::atomic::Ordering;
+
+#[test]
+fn records_counter_increments_and_histogram_samples() {
+    let registry = MetricsRegistry::new();
+    let mut recorder = BaselineRecorder::default();
+    let counter = registry.get_or_create_counter(&Key::from_static_name("spawned"));
+    let histogram = registry.get_or_create_histogram(&Key::from_static_name("frame_time"));
+
+    for frame in 0..4 {
+        counter.fetch_add(2, Ordering::Relaxed);
+        histogram.push(frame as f64);
+        recorder.sample(&registry);
+    }
+
+    let baseline = recorder.baseline();
+    let names: Vec<_> = baseline.metrics.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, ["frame_time", "spawned"]);
+    let frame_time = &baseline.metrics[0].stats;
+    assert_eq!((frame_time.count, frame_time.mean), (4, 1.5));
+    // The first sample only establishes the counter's starting value.
+    let spawned = &baseline.metrics[1].stats;
+    assert_eq!((spawned.count, spawned.mean), (3, 2.0));
+}
+
+#[test]
+fn compare_reports_deviations_beyond_tolerance() {
+    let registry = MetricsRegistry::new();
+    let gauge = registry.get_or_create_gauge(&Key::from_static_name("health"));
+    let run = |value: f64| {
+        let mut recorder = BaselineRecorder::default();
+        gauge.store(value.to_bits(), Ordering::Relaxed);
+        recorder.sample(&registry);
+        Baseline::from_json(&recorder.baseline().to_json()).unwrap()
+    };
+    let previous = run(100.0);
+    let current = run(105.0);
+
+    assert!(current.compare(&previous, &Tolerances::new(0.1)).passed());
+    let strict = Tolerances::new(0.1).with_metric("health", 0.01);
+    let report = current.compare(&previous, &strict);
+    assert!(!report.passed());
+    assert_eq!(report.deviations[0].statistic, "mean");
+    assert!(report.missing.is_empty() && report.added.is_empty());
+}
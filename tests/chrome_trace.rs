@@ -0,0 +1,42 @@
+#![cfg(feature = "serde")]
+
+use bevy_metrics_dashboard::{
+    chrome_trace::ChromeTraceRecorder,
+    metrics::{Key, Label},
+    registry::MetricsRegistry,
+};
+use std::{sync::atomic::Ordering, time::Duration};
+
+#[test]
+fn records_counter_tracks_when_values_change() {
+    let registry = MetricsRegistry::new();
+    let mut recorder = ChromeTraceRecorder::default();
+    let key = Key::from_parts("spawned", vec![Label::new("kind", "enemy")]);
+    let counter = registry.get_or_create_counter(&key);
+    let histogram = registry.get_or_create_histogram(&Key::from_static_name("frame_time"));
+
+    for frame in 0..4u64 {
+        if frame != 2 {
+            counter.fetch_add(1, Ordering::Relaxed);
+        }
+        histogram.push(frame as f64);
+        recorder.sample(&registry, Duration::from_millis(16 * frame));
+    }
+    // The counter didn't change on the third frame.
+    assert_eq!(recorder.len(), 3 + 4);
+
+    let trace: serde_json::Value = serde_json::from_str(&recorder.to_json()).unwrap();
+    let events = trace["traceEvents"].as_array().unwrap();
+    let counters: Vec<_> = events
+        .iter()
+        .filter(|e| e["name"] == "spawned{kind=enemy}")
+        .collect();
+    assert!(counters.iter().all(|e| e["ph"] == "C"));
+    let times: Vec<_> = counters.iter().map(|e| e["ts"].as_f64().unwrap()).collect();
+    assert_eq!(times, [0.0, 16_000.0, 48_000.0]);
+    assert_eq!(counters[2]["args"]["value"], 3.0);
+
+    let last = events.iter().rfind(|e| e["name"] == "frame_time").unwrap();
+    assert_eq!(last["args"]["mean"], 3.0);
+    assert_eq!(last["args"]["count"], 1.0);
+}
@@ -0,0 +1,1459 @@
+use bevy_metrics_dashboard::{
+    bevy_egui::egui,
+    draw_dashboard,
+    metrics::{Key, Label, Unit},
+    metrics_util::MetricKind,
+    plot_kind::{PlotContext, PlotKind},
+    plots::{
+        CounterPlotConfig, CustomSeries, EmptyBuckets, GaugePlotConfig, HistogramPlotConfig,
+        HistogramPlotMode, MetricPlot, MetricPlotConfig, MultiSeriesPlot, YAxisLock, YAxisSide,
+    },
+    registry::{MetricKey, MetricsRegistry},
+    ring::Ring,
+    search_bar::SearchBar,
+    timeline::{Annotation, SessionTimeline},
+    AxisFormat, CachedPlotConfigs, CachedPlotData, Dashboard, DashboardConfig, DashboardResources,
+    FrameSnapshot, Palette, PlotShortcut, PlotSortMode, PlotTemplates, ValueFormatters,
+};
+use egui_plot::PlotPoint;
+use std::{
+    sync::atomic::Ordering,
+    time::{Duration, Instant},
+};
+
+fn key(name: &'static str, kind: MetricKind) -> MetricKey {
+    MetricKey::new(Key::from_static_name(name), kind)
+}
+
+fn add_default_plot(dashboard: &mut Dashboard, registry: &MetricsRegistry, key: MetricKey) {
+    let config = MetricPlotConfig::default_for_kind(key.kind);
+    dashboard.add_plot(registry, key, None, config);
+}
+
+#[test]
+fn duplicate_plots_get_unique_names() {
+    let registry = MetricsRegistry::new();
+    let mut dashboard = Dashboard::new();
+    let counter = key("spawned", MetricKind::Counter);
+
+    add_default_plot(&mut dashboard, &registry, counter.clone());
+    add_default_plot(&mut dashboard, &registry, counter.clone());
+
+    let names: Vec<_> = dashboard.plots().iter().map(|p| p.name()).collect();
+    assert_eq!(names, ["spawned (counter)", "spawned (counter) 1"]);
+}
+
+#[test]
+fn plot_requests_are_deduplicated() {
+    let registry = MetricsRegistry::new();
+    let mut dashboard = Dashboard::new();
+    let fps = key("fps", MetricKind::Gauge);
+    let spawned = key("spawned", MetricKind::Counter);
+    let mut cached_data = CachedPlotData::default();
+
+    dashboard.apply_plot_requests(
+        &registry,
+        &CachedPlotConfigs::default(),
+        &mut cached_data,
+        [(fps.clone(), None), (spawned, None), (fps.clone(), None)],
+    );
+    assert_eq!(dashboard.plots().len(), 2);
+
+    // Later requests can add duplicates on purpose.
+    dashboard.apply_plot_requests(
+        &registry,
+        &CachedPlotConfigs::default(),
+        &mut cached_data,
+        [(fps, None)],
+    );
+    assert_eq!(dashboard.plots().len(), 3);
+}
+
+#[test]
+fn remove_plot() {
+    let registry = MetricsRegistry::new();
+    let mut dashboard = Dashboard::new();
+    let gauge = key("health", MetricKind::Gauge);
+    let counter = key("spawned", MetricKind::Counter);
+    add_default_plot(&mut dashboard, &registry, gauge.clone());
+    add_default_plot(&mut dashboard, &registry, counter.clone());
+
+    let removed = dashboard.remove_plot(0);
+
+    assert_eq!(removed.key(), &gauge);
+    assert_eq!(dashboard.plots().len(), 1);
+    assert_eq!(dashboard.plots()[0].key(), &counter);
+}
+
+#[test]
+fn update_pulls_latest_values() {
+    let registry = MetricsRegistry::new();
+    let mut dashboard = Dashboard::new();
+    let counter = key("spawned", MetricKind::Counter);
+    add_default_plot(&mut dashboard, &registry, counter.clone());
+
+    registry
+        .get_or_create_counter(&counter.key)
+        .store(3, Ordering::Relaxed);
+    dashboard.update();
+
+    assert_eq!(dashboard.plots()[0].latest_value(), Some(3.0));
+}
+
+#[test]
+fn plots_follow_recreated_metrics() {
+    let registry = MetricsRegistry::new();
+    let mut dashboard = Dashboard::new();
+    let counter = key("spawned", MetricKind::Counter);
+    add_default_plot(&mut dashboard, &registry, counter.clone());
+
+    registry.upkeep();
+    registry.upkeep();
+    assert_eq!(registry.remove_idle(0), vec![counter.clone()]);
+    dashboard.metric_expired(&counter);
+    dashboard.update();
+    assert!(!registry.contains(&counter));
+
+    registry
+        .get_or_create_counter(&counter.key)
+        .store(7, Ordering::Relaxed);
+    dashboard.update();
+
+    assert_eq!(dashboard.plots()[0].latest_value(), Some(7.0));
+}
+
+#[test]
+fn paused_dashboard_does_not_update() {
+    let registry = MetricsRegistry::new();
+    let mut dashboard = Dashboard::new();
+    let gauge = key("health", MetricKind::Gauge);
+    add_default_plot(&mut dashboard, &registry, gauge.clone());
+
+    dashboard.config_mut().paused = true;
+    registry
+        .get_or_create_gauge(&gauge.key)
+        .store(5.0f64.to_bits(), Ordering::Relaxed);
+    dashboard.update();
+
+    assert_eq!(dashboard.plots()[0].latest_value(), None);
+}
+
+fn draw_frames(
+    dashboard: &mut Dashboard,
+    registry: &MetricsRegistry,
+    n_frames: usize,
+    mut before_frame: impl FnMut(usize),
+) {
+    draw_frames_with_input(dashboard, registry, n_frames, |frame| {
+        before_frame(frame);
+        egui::RawInput::default()
+    });
+}
+
+/// Like `draw_frames`, with the input returned by `input` on each frame.
+fn draw_frames_with_input(
+    dashboard: &mut Dashboard,
+    registry: &MetricsRegistry,
+    n_frames: usize,
+    mut input: impl FnMut(usize) -> egui::RawInput,
+) {
+    let mut search_bar = SearchBar::default();
+    let mut cached_configs = CachedPlotConfigs::default();
+    let mut cached_data = CachedPlotData::default();
+    let formatters = ValueFormatters::default();
+    let templates = PlotTemplates::default();
+    let palette = Palette::default();
+    let ctx = egui::Context::default();
+    for frame in 0..n_frames {
+        let input = input(frame);
+        dashboard.update();
+        let _ = ctx.run(input, |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                let response = draw_dashboard(
+                    dashboard,
+                    &mut search_bar,
+                    DashboardResources {
+                        registry,
+                        cached_configs: &mut cached_configs,
+                        cached_data: &mut cached_data,
+                        formatters: &formatters,
+                        templates: &templates,
+                        palette: &palette,
+                    },
+                    ui,
+                );
+                assert!(!response.browse_clicked);
+            });
+        });
+    }
+}
+
+/// Draw with `draw` on each frame, with the input returned by `input` from the
+/// shapes of the previous frame. Returns the shapes of the last frame.
+///
+/// Unlike `draw_frames`, plots drawn this way are open.
+fn draw_open_frames(
+    n_frames: usize,
+    mut input: impl FnMut(usize, &[egui::Shape]) -> egui::RawInput,
+    mut draw: impl FnMut(usize, &mut egui::Ui),
+) -> Vec<egui::Shape> {
+    let ctx = egui::Context::default();
+    let mut shapes = Vec::new();
+    for frame in 0..n_frames {
+        let mut input = input(frame, &shapes);
+        input.screen_rect = Some(egui::Rect::from_min_size(
+            egui::Pos2::ZERO,
+            egui::vec2(800.0, 1200.0),
+        ));
+        let output = ctx.run(input, |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| draw(frame, ui));
+        });
+        shapes.clear();
+        for clipped in output.shapes {
+            flatten_shape(clipped.shape, &mut shapes);
+        }
+    }
+    shapes
+}
+
+fn flatten_shape(shape: egui::Shape, shapes: &mut Vec<egui::Shape>) {
+    match shape {
+        egui::Shape::Vec(nested) => {
+            for shape in nested {
+                flatten_shape(shape, shapes);
+            }
+        }
+        shape => shapes.push(shape),
+    }
+}
+
+/// The rect of the first painted text that starts with `prefix`.
+fn text_rect(shapes: &[egui::Shape], prefix: &str) -> Option<egui::Rect> {
+    shapes.iter().find_map(|shape| match shape {
+        egui::Shape::Text(text) if text.galley.text().starts_with(prefix) => {
+            Some(text.visual_bounding_rect())
+        }
+        _ => None,
+    })
+}
+
+fn painted_texts(shapes: &[egui::Shape]) -> impl Iterator<Item = &str> {
+    shapes.iter().filter_map(|shape| match shape {
+        egui::Shape::Text(text) => Some(text.galley.text()),
+        _ => None,
+    })
+}
+
+/// A press and then a release of the primary button at `pos`, over two frames.
+fn click_input(pos: egui::Pos2, release: bool) -> egui::RawInput {
+    let mut input = egui::RawInput::default();
+    input.events.push(egui::Event::PointerMoved(pos));
+    input.events.push(egui::Event::PointerButton {
+        pos,
+        button: egui::PointerButton::Primary,
+        pressed: !release,
+        modifiers: egui::Modifiers::NONE,
+    });
+    input
+}
+
+#[test]
+fn draw_headless() {
+    let registry = MetricsRegistry::new();
+    let mut dashboard = Dashboard::new();
+    let histogram = key("frame_time", MetricKind::Histogram);
+    for k in [
+        key("spawned", MetricKind::Counter),
+        key("health", MetricKind::Gauge),
+        histogram.clone(),
+    ] {
+        add_default_plot(&mut dashboard, &registry, k);
+    }
+    let bucket = registry.get_or_create_histogram(&histogram.key);
+
+    draw_frames(&mut dashboard, &registry, 3, |frame| {
+        bucket.push(frame as f64)
+    });
+
+    assert_eq!(dashboard.plots().len(), 3);
+}
+
+#[test]
+fn draw_minimaps_headless() {
+    let registry = MetricsRegistry::new();
+    let gauge = key("health", MetricKind::Gauge);
+    let atomic = registry.get_or_create_gauge(&gauge.key);
+    let (formatters, templates, palette) = Default::default();
+
+    // Lines drawn under the plot's X axis label.
+    let lines_under_plot = |show_minimaps| {
+        let config = MetricPlotConfig::default_for_kind(MetricKind::Gauge);
+        let mut plot = MetricPlot::new(&registry, "health", gauge.clone(), None, config);
+        let dash_config = DashboardConfig {
+            show_minimaps,
+            ..Default::default()
+        };
+        let shapes = draw_open_frames(
+            3,
+            |_, _| Default::default(),
+            |frame, ui| {
+                atomic.store((frame as f64).to_bits(), Ordering::Relaxed);
+                plot.update();
+                plot.draw(&dash_config, &formatters, &templates, &palette, ui);
+            },
+        );
+        let plot_bottom = text_rect(&shapes, "frame").unwrap().bottom();
+        shapes
+            .iter()
+            .filter(|shape| match shape {
+                egui::Shape::Path(path) => path.points.iter().all(|p| p.y > plot_bottom),
+                _ => false,
+            })
+            .count()
+    };
+
+    assert_eq!(lines_under_plot(false) + 1, lines_under_plot(true));
+}
+
+#[test]
+fn throttled_redraw_still_samples() {
+    let registry = MetricsRegistry::new();
+    let gauge = key("health", MetricKind::Gauge);
+    let config = MetricPlotConfig::Gauge(GaugePlotConfig {
+        smoothing_weight: 0.0,
+        ..Default::default()
+    });
+    let mut plot = MetricPlot::new(&registry, "health", gauge.clone(), None, config);
+    let dash_config = DashboardConfig {
+        redraw_interval: Some(Duration::from_secs(60)),
+        ..Default::default()
+    };
+    let (formatters, templates, palette) = Default::default();
+    let atomic = registry.get_or_create_gauge(&gauge.key);
+
+    let draw = |plot: &mut MetricPlot, n_frames| {
+        draw_open_frames(
+            n_frames,
+            |_, _| Default::default(),
+            |frame, ui| {
+                atomic.store((frame as f64).to_bits(), Ordering::Relaxed);
+                plot.update();
+                plot.draw(&dash_config, &formatters, &templates, &palette, ui);
+            },
+        )
+    };
+    let is_mesh = |shape: &egui::Shape| matches!(shape, egui::Shape::Mesh(_));
+
+    // The first frame lays out the plot, and later frames repaint its meshes.
+    assert!(!draw(&mut plot, 1).iter().any(is_mesh));
+    assert!(draw(&mut plot, 3).iter().any(is_mesh));
+    assert_eq!(plot.latest_value(), Some(2.0));
+}
+
+#[test]
+fn plot_keeps_unit() {
+    let registry = MetricsRegistry::new();
+    let mut dashboard = Dashboard::new();
+    let gauge = key("frame_time", MetricKind::Gauge);
+    let config = MetricPlotConfig::default_for_kind(gauge.kind);
+
+    let plot = dashboard.add_plot(&registry, gauge, Some(Unit::Milliseconds), config);
+
+    assert_eq!(plot.unit(), Some(Unit::Milliseconds));
+}
+
+#[test]
+fn update_from_snapshot_ignores_later_writes() {
+    let registry = MetricsRegistry::new();
+    let mut dashboard = Dashboard::new();
+    let gauge = key("health", MetricKind::Gauge);
+    add_default_plot(&mut dashboard, &registry, gauge.clone());
+    let atomic = registry.get_or_create_gauge(&gauge.key);
+
+    atomic.store(1.0f64.to_bits(), Ordering::Relaxed);
+    let mut snapshot = FrameSnapshot::default();
+    snapshot.capture(&registry);
+    atomic.store(2.0f64.to_bits(), Ordering::Relaxed);
+    dashboard.update_from_snapshot(&snapshot);
+
+    assert_eq!(dashboard.plots()[0].latest_value(), Some(1.0));
+}
+
+#[test]
+fn custom_series_reads_channel() {
+    let mut dashboard = Dashboard::new();
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let unsmoothed = GaugePlotConfig {
+        smoothing_weight: 0.0,
+        ..Default::default()
+    };
+    dashboard
+        .add_custom_series(CustomSeries::from_receiver("loss", receiver).with_config(unsmoothed));
+
+    sender.send(3.0).unwrap();
+    sender.send(2.0).unwrap();
+    dashboard.update();
+
+    assert_eq!(dashboard.custom_series()[0].latest_value(), Some(2.0));
+}
+
+#[test]
+fn difference_series_subtracts_metrics() {
+    let registry = MetricsRegistry::default();
+    let produced = key("produced", MetricKind::Counter);
+    let consumed = key("consumed", MetricKind::Counter);
+    let histogram = key("latency", MetricKind::Histogram);
+    registry
+        .get_or_create_counter(&produced.key)
+        .store(10, Ordering::Relaxed);
+    registry
+        .get_or_create_counter(&consumed.key)
+        .store(7, Ordering::Relaxed);
+    assert!(CustomSeries::difference(&registry, &produced, &histogram).is_none());
+
+    let unsmoothed = GaugePlotConfig {
+        smoothing_weight: 0.0,
+        ..Default::default()
+    };
+    let mut dashboard = Dashboard::new();
+    dashboard.add_custom_series(
+        CustomSeries::difference(&registry, &produced, &consumed)
+            .unwrap()
+            .with_config(unsmoothed),
+    );
+    dashboard.update();
+
+    assert_eq!(dashboard.custom_series()[0].latest_value(), Some(3.0));
+}
+
+#[test]
+fn duration_histogram_fits_bucket_range() {
+    let registry = MetricsRegistry::new();
+    let mut dashboard = Dashboard::new();
+    let histogram = key("frame_time", MetricKind::Histogram);
+    let config = MetricPlotConfig::default_for_kind(histogram.kind);
+    dashboard.add_plot(
+        &registry,
+        histogram.clone(),
+        Some(Unit::Milliseconds),
+        config,
+    );
+    let bucket = registry.get_or_create_histogram(&histogram.key);
+
+    for i in 0..30 {
+        bucket.push(i as f64);
+    }
+    dashboard.update();
+
+    let MetricPlotConfig::Histogram(config) = dashboard.plots()[0].clone_config() else {
+        panic!("expected a histogram config");
+    };
+    assert!(!config.auto_range);
+    assert_eq!(config.buckets.range_input.min, 0.0);
+    assert_eq!(config.buckets.range_input.max, 50.0);
+}
+
+#[test]
+fn draw_merged_empty_buckets_headless() {
+    // The bars, vertical overlay lines, and shapes of a histogram with only
+    // its first and last buckets occupied. Grid lines are line segments, not
+    // paths.
+    let draw = |empty_buckets, overlay_percentile| {
+        let registry = MetricsRegistry::new();
+        let histogram = key("frame_time", MetricKind::Histogram);
+        let MetricPlotConfig::Histogram(mut config) =
+            MetricPlotConfig::default_for_kind(histogram.kind)
+        else {
+            unreachable!()
+        };
+        config.empty_buckets = empty_buckets;
+        config.overlay_percentile = overlay_percentile;
+        let config = MetricPlotConfig::Histogram(config);
+        let mut plot = MetricPlot::new(&registry, "frame_time", histogram.clone(), None, config);
+        let bucket = registry.get_or_create_histogram(&histogram.key);
+        let dash_config = DashboardConfig::default();
+        let (formatters, templates, palette) = Default::default();
+        let shapes = draw_open_frames(
+            3,
+            |_, _| Default::default(),
+            |frame, ui| {
+                bucket.push(if frame % 2 == 0 { 0.5 } else { 9.5 });
+                plot.update();
+                plot.draw(&dash_config, &formatters, &templates, &palette, ui);
+            },
+        );
+        // Bars are the only translucent rects.
+        let bars: Vec<_> = shapes
+            .iter()
+            .filter_map(|shape| match shape {
+                egui::Shape::Rect(rect) if rect.fill.a() < 255 => Some(rect.rect),
+                _ => None,
+            })
+            .collect();
+        let vlines: Vec<_> = shapes
+            .iter()
+            .filter_map(|shape| match shape {
+                egui::Shape::Path(path) => match path.points[..] {
+                    [a, b] if a.x == b.x => Some(a.x),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect();
+        (bars, vlines, shapes)
+    };
+
+    let (shown, _, _) = draw(EmptyBuckets::Show, None);
+    let (merged, vlines, shapes) = draw(EmptyBuckets::Merge, Some(50.0));
+
+    assert!(shown.len() > 2);
+    let [first, last] = merged[..] else {
+        panic!("expected 2 bars, not {}", merged.len());
+    };
+    // The empty buckets between them collapse into a gap narrower than a bar,
+    // and the X axis still labels the last bar with its bucket value.
+    assert!(last.left() - first.right() < first.width());
+    let label = text_rect(&shapes, "9.5").unwrap();
+    assert!(last.x_range().contains(label.center().x));
+    // The median is in the first bucket.
+    let [overlay] = vlines[..] else {
+        panic!("expected 1 overlay, not {}", vlines.len());
+    };
+    assert!(first.x_range().contains(overlay));
+}
+
+#[test]
+fn gauge_plotted_as_histogram() {
+    let registry = MetricsRegistry::new();
+    let mut dashboard = Dashboard::new();
+    let gauge = key("queue_length", MetricKind::Gauge);
+    let config = HistogramPlotConfig {
+        mode: HistogramPlotMode::Percentile {
+            percentile: 50.0,
+            history_size: 10,
+        },
+        ..Default::default()
+    };
+    assert!(MetricPlotConfig::Histogram(Box::new(config.clone())).supports_kind(MetricKind::Gauge));
+    dashboard.add_plot(
+        &registry,
+        gauge.clone(),
+        None,
+        MetricPlotConfig::Histogram(Box::new(config)),
+    );
+    let source = registry.get_or_create_gauge(&gauge.key);
+
+    draw_frames(&mut dashboard, &registry, 5, |frame| {
+        source.store((frame as f64).to_bits(), Ordering::Relaxed)
+    });
+    assert_eq!(dashboard.plots()[0].latest_value(), Some(2.0));
+
+    let plot = &mut dashboard.plots_mut()[0];
+    plot.set_config(MetricPlotConfig::default_for_kind(MetricKind::Gauge));
+    assert!(matches!(plot.clone_config(), MetricPlotConfig::Gauge(_)));
+}
+
+#[test]
+fn draw_normalized_histogram_headless() {
+    let registry = MetricsRegistry::new();
+    let histogram = key("frame_time", MetricKind::Histogram);
+    let MetricPlotConfig::Histogram(mut config) =
+        MetricPlotConfig::default_for_kind(histogram.kind)
+    else {
+        unreachable!()
+    };
+    config.normalize = true;
+    let config = MetricPlotConfig::Histogram(config);
+    let mut plot = MetricPlot::new(&registry, "frame_time", histogram.clone(), None, config);
+    let bucket = registry.get_or_create_histogram(&histogram.key);
+    let dash_config = DashboardConfig::default();
+    let (formatters, templates, palette) = Default::default();
+
+    let shapes = draw_open_frames(
+        3,
+        |_, _| Default::default(),
+        |frame, ui| {
+            bucket.push(frame as f64);
+            plot.update();
+            plot.draw(&dash_config, &formatters, &templates, &palette, ui);
+        },
+    );
+    // Each of the 3 samples is a third of the total.
+    let texts: Vec<_> = painted_texts(&shapes).collect();
+    assert!(texts.contains(&"%"));
+    assert!(texts.contains(&"30"));
+    assert!(!texts.contains(&"40"));
+}
+
+#[test]
+fn frozen_histogram_baseline_keeps_old_buckets() {
+    let registry = MetricsRegistry::new();
+    let histogram = key("frame_time", MetricKind::Histogram);
+    let config = MetricPlotConfig::Histogram(Box::new(HistogramPlotConfig {
+        auto_range: false,
+        empty_buckets: EmptyBuckets::Merge,
+        ..Default::default()
+    }));
+    let mut plot = MetricPlot::new(
+        &registry,
+        "frame_time",
+        histogram.clone(),
+        None,
+        config.clone(),
+    );
+    let bucket = registry.get_or_create_histogram(&histogram.key);
+    let dash_config = DashboardConfig::default();
+    let (formatters, templates, palette) = Default::default();
+    let draw = |plot: &mut MetricPlot, value: f64| {
+        draw_open_frames(
+            2,
+            |_, _| Default::default(),
+            |_, ui| {
+                bucket.push(value);
+                plot.update();
+                plot.draw(&dash_config, &formatters, &templates, &palette, ui);
+            },
+        )
+    };
+    // The baseline is outlined, unlike the live bars.
+    let outlines = |shapes: &[egui::Shape]| {
+        shapes
+            .iter()
+            .filter(|shape| {
+                matches!(shape, egui::Shape::Rect(rect)
+                    if rect.fill == egui::Color32::TRANSPARENT
+                        && rect.stroke.color == egui::Color32::GRAY)
+            })
+            .count()
+    };
+
+    draw(&mut plot, 1.0);
+    assert_eq!(plot.histogram_baseline(), None);
+    assert!(plot.freeze_histogram_baseline());
+    let frozen = plot.histogram_bucket_counts().unwrap().to_vec();
+
+    let shapes = draw(&mut plot, 50.0);
+    assert_eq!(plot.histogram_baseline(), Some(frozen.as_slice()));
+    assert_ne!(plot.histogram_bucket_counts(), Some(frozen.as_slice()));
+    assert!(painted_texts(&shapes).any(|t| t == "Clear Baseline"));
+    assert_eq!(outlines(&shapes), 1);
+    plot.clear_histogram_baseline();
+    assert_eq!(plot.histogram_baseline(), None);
+
+    // New buckets don't match the baseline, even with the same number of them.
+    assert!(plot.freeze_histogram_baseline());
+    plot.set_config(config);
+    assert_eq!(plot.histogram_baseline(), None);
+    assert_eq!(outlines(&draw(&mut plot, 1.0)), 0);
+}
+
+#[test]
+fn multi_series_plot_draws_both_axes() {
+    let registry = MetricsRegistry::new();
+    let fps = key("fps", MetricKind::Gauge);
+    let entities = key("entities", MetricKind::Counter);
+    let mut plot = MultiSeriesPlot::new("fps vs entities")
+        .with_metric(
+            &registry,
+            fps.clone(),
+            Some(Unit::CountPerSecond),
+            YAxisSide::Left,
+        )
+        .with_metric(
+            &registry,
+            entities.clone(),
+            Some(Unit::Count),
+            YAxisSide::Right,
+        );
+    let fps_atomic = registry.get_or_create_gauge(&fps.key);
+    let entities_atomic = registry.get_or_create_counter(&entities.key);
+    let dash_config = DashboardConfig::default();
+    let (formatters, palette) = Default::default();
+
+    let shapes = draw_open_frames(
+        3,
+        |_, _| Default::default(),
+        |frame, ui| {
+            fps_atomic.store(60f64.to_bits(), Ordering::Relaxed);
+            entities_atomic.store(1000 * frame as u64, Ordering::Relaxed);
+            plot.update();
+            plot.draw(&dash_config, &formatters, &palette, ui);
+        },
+    );
+    // Each axis is labeled with its unit, and the right axis with its values.
+    assert!(text_rect(&shapes, "hz").is_some());
+    assert!(text_rect(&shapes, "count").is_some());
+    assert!(text_rect(&shapes, "2000").is_some());
+
+    let series = plot.series();
+    assert_eq!(series[0].0.latest_value(), Some(60.0));
+    assert_eq!(series[1].0.latest_value(), Some(2000.0));
+    assert_eq!(series[1].1, YAxisSide::Right);
+}
+
+#[test]
+fn multi_series_hover_reads_every_series_at_x() {
+    let registry = MetricsRegistry::new();
+    let mut dashboard = Dashboard::new();
+    let fps = key("fps", MetricKind::Gauge);
+    let entities = key("entities", MetricKind::Gauge);
+    let unsmoothed = MetricPlotConfig::Gauge(GaugePlotConfig {
+        smoothing_weight: 0.0,
+        ..Default::default()
+    });
+    let series = |key: &MetricKey| {
+        let name = key.title(None, 0);
+        MetricPlot::new(&registry, name, key.clone(), None, unsmoothed.clone())
+    };
+    let plot = MultiSeriesPlot::new("fps vs entities")
+        .with_series(series(&fps), YAxisSide::Left)
+        .with_series(series(&entities), YAxisSide::Right);
+    assert!(plot.has_shared_hover());
+    dashboard.add_multi_series_plot(plot);
+    let fps_atomic = registry.get_or_create_gauge(&fps.key);
+    let entities_atomic = registry.get_or_create_gauge(&entities.key);
+
+    draw_frames(&mut dashboard, &registry, 3, |frame| {
+        fps_atomic.store((60.0 - frame as f64).to_bits(), Ordering::Relaxed);
+        entities_atomic.store((100.0 * frame as f64).to_bits(), Ordering::Relaxed);
+    });
+
+    let plot = &dashboard.multi_series_plots()[0];
+    let values: Vec<_> = plot
+        .values_at(1.2, false)
+        .into_iter()
+        .map(|(series, y)| (series.key().clone(), y))
+        .collect();
+    assert_eq!(values, [(fps, 59.0), (entities, 100.0)]);
+    assert!(plot.values_at(5.0, false).is_empty());
+}
+
+#[test]
+fn stacked_counters_plot_every_labeled_series() {
+    let registry = MetricsRegistry::new();
+    let mut dashboard = Dashboard::new();
+    let cameras = ["main", "minimap"].map(|camera| {
+        let key = Key::from_parts("draw_calls", vec![Label::new("camera", camera)]);
+        registry.get_or_create_counter(&key)
+    });
+    registry.get_or_create_gauge(&Key::from_static_name("draw_calls"));
+    dashboard.add_multi_series_plot(MultiSeriesPlot::stacked_counters(&registry, "draw_calls"));
+
+    draw_frames(&mut dashboard, &registry, 2, |frame| {
+        cameras[0].store(10 * frame as u64, Ordering::Relaxed);
+        cameras[1].store(frame as u64, Ordering::Relaxed);
+    });
+
+    let plot = &dashboard.multi_series_plots()[0];
+    assert!(plot.is_stacked());
+    let names: Vec<_> = plot.series().iter().map(|(s, _)| s.name()).collect();
+    assert_eq!(names, ["camera=main", "camera=minimap"]);
+    assert_eq!(plot.series()[0].0.latest_value(), Some(10.0));
+    assert_eq!(plot.series()[1].0.latest_value(), Some(1.0));
+}
+
+#[test]
+fn stacked_plot_fills_areas() {
+    let registry = MetricsRegistry::new();
+    let cameras = ["main", "minimap"].map(|camera| {
+        let key = Key::from_parts("draw_calls", vec![Label::new("camera", camera)]);
+        registry.get_or_create_counter(&key)
+    });
+    let mut plot = MultiSeriesPlot::stacked_counters(&registry, "draw_calls");
+    let dash_config = DashboardConfig::default();
+    let (formatters, palette) = Default::default();
+
+    let shapes = draw_open_frames(
+        3,
+        |_, _| Default::default(),
+        |frame, ui| {
+            cameras[0].store(10 * frame as u64, Ordering::Relaxed);
+            cameras[1].store(frame as u64, Ordering::Relaxed);
+            plot.update();
+            plot.draw(&dash_config, &formatters, &palette, ui);
+        },
+    );
+
+    // Each series fills between each pair of its 3 samples.
+    let n_areas = shapes
+        .iter()
+        .filter(|shape| match shape {
+            egui::Shape::Path(path) => path.closed && path.fill != egui::Color32::TRANSPARENT,
+            _ => false,
+        })
+        .count();
+    assert_eq!(n_areas, 2 * 2);
+}
+
+#[test]
+fn plot_order_follows_sort_mode() {
+    let registry = MetricsRegistry::new();
+    let mut dashboard = Dashboard::new();
+    for k in [
+        key("health", MetricKind::Gauge),
+        key("spawned", MetricKind::Counter),
+        key("frame_time", MetricKind::Histogram),
+    ] {
+        add_default_plot(&mut dashboard, &registry, k);
+    }
+
+    assert_eq!(dashboard.plot_order(), [2, 1, 0]);
+    dashboard.config_mut().sort_mode = PlotSortMode::Name;
+    assert_eq!(dashboard.plot_order(), [2, 0, 1]);
+    dashboard.config_mut().sort_mode = PlotSortMode::Kind;
+    assert_eq!(dashboard.plot_order(), [1, 0, 2]);
+}
+
+#[test]
+fn compact_axis_format_draws() {
+    let registry = MetricsRegistry::new();
+    let bytes = key("allocated", MetricKind::Gauge);
+    let config = MetricPlotConfig::default_for_kind(bytes.kind);
+    let mut plot = MetricPlot::new(
+        &registry,
+        "allocated",
+        bytes.clone(),
+        Some(Unit::Bytes),
+        config,
+    );
+    plot.set_axis_format(AxisFormat::Compact);
+    let atomic = registry.get_or_create_gauge(&bytes.key);
+    let dash_config = DashboardConfig::default();
+    let (formatters, templates, palette) = Default::default();
+
+    let shapes = draw_open_frames(
+        3,
+        |_, _| Default::default(),
+        |frame, ui| {
+            atomic.store((frame as f64 * 1.5e6).to_bits(), Ordering::Relaxed);
+            plot.update();
+            plot.draw(&dash_config, &formatters, &templates, &palette, ui);
+        },
+    );
+    assert!(painted_texts(&shapes).any(|text| text.ends_with(" KiB")));
+}
+
+#[test]
+fn measurement_cursors_follow_clicks() {
+    let registry = MetricsRegistry::new();
+    let gauge = key("health", MetricKind::Gauge);
+    let config = MetricPlotConfig::Gauge(GaugePlotConfig {
+        smoothing_weight: 0.0,
+        ..Default::default()
+    });
+    let mut plot = MetricPlot::new(&registry, "health", gauge.clone(), None, config);
+    let atomic = registry.get_or_create_gauge(&gauge.key);
+    let dash_config = DashboardConfig::default();
+    let (formatters, templates, palette) = Default::default();
+
+    // Toggle measuring, and then click two points on the plot.
+    let shapes = draw_open_frames(
+        8,
+        |frame, shapes| {
+            let frame_label = text_rect(shapes, "frame");
+            let target = match frame {
+                2 | 3 => text_rect(shapes, "Measure").map(|r| r.center()),
+                4 | 5 => frame_label.map(|r| r.center_top() + egui::vec2(-200.0, -100.0)),
+                6 | 7 => frame_label.map(|r| r.center_top() + egui::vec2(200.0, -100.0)),
+                _ => None,
+            };
+            target.map_or_else(Default::default, |pos| click_input(pos, frame % 2 == 1))
+        },
+        |frame, ui| {
+            if frame < 2 {
+                atomic.store((10.0 * frame as f64).to_bits(), Ordering::Relaxed);
+                plot.update();
+            }
+            plot.draw(&dash_config, &formatters, &templates, &palette, ui);
+        },
+    );
+
+    assert!(text_rect(&shapes, "Δ = 10").is_some());
+}
+
+#[test]
+fn jump_to_pauses_dashboard() {
+    let registry = MetricsRegistry::new();
+    let mut dashboard = Dashboard::new();
+    let gauge = key("health", MetricKind::Gauge);
+    add_default_plot(&mut dashboard, &registry, gauge.clone());
+    let atomic = registry.get_or_create_gauge(&gauge.key);
+    let mut moment = None;
+
+    draw_frames(&mut dashboard, &registry, 3, |frame| {
+        atomic.store((frame as f64).to_bits(), Ordering::Relaxed);
+        if frame == 1 {
+            moment = Some(Instant::now());
+        }
+    });
+    let latest = dashboard.plots()[0].latest_value();
+    dashboard.jump_to(moment.unwrap());
+    draw_frames(&mut dashboard, &registry, 2, |_| {
+        atomic.store(10f64.to_bits(), Ordering::Relaxed)
+    });
+
+    assert!(dashboard.config().paused);
+    assert_eq!(dashboard.plots()[0].latest_value(), latest);
+}
+
+#[test]
+fn timeline_keeps_latest_segments() {
+    let start = Instant::now();
+    let mut timeline = SessionTimeline::new(start).with_max_segments(2);
+    let now = start + Duration::from_secs(10);
+
+    for label in ["load", "spike", "save"] {
+        timeline.annotate(
+            &Annotation::new(label).with_duration(Duration::from_secs(4)),
+            now,
+        );
+    }
+
+    let segments: Vec<_> = timeline.segments().collect();
+    assert_eq!(segments.len(), 2);
+    assert_eq!(segments[0].label, "spike");
+    assert_eq!(segments[1].start, start + Duration::from_secs(6));
+}
+
+#[test]
+fn growing_gauge_has_positive_trend() {
+    let registry = MetricsRegistry::new();
+    let mut dashboard = Dashboard::new();
+    let gauge = key("entities", MetricKind::Gauge);
+    let counter = key("spawned", MetricKind::Counter);
+    add_default_plot(&mut dashboard, &registry, gauge.clone());
+    add_default_plot(&mut dashboard, &registry, counter);
+    let atomic = registry.get_or_create_gauge(&gauge.key);
+
+    for i in 0..10 {
+        atomic.store((100.0 * i as f64).to_bits(), Ordering::Relaxed);
+        dashboard.update();
+        std::thread::sleep(Duration::from_millis(1));
+    }
+
+    assert!(dashboard.plots()[0].trend_per_minute().unwrap() > 0.0);
+    assert_eq!(dashboard.plots()[1].trend_per_minute(), None);
+}
+
+#[test]
+fn y_axis_lock_is_kept_in_plot_config() {
+    let registry = MetricsRegistry::new();
+    let mut dashboard = Dashboard::new();
+    let gauge = key("entity_count", MetricKind::Gauge);
+    let y_lock = YAxisLock {
+        min: Some(0.0),
+        max: None,
+    };
+    let config = GaugePlotConfig {
+        y_lock,
+        ..Default::default()
+    };
+    dashboard.add_plot(&registry, gauge, None, MetricPlotConfig::Gauge(config));
+    draw_frames(&mut dashboard, &registry, 3, |_| {});
+
+    let MetricPlotConfig::Gauge(config) = dashboard.plots()[0].clone_config() else {
+        panic!("expected a gauge config");
+    };
+    assert_eq!(config.y_lock, y_lock);
+
+    let points = [PlotPoint::new(0.0, 5.0), PlotPoint::new(1.0, 20.0)];
+    assert_eq!(y_lock.range(&points), Some(0.0..=20.0));
+    assert_eq!(y_lock.range(&[]), Some(0.0..=1.0));
+    assert_eq!(YAxisLock::default().range(&points), None);
+}
+
+#[test]
+fn shortcuts_apply_to_focused_plot() {
+    let registry = MetricsRegistry::new();
+    let mut dashboard = Dashboard::new();
+    let health = key("health", MetricKind::Gauge);
+    add_default_plot(
+        &mut dashboard,
+        &registry,
+        key("spawned", MetricKind::Counter),
+    );
+    add_default_plot(&mut dashboard, &registry, health.clone());
+    dashboard.set_focused_plot(Some(health.clone()));
+
+    let presses = [
+        None,
+        Some(PlotShortcut::Pause),
+        Some(PlotShortcut::Derivative),
+    ];
+    draw_frames_with_input(&mut dashboard, &registry, presses.len(), |frame| {
+        let mut input = egui::RawInput::default();
+        // Over the dashboard, below the plots.
+        input
+            .events
+            .push(egui::Event::PointerMoved(egui::pos2(400.0, 1100.0)));
+        if let Some(shortcut) = presses[frame] {
+            input.events.push(egui::Event::Key {
+                key: shortcut.key(),
+                physical_key: None,
+                pressed: true,
+                repeat: false,
+                modifiers: egui::Modifiers::NONE,
+            });
+        }
+        input
+    });
+
+    let plots = dashboard.plots();
+    assert!(!plots[0].is_paused());
+    assert!(plots[1].is_paused());
+    let MetricPlotConfig::Gauge(config) = plots[1].clone_config() else {
+        panic!("expected a gauge config");
+    };
+    assert!(config.derivative);
+
+    let mut cached_configs = CachedPlotConfigs::default();
+    let mut cached_data = CachedPlotData::with_retention(Duration::from_secs(60));
+    dashboard.config_mut().read_only = true;
+    assert!(!dashboard.apply_shortcut(
+        1,
+        PlotShortcut::Remove,
+        &mut cached_configs,
+        &mut cached_data
+    ));
+    dashboard.config_mut().read_only = false;
+    assert!(dashboard.apply_shortcut(
+        1,
+        PlotShortcut::Remove,
+        &mut cached_configs,
+        &mut cached_data
+    ));
+    assert_eq!(dashboard.plots().len(), 1);
+
+    // Removed plots are cached like with the close button.
+    dashboard.apply_plot_requests(
+        &registry,
+        &cached_configs,
+        &mut cached_data,
+        [(health, None)],
+    );
+    assert!(dashboard.plots()[1].is_paused());
+}
+
+#[test]
+fn settings_shortcut_opens_plot() {
+    let registry = MetricsRegistry::new();
+    let mut dashboard = Dashboard::new();
+    let health = key("health", MetricKind::Gauge);
+    add_default_plot(&mut dashboard, &registry, health.clone());
+    dashboard.set_focused_plot(Some(health));
+
+    let mut search_bar = SearchBar::default();
+    let mut cached_configs = CachedPlotConfigs::default();
+    let mut cached_data = CachedPlotData::default();
+    let formatters = ValueFormatters::default();
+    let templates = PlotTemplates::default();
+    let palette = Palette::default();
+    let mut draw = |n_frames, press: Option<usize>| {
+        draw_open_frames(
+            n_frames,
+            |frame, _| {
+                let mut input = egui::RawInput {
+                    // Let the header finish opening.
+                    time: Some(frame as f64),
+                    events: vec![egui::Event::PointerMoved(egui::pos2(400.0, 1100.0))],
+                    ..Default::default()
+                };
+                if press == Some(frame) {
+                    input.events.push(egui::Event::Key {
+                        key: PlotShortcut::Settings.key(),
+                        physical_key: None,
+                        pressed: true,
+                        repeat: false,
+                        modifiers: egui::Modifiers::NONE,
+                    });
+                }
+                input
+            },
+            |_, ui| {
+                draw_dashboard(
+                    &mut dashboard,
+                    &mut search_bar,
+                    DashboardResources {
+                        registry: &registry,
+                        cached_configs: &mut cached_configs,
+                        cached_data: &mut cached_data,
+                        formatters: &formatters,
+                        templates: &templates,
+                        palette: &palette,
+                    },
+                    ui,
+                );
+            },
+        )
+    };
+
+    let shapes = draw(2, None);
+    assert!(!painted_texts(&shapes).any(|text| text == "Settings"));
+    let shapes = draw(3, Some(1));
+    assert!(painted_texts(&shapes).any(|text| text == "Settings"));
+}
+
+#[test]
+fn shortcuts_ignored_while_pointer_is_off_dashboard() {
+    let registry = MetricsRegistry::new();
+    let mut dashboard = Dashboard::new();
+    let health = key("health", MetricKind::Gauge);
+    add_default_plot(&mut dashboard, &registry, health.clone());
+    dashboard.set_focused_plot(Some(health));
+
+    let mut search_bar = SearchBar::default();
+    let mut cached_configs = CachedPlotConfigs::default();
+    let mut cached_data = CachedPlotData::default();
+    let formatters = ValueFormatters::default();
+    let templates = PlotTemplates::default();
+    let palette = Palette::default();
+    draw_open_frames(
+        2,
+        |frame, _| {
+            // The dashboard only covers the left half of the screen.
+            let mut events = vec![egui::Event::PointerMoved(egui::pos2(600.0, 100.0))];
+            if frame == 1 {
+                for shortcut in [PlotShortcut::Pause, PlotShortcut::Remove] {
+                    events.push(egui::Event::Key {
+                        key: shortcut.key(),
+                        physical_key: None,
+                        pressed: true,
+                        repeat: false,
+                        modifiers: egui::Modifiers::NONE,
+                    });
+                }
+            }
+            egui::RawInput {
+                events,
+                ..Default::default()
+            }
+        },
+        |_, ui| {
+            let rect = egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(400.0, 1200.0));
+            ui.allocate_new_ui(egui::UiBuilder::new().max_rect(rect), |ui| {
+                draw_dashboard(
+                    &mut dashboard,
+                    &mut search_bar,
+                    DashboardResources {
+                        registry: &registry,
+                        cached_configs: &mut cached_configs,
+                        cached_data: &mut cached_data,
+                        formatters: &formatters,
+                        templates: &templates,
+                        palette: &palette,
+                    },
+                    ui,
+                );
+            });
+        },
+    );
+
+    assert_eq!(dashboard.plots().len(), 1);
+    assert!(!dashboard.plots()[0].is_paused());
+    assert_eq!(dashboard.focused_plot(), None);
+}
+
+#[test]
+fn event_strip_marks_counter_increments() {
+    let registry = MetricsRegistry::new();
+    let mut dashboard = Dashboard::new();
+    let gauge = key("frame_time", MetricKind::Gauge);
+    let gc_runs = key("gc_runs", MetricKind::Counter);
+    add_default_plot(&mut dashboard, &registry, gauge.clone());
+    let plot = &mut dashboard.plots_mut()[0];
+    assert!(!plot.set_event_counter(&registry, Some(gauge)));
+    assert!(plot.set_event_counter(&registry, Some(gc_runs.clone())));
+    assert_eq!(plot.event_counter(), Some(&gc_runs));
+    let counter = registry.get_or_create_counter(&gc_runs.key);
+
+    draw_frames(&mut dashboard, &registry, 5, |frame| {
+        if frame == 1 || frame == 3 {
+            counter.fetch_add(1, Ordering::Relaxed);
+        }
+    });
+
+    assert_eq!(dashboard.plots()[0].event_xs(false), vec![1.0, 3.0]);
+}
+
+#[test]
+fn bulk_config_applies_to_plots_and_cached_configs() {
+    let registry = MetricsRegistry::new();
+    let mut dashboard = Dashboard::new();
+    let gauge = key("health", MetricKind::Gauge);
+    let histogram = key("frame_time", MetricKind::Histogram);
+    add_default_plot(&mut dashboard, &registry, gauge.clone());
+    add_default_plot(&mut dashboard, &registry, histogram.clone());
+    let mut cached_configs = CachedPlotConfigs::default();
+    let cached_gauge = key("mana", MetricKind::Gauge);
+    cached_configs.insert(
+        cached_gauge.clone(),
+        MetricPlotConfig::default_for_kind(MetricKind::Gauge),
+    );
+
+    dashboard.set_all_gauge_smoothing(&mut cached_configs, 0.5);
+    dashboard.set_all_histogram_windows(&mut cached_configs, Some(1000));
+
+    let MetricPlotConfig::Gauge(config) = dashboard.plots()[0].clone_config() else {
+        panic!("expected a gauge config");
+    };
+    assert_eq!(config.smoothing_weight, 0.5);
+    let MetricPlotConfig::Histogram(config) = dashboard.plots()[1].clone_config() else {
+        panic!("expected a histogram config");
+    };
+    assert_eq!(config.window_size, Some(1000));
+    let Some(MetricPlotConfig::Gauge(config)) = cached_configs.get(&cached_gauge) else {
+        panic!("expected a cached gauge config");
+    };
+    assert_eq!(config.smoothing_weight, 0.5);
+}
+
+#[test]
+fn counter_increments_bridge_wraparound_and_reset() {
+    let registry = MetricsRegistry::new();
+    let mut dashboard = Dashboard::new();
+    let counter = key("bytes_sent", MetricKind::Counter);
+    let config = MetricPlotConfig::Counter(CounterPlotConfig {
+        increment_bars: true,
+        ..Default::default()
+    });
+    dashboard.add_plot(&registry, counter.clone(), None, config);
+    let atomic = registry.get_or_create_counter(&counter.key);
+
+    for value in [u64::MAX - 1, 2, 10, 4] {
+        atomic.store(value, Ordering::Relaxed);
+        dashboard.update();
+    }
+
+    let points = dashboard.plots()[0].line_points(false).unwrap();
+    let increments: Vec<_> = points.iter().map(|p| p.y).collect();
+    // Wrap past u64::MAX, then a normal increase, then a reset to zero.
+    assert_eq!(increments, vec![4.0, 8.0, 4.0]);
+}
+
+/// Plots the number of updates instead of the metric.
+struct UpdateCount(u32);
+
+impl PlotKind for UpdateCount {
+    fn update(&mut self, _key: &MetricKey, _snapshot: Option<&FrameSnapshot>) {
+        self.0 += 1;
+    }
+
+    fn draw(&mut self, ctx: &PlotContext, ui: &mut egui::Ui) {
+        ui.label(format!("{}: {}", ctx.name(), self.0));
+    }
+
+    fn latest_value(&self) -> Option<f64> {
+        Some(self.0.into())
+    }
+}
+
+#[test]
+fn custom_plot_kind_participates_in_dashboard() {
+    let registry = MetricsRegistry::new();
+    let mut dashboard = Dashboard::new();
+    let mut templates = PlotTemplates::empty();
+    templates.register_kind("updates", MetricKind::Gauge, |_, _, _| {
+        Box::new(UpdateCount(0))
+    });
+    let template = templates.matching(MetricKind::Gauge, None).next().unwrap();
+    let gauge = key("health", MetricKind::Gauge);
+    dashboard.add_plot(&registry, gauge, None, template.config.clone());
+
+    draw_frames(&mut dashboard, &registry, 3, |_| {});
+
+    let plot = &dashboard.plots()[0];
+    assert_eq!(plot.latest_value(), Some(3.0));
+    assert!(matches!(plot.clone_config(), MetricPlotConfig::Custom(_)));
+}
+
+#[test]
+fn ring_finds_sampling_gaps() {
+    let start = Instant::now();
+    let mut by_time = Ring::new(10);
+    for secs in [0, 1, 2, 3, 10, 11, 12] {
+        by_time.push_at(secs, start + Duration::from_secs(secs));
+    }
+    assert_eq!(by_time.gap_indices(4.0, Duration::from_millis(250)), [4]);
+
+    // Frames skipped while paused count, however little time passed.
+    let mut by_frame = Ring::new(10);
+    for frame in [1, 2, 3, 4, 20, 21] {
+        by_frame.push_with_frame(frame, Some(frame));
+    }
+    assert_eq!(by_frame.gap_indices(4.0, Duration::from_millis(250)), [4]);
+
+    // Slow but steady sampling has no gaps.
+    let mut steady = Ring::new(10);
+    for secs in 0..5 {
+        steady.push_at(secs, start + Duration::from_secs(2 * secs));
+    }
+    assert!(steady
+        .gap_indices(4.0, Duration::from_millis(250))
+        .is_empty());
+}
+
+#[test]
+fn ring_measures_push_rate() {
+    let start = Instant::now();
+    let mut ring = Ring::new(100);
+    assert_eq!(ring.push_rate(), None);
+    ring.push_at(0, start);
+    assert_eq!(ring.push_rate(), None);
+    for i in 1..=20 {
+        ring.push_at(i, start + Duration::from_millis(100 * i));
+    }
+    assert_eq!(ring.push_rate(), Some(10.0));
+}
+
+#[test]
+fn histogram_summary_readouts_cover_all_samples() {
+    let registry = MetricsRegistry::new();
+    let mut dashboard = Dashboard::new();
+    let histogram = key("frame_time", MetricKind::Histogram);
+    let config = HistogramPlotConfig {
+        window_size: Some(10),
+        summary_readouts: true,
+        ..Default::default()
+    };
+    dashboard.add_plot(
+        &registry,
+        histogram.clone(),
+        None,
+        MetricPlotConfig::Histogram(Box::new(config.clone())),
+    );
+    let bucket = registry.get_or_create_histogram(&histogram.key);
+
+    // More samples than fit in the sliding window.
+    for frame in 0..10 {
+        for i in 1..=10 {
+            bucket.push((10 * frame + i) as f64);
+        }
+        dashboard.update();
+        registry.clear_atomic_buckets();
+    }
+
+    let summary = dashboard.plots()[0].histogram_summary().unwrap();
+    assert_eq!(summary.count(), 100);
+    assert_eq!(summary.sum(), 5050.0);
+    assert_eq!(summary.mean(), Some(50.5));
+    let median = summary.quantile(0.5).unwrap();
+    assert!((median - 50.0).abs() <= 1.0, "{median}");
+
+    draw_frames(&mut dashboard, &registry, 1, |_| {});
+
+    // A new config starts a new summary, or drops it.
+    dashboard.plots_mut()[0].set_config(MetricPlotConfig::Histogram(Box::new(config)));
+    assert_eq!(dashboard.plots()[0].histogram_summary().unwrap().count(), 0);
+    dashboard.plots_mut()[0].set_config(MetricPlotConfig::default_for_kind(MetricKind::Histogram));
+    assert!(dashboard.plots()[0].histogram_summary().is_none());
+}
+
+#[test]
+fn recording_writes_every_sample_while_paused() {
+    let registry = MetricsRegistry::new();
+    let mut dashboard = Dashboard::new();
+    let gauge = key("health", MetricKind::Gauge);
+    let histogram = key("frame_time", MetricKind::Histogram);
+    add_default_plot(&mut dashboard, &registry, gauge.clone());
+    add_default_plot(&mut dashboard, &registry, histogram.clone());
+    let dir = std::env::temp_dir();
+    let gauge_path = dir.join(format!("recording-gauge-{}.csv", std::process::id()));
+    let histogram_path = dir.join(format!("recording-histogram-{}.csv", std::process::id()));
+    dashboard.plots_mut()[0]
+        .start_recording(&gauge_path)
+        .unwrap();
+    dashboard.plots_mut()[1]
+        .start_recording(&histogram_path)
+        .unwrap();
+    assert_eq!(
+        dashboard.plots()[0].recording_path(),
+        Some(gauge_path.as_path())
+    );
+
+    dashboard.plots_mut()[0].set_paused(true);
+    let atomic = registry.get_or_create_gauge(&gauge.key);
+    let bucket = registry.get_or_create_histogram(&histogram.key);
+    for i in 0..3 {
+        atomic.store(f64::from(i).to_bits(), Ordering::Relaxed);
+        bucket.push(1.0);
+        bucket.push(2.0);
+        dashboard.update();
+    }
+    for plot in dashboard.plots_mut() {
+        plot.stop_recording().unwrap();
+    }
+    assert_eq!(dashboard.plots()[0].recording_path(), None);
+
+    let gauge_csv = std::fs::read_to_string(&gauge_path).unwrap();
+    let histogram_csv = std::fs::read_to_string(&histogram_path).unwrap();
+    std::fs::remove_file(gauge_path).unwrap();
+    std::fs::remove_file(histogram_path).unwrap();
+    let gauge_values: Vec<_> = gauge_csv
+        .lines()
+        .skip(1)
+        .map(|line| line.rsplit(',').next().unwrap())
+        .collect();
+    assert_eq!(gauge_csv.lines().next(), Some("frame,seconds,value"));
+    assert_eq!(gauge_values, ["0", "1", "2"]);
+    assert_eq!(histogram_csv.lines().count(), 7);
+}
+
+#[test]
+fn recording_continues_while_dashboard_is_paused() {
+    let registry = MetricsRegistry::new();
+    let mut dashboard = Dashboard::new();
+    let gauge = key("health", MetricKind::Gauge);
+    add_default_plot(&mut dashboard, &registry, gauge.clone());
+    let path = std::env::temp_dir().join(format!("recording-paused-{}.csv", std::process::id()));
+    dashboard.plots_mut()[0].start_recording(&path).unwrap();
+
+    dashboard.config_mut().paused = true;
+    let atomic = registry.get_or_create_gauge(&gauge.key);
+    for i in 0..3 {
+        atomic.store(f64::from(i).to_bits(), Ordering::Relaxed);
+        dashboard.update();
+    }
+    assert_eq!(dashboard.plots()[0].latest_value(), None);
+    dashboard.plots_mut()[0].stop_recording().unwrap();
+
+    let csv = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(path).unwrap();
+    assert_eq!(csv.lines().count(), 4);
+}
+
+#[test]
+fn removing_plots_finishes_recordings() {
+    let registry = MetricsRegistry::new();
+    let mut dashboard = Dashboard::new();
+    let mut cached_configs = CachedPlotConfigs::default();
+    let mut cached_data = CachedPlotData::with_retention(Duration::from_secs(60));
+    let paths: Vec<_> = ["removed", "shortcut", "cleared"]
+        .iter()
+        .map(|name| {
+            let path =
+                std::env::temp_dir().join(format!("recording-{name}-{}.csv", std::process::id()));
+            add_default_plot(&mut dashboard, &registry, key(name, MetricKind::Gauge));
+            let plot = dashboard.plots_mut().last_mut().unwrap();
+            plot.start_recording(&path).unwrap();
+            path
+        })
+        .collect();
+    dashboard.update();
+
+    let removed = dashboard.remove_plot(0);
+    assert_eq!(removed.recording_path(), None);
+    assert!(dashboard.apply_shortcut(
+        0,
+        PlotShortcut::Remove,
+        &mut cached_configs,
+        &mut cached_data
+    ));
+    dashboard.clear();
+
+    // Each file was flushed, though two of the plots are still alive.
+    for path in paths {
+        let csv = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(csv.lines().count(), 2);
+    }
+}
@@ -0,0 +1,359 @@
+#[cfg(feature = "states")]
+use bevy::state::app::StatesPlugin;
+use bevy::{
+    core::FrameCount,
+    ecs::{schedule::ScheduleLabel, system::RunSystemOnce},
+    prelude::*,
+    time::TimePlugin,
+};
+use bevy_metrics_dashboard::{
+    bevy_egui::EguiUserTextures,
+    metrics::Key,
+    metrics_util::MetricKind,
+    namespace_tree::NamespaceTreeWindow,
+    plots::{MetricPlot, MetricPlotConfig, MultiSeriesPlot, YAxisSide},
+    registry::{MetricKey, MetricsRegistry},
+    spike_log::SpikeLogWindow,
+    startup_config::StartupConfig,
+    ClearBucketsPolicy, DashboardActivity, DashboardPlugin, DashboardQuery, DashboardWindow,
+    DashboardsVisible, HistogramConsumerAppExt, HistogramConsumers, HistogramRetention,
+    HistogramSketches, RegistryPlugin, RequestPlot,
+};
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq, ScheduleLabel)]
+struct UpdatePlots;
+
+fn n_systems(app: &App, schedule: impl ScheduleLabel) -> usize {
+    app.get_schedule(schedule).map_or(0, Schedule::systems_len)
+}
+
+#[test]
+fn plot_updates_move_to_custom_schedule() {
+    let mut default_app = App::new();
+    default_app.add_plugins(DashboardPlugin::new());
+
+    let mut custom_app = App::new();
+    custom_app.add_plugins(DashboardPlugin::new().with_update_schedule(UpdatePlots));
+
+    let n_moved = n_systems(&custom_app, UpdatePlots);
+    assert!(n_moved > 0);
+    assert_eq!(n_systems(&default_app, UpdatePlots), 0);
+    assert_eq!(
+        n_systems(&default_app, Last),
+        n_systems(&custom_app, Last) + n_moved
+    );
+}
+
+#[test]
+fn background_mode_sketches_histograms_without_windows() {
+    let registry = MetricsRegistry::default();
+    let key = Key::from_static_name("latency");
+    let bucket = registry.get_or_create_histogram(&key);
+
+    let mut app = App::new();
+    app.init_resource::<FrameCount>().add_plugins((
+        RegistryPlugin::with_registry(registry.clone()),
+        DashboardPlugin::new().with_background_mode(),
+    ));
+
+    for value in 1..=100 {
+        bucket.push(value as f64);
+    }
+    app.world_mut().run_schedule(Last);
+
+    assert_eq!(
+        *app.world().resource::<DashboardActivity>(),
+        DashboardActivity::Background
+    );
+    assert!(bucket.is_empty());
+    let sketch = app
+        .world()
+        .resource::<HistogramSketches>()
+        .get(&key)
+        .unwrap();
+    assert_eq!(sketch.count(), 100);
+    let median = sketch.quantile(0.5).unwrap();
+    assert!((median - 50.0).abs() < 1.5, "{median}");
+
+    app.world_mut().spawn(DashboardWindow::new("Metrics"));
+    bucket.push(1.0);
+    app.world_mut().run_schedule(Last);
+
+    assert_eq!(
+        *app.world().resource::<DashboardActivity>(),
+        DashboardActivity::Foreground
+    );
+    assert_eq!(
+        *app.world().resource::<HistogramRetention>(),
+        HistogramRetention::Buckets
+    );
+    let sketch = app
+        .world()
+        .resource::<HistogramSketches>()
+        .get(&key)
+        .unwrap();
+    assert_eq!(sketch.count(), 100);
+}
+
+#[test]
+fn sketches_wait_for_consumers() {
+    let registry = MetricsRegistry::default();
+    let key = Key::from_static_name("latency");
+    let bucket = registry.get_or_create_histogram(&key);
+
+    let mut app = App::new();
+    app.add_plugins(
+        RegistryPlugin::with_registry(registry.clone())
+            .with_clear_buckets_policy(ClearBucketsPolicy::WhenConsumed),
+    )
+    .insert_resource(HistogramRetention::Sketch);
+    let consumers = app.world().resource::<HistogramConsumers>().clone();
+    let consumer = consumers.register();
+
+    bucket.push(1.0);
+    app.world_mut().run_schedule(Last);
+    assert!(!bucket.is_empty());
+    assert!(app
+        .world()
+        .resource::<HistogramSketches>()
+        .get(&key)
+        .is_none());
+
+    consumers.mark_read(consumer);
+    app.world_mut().run_schedule(Last);
+    assert!(bucket.is_empty());
+    let sketches = app.world().resource::<HistogramSketches>();
+    assert_eq!(sketches.get(&key).unwrap().count(), 1);
+}
+
+#[test]
+fn histogram_consumers_read_before_clearing() {
+    #[derive(Default, Resource)]
+    struct Samples(usize);
+
+    let registry = MetricsRegistry::default();
+    let bucket = registry.get_or_create_histogram(&Key::from_static_name("latency"));
+
+    let mut app = App::new();
+    app.add_plugins(
+        RegistryPlugin::with_registry(registry.clone())
+            .with_clear_buckets_policy(ClearBucketsPolicy::WhenConsumed),
+    )
+    .init_resource::<Samples>()
+    .add_histogram_consumer(
+        |registry: Res<MetricsRegistry>, mut samples: ResMut<Samples>| {
+            registry.visit_histograms(|_, bucket| bucket.data_with(|s| samples.0 += s.len()));
+        },
+    );
+
+    bucket.push(1.0);
+    app.world_mut().run_schedule(Last);
+    assert_eq!(app.world().resource::<Samples>().0, 1);
+    assert!(bucket.is_empty());
+}
+
+#[test]
+fn startup_config_from_vars_spawns_windows() {
+    let vars = [
+        ("BMD_AUTOSPAWN", "1"),
+        ("BMD_PAUSE_KEY", "F3"),
+        ("BMD_READ_ONLY", "maybe"),
+        ("PATH", "/usr/bin"),
+    ];
+    let config = StartupConfig::from_vars(vars.map(|(k, v)| (k.into(), v.into())));
+    assert_eq!(
+        config,
+        StartupConfig {
+            autospawn: true,
+            layout: None,
+            pause_key: Some(KeyCode::F3),
+            read_only: None,
+        }
+    );
+
+    let mut app = App::new();
+    app.add_plugins((
+        RegistryPlugin::default(),
+        DashboardPlugin::new().with_startup_config(config),
+    ));
+    app.world_mut().run_schedule(Startup);
+    let world = app.world_mut();
+    assert_eq!(world.query::<&DashboardWindow>().iter(world).count(), 1);
+    assert_eq!(world.query::<&NamespaceTreeWindow>().iter(world).count(), 1);
+}
+
+#[test]
+fn startup_config_ignores_empty_values() {
+    let vars = [
+        ("BMD_AUTOSPAWN", "1"),
+        ("BMD_AUTOSPAWN", ""),
+        ("BMD_PAUSE_KEY", "F3"),
+        ("BMD_PAUSE_KEY", ""),
+        ("BMD_READ_ONLY", "1"),
+        ("BMD_READ_ONLY", ""),
+    ];
+    let config = StartupConfig::from_vars(vars.map(|(k, v)| (k.into(), v.into())));
+    assert!(config.autospawn);
+    assert_eq!(config.pause_key, Some(KeyCode::F3));
+    assert_eq!(config.read_only, Some(true));
+}
+
+#[cfg(feature = "states")]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, States)]
+enum AppState {
+    #[default]
+    Gameplay,
+    Dev,
+    Paused,
+}
+
+#[test]
+fn plot_requests_apply_while_hidden() {
+    let mut app = App::new();
+    app.add_plugins((
+        RegistryPlugin::with_registry(MetricsRegistry::new()),
+        DashboardPlugin::new(),
+    ))
+    .insert_resource(DashboardsVisible(false))
+    .init_resource::<FrameCount>();
+    let window = app.world_mut().spawn(DashboardWindow::new("Hidden")).id();
+
+    let key = MetricKey::new(Key::from_static_name("fps"), MetricKind::Gauge);
+    app.world_mut()
+        .send_event(RequestPlot::new(key.clone(), None));
+    app.update();
+    app.update();
+
+    let window = app.world().get::<DashboardWindow>(window).unwrap();
+    let plots = window.dashboard().plots();
+    assert_eq!(plots.len(), 1);
+    assert_eq!(plots[0].key(), &key);
+}
+
+#[test]
+fn plugin_read_only_keeps_window_config() {
+    let mut app = App::new();
+    app.add_plugins((
+        TimePlugin,
+        RegistryPlugin::with_registry(MetricsRegistry::new()),
+        DashboardPlugin::new().with_read_only(true),
+    ))
+    .init_resource::<EguiUserTextures>()
+    .init_resource::<FrameCount>();
+    let window = app.world_mut().spawn(DashboardWindow::new("Editable")).id();
+    app.update();
+    app.update();
+
+    let window = app.world().get::<DashboardWindow>(window).unwrap();
+    assert!(!window.dashboard().config().read_only);
+}
+
+#[cfg(feature = "states")]
+#[test]
+fn windows_only_visible_in_chosen_states() {
+    let mut app = App::new();
+    app.add_plugins((
+        StatesPlugin,
+        TimePlugin,
+        RegistryPlugin::default(),
+        DashboardPlugin::new().visible_in_states([AppState::Dev, AppState::Paused]),
+    ))
+    .init_state::<AppState>()
+    .init_resource::<EguiUserTextures>()
+    .init_resource::<FrameCount>();
+
+    app.update();
+    assert_eq!(
+        *app.world().resource::<DashboardsVisible>(),
+        DashboardsVisible(false)
+    );
+
+    app.world_mut()
+        .resource_mut::<NextState<AppState>>()
+        .set(AppState::Dev);
+    app.update();
+    assert_eq!(
+        *app.world().resource::<DashboardsVisible>(),
+        DashboardsVisible(true)
+    );
+}
+
+#[test]
+fn dashboard_query_lists_plotted_metrics_by_window() {
+    let registry = MetricsRegistry::new();
+    let key = |name| MetricKey::new(Key::from_static_name(name), MetricKind::Gauge);
+    let plot = |name| {
+        let config = MetricPlotConfig::default_for_kind(MetricKind::Gauge);
+        MetricPlot::new(&registry, "", key(name), None, config)
+    };
+
+    let mut world = World::new();
+    let mut perf = DashboardWindow::new("Perf");
+    perf.dashboard_mut().insert_plot(plot("fps"));
+    perf.dashboard_mut().add_multi_series_plot(
+        MultiSeriesPlot::new("load")
+            .with_series(plot("fps"), YAxisSide::Left)
+            .with_series(plot("entities"), YAxisSide::Right),
+    );
+    let perf = world.spawn(perf).id();
+    let mut ai = DashboardWindow::new("AI");
+    ai.dashboard_mut().insert_plot(plot("think_time"));
+    let ai = world.spawn(ai).id();
+    let empty = world.spawn(DashboardWindow::new("Empty")).id();
+
+    world
+        .run_system_once(move |dashboards: DashboardQuery| {
+            assert_eq!(dashboards.windows().count(), 3);
+            assert_eq!(dashboards.plotted_metrics().count(), 4);
+            let ai_plot = dashboards
+                .plotted_metrics()
+                .find(|p| p.window == ai)
+                .unwrap();
+            assert_eq!((ai_plot.title, ai_plot.key), ("AI", &key("think_time")));
+
+            assert_eq!(
+                dashboards.metrics_in(perf).unwrap(),
+                [&key("fps"), &key("fps"), &key("entities")]
+            );
+            assert_eq!(
+                dashboards.metrics_in(empty).unwrap(),
+                Vec::<&MetricKey>::new()
+            );
+            assert_eq!(dashboards.windows_plotting(&key("entities")), [perf]);
+            assert!(dashboards.is_plotted(&key("think_time")));
+            assert!(!dashboards.is_plotted(&key("frame_time")));
+        })
+        .unwrap();
+}
+
+#[test]
+fn spike_is_logged_once_without_new_samples() {
+    let registry = MetricsRegistry::default();
+    let key = MetricKey::new(Key::from_static_name("frame_time"), MetricKind::Histogram);
+    let bucket = registry.get_or_create_histogram(&key.key);
+
+    let mut app = App::new();
+    app.init_resource::<FrameCount>().add_plugins((
+        RegistryPlugin::with_registry(registry.clone()),
+        DashboardPlugin::new(),
+    ));
+    let window = SpikeLogWindow::new("Spikes", &registry, key, None, 10.0);
+    let window = app.world_mut().spawn(window).id();
+
+    bucket.push(20.0);
+    for _ in 0..3 {
+        app.world_mut().run_schedule(Last);
+    }
+    let entries = |app: &App| {
+        app.world()
+            .get::<SpikeLogWindow>(window)
+            .unwrap()
+            .entries()
+            .count()
+    };
+    assert_eq!(entries(&app), 1);
+
+    bucket.push(30.0);
+    app.world_mut().run_schedule(Last);
+    assert_eq!(entries(&app), 2);
+}
@@ -0,0 +1,42 @@
+#![cfg(feature = "native_ui")]
+
+use bevy::prelude::*;
+use bevy_metrics_dashboard::{
+    metrics::{Key, Unit},
+    metrics_util::MetricKind,
+    native_ui::{NativeDashboard, NativeDashboardPlugin},
+    registry::{MetricKey, MetricsRegistry},
+    RegistryPlugin,
+};
+use std::sync::atomic::Ordering;
+
+#[test]
+fn native_dashboard_shows_latest_value() {
+    let registry = MetricsRegistry::default();
+    let key = MetricKey::new(Key::from_static_name("entities"), MetricKind::Gauge);
+    let gauge = registry.get_or_create_gauge(&key.key);
+
+    let mut app = App::new();
+    app.add_plugins((
+        RegistryPlugin::with_registry(registry.clone()),
+        NativeDashboardPlugin,
+    ));
+    let entity = app
+        .world_mut()
+        .spawn(NativeDashboard::new().with_metric(key.clone(), Some(Unit::Count)))
+        .id();
+
+    gauge.store(42f64.to_bits(), Ordering::Relaxed);
+    app.update();
+    app.update();
+
+    let dashboard = app.world().get::<NativeDashboard>(entity).unwrap();
+    assert_eq!(dashboard.latest_value(&key), Some(42.0));
+    let readouts: Vec<_> = app
+        .world_mut()
+        .query::<&Text>()
+        .iter(app.world())
+        .map(|t| t.0.clone())
+        .collect();
+    assert_eq!(readouts, ["entities (gauge) = 42.000 count"]);
+}
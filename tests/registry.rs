@@ -0,0 +1,567 @@
+use bevy::prelude::*;
+use bevy_metrics_dashboard::{
+    metric_triggers::{MetricTriggers, TriggerRule},
+    metrics::{counter, with_local_recorder, Key, KeyName, Label, Level, Metadata, Recorder, Unit},
+    metrics_util::MetricKind,
+    registry::{DescriptionKey, FuzzyNameMatcher, MetricKey, MetricsRegistry, PrefixNameMatcher},
+    ClearBucketsSystem, FinalFlush, FinalFlushReason, MetricProducerAppExt, OrderingDiagnostics,
+    OrderingProblem, ProduceMetricsSystem, RegistryPlugin, WriteCoalescing, WriteDiagnostics,
+};
+use std::sync::{atomic::Ordering, Arc, Mutex};
+
+#[test]
+fn downstream_receives_samples() {
+    let downstream = MetricsRegistry::new();
+    let registry = MetricsRegistry::with_downstream(downstream.clone());
+    let key = Key::from_static_name("spawned");
+    let metadata = Metadata::new(module_path!(), Level::INFO, None);
+
+    registry.register_counter(&key, &metadata).increment(2);
+
+    let local = registry.get_or_create_counter(&key).load(Ordering::Relaxed);
+    let forwarded = downstream
+        .get_or_create_counter(&key)
+        .load(Ordering::Relaxed);
+    assert_eq!((local, forwarded), (2, 2));
+}
+
+#[test]
+fn coalesced_counters_apply_increments_before_absolute_values() {
+    let registry = MetricsRegistry::new();
+    registry.set_write_coalescing(WriteCoalescing::all());
+    let metadata = Metadata::new(module_path!(), Level::INFO, None);
+    let key = Key::from_static_name("loaded");
+    let counter = registry.register_counter(&key, &metadata);
+    let atomic = registry.get_or_create_counter(&key);
+    let unwritten_refs = Arc::strong_count(&atomic);
+
+    counter.increment(5);
+    counter.absolute(7);
+    registry.flush_coalesced_writes();
+    assert_eq!(atomic.load(Ordering::Relaxed), 7);
+
+    // Flushed buffers don't keep metrics alive.
+    counter.increment(1);
+    drop(counter);
+    registry.flush_coalesced_writes();
+    assert_eq!(atomic.load(Ordering::Relaxed), 8);
+    assert_eq!(Arc::strong_count(&atomic), unwritten_refs - 1);
+}
+
+#[test]
+fn coalesced_writes_wait_for_flush() {
+    let registry = MetricsRegistry::new();
+    registry.set_write_coalescing(WriteCoalescing::all());
+    let metadata = Metadata::new(module_path!(), Level::INFO, None);
+    let counter_key = Key::from_static_name("spawned");
+    let gauge_key = Key::from_static_name("health");
+    let counter = registry.register_counter(&counter_key, &metadata);
+    let gauge = registry.register_gauge(&gauge_key, &metadata);
+
+    for i in 0..10 {
+        counter.increment(1);
+        gauge.set(i as f64);
+    }
+    gauge.increment(0.5);
+    let load_counter = || {
+        registry
+            .get_or_create_counter(&counter_key)
+            .load(Ordering::Relaxed)
+    };
+    let load_gauge = || {
+        f64::from_bits(
+            registry
+                .get_or_create_gauge(&gauge_key)
+                .load(Ordering::Relaxed),
+        )
+    };
+    assert_eq!((load_counter(), load_gauge()), (0, 0.0));
+
+    registry.flush_coalesced_writes();
+    assert_eq!((load_counter(), load_gauge()), (10, 9.5));
+}
+
+#[test]
+fn sharded_counters_aggregate_stripes_on_flush() {
+    let registry = MetricsRegistry::new();
+    registry.set_counter_sharding(true);
+    let metadata = Metadata::new(module_path!(), Level::INFO, None);
+    let key = Key::from_static_name("visited");
+    let counter = registry.register_counter(&key, &metadata);
+
+    std::thread::scope(|scope| {
+        for _ in 0..4 {
+            let counter = counter.clone();
+            scope.spawn(move || {
+                for _ in 0..1000 {
+                    counter.increment(1);
+                }
+            });
+        }
+    });
+    let load = || registry.get_or_create_counter(&key).load(Ordering::Relaxed);
+    assert_eq!(load(), 0);
+    assert_eq!(registry.pending_coalesced_writes(), 1);
+
+    registry.flush_coalesced_writes();
+    assert_eq!(load(), 4000);
+    assert_eq!(registry.pending_coalesced_writes(), 0);
+
+    // Absolute values apply after the increments before them.
+    registry.register_counter(&key, &metadata).increment(5);
+    counter.absolute(4001);
+    assert_eq!(load(), 4005);
+}
+
+#[test]
+fn sharded_counters_share_stripes_across_macro_calls() {
+    let registry = MetricsRegistry::new();
+    registry.set_counter_sharding(true);
+
+    // The macros register a new handle on every call.
+    std::thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                with_local_recorder(&registry, || {
+                    for _ in 0..1000 {
+                        counter!("visited").increment(1);
+                    }
+                });
+            });
+        }
+    });
+    let key = Key::from_static_name("visited");
+    let load = || registry.get_or_create_counter(&key).load(Ordering::Relaxed);
+    assert_eq!(load(), 0);
+    assert_eq!(registry.pending_coalesced_writes(), 1);
+
+    registry.flush_coalesced_writes();
+    assert_eq!(load(), 4000);
+    assert_eq!(registry.pending_coalesced_writes(), 0);
+}
+
+#[test]
+fn registration_records_target() {
+    let registry = MetricsRegistry::new();
+    let key = Key::from_static_name("spawned");
+    let metadata = Metadata::new("my_game::spawner", Level::INFO, None);
+
+    let _ = registry.register_counter(&key, &metadata);
+
+    let desc_key = DescriptionKey {
+        name: key.name_shared(),
+        kind: MetricKind::Counter,
+    };
+    assert_eq!(
+        registry.get_target(&desc_key).as_deref(),
+        Some("my_game::spawner")
+    );
+}
+
+#[test]
+fn registration_records_target_of_existing_metric() {
+    let registry = MetricsRegistry::new();
+    let key = Key::from_static_name("spawned");
+    registry.get_or_create_counter(&key);
+
+    let _ = registry.register_counter(&key, &Metadata::new("my_game::spawner", Level::INFO, None));
+    let _ = registry.register_counter(&key, &Metadata::new("my_game::other", Level::INFO, None));
+
+    let desc_key = DescriptionKey {
+        name: key.name_shared(),
+        kind: MetricKind::Counter,
+    };
+    assert_eq!(
+        registry.get_target(&desc_key).as_deref(),
+        Some("my_game::spawner")
+    );
+}
+
+#[test]
+fn find_by_name_matches_any_labels() {
+    let registry = MetricsRegistry::new();
+    let labels = vec![Label::new("system", "physics")];
+    registry.get_or_create_gauge(&Key::from_parts("busy", labels));
+    registry.get_or_create_gauge(&Key::from_static_name("busy"));
+    registry.get_or_create_counter(&Key::from_static_name("busy"));
+
+    assert!(registry.find_by_name("idle", MetricKind::Gauge).is_empty());
+    assert_eq!(registry.find_by_name("busy", MetricKind::Gauge).len(), 2);
+}
+
+#[test]
+fn search_results_are_scored() {
+    let registry = MetricsRegistry::new();
+    for name in ["render_time", "Render_passes", "frame_render"] {
+        registry.get_or_create_gauge(&Key::from_static_name(name));
+    }
+    let names = |results: &[bevy_metrics_dashboard::registry::SearchResult]| {
+        results
+            .iter()
+            .map(|r| r.key.key.name().to_owned())
+            .collect::<Vec<_>>()
+    };
+
+    let fuzzy = registry.fuzzy_search_by_name("render");
+    assert_eq!(fuzzy.len(), 3);
+    assert!(fuzzy.windows(2).all(|w| w[0].score >= w[1].score));
+    assert!(fuzzy.iter().all(|r| r.score.is_some()));
+
+    let prefix = registry.search_by_name("render", &PrefixNameMatcher::default());
+    assert_eq!(names(&prefix), ["render_time", "Render_passes"]);
+
+    let case_sensitive = PrefixNameMatcher {
+        case_sensitive: true,
+    };
+    let prefix = registry.search_by_name("render", &case_sensitive);
+    assert_eq!(names(&prefix), ["render_time"]);
+    assert!(registry.all_metrics().iter().all(|r| r.score.is_none()));
+}
+
+#[test]
+fn on_metric_added_runs_once_per_series() {
+    let registry = MetricsRegistry::new();
+    let added = Arc::new(Mutex::new(Vec::new()));
+    let hook_added = added.clone();
+    registry.on_metric_added(move |key| hook_added.lock().unwrap().push(key.clone()));
+    let metadata = Metadata::new(module_path!(), Level::INFO, None);
+    let key = Key::from_static_name("spawned");
+
+    registry.register_counter(&key, &metadata).increment(1);
+    registry.register_counter(&key, &metadata).increment(1);
+    registry.get_or_create_gauge(&key);
+
+    let kinds: Vec<_> = added.lock().unwrap().iter().map(|k| k.kind).collect();
+    assert_eq!(kinds, [MetricKind::Counter, MetricKind::Gauge]);
+}
+
+#[test]
+fn trigger_fires_on_rising_edge() {
+    let registry = MetricsRegistry::new();
+    let key = MetricKey::new(Key::from_static_name("frame_time"), MetricKind::Gauge);
+    let gauge = registry.get_or_create_gauge(&key.key);
+    let mut triggers = MetricTriggers::default();
+    triggers.add(TriggerRule::above("slow frame", key, 30.0));
+
+    let fired: Vec<_> = [10.0, 40.0, 50.0, 10.0, 35.0]
+        .into_iter()
+        .map(|value: f64| {
+            gauge.store(value.to_bits(), Ordering::Relaxed);
+            triggers.check(&registry, None).len()
+        })
+        .collect();
+
+    assert_eq!(fired, [0, 1, 0, 0, 1]);
+}
+
+#[test]
+fn upkeep_tracks_recency_and_expires_idle_metrics() {
+    let registry = MetricsRegistry::new();
+    let metadata = Metadata::new(module_path!(), Level::INFO, None);
+    let active = MetricKey::new(Key::from_static_name("active"), MetricKind::Counter);
+    let constant = MetricKey::new(Key::from_static_name("constant"), MetricKind::Gauge);
+    let idle = MetricKey::new(Key::from_static_name("idle"), MetricKind::Gauge);
+    let counter = registry.register_counter(&active.key, &metadata);
+    let gauge = registry.register_gauge(&constant.key, &metadata);
+    registry.register_gauge(&idle.key, &metadata).set(1.0);
+    let samples = registry.register_histogram(&Key::from_static_name("samples"), &metadata);
+
+    registry.upkeep();
+    for _ in 0..3 {
+        counter.increment(1);
+        // Writing the same value still counts as an update.
+        gauge.set(1.0);
+        samples.record(1.0);
+        registry.upkeep();
+    }
+
+    assert_eq!(registry.upkeep_generation(), 4);
+    assert_eq!(registry.generations_since_update(&active), Some(0));
+    assert_eq!(registry.generations_since_update(&constant), Some(0));
+    assert_eq!(registry.generations_since_update(&idle), Some(3));
+    assert_eq!(registry.last_update_generation(&idle), Some(1));
+
+    let mut results = registry.all_metrics();
+    registry.sort_by_recency(&mut results);
+    assert_eq!(results.last().unwrap().key, idle);
+
+    assert_eq!(registry.remove_idle(2), vec![idle.clone()]);
+    assert_eq!(registry.generations_since_update(&idle), None);
+    assert!(registry.all_metrics().iter().all(|r| r.key != idle));
+}
+
+#[test]
+fn trigger_hysteresis_and_min_frames() {
+    let registry = MetricsRegistry::new();
+    let key = MetricKey::new(Key::from_static_name("frame_time"), MetricKind::Gauge);
+    let gauge = registry.get_or_create_gauge(&key.key);
+    let mut triggers = MetricTriggers::default();
+    triggers.add(TriggerRule::above("hysteresis", key.clone(), 30.0).with_exit_below(20.0));
+    triggers.add(TriggerRule::above("debounced", key, 30.0).with_min_frames(2));
+
+    // A single-frame spike, flapping around the threshold, then recovering.
+    let fired: Vec<Vec<_>> = [10.0, 40.0, 25.0, 35.0, 40.0, 15.0, 35.0]
+        .into_iter()
+        .map(|value: f64| {
+            gauge.store(value.to_bits(), Ordering::Relaxed);
+            let events = triggers.check(&registry, None);
+            events.into_iter().map(|e| e.rule).collect()
+        })
+        .collect();
+
+    let expected: [&[&str]; 7] = [
+        &[],
+        &["hysteresis"],
+        &[],
+        &[],
+        &["debounced"],
+        &[],
+        &["hysteresis"],
+    ];
+    assert_eq!(fired, expected);
+}
+
+#[test]
+fn namespace_descriptions_fill_in_missing_describes() {
+    let registry = MetricsRegistry::new();
+    registry.describe_namespace("net::bytes::*", Some(Unit::Bytes), "Network traffic");
+    registry.describe_namespace("net", None, "Networking");
+    registry.describe_gauge(
+        KeyName::from("net::bytes::buffered"),
+        Some(Unit::Kibibytes),
+        "Bytes waiting to be sent".into(),
+    );
+    for name in [
+        "net::bytes::sent",
+        "net::bytes::buffered",
+        "net::latency",
+        "net::bytes_total",
+    ] {
+        registry.get_or_create_gauge(&Key::from_static_name(name));
+    }
+
+    let unit = |name: &'static str| {
+        let key = DescriptionKey {
+            name: KeyName::from_const_str(name),
+            kind: MetricKind::Gauge,
+        };
+        registry.get_description(&key).and_then(|d| d.unit)
+    };
+    assert_eq!(unit("net::bytes::sent"), Some(Unit::Bytes));
+    assert_eq!(unit("net::bytes::buffered"), Some(Unit::Kibibytes));
+    assert_eq!(unit("net::bytes_total"), None);
+    let latency = registry.find_by_name("net::latency", MetricKind::Gauge);
+    assert_eq!(
+        &*latency[0].description.as_ref().unwrap().text,
+        "Networking"
+    );
+}
+
+#[test]
+fn final_flush_on_replacement_and_exit() {
+    let first = MetricsRegistry::new();
+    let mut app = App::new();
+    app.add_plugins(
+        RegistryPlugin::with_registry(first.clone())
+            .with_write_coalescing(WriteCoalescing::all())
+            .with_final_flush(),
+    );
+    app.update();
+
+    let metadata = Metadata::new(module_path!(), Level::INFO, None);
+    let key = Key::from_static_name("requests");
+    first.register_counter(&key, &metadata).increment(3);
+    assert_eq!(first.pending_coalesced_writes(), 1);
+
+    let second = MetricsRegistry::new();
+    app.insert_resource(second.clone());
+    app.update();
+    assert_eq!(first.pending_coalesced_writes(), 0);
+    assert_eq!(first.get_or_create_counter(&key).load(Ordering::Relaxed), 3);
+
+    app.world_mut().send_event(AppExit::Success);
+    app.update();
+
+    let events = app.world().resource::<Events<FinalFlush>>();
+    let flushes: Vec<_> = events
+        .get_cursor()
+        .read(events)
+        .map(|f| (f.reason, f.registry.ptr_eq(&first)))
+        .collect();
+    assert_eq!(
+        flushes,
+        [
+            (FinalFlushReason::Replaced, true),
+            (FinalFlushReason::AppExit, false)
+        ]
+    );
+}
+
+#[test]
+fn search_prefers_last_component_and_scopes_to_namespace() {
+    let registry = MetricsRegistry::new();
+    for name in [
+        "timeline::events::count",
+        "render::time",
+        "render::passes::time",
+    ] {
+        registry.get_or_create_gauge(&Key::from_static_name(name));
+    }
+    let names = |results: Vec<bevy_metrics_dashboard::registry::SearchResult>| {
+        results
+            .into_iter()
+            .map(|r| r.key.key.name().to_owned())
+            .collect::<Vec<_>>()
+    };
+
+    let fuzzy = names(registry.fuzzy_search_by_name("time"));
+    assert_eq!(fuzzy.last().unwrap(), "timeline::events::count");
+
+    let unweighted = FuzzyNameMatcher::default().with_last_component_weight(0);
+    let unweighted = registry.search_by_name("time", &unweighted);
+    assert_eq!(unweighted[0].key.key.name(), "timeline::events::count");
+
+    let scoped = registry.search_in_namespace("render", "time", &FuzzyNameMatcher::default());
+    assert_eq!(names(scoped), ["render::time", "render::passes::time"]);
+    let scoped = registry.search_in_namespace("render", "render", &FuzzyNameMatcher::default());
+    assert!(scoped.is_empty());
+}
+
+#[test]
+fn search_terms_must_all_match_and_exclude_negated_terms() {
+    let registry = MetricsRegistry::new();
+    for name in [
+        "render::time",
+        "gpu::time",
+        "gpu::memory",
+        "physics::step_time",
+    ] {
+        registry.get_or_create_gauge(&Key::from_static_name(name));
+    }
+    let search = |input| {
+        let mut names: Vec<_> = registry
+            .search_by_name(input, &PrefixNameMatcher::default())
+            .into_iter()
+            .chain(registry.fuzzy_search_by_name(input))
+            .map(|r| r.key.key.name().to_owned())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    };
+
+    assert_eq!(search("time -gpu"), ["physics::step_time", "render::time"]);
+    assert_eq!(search("time -GPU -step"), ["render::time"]);
+    assert_eq!(search("gpu time"), ["gpu::time"]);
+    assert_eq!(search("-time"), ["gpu::memory"]);
+    // A lone "-" is a term, not an exclusion.
+    assert!(search("-").is_empty());
+
+    let scoped = registry.search_in_namespace("gpu", "-memory", &FuzzyNameMatcher::default());
+    assert_eq!(scoped.len(), 1);
+    assert_eq!(scoped[0].key.key.name(), "gpu::time");
+}
+
+#[test]
+fn write_counts_are_sampled_every_interval() {
+    let registry = MetricsRegistry::new();
+    registry.set_write_counting(true);
+    let metadata = Metadata::new(module_path!(), Level::INFO, None);
+    let dead = registry.register_gauge(&Key::from_static_name("dead"), &metadata);
+    dead.set(1.0);
+    let hot_key = MetricKey::new(Key::from_static_name("hot"), MetricKind::Counter);
+    let dead_key = MetricKey::new(Key::from_static_name("dead"), MetricKind::Gauge);
+
+    // Every second frame is sampled, starting with the second.
+    for frame in 1..=6 {
+        // Like the `counter!` macro, register the handle again every frame.
+        let hot = registry.register_counter(&Key::from_static_name("hot"), &metadata);
+        for _ in 0..frame * 10 {
+            hot.increment(1);
+        }
+        registry.sample_write_counts(2);
+    }
+    let hot_stats = registry.write_stats(&hot_key).unwrap();
+    assert_eq!(hot_stats.writes_per_frame, 50);
+    assert_eq!(hot_stats.max_writes_per_frame, 50);
+    assert_eq!(hot_stats.samples, 2);
+    assert_eq!(registry.write_stats(&dead_key).unwrap().idle_samples, 2);
+    // Writes are still applied while not counted.
+    assert_eq!(
+        registry
+            .get_or_create_counter(&hot_key.key)
+            .load(Ordering::Relaxed),
+        210
+    );
+
+    let diagnostics = WriteDiagnostics {
+        hot_writes_per_frame: 50,
+        dead_after_samples: 2,
+    };
+    assert!(diagnostics.is_hot(&hot_stats));
+    assert!(diagnostics.is_dead(&registry.write_stats(&dead_key).unwrap()));
+    assert_eq!(registry.all_write_stats().len(), 2);
+
+    // Metrics registered before counting was enabled don't count.
+    let quiet = Key::from_static_name("quiet");
+    registry.set_write_counting(false);
+    registry.register_counter(&quiet, &metadata).increment(1);
+    registry.set_write_counting(true);
+    registry.register_counter(&quiet, &metadata).increment(1);
+    registry.sample_write_counts(1);
+    registry.sample_write_counts(1);
+    let quiet = MetricKey::new(quiet, MetricKind::Counter);
+    assert_eq!(registry.write_stats(&quiet), None);
+}
+
+#[test]
+fn ordering_diagnostics_name_misordered_systems() {
+    fn produce_frame_time() {}
+    fn plot_frame_time(_: Res<MetricsRegistry>) {}
+    fn plot_after_clear(_: Res<MetricsRegistry>) {}
+    fn plot_anytime(_: Res<MetricsRegistry>) {}
+    fn plot_before_producers(_: Res<MetricsRegistry>) {}
+
+    let mut app = App::new();
+    app.add_plugins(
+        RegistryPlugin::with_registry(MetricsRegistry::new())
+            .with_sharded_counters()
+            .with_write_counting(10)
+            .with_metric_expiry(100)
+            .with_final_flush()
+            .with_ordering_diagnostics(),
+    )
+    .add_metric_producer(produce_frame_time)
+    .add_systems(
+        Last,
+        (
+            plot_frame_time
+                .after(ProduceMetricsSystem)
+                .before(ClearBucketsSystem),
+            plot_after_clear.after(ClearBucketsSystem),
+            plot_anytime,
+            plot_before_producers.before(ClearBucketsSystem),
+        ),
+    );
+    app.update();
+    app.update();
+
+    let diagnostics = app.world().resource::<OrderingDiagnostics>();
+    assert!(diagnostics.is_checked());
+    let name = |f: &str| format!("registry::ordering_diagnostics_name_misordered_systems::{f}");
+    assert_eq!(
+        diagnostics.problems(),
+        [
+            OrderingProblem::ConsumerAfterClear {
+                consumer: name("plot_after_clear")
+            },
+            OrderingProblem::ConsumerNotBeforeClear {
+                consumer: name("plot_anytime")
+            },
+            OrderingProblem::ProducerNotBeforeConsumer {
+                producer: name("produce_frame_time"),
+                consumer: name("plot_before_producers"),
+            },
+        ]
+    );
+}
@@ -0,0 +1,108 @@
+#![cfg(feature = "remote")]
+
+use bevy::{core::FrameCount, prelude::*};
+use bevy_metrics_dashboard::{
+    metrics::{Key, Label},
+    metrics_util::MetricKind,
+    registry::{MetricKey, MetricsRegistry},
+    remote_plugin::{DashboardRemotePlugin, REQUEST_PLOT_METHOD, SNAPSHOT_METHOD},
+    RegistryPlugin, RequestPlot,
+};
+use bevy_remote::{BrpResult, RemoteMethodSystemId, RemoteMethods, RemotePlugin};
+use serde_json::{json, Value};
+use std::sync::atomic::Ordering;
+
+fn remote_app(registry: &MetricsRegistry) -> App {
+    let mut app = App::new();
+    app.init_resource::<FrameCount>().add_plugins((
+        RemotePlugin::default(),
+        RegistryPlugin::with_registry(registry.clone()),
+        DashboardRemotePlugin,
+    ));
+    app
+}
+
+/// Handle a request like the BRP server does, with the system registered for
+/// `method`.
+fn send_request(app: &mut App, method: &str, params: Option<Value>) -> BrpResult {
+    let Some(&RemoteMethodSystemId::Instant(system)) =
+        app.world().resource::<RemoteMethods>().get(method)
+    else {
+        panic!("{method} isn't registered");
+    };
+    app.world_mut()
+        .run_system_with_input(system, params)
+        .unwrap()
+}
+
+#[test]
+fn snapshot_reads_values_and_histogram_summaries() {
+    let registry = MetricsRegistry::new();
+    let mut app = remote_app(&registry);
+    let gauge = registry.get_or_create_gauge(&Key::from_static_name("health"));
+    gauge.store(5.0f64.to_bits(), Ordering::Relaxed);
+    let bucket = registry.get_or_create_histogram(&Key::from_static_name("frame_time"));
+    bucket.push(1.0);
+    bucket.push(3.0);
+    app.update();
+
+    let result = send_request(&mut app, SNAPSHOT_METHOD, None).unwrap();
+    let snapshots = result.as_array().unwrap();
+    let value = |name: &str| {
+        snapshots
+            .iter()
+            .find(|s| s["name"] == name)
+            .map(|s| s["value"].clone())
+    };
+    assert_eq!(value("health"), Some(json!(5.0)));
+    assert_eq!(
+        value("frame_time"),
+        Some(json!({ "count": 2, "min": 1.0, "max": 3.0, "mean": 2.0 }))
+    );
+
+    let params = json!({ "names": ["health"] });
+    let result = send_request(&mut app, SNAPSHOT_METHOD, Some(params)).unwrap();
+    assert_eq!(result.as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn request_plot_sends_event_without_dashboard_plugin() {
+    let registry = MetricsRegistry::new();
+    let mut app = remote_app(&registry);
+    let key = Key::from_parts(
+        "render_time",
+        vec![Label::new("gpu", "0"), Label::new("pass", "main")],
+    );
+    registry.get_or_create_gauge(&key);
+
+    // Label order doesn't matter.
+    let params = json!({
+        "name": "render_time",
+        "kind": "gauge",
+        "labels": { "pass": "main", "gpu": "0" },
+    });
+    let result = send_request(&mut app, REQUEST_PLOT_METHOD, Some(params));
+    assert_eq!(result.unwrap(), Value::Null);
+    let events = app.world().resource::<Events<RequestPlot>>();
+    let requested: Vec<_> = events
+        .iter_current_update_events()
+        .map(|request| request.key.clone())
+        .collect();
+    assert_eq!(requested, [MetricKey::new(key, MetricKind::Gauge)]);
+
+    let params = json!({ "name": "render_time", "kind": "meter" });
+    assert!(send_request(&mut app, REQUEST_PLOT_METHOD, Some(params)).is_err());
+    assert!(send_request(&mut app, REQUEST_PLOT_METHOD, None).is_err());
+}
+
+#[test]
+fn requires_remote_plugin() {
+    let n_systems = |app: &App| app.get_schedule(Last).map_or(0, Schedule::systems_len);
+    let mut app = App::new();
+    app.add_plugins(RegistryPlugin::with_registry(MetricsRegistry::new()));
+    let n_registry_systems = n_systems(&app);
+    app.add_plugins(DashboardRemotePlugin);
+
+    assert_eq!(n_systems(&app), n_registry_systems);
+    assert!(!app.world().contains_resource::<Events<RequestPlot>>());
+}
@@ -0,0 +1,40 @@
+use bevy::prelude::*;
+use bevy_metrics_dashboard::{
+    metrics::{Key, Label},
+    registry::MetricsRegistry,
+    RegistryPlugin, StartupMetricsPlugin,
+};
+use std::sync::atomic::Ordering;
+
+#[test]
+fn startup_phases_are_recorded() {
+    let registry = MetricsRegistry::new();
+    let mut app = App::new();
+    app.add_plugins((
+        StartupMetricsPlugin,
+        RegistryPlugin::with_registry(registry.clone()),
+    ));
+
+    app.update();
+
+    let mut phases = Vec::new();
+    registry.visit_gauges(|key, _| {
+        if key.name() == "startup_schedule_time" {
+            phases.extend(key.labels().map(|l| l.value().to_owned()));
+        }
+    });
+    phases.sort();
+    assert_eq!(phases, ["PostStartup", "PreStartup", "Startup"]);
+
+    let gauge =
+        |key: Key| f64::from_bits(registry.get_or_create_gauge(&key).load(Ordering::Relaxed));
+    let first_frame = gauge(Key::from_static_name("startup_time_to_first_frame"));
+    assert!(first_frame >= gauge(Key::from_static_name("startup_plugin_time")));
+    assert!(
+        first_frame
+            >= gauge(Key::from_parts(
+                "startup_schedule_time",
+                vec![Label::new("schedule", "Startup")]
+            ))
+    );
+}
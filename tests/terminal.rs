@@ -0,0 +1,41 @@
+#![cfg(feature = "terminal")]
+
+use bevy_metrics_dashboard::{
+    metrics::{Key, Label},
+    registry::MetricsRegistry,
+    terminal::TerminalTable,
+};
+use std::{sync::atomic::Ordering, time::Duration};
+
+#[test]
+fn table_shows_selected_metrics() {
+    let registry = MetricsRegistry::new();
+    let requests = Key::from_parts("requests", vec![Label::new("route", "/")]);
+    let frame_time = Key::from_static_name("frame_time");
+    registry
+        .get_or_create_counter(&requests)
+        .store(10, Ordering::Relaxed);
+    registry.get_or_create_gauge(&Key::from_static_name("hidden"));
+    let histogram = registry.get_or_create_histogram(&frame_time);
+    histogram.push(10.0);
+    histogram.push(20.0);
+
+    let mut table = TerminalTable::new(vec!["requests".into(), "frame_time".into()]);
+    table.sample(&registry);
+    let _ = table.render(&registry, Duration::from_secs(1));
+
+    registry
+        .get_or_create_counter(&requests)
+        .fetch_add(20, Ordering::Relaxed);
+    histogram.push(30.0);
+    table.sample(&registry);
+    let text = table.render(&registry, Duration::from_secs(2));
+
+    let lines: Vec<_> = text.lines().collect();
+    assert_eq!(lines.len(), 3, "{text}");
+    assert!(lines[0].starts_with("METRIC"));
+    assert!(lines[1].starts_with("frame_time"));
+    assert!(lines[1].ends_with("n=1 max=30.000"), "{text}");
+    assert!(lines[2].starts_with("requests{route=/}"));
+    assert!(lines[2].ends_with("30  10.0/s"), "{text}");
+}
@@ -0,0 +1,50 @@
+use bevy_metrics_dashboard::{
+    metrics::{Key, Label},
+    metrics_util::MetricKind,
+    registry::{MetricKey, MetricsRegistry},
+    wide_csv::WideCsvRecorder,
+};
+use std::{sync::atomic::Ordering, time::Duration};
+
+#[test]
+fn samples_every_column_on_the_same_rows() {
+    let registry = MetricsRegistry::new();
+    let spawned = Key::from_parts("spawned", vec![Label::new("kind", "enemy")]);
+    let counter = registry.get_or_create_counter(&spawned);
+    let gauge = registry.get_or_create_gauge(&Key::from_static_name("fps"));
+    let histogram = registry.get_or_create_histogram(&Key::from_static_name("frame_time"));
+    let mut recorder = WideCsvRecorder::new([
+        MetricKey::new(spawned.clone(), MetricKind::Counter),
+        MetricKey::new(Key::from_static_name("fps"), MetricKind::Gauge),
+    ]);
+
+    for frame in 0..2 {
+        counter.fetch_add(1, Ordering::Relaxed);
+        gauge.store((60.0 - frame as f64).to_bits(), Ordering::Relaxed);
+        histogram.push(4.0);
+        histogram.push(8.0);
+        if frame == 1 {
+            recorder.add_metric(MetricKey::new(
+                Key::from_static_name("frame_time"),
+                MetricKind::Histogram,
+            ));
+        }
+        recorder.sample(&registry, frame, Duration::from_millis(500 * frame as u64));
+    }
+    // No new histogram samples.
+    recorder.sample(&registry, 2, Duration::from_secs(1));
+
+    assert_eq!(recorder.len(), 3);
+    assert_eq!(
+        recorder.to_csv(),
+        "frame,seconds,spawned{kind=enemy},fps,frame_time\n\
+        0,0,1,60,\n\
+        1,0.5,2,59,6\n\
+        2,1,2,59,\n"
+    );
+
+    recorder.remove_metric(&MetricKey::new(spawned, MetricKind::Counter));
+    assert!(recorder
+        .to_csv()
+        .starts_with("frame,seconds,fps,frame_time\n0,0,60,\n"));
+}
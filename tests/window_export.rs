@@ -0,0 +1,222 @@
+#![cfg(feature = "serde")]
+
+use bevy_metrics_dashboard::{
+    bevy_egui::egui,
+    metrics::Key,
+    metrics_util::MetricKind,
+    namespace_tree::{NamespaceTreeWindow, TreeGrouping},
+    plot_kind::{PlotContext, PlotKind},
+    plots::{HistogramPlotConfig, MetricPlotConfig},
+    registry::{MetricKey, MetricsRegistry},
+    window_export::{DashboardExport, LayoutExport, NamespaceTreeExport},
+    DashboardWindow, FrameSnapshot, PlotTemplates,
+};
+use smallvec::smallvec;
+use std::time::Duration;
+
+#[test]
+fn namespace_tree_settings_round_trip() {
+    let mut window = NamespaceTreeWindow::new("Render Metrics");
+    window.set_filter("render");
+    window.set_grouping(TreeGrouping::Target);
+    window.set_refresh_period(Duration::from_secs(30));
+
+    let json = window.export().to_json();
+    let imported = NamespaceTreeWindow::import(&NamespaceTreeExport::from_json(&json).unwrap());
+
+    assert_eq!(imported.title(), "Render Metrics");
+    assert_eq!(imported.tree().filter(), "render");
+    assert_eq!(imported.tree().grouping(), TreeGrouping::Target);
+    assert_eq!(
+        imported.tree().refresh_period(),
+        Some(Duration::from_secs(30))
+    );
+}
+
+#[test]
+fn manual_refresh_round_trips() {
+    let mut window = NamespaceTreeWindow::new("Namespace Viewer");
+    window.set_manual_refresh();
+
+    let json = window.export().to_json();
+    let imported = NamespaceTreeWindow::import(&NamespaceTreeExport::from_json(&json).unwrap());
+
+    assert_eq!(imported.tree().refresh_period(), None);
+}
+
+#[test]
+fn invalid_refresh_period_is_ignored() {
+    let default_period = NamespaceTreeWindow::new("").tree().refresh_period();
+    for secs in ["-1.0", "1e300"] {
+        let json = format!(r#"{{"refresh_period_secs": {secs}}}"#);
+        let imported = NamespaceTreeWindow::import(&NamespaceTreeExport::from_json(&json).unwrap());
+        assert_eq!(imported.tree().refresh_period(), default_period);
+    }
+}
+
+#[test]
+fn layouts_include_namespace_trees() {
+    let mut tree = NamespaceTreeWindow::new("Render Metrics");
+    tree.set_manual_refresh();
+    let layout = LayoutExport {
+        dashboards: vec![DashboardWindow::new("Frame Times").export()],
+        namespace_trees: vec![tree.export()],
+    };
+
+    let imported = LayoutExport::from_json(&layout.to_json()).unwrap();
+
+    assert_eq!(imported.dashboards.len(), 1);
+    assert_eq!(imported.namespace_trees.len(), 1);
+    let tree = NamespaceTreeWindow::import(&imported.namespace_trees[0]);
+    assert_eq!(tree.title(), "Render Metrics");
+    assert_eq!(tree.tree().refresh_period(), None);
+}
+
+#[test]
+fn dashboard_exports_are_layouts() {
+    let export = DashboardWindow::new("Frame Times").export();
+
+    let single = LayoutExport::from_json(&export.to_json()).unwrap();
+    let array = LayoutExport::from_json(&format!("[{}]", export.to_json())).unwrap();
+
+    for layout in [single, array] {
+        assert_eq!(layout.dashboards.len(), 1);
+        assert!(layout.namespace_trees.is_empty());
+    }
+}
+
+#[test]
+fn dashboard_geometry_round_trips() {
+    let window = DashboardWindow::builder("Frame Times")
+        .default_pos(egui::pos2(40.0, 60.0))
+        .default_size(egui::vec2(300.0, 200.0))
+        .build();
+
+    let json = window.export().to_json();
+    let imported = DashboardWindow::import(
+        &MetricsRegistry::new(),
+        &PlotTemplates::default(),
+        &DashboardExport::from_json(&json).unwrap(),
+    );
+
+    assert_eq!(imported.default_pos(), Some(egui::pos2(40.0, 60.0)));
+    assert_eq!(imported.default_size(), Some(egui::vec2(300.0, 200.0)));
+}
+
+#[test]
+fn plot_notes_round_trip() {
+    let registry = MetricsRegistry::new();
+    let mut window = DashboardWindow::new("Loading");
+    let key = MetricKey::new(Key::from_static_name("frame_time"), MetricKind::Gauge);
+    window
+        .dashboard_mut()
+        .add_plot(
+            &registry,
+            key,
+            None,
+            MetricPlotConfig::default_for_kind(MetricKind::Gauge),
+        )
+        .set_note("expected to spike during load");
+
+    let json = window.export().to_json();
+    let imported = DashboardWindow::import(
+        &registry,
+        &PlotTemplates::default(),
+        &DashboardExport::from_json(&json).unwrap(),
+    );
+
+    assert_eq!(
+        imported.dashboard().plots()[0].note(),
+        "expected to spike during load"
+    );
+}
+
+struct Sparkline;
+
+impl PlotKind for Sparkline {
+    fn update(&mut self, _key: &MetricKey, _snapshot: Option<&FrameSnapshot>) {}
+
+    fn draw(&mut self, _ctx: &PlotContext, _ui: &mut egui::Ui) {}
+}
+
+#[test]
+fn custom_plot_kinds_round_trip() {
+    let registry = MetricsRegistry::new();
+    let mut templates = PlotTemplates::empty();
+    templates.register_kind("sparkline", MetricKind::Gauge, |_, _, _| {
+        Box::new(Sparkline)
+    });
+    let config = templates.iter().next().unwrap().config.clone();
+    let mut window = DashboardWindow::new("Custom");
+    let key = MetricKey::new(Key::from_static_name("frame_time"), MetricKind::Gauge);
+    window
+        .dashboard_mut()
+        .add_plot(&registry, key, None, config);
+
+    let export = DashboardExport::from_json(&window.export().to_json()).unwrap();
+    let imported = DashboardWindow::import(&registry, &templates, &export);
+    let MetricPlotConfig::Custom(config) = imported.dashboard().plots()[0].clone_config() else {
+        panic!("expected a custom config");
+    };
+    assert_eq!(config.name, "sparkline");
+
+    // Without the kind, the plot falls back to the default config.
+    let imported = DashboardWindow::import(&registry, &PlotTemplates::empty(), &export);
+    assert!(matches!(
+        imported.dashboard().plots()[0].clone_config(),
+        MetricPlotConfig::Gauge(_)
+    ));
+}
+
+#[test]
+fn invalid_histogram_buckets_are_fixed_on_import() {
+    let registry = MetricsRegistry::new();
+    let key = MetricKey::new(Key::from_static_name("frame_time"), MetricKind::Histogram);
+    let bucket = registry.get_or_create_histogram(&key.key);
+    let breakages: [fn(&mut HistogramPlotConfig); 5] = [
+        |config| config.buckets.bounds.clear(),
+        |config| config.buckets.bounds = smallvec![1.0],
+        |config| config.buckets.bounds = smallvec![1.0, 3.0, 2.0],
+        |config| {
+            let range = &mut config.buckets.range_input;
+            (range.min, range.max) = (10.0, 0.0);
+        },
+        |config| {
+            let range = &mut config.buckets.range_input;
+            (range.min, range.max, range.n_buckets) = (5.0, 5.0, 0);
+        },
+    ];
+    for (i, breakage) in breakages.into_iter().enumerate() {
+        let mut window = DashboardWindow::new("Histograms");
+        let mut config = HistogramPlotConfig::default();
+        breakage(&mut config);
+        window.dashboard_mut().add_plot(
+            &registry,
+            key.clone(),
+            None,
+            MetricPlotConfig::Histogram(Box::new(config)),
+        );
+
+        let export = DashboardExport::from_json(&window.export().to_json()).unwrap();
+        let mut imported = DashboardWindow::import(&registry, &PlotTemplates::default(), &export);
+        let MetricPlotConfig::Histogram(config) = imported.dashboard().plots()[0].clone_config()
+        else {
+            panic!("expected a histogram config");
+        };
+        let bounds = &config.buckets.bounds;
+        assert!(bounds.len() >= 2, "{i}: {bounds:?}");
+        assert!(bounds.windows(2).all(|w| w[0] < w[1]), "{i}: {bounds:?}");
+
+        // Drawing and rebuilding the buckets doesn't panic.
+        bucket.push(3.0);
+        let plot = &mut imported.dashboard_mut().plots_mut()[0];
+        plot.update();
+        assert!(config.buckets.get_bounds().is_some());
+        let (dash_config, formatters, templates, palette) = Default::default();
+        let _ = egui::Context::default().run(Default::default(), |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                plot.draw(&dash_config, &formatters, &templates, &palette, ui);
+            });
+        });
+    }
+}
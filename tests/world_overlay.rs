@@ -0,0 +1,46 @@
+#![cfg(feature = "gizmos")]
+
+use bevy_metrics_dashboard::{
+    metrics::{Key, Label},
+    metrics_util::MetricKind,
+    registry::MetricsRegistry,
+    world_overlay::{WorldOverlay, WorldOverlays},
+};
+use std::sync::atomic::Ordering;
+
+#[test]
+fn world_overlays_sample_each_labeled_series() {
+    let registry = MetricsRegistry::new();
+    let key = |chunk: &'static str| Key::from_parts("mesh_time", vec![Label::new("chunk", chunk)]);
+    let north = registry.get_or_create_histogram(&key("north"));
+    let south = registry.get_or_create_histogram(&key("south"));
+    registry
+        .get_or_create_histogram(&Key::from_static_name("mesh_time"))
+        .push(9.0);
+    let gauge_key = Key::from_parts("npc_time", vec![Label::new("entity", "3v1")]);
+    let gauge = registry.get_or_create_gauge(&gauge_key);
+    gauge.store(2.5f64.to_bits(), Ordering::Relaxed);
+
+    let mut overlays = WorldOverlays::new([
+        WorldOverlay::new("mesh_time", MetricKind::Histogram).with_label("chunk"),
+        WorldOverlay::new("npc_time", MetricKind::Gauge),
+    ]);
+    north.push(1.0);
+    north.push(3.0);
+    south.push(4.0);
+    overlays.sample(&registry);
+    assert_eq!(overlays.value("mesh_time", "north"), Some(2.0));
+    assert_eq!(overlays.value("mesh_time", "south"), Some(4.0));
+    assert_eq!(overlays.value("npc_time", "3v1"), Some(2.5));
+
+    // Histograms keep their mean until they get new samples.
+    south.push(6.0);
+    overlays.sample(&registry);
+    assert_eq!(overlays.value("mesh_time", "north"), Some(2.0));
+    assert_eq!(overlays.value("mesh_time", "south"), Some(6.0));
+
+    overlays.remove("npc_time", MetricKind::Gauge);
+    overlays.sample(&registry);
+    assert_eq!(overlays.value("npc_time", "3v1"), None);
+    assert_eq!(overlays.overlays().len(), 1);
+}